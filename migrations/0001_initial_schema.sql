@@ -0,0 +1,212 @@
+-- Baseline schema. Safe to run on an empty database and on databases that were
+-- created by the old scripts/init_db.sql (every statement is idempotent).
+
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+-- Users
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    username VARCHAR(50) UNIQUE,
+    email VARCHAR(255) UNIQUE,
+    password_hash VARCHAR(255),
+    reputation INTEGER DEFAULT 0,
+    irys_address VARCHAR(255),
+    ethereum_address VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS posts_count INTEGER DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS comments_count INTEGER DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS reputation INTEGER DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS bio TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar VARCHAR(255);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS has_username BOOLEAN DEFAULT FALSE;
+
+-- Wallet users have no username, email or password until they register one
+ALTER TABLE users ALTER COLUMN username DROP NOT NULL;
+ALTER TABLE users ALTER COLUMN email DROP NOT NULL;
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT FROM information_schema.table_constraints WHERE table_name = 'users' AND constraint_name = 'users_ethereum_address_key') THEN
+        ALTER TABLE users ADD CONSTRAINT users_ethereum_address_key UNIQUE (ethereum_address);
+    END IF;
+END $$;
+
+-- Posts
+CREATE TABLE IF NOT EXISTS posts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    author_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    likes INTEGER DEFAULT 0,
+    comments_count INTEGER DEFAULT 0
+);
+
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS likes INTEGER DEFAULT 0;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS comments_count INTEGER DEFAULT 0;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS tags TEXT[] DEFAULT '{}';
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS irys_transaction_id VARCHAR(255);
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS author_name VARCHAR(100);
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS image TEXT;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_hash VARCHAR(64);
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS category VARCHAR(50) DEFAULT 'general';
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS upvotes INTEGER DEFAULT 0;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS views INTEGER DEFAULT 0;
+-- Blockchain transaction hash, for preventing replay attacks
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS blockchain_transaction_hash VARCHAR(66);
+-- Smart contract post ID
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS blockchain_post_id INTEGER;
+
+-- Comments
+CREATE TABLE IF NOT EXISTS comments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    content TEXT NOT NULL,
+    author_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    parent_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    likes INTEGER DEFAULT 0
+);
+
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS likes INTEGER DEFAULT 0;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS irys_transaction_id VARCHAR(255);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS author_name VARCHAR(100);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS image TEXT;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS content_hash VARCHAR(64);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS upvotes INTEGER DEFAULT 0;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW();
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS blockchain_transaction_hash VARCHAR(66);
+
+-- Like records (one like per user per post/comment)
+CREATE TABLE IF NOT EXISTS comment_likes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    comment_id UUID NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
+    user_address VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(comment_id, user_address)
+);
+
+CREATE TABLE IF NOT EXISTS post_likes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_address VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(post_id, user_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_posts_author_id ON posts(author_id);
+CREATE INDEX IF NOT EXISTS idx_posts_created_at ON posts(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_posts_views ON posts(views);
+CREATE INDEX IF NOT EXISTS idx_posts_blockchain_tx ON posts(blockchain_transaction_hash);
+CREATE INDEX IF NOT EXISTS idx_comments_post_id ON comments(post_id);
+CREATE INDEX IF NOT EXISTS idx_comments_author_id ON comments(author_id);
+CREATE INDEX IF NOT EXISTS idx_comments_blockchain_tx ON comments(blockchain_transaction_hash);
+CREATE INDEX IF NOT EXISTS idx_users_ethereum_address ON users(ethereum_address);
+CREATE INDEX IF NOT EXISTS idx_users_irys_address ON users(irys_address);
+CREATE INDEX IF NOT EXISTS idx_comment_likes_comment_id ON comment_likes(comment_id);
+CREATE INDEX IF NOT EXISTS idx_comment_likes_user_address ON comment_likes(user_address);
+CREATE INDEX IF NOT EXISTS idx_post_likes_post_id ON post_likes(post_id);
+CREATE INDEX IF NOT EXISTS idx_post_likes_user_address ON post_likes(user_address);
+
+-- Keep updated_at current on posts and comments
+CREATE OR REPLACE FUNCTION update_updated_at_column()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS update_posts_updated_at ON posts;
+CREATE TRIGGER update_posts_updated_at
+    BEFORE UPDATE ON posts
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_comments_updated_at ON comments;
+CREATE TRIGGER update_comments_updated_at
+    BEFORE UPDATE ON comments
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Used blockchain transactions (replay protection)
+DO $$ BEGIN
+    CREATE TYPE TRANSACTION_TYPE AS ENUM ('POST', 'COMMENT', 'USERNAME_REGISTER');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS used_transactions (
+    id SERIAL PRIMARY KEY,
+    transaction_hash VARCHAR(66) NOT NULL UNIQUE,
+    transaction_type TRANSACTION_TYPE NOT NULL,
+    user_address VARCHAR(42) NOT NULL,
+    block_number BIGINT,
+    block_timestamp TIMESTAMP,
+    verified_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    post_id UUID REFERENCES posts(id) ON DELETE SET NULL,
+    comment_id UUID REFERENCES comments(id) ON DELETE SET NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_used_transactions_hash ON used_transactions(transaction_hash);
+CREATE INDEX IF NOT EXISTS idx_used_transactions_user ON used_transactions(user_address);
+
+-- Follows
+CREATE TABLE IF NOT EXISTS follows (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    follower_address VARCHAR(42) NOT NULL,
+    following_address VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(follower_address, following_address),
+    CHECK(follower_address != following_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_follows_follower ON follows(follower_address);
+CREATE INDEX IF NOT EXISTS idx_follows_following ON follows(following_address);
+CREATE INDEX IF NOT EXISTS idx_follows_created_at ON follows(created_at);
+
+-- Daily recommendations
+CREATE TABLE IF NOT EXISTS daily_recommendations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    rank_position INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    rec_day DATE NOT NULL DEFAULT CURRENT_DATE
+);
+
+ALTER TABLE daily_recommendations ADD COLUMN IF NOT EXISTS rec_day DATE;
+UPDATE daily_recommendations SET rec_day = created_at::date WHERE rec_day IS DISTINCT FROM created_at::date;
+ALTER TABLE daily_recommendations ALTER COLUMN rec_day SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_daily_recommendations_rank ON daily_recommendations(rank_position);
+CREATE INDEX IF NOT EXISTS idx_daily_recs_created_at ON daily_recommendations (created_at);
+CREATE INDEX IF NOT EXISTS idx_daily_recs_day ON daily_recommendations (rec_day);
+CREATE UNIQUE INDEX IF NOT EXISTS ux_daily_recs_day_rank ON daily_recommendations (rec_day, rank_position);
+
+-- Keep rec_day synchronized with created_at
+CREATE OR REPLACE FUNCTION set_daily_recs_rec_day()
+RETURNS trigger AS $$
+BEGIN
+    NEW.rec_day := NEW.created_at::date;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_set_daily_recs_rec_day ON daily_recommendations;
+CREATE TRIGGER trg_set_daily_recs_rec_day
+    BEFORE INSERT OR UPDATE ON daily_recommendations
+    FOR EACH ROW
+    EXECUTE FUNCTION set_daily_recs_rec_day();
+
+-- Post view counter
+CREATE OR REPLACE FUNCTION increment_post_views(post_uuid UUID)
+RETURNS void AS $$
+BEGIN
+    UPDATE posts SET views = COALESCE(views, 0) + 1 WHERE id = post_uuid;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,94 @@
+-- Chain indexer tables (see src/indexer.rs)
+
+-- Block checkpoints (one row per indexed batch, used to detect reorgs)
+CREATE TABLE IF NOT EXISTS chain_indexer_checkpoints (
+    block_number BIGINT PRIMARY KEY,
+    block_hash VARCHAR(66) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Decoded contract events, keyed by (transaction_hash, log_index)
+CREATE TABLE IF NOT EXISTS chain_post_created (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    post_id BIGINT NOT NULL,
+    author_address VARCHAR(42) NOT NULL,
+    title TEXT NOT NULL,
+    points_earned NUMERIC(78, 0) NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_hash, log_index)
+);
+
+CREATE TABLE IF NOT EXISTS chain_comment_created (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    comment_id BIGINT NOT NULL,
+    post_id BIGINT NOT NULL,
+    author_address VARCHAR(42) NOT NULL,
+    points_earned NUMERIC(78, 0) NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_hash, log_index)
+);
+
+CREATE TABLE IF NOT EXISTS chain_post_liked (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    post_id BIGINT NOT NULL,
+    liker_address VARCHAR(42) NOT NULL,
+    author_address VARCHAR(42) NOT NULL,
+    points_earned NUMERIC(78, 0) NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_hash, log_index)
+);
+
+CREATE TABLE IF NOT EXISTS chain_comment_liked (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    comment_id BIGINT NOT NULL,
+    liker_address VARCHAR(42) NOT NULL,
+    author_address VARCHAR(42) NOT NULL,
+    points_earned NUMERIC(78, 0) NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_hash, log_index)
+);
+
+CREATE TABLE IF NOT EXISTS chain_username_registered (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    user_address VARCHAR(42) NOT NULL,
+    username TEXT NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_hash, log_index)
+);
+
+CREATE TABLE IF NOT EXISTS chain_points_earned (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash VARCHAR(66) NOT NULL,
+    user_address VARCHAR(42) NOT NULL,
+    points NUMERIC(78, 0) NOT NULL,
+    reason TEXT NOT NULL,
+    indexed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (transaction_hash, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_chain_post_created_block ON chain_post_created(block_number);
+CREATE INDEX IF NOT EXISTS idx_chain_post_created_post_id ON chain_post_created(post_id);
+CREATE INDEX IF NOT EXISTS idx_chain_comment_created_block ON chain_comment_created(block_number);
+CREATE INDEX IF NOT EXISTS idx_chain_comment_created_post_id ON chain_comment_created(post_id);
+CREATE INDEX IF NOT EXISTS idx_chain_post_liked_block ON chain_post_liked(block_number);
+CREATE INDEX IF NOT EXISTS idx_chain_comment_liked_block ON chain_comment_liked(block_number);
+CREATE INDEX IF NOT EXISTS idx_chain_username_registered_block ON chain_username_registered(block_number);
+CREATE INDEX IF NOT EXISTS idx_chain_points_earned_block ON chain_points_earned(block_number);
+CREATE INDEX IF NOT EXISTS idx_chain_points_earned_user ON chain_points_earned(user_address);
@@ -0,0 +1,20 @@
+-- Durable async task queue (see src/async_queue.rs)
+
+-- status: pending -> processing -> completed, or dead (dead-letter list) once retries are exhausted
+CREATE TABLE IF NOT EXISTS async_tasks (
+    id VARCHAR(255) PRIMARY KEY,
+    kind VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    result JSONB,
+    run_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_async_tasks_due ON async_tasks(run_at) WHERE status IN ('pending', 'processing');
+CREATE INDEX IF NOT EXISTS idx_async_tasks_status ON async_tasks(status, completed_at);
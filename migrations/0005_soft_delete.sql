@@ -0,0 +1,12 @@
+-- Soft delete for posts and comments (DELETE /api/posts/{id}, /api/comments/{comment_id})
+-- and user roles for moderator deletion
+
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS deleted_by VARCHAR(42);
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS deleted_by VARCHAR(42);
+
+-- 'user', 'moderator' or 'admin'; set with `admin set-role`
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'user';
+
+CREATE INDEX IF NOT EXISTS idx_comments_parent_id ON comments(parent_id);
@@ -0,0 +1,16 @@
+-- Keyset (cursor) pagination over (created_at, id) for post, comment and follow lists.
+-- Every row needs a timestamp to have a position, and the composite indexes serve both
+-- the ordering and the `(created_at, id) < cursor` predicate.
+
+UPDATE posts SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE posts ALTER COLUMN created_at SET NOT NULL;
+UPDATE comments SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE comments ALTER COLUMN created_at SET NOT NULL;
+UPDATE follows SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE follows ALTER COLUMN created_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_posts_created_at_id ON posts(created_at DESC, id DESC) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_posts_author_created_at_id ON posts(author_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_comments_post_created_at_id ON comments(post_id, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_follows_follower_created_at_id ON follows(follower_address, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_follows_following_created_at_id ON follows(following_address, created_at DESC, id DESC);
@@ -0,0 +1,19 @@
+-- Notifications for likes, comments, replies, follows and mentions (GET /api/notifications).
+-- Addresses are stored lowercase. `kind` holds a NotificationKind name, so new kinds need
+-- no schema change; rows with the same recipient and `group_key` are shown as one group.
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    recipient_address VARCHAR(42) NOT NULL,
+    actor_address VARCHAR(42) NOT NULL,
+    kind VARCHAR(32) NOT NULL,
+    post_id UUID REFERENCES posts(id) ON DELETE CASCADE,
+    comment_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    group_key TEXT NOT NULL,
+    read_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Repeating an action (like, unlike, like) refreshes the row instead of adding one
+    UNIQUE (recipient_address, group_key, actor_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_unread ON notifications(recipient_address) WHERE read_at IS NULL;
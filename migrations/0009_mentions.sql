@@ -0,0 +1,18 @@
+-- Resolved @username mentions in posts and comments. A post's own mentions have no
+-- comment_id; rows are replaced whenever the content they were parsed from changes.
+
+CREATE TABLE IF NOT EXISTS mentions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    comment_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    mentioned_address VARCHAR(42) NOT NULL,
+    username VARCHAR(50) NOT NULL,
+    -- Char offsets of "@username" in the content
+    start_offset INTEGER NOT NULL,
+    end_offset INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentions_post ON mentions(post_id) WHERE comment_id IS NULL;
+CREATE INDEX IF NOT EXISTS idx_mentions_comment ON mentions(comment_id) WHERE comment_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_mentions_address ON mentions(LOWER(mentioned_address), created_at DESC);
@@ -0,0 +1,73 @@
+-- Tags as first-class entities. Post tags are normalized by the application (lowercase,
+-- 1-32 letters, digits, "_" or "-"); existing rows are normalized the same way here.
+-- `tags.post_count` counts live posts and is kept in sync by a trigger on posts, and
+-- idx_posts_tags (GIN, migration 0006) serves tag pages and trending queries.
+
+UPDATE posts p
+SET tags = normalized.tags
+FROM (
+    SELECT id, ARRAY(
+        SELECT n.tag
+        FROM (
+            SELECT DISTINCT ON (t.tag) t.tag, t.ord
+            FROM unnest(COALESCE(posts.tags, '{}')) WITH ORDINALITY AS u(raw, ord)
+            CROSS JOIN LATERAL (SELECT LOWER(BTRIM(LTRIM(BTRIM(u.raw), '#'))) AS tag, u.ord) t
+            WHERE t.tag ~ '^[[:alnum:]_-]{1,32}$' AND t.tag ~ '[[:alnum:]]'
+            ORDER BY t.tag, t.ord
+        ) n
+        ORDER BY n.ord
+        LIMIT 10
+    ) AS tags
+    FROM posts
+) normalized
+WHERE p.id = normalized.id AND p.tags IS DISTINCT FROM normalized.tags;
+
+ALTER TABLE posts ALTER COLUMN tags SET NOT NULL;
+
+CREATE TABLE IF NOT EXISTS tags (
+    name VARCHAR(32) PRIMARY KEY,
+    post_count INTEGER NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_tags_post_count ON tags(post_count DESC);
+
+CREATE OR REPLACE FUNCTION sync_tag_counts()
+RETURNS TRIGGER AS $$
+DECLARE
+    old_tags TEXT[] := '{}';
+    new_tags TEXT[] := '{}';
+BEGIN
+    IF TG_OP <> 'INSERT' AND OLD.deleted_at IS NULL THEN
+        old_tags := OLD.tags;
+    END IF;
+    IF TG_OP <> 'DELETE' AND NEW.deleted_at IS NULL THEN
+        new_tags := NEW.tags;
+    END IF;
+
+    UPDATE tags SET post_count = GREATEST(post_count - 1, 0)
+    WHERE name IN (SELECT unnest(old_tags) EXCEPT SELECT unnest(new_tags));
+
+    INSERT INTO tags (name, post_count, last_used_at)
+    SELECT added.name, 1, NOW()
+    FROM (SELECT unnest(new_tags) EXCEPT SELECT unnest(old_tags)) AS added(name)
+    ON CONFLICT (name) DO UPDATE
+    SET post_count = tags.post_count + 1, last_used_at = NOW();
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_sync_tag_counts ON posts;
+CREATE TRIGGER trg_sync_tag_counts
+    AFTER INSERT OR DELETE OR UPDATE OF tags, deleted_at ON posts
+    FOR EACH ROW
+    EXECUTE FUNCTION sync_tag_counts();
+
+INSERT INTO tags (name, post_count, last_used_at)
+SELECT t.name, COUNT(*), MAX(p.created_at)
+FROM posts p, unnest(p.tags) AS t(name)
+WHERE p.deleted_at IS NULL
+GROUP BY t.name
+ON CONFLICT (name) DO UPDATE
+SET post_count = EXCLUDED.post_count, last_used_at = EXCLUDED.last_used_at;
@@ -0,0 +1,12 @@
+-- Saved posts (GET /api/bookmarks). Addresses are stored lowercase; bookmarks of deleted
+-- posts are kept but hidden, and go away with the post row.
+
+CREATE TABLE IF NOT EXISTS bookmarks (
+    user_address VARCHAR(42) NOT NULL,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_address, post_id)
+);
+
+-- Keyset pages of one user's bookmarks, newest first
+CREATE INDEX IF NOT EXISTS idx_bookmarks_user_created_at ON bookmarks(user_address, created_at DESC, post_id DESC);
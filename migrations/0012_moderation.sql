@@ -0,0 +1,68 @@
+-- User reports, moderator actions and the moderation audit log (/api/moderation/*).
+-- Addresses are stored lowercase. Roles live in `users.role` (migration 0005).
+
+CREATE TABLE IF NOT EXISTS reports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    reporter_address VARCHAR(42) NOT NULL,
+    -- 'post', 'comment' or 'user'; target_id is the post/comment id or the user's address
+    target_type VARCHAR(16) NOT NULL,
+    target_id VARCHAR(64) NOT NULL,
+    reason VARCHAR(32) NOT NULL,
+    details TEXT,
+    -- 'open', 'resolved' or 'dismissed'
+    status VARCHAR(16) NOT NULL DEFAULT 'open',
+    resolution VARCHAR(32),
+    resolved_by VARCHAR(42),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- A reporter has at most one open report per target
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_open_reporter ON reports(reporter_address, target_type, target_id) WHERE status = 'open';
+-- The queue: open reports, oldest first
+CREATE INDEX IF NOT EXISTS idx_reports_open_queue ON reports(created_at, id) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_reports_open_target ON reports(target_type, target_id) WHERE status = 'open';
+
+-- Locked threads accept no new comments
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS locked_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS locked_by VARCHAR(42);
+
+-- Suspended addresses cannot post or comment; expires_at NULL means until lifted
+CREATE TABLE IF NOT EXISTS suspensions (
+    address VARCHAR(42) PRIMARY KEY,
+    reason TEXT,
+    suspended_by VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE TABLE IF NOT EXISTS moderation_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    moderator_address VARCHAR(42) NOT NULL,
+    action VARCHAR(32) NOT NULL,
+    target_type VARCHAR(16) NOT NULL,
+    target_id VARCHAR(64) NOT NULL,
+    report_id UUID REFERENCES reports(id),
+    reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_moderation_log_created_at ON moderation_log(created_at DESC, id DESC);
+
+-- The audit log is append-only
+CREATE OR REPLACE FUNCTION reject_moderation_log_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'moderation_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS moderation_log_append_only ON moderation_log;
+CREATE TRIGGER moderation_log_append_only
+    BEFORE UPDATE OR DELETE ON moderation_log
+    FOR EACH ROW EXECUTE FUNCTION reject_moderation_log_change();
+
+DROP TRIGGER IF EXISTS moderation_log_no_truncate ON moderation_log;
+CREATE TRIGGER moderation_log_no_truncate
+    BEFORE TRUNCATE ON moderation_log
+    FOR EACH STATEMENT EXECUTE FUNCTION reject_moderation_log_change();
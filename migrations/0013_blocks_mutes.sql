@@ -0,0 +1,19 @@
+-- Per-user blocks and mutes. Addresses are stored lowercase.
+-- A blocked user cannot follow, reply to or mention the blocker; blocking also removes any
+-- follow between the two. Posts by muted users are left out of the muter's lists and feeds.
+
+CREATE TABLE IF NOT EXISTS blocks (
+    blocker_address VARCHAR(42) NOT NULL,
+    blocked_address VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (blocker_address, blocked_address),
+    CHECK (blocker_address <> blocked_address)
+);
+
+CREATE TABLE IF NOT EXISTS mutes (
+    muter_address VARCHAR(42) NOT NULL,
+    muted_address VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (muter_address, muted_address),
+    CHECK (muter_address <> muted_address)
+);
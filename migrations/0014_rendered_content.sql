@@ -0,0 +1,5 @@
+-- Sanitized HTML rendered from the Markdown in `content` (see src/markdown.rs). Rows written
+-- before this migration keep NULL and are rendered when read.
+
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS content_html TEXT;
+ALTER TABLE comments ADD COLUMN IF NOT EXISTS content_html TEXT;
@@ -0,0 +1,36 @@
+-- Spam filter state. Addresses are stored lowercase.
+
+-- Simhash of every live post and comment, for near-duplicate detection across authors and
+-- per-author submission counts. simhash is NULL for text too short to compare.
+CREATE TABLE IF NOT EXISTS content_fingerprints (
+    content_id VARCHAR(64) PRIMARY KEY,
+    -- 'post' or 'comment'
+    content_type VARCHAR(16) NOT NULL,
+    author_address VARCHAR(42) NOT NULL,
+    simhash BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_fingerprints_created_at ON content_fingerprints(created_at DESC) WHERE simhash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_content_fingerprints_author ON content_fingerprints(author_address, created_at);
+
+-- Submissions the spam filter held for moderator review. `submission` is the original request
+-- (plus its verified transaction), so approving it creates the post or comment as submitted.
+CREATE TABLE IF NOT EXISTS held_content (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    content_type VARCHAR(16) NOT NULL,
+    author_address VARCHAR(42) NOT NULL,
+    submission JSONB NOT NULL,
+    score INTEGER NOT NULL,
+    signals JSONB NOT NULL DEFAULT '[]',
+    -- 'held', 'approved' or 'rejected'
+    status VARCHAR(16) NOT NULL DEFAULT 'held',
+    -- The post or comment created on approval
+    content_id VARCHAR(64),
+    resolved_by VARCHAR(42),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- The queue: held submissions, oldest first
+CREATE INDEX IF NOT EXISTS idx_held_content_queue ON held_content(created_at, id) WHERE status = 'held';
@@ -0,0 +1,21 @@
+-- Uploaded images. `id` is the SHA-256 of the decoded pixels (see media::ingest), and the files
+-- live under MEDIA_DIR/<id>/, so the same picture uploaded twice shares one row and one set of files.
+CREATE TABLE IF NOT EXISTS media (
+    id VARCHAR(64) PRIMARY KEY,
+    -- 'avatar' or 'post'
+    kind VARCHAR(16) NOT NULL,
+    -- First uploader, lowercase
+    owner_address VARCHAR(42) NOT NULL,
+    -- Sniffed format of the upload: 'jpeg', 'png', 'gif' or 'webp'
+    source_format VARCHAR(8) NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    -- [{ name, format, width, height, bytes, url }]
+    variants JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_owner ON media(owner_address, created_at DESC);
+
+-- Avatar replacement checks whether anyone else still uses the old avatar
+CREATE INDEX IF NOT EXISTS idx_users_avatar ON users(avatar) WHERE avatar IS NOT NULL;
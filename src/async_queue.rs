@@ -1,30 +1,40 @@
-use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::sync::Notify;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::blockchain::TransactionVerificationError;
 use crate::models::*;
+use crate::services::ContentActionError;
+use crate::spam::SpamError;
+use crate::store::ForumStore;
 
+/// Task payload, stored as JSON with the queued task
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum QueueTask {
     PostCreation {
-        task_id: String,
         request: CreatePostRequest,
         tx_hash: String,
-        user_address: String,
-        timestamp: DateTime<Utc>,
     },
     CommentCreation {
-        task_id: String,
         request: CreateCommentRequest,
         tx_hash: String,
-        user_address: String,
-        timestamp: DateTime<Utc>,
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl QueueTask {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QueueTask::PostCreation { .. } => "post_creation",
+            QueueTask::CommentCreation { .. } => "comment_creation",
+        }
+    }
+}
+
+/// `Failed` tasks have used up their retries and sit in the dead-letter list until replayed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TaskStatus {
     Pending,
     Processing,
@@ -32,443 +42,412 @@ pub enum TaskStatus {
     Failed(String),
 }
 
+impl TaskStatus {
+    /// Map the `status` column (`pending`, `processing`, `completed`, `dead`)
+    pub fn from_db(status: &str, last_error: Option<&str>) -> Self {
+        match status {
+            "processing" => TaskStatus::Processing,
+            "completed" => TaskStatus::Completed,
+            "dead" => TaskStatus::Failed(last_error.unwrap_or_default().to_string()),
+            _ => TaskStatus::Pending,
+        }
+    }
+}
+
+/// A task as persisted by the `ForumStore`
 #[derive(Debug, Clone)]
-pub struct TaskResult {
-    pub task_id: String,
+pub struct QueuedTask {
+    pub id: String,
+    pub task: QueueTask,
     pub status: TaskStatus,
+    /// Attempts started so far, including the one in progress
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub result: Option<serde_json::Value>,
+    /// Earliest time the task may be (re)tried
+    pub run_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
-    pub result_data: Option<serde_json::Value>,
 }
 
+impl QueuedTask {
+    pub fn new(task: QueueTask) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            task,
+            status: TaskStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            result: None,
+            run_at: now,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+        }
+    }
+}
+
+/// Why a task attempt failed. Retryable errors (e.g. a transaction that is not
+/// mined yet) are retried with backoff; permanent ones go straight to the dead-letter list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskError {
+    Retryable(String),
+    Permanent(String),
+}
+
+impl TaskError {
+    fn message(&self) -> &str {
+        match self {
+            TaskError::Retryable(message) | TaskError::Permanent(message) => message,
+        }
+    }
+}
+
+/// Runs claimed tasks. Implemented by `ForumService`, so queued posts and comments go through
+/// the same checks and creation path as synchronous ones.
+#[async_trait]
+pub trait TaskProcessor: Send + Sync {
+    async fn process(&self, task: &QueuedTask) -> Result<serde_json::Value, TaskError>;
+}
+
+/// Only a transaction that may still verify (not mined yet, node unreachable) is worth retrying;
+/// a mined one that does not match the submission never will
+pub fn verification_task_error(e: TransactionVerificationError) -> TaskError {
+    let message = format!("Blockchain verification failed: {}", e);
+    match e {
+        TransactionVerificationError::Pending(_) => TaskError::Retryable(message),
+        TransactionVerificationError::Invalid(_) => TaskError::Permanent(message),
+    }
+}
+
+/// Map a failed creation to the task outcome. A held submission completes the task (a moderator
+/// decides from there); rejected content (spam, suspended author, locked thread, duplicate, used
+/// transaction, ...) fails for good; storage and upload failures are retried.
+pub fn creation_task_result(e: Box<dyn std::error::Error>) -> Result<serde_json::Value, TaskError> {
+    let e = match e.downcast::<SpamError>() {
+        Ok(e) => {
+            let message = e.to_string();
+            return match *e {
+                SpamError::Held { held_id } => Ok(serde_json::json!({
+                    "success": true,
+                    "held": true,
+                    "held_id": held_id,
+                    "message": message
+                })),
+                SpamError::Internal(_) => Err(TaskError::Retryable(message)),
+                SpamError::Rejected => Err(TaskError::Permanent(message)),
+            };
+        }
+        Err(e) => e,
+    };
+    match e.downcast::<ContentActionError>() {
+        Ok(e) => match *e {
+            ContentActionError::Internal(message) => Err(TaskError::Retryable(message)),
+            other => Err(TaskError::Permanent(other.to_string())),
+        },
+        Err(e) => Err(TaskError::Retryable(e.to_string())),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QueueConfig {
+    pub worker_count: usize,
+    /// Maximum number of pending + processing tasks; submissions beyond it are rejected
+    pub max_size: u64,
+    pub task_timeout: Duration,
+    /// Retries after the first attempt
+    pub retry_attempts: u32,
+    /// Delay before the first retry; doubled for every further retry
+    pub retry_delay: Duration,
+    /// How long completed task status is kept before eviction
+    pub status_retention: Duration,
+    pub poll_interval: Duration,
+}
+
+impl QueueConfig {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            worker_count: env_u64("ASYNC_WORKER_COUNT", 10).max(1) as usize,
+            max_size: env_u64("QUEUE_MAX_SIZE", 1000),
+            task_timeout: Duration::from_secs(env_u64("TASK_TIMEOUT", 30).max(1)),
+            retry_attempts: env_u64("TASK_RETRY_ATTEMPTS", 3) as u32,
+            retry_delay: Duration::from_secs(env_u64("TASK_RETRY_DELAY", 5)),
+            status_retention: Duration::from_secs(env_u64("TASK_STATUS_RETENTION", 86400)),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Delay before retry number `attempt` (1-based): `retry_delay * 2^(attempt - 1)`, capped at one hour
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.retry_delay.saturating_mul(factor).min(Duration::from_secs(3600))
+    }
+
+    /// A claimed task is handed to another worker if its lease runs out (e.g. the process died)
+    fn lease(&self) -> Duration {
+        self.task_timeout * 2
+    }
+}
+
+/// Durable task queue. Tasks are persisted through the `ForumStore` (the
+/// `async_tasks` table on Postgres), so pending work survives restarts: workers
+/// claim due tasks with a lease, retry failures with exponential backoff and
+/// move tasks that exhausted their retries to the dead-letter list.
 pub struct AsyncQueueService {
-    task_sender: mpsc::UnboundedSender<QueueTask>,
-    task_status: Arc<RwLock<HashMap<String, TaskResult>>>,
-    blockchain_service: Option<Arc<crate::blockchain::BlockchainService>>,
-    database_service: Option<Arc<crate::database::DatabaseService>>,
+    store: Arc<dyn ForumStore>,
+    config: QueueConfig,
+    task_available: Arc<Notify>,
 }
 
 impl AsyncQueueService {
-    pub fn new(
-        blockchain_service: Option<Arc<crate::blockchain::BlockchainService>>,
-        database_service: Option<Arc<crate::database::DatabaseService>>,
-    ) -> Self {
-        let (task_sender, task_receiver) = mpsc::unbounded_channel();
-        let task_status = Arc::new(RwLock::new(HashMap::new()));
-        
+    pub fn new(store: Arc<dyn ForumStore>) -> Self {
+        Self::with_config(store, QueueConfig::from_env())
+    }
+
+    /// Tasks are accepted right away but only run once `start_workers` is called
+    pub fn with_config(store: Arc<dyn ForumStore>, config: QueueConfig) -> Self {
         let service = Self {
-            task_sender,
-            task_status: task_status.clone(),
-            blockchain_service,
-            database_service,
+            store,
+            config,
+            task_available: Arc::new(Notify::new()),
         };
-        
-        
-        service.start_task_processor(task_receiver, task_status);
+
+        service.start_status_eviction();
         service
     }
-    
+
     /// Submit async post creation task
     pub async fn submit_post_creation(
         &self,
         request: CreatePostRequest,
         tx_hash: String,
     ) -> Result<String, String> {
-        let task_id = Uuid::new_v4().to_string();
-        let task = QueueTask::PostCreation {
-            task_id: task_id.clone(),
-            request,
-            tx_hash,
-            user_address: "".to_string(),
-            timestamp: Utc::now(),
-        };
-        
-        
-        {
-            let mut status_map = self.task_status.write().await;
-            status_map.insert(task_id.clone(), TaskResult {
-                task_id: task_id.clone(),
-                status: TaskStatus::Pending,
-                created_at: Utc::now(),
-                completed_at: None,
-                result_data: None,
-            });
-        }
-        
-        
-        self.task_sender.send(task).map_err(|e| format!("Queue send failed: {}", e))?;
-        Ok(task_id)
+        self.submit(QueueTask::PostCreation { request, tx_hash }).await
     }
-    
+
     pub async fn submit_comment_creation(
         &self,
         request: CreateCommentRequest,
         tx_hash: String,
     ) -> Result<String, String> {
-        let task_id = Uuid::new_v4().to_string();
-        let task = QueueTask::CommentCreation {
-            task_id: task_id.clone(),
-            request,
-            tx_hash,
-            user_address: "".to_string(),
-            timestamp: Utc::now(),
-        };
-        
-        
-        {
-            let mut status_map = self.task_status.write().await;
-            status_map.insert(task_id.clone(), TaskResult {
-                task_id: task_id.clone(),
-                status: TaskStatus::Pending,
-                created_at: Utc::now(),
-                completed_at: None,
-                result_data: None,
-            });
+        self.submit(QueueTask::CommentCreation { request, tx_hash }).await
+    }
+
+    async fn submit(&self, task: QueueTask) -> Result<String, String> {
+        let task = QueuedTask::new(task);
+        let accepted = self.store.enqueue_task(&task, self.config.max_size).await
+            .map_err(|e| format!("Queue save failed: {}", e))?;
+        if !accepted {
+            return Err(format!("Task queue is full ({} tasks), please try again later", self.config.max_size));
         }
-        
-        self.task_sender.send(task).map_err(|e| format!("Queue send failed: {}", e))?;
-        Ok(task_id)
+
+        self.task_available.notify_one();
+        Ok(task.id)
     }
-    
+
     /// Query task status by task_id
-    pub async fn get_task_status(&self, task_id: &str) -> Option<TaskResult> {
-        let status_map = self.task_status.read().await;
-        status_map.get(task_id).cloned()
+    pub async fn get_task_status(&self, task_id: &str) -> Option<QueuedTask> {
+        match self.store.get_task(task_id).await {
+            Ok(task) => task,
+            Err(e) => {
+                log::warn!("⚠️ Task status query failed: {}", e);
+                None
+            }
+        }
     }
-    
-    
-    fn start_task_processor(
-        &self,
-        mut task_receiver: mpsc::UnboundedReceiver<QueueTask>,
-        task_status: Arc<RwLock<HashMap<String, TaskResult>>>,
-    ) {
-        let blockchain_service = self.blockchain_service.clone();
-        let database_service = self.database_service.clone();
-        
+
+    pub fn start_workers(&self, processor: Arc<dyn TaskProcessor>) {
+        for worker_id in 0..self.config.worker_count {
+            let store = self.store.clone();
+            let config = self.config.clone();
+            let task_available = self.task_available.clone();
+            let processor = processor.clone();
+
+            tokio::spawn(async move {
+                Self::worker_loop(worker_id, store, config, task_available, processor).await;
+            });
+        }
+    }
+
+    fn start_status_eviction(&self) {
+        let store = self.store.clone();
+        let retention = self.config.status_retention;
+
         tokio::spawn(async move {
-            
-            let worker_count = std::env::var("ASYNC_WORKER_COUNT")
-                .unwrap_or_else(|_| "10".to_string())
-                .parse::<usize>()
-                .unwrap_or(10);
-            
-            let (work_sender, work_receiver) = mpsc::unbounded_channel();
-            let work_receiver = Arc::new(tokio::sync::Mutex::new(work_receiver));
-            
-            
-            for worker_id in 0..worker_count {
-                let work_receiver = work_receiver.clone();
-                let task_status = task_status.clone();
-                let blockchain_service = blockchain_service.clone();
-                let database_service = database_service.clone();
-                
-                tokio::spawn(async move {
-                    Self::worker_loop(
-                        worker_id,
-                        work_receiver,
-                        task_status,
-                        blockchain_service,
-                        database_service,
-                    ).await;
-                });
-            }
-            
-            
-            while let Some(task) = task_receiver.recv().await {
-                if let Err(_) = work_sender.send(task) {
-                    log::error!("Work queue closed");
-                    break;
+            let mut interval = tokio::time::interval(retention.clamp(Duration::from_secs(1), Duration::from_secs(600)));
+            loop {
+                interval.tick().await;
+                let cutoff = Utc::now() - chrono::Duration::from_std(retention).unwrap_or_else(|_| chrono::Duration::days(1));
+                match store.evict_completed_tasks(cutoff).await {
+                    Ok(0) => {}
+                    Ok(evicted) => log::info!("🧹 Evicted {} completed tasks", evicted),
+                    Err(e) => log::warn!("⚠️ Task status eviction failed: {}", e),
                 }
             }
         });
     }
-    
+
     async fn worker_loop(
         worker_id: usize,
-        work_receiver: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<QueueTask>>>,
-        task_status: Arc<RwLock<HashMap<String, TaskResult>>>,
-        blockchain_service: Option<Arc<crate::blockchain::BlockchainService>>,
-        database_service: Option<Arc<crate::database::DatabaseService>>,
+        store: Arc<dyn ForumStore>,
+        config: QueueConfig,
+        task_available: Arc<Notify>,
+        processor: Arc<dyn TaskProcessor>,
     ) {
         log::info!("🔧 Worker {} started", worker_id);
-        
+
         loop {
-            let task = {
-                let mut receiver = work_receiver.lock().await;
-                receiver.recv().await
-            };
-            
-            match task {
-                Some(task) => {
-                    log::info!("🔄 Worker {} processing task: {:?}", worker_id, task);
-                    Self::process_task(
-                        task,
-                        task_status.clone(),
-                        blockchain_service.clone(),
-                        database_service.clone(),
-                    ).await;
+            match store.claim_task(config.lease()).await {
+                Ok(Some(task)) => {
+                    log::info!("🔄 Worker {} processing task {} (attempt {})", worker_id, task.id, task.attempts);
+                    Self::run_task(task, &store, &config, processor.as_ref()).await;
                 }
-                None => {
-                    log::info!("🛑 Worker {} received shutdown signal", worker_id);
-                    break;
+                Ok(None) => {
+                    tokio::select! {
+                        _ = task_available.notified() => {}
+                        _ = tokio::time::sleep(config.poll_interval) => {}
+                    }
+                }
+                Err(e) => {
+                    log::warn!("⚠️ Worker {} failed to claim a task: {}", worker_id, e);
+                    tokio::time::sleep(config.poll_interval).await;
                 }
             }
         }
     }
-    
-    async fn process_task(
-        task: QueueTask,
-        task_status: Arc<RwLock<HashMap<String, TaskResult>>>,
-        blockchain_service: Option<Arc<crate::blockchain::BlockchainService>>,
-        database_service: Option<Arc<crate::database::DatabaseService>>,
+
+    async fn run_task(
+        task: QueuedTask,
+        store: &Arc<dyn ForumStore>,
+        config: &QueueConfig,
+        processor: &dyn TaskProcessor,
     ) {
-        let task_id = match &task {
-            QueueTask::PostCreation { task_id, .. } => task_id.clone(),
-            QueueTask::CommentCreation { task_id, .. } => task_id.clone(),
+        let result = match tokio::time::timeout(config.task_timeout, processor.process(&task)).await {
+            Ok(result) => result,
+            Err(_) => Err(TaskError::Retryable(format!("Task timed out after {}s", config.task_timeout.as_secs()))),
         };
-        
-        
-        {
-            let mut status_map = task_status.write().await;
-            if let Some(status) = status_map.get_mut(&task_id) {
-                status.status = TaskStatus::Processing;
-            }
-        }
-        
-        let result = match task {
-            QueueTask::PostCreation { request, tx_hash, .. } => {
-                Self::process_post_creation(
-                    request,
-                    tx_hash,
-                    blockchain_service,
-                    database_service,
-                ).await
+
+        let saved = match result {
+            Ok(data) => store.complete_task(&task.id, &data).await,
+            Err(TaskError::Retryable(error)) if task.attempts <= config.retry_attempts => {
+                let delay = config.backoff(task.attempts);
+                log::warn!("⚠️ Task {} failed (attempt {}), retrying in {}s: {}", task.id, task.attempts, delay.as_secs(), error);
+                let run_at = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::hours(1));
+                store.reschedule_task(&task.id, &error, run_at).await
             }
-            QueueTask::CommentCreation { request, tx_hash, .. } => {
-                Self::process_comment_creation(
-                    request,
-                    tx_hash,
-                    blockchain_service,
-                    database_service,
-                ).await
+            Err(error) => {
+                log::error!("❌ Task {} moved to dead-letter list after {} attempts: {}", task.id, task.attempts, error.message());
+                store.dead_letter_task(&task.id, error.message()).await
             }
         };
-        
-        
-        {
-            let mut status_map = task_status.write().await;
-            if let Some(status) = status_map.get_mut(&task_id) {
-                status.completed_at = Some(Utc::now());
-                match result {
-                    Ok(data) => {
-                        status.status = TaskStatus::Completed;
-                        status.result_data = Some(data);
-                    }
-                    Err(error) => {
-                        status.status = TaskStatus::Failed(error);
-                    }
-                }
-            }
+
+        if let Err(e) = saved {
+            log::error!("❌ Failed to save task {} state: {}", task.id, e);
         }
     }
-    
-    async fn process_post_creation(
-        request: CreatePostRequest,
-        tx_hash: String,
-        blockchain_service: Option<Arc<crate::blockchain::BlockchainService>>,
-        database_service: Option<Arc<crate::database::DatabaseService>>,
-    ) -> Result<serde_json::Value, String> {
-        log::info!("🔄 Starting async post creation: {}", tx_hash);
-        
-        
-        if let Some(blockchain) = blockchain_service {
-            let verification = blockchain
-                .verify_post_transaction(&tx_hash, &request.author_address)
-                .await
-                .map_err(|e| format!("Blockchain verification failed: {}", e))?;
-            
-            log::info!("✅ Blockchain verification succeeded: {}", verification.transaction_hash);
-            
-
-            if let Some(database) = database_service {
-         
-                match database.is_transaction_used(&tx_hash).await {
-                    Ok(true) => {
-                        return Err("The transaction hash has been used".to_string());
-                    }
-                    Ok(false) => {
-                        log::info!("✅ Transaction hash verification passed");
-                    }
-                    Err(e) => {
-                        log::warn!("⚠️ Transaction verification failed, continue processing: {}", e);
-                    }
-                }
-                
-   
-                let post_id = uuid::Uuid::new_v4().to_string();
-                let now = chrono::Utc::now();
-                
-                let post = crate::models::Post {
-                    id: post_id.clone(),
-                    title: request.title.clone(),
-                    content: request.content.clone(),
-                    author_address: request.author_address.clone(),
-                    author_id: None, 
-                    author_name: request.author_name.clone(),
-                    author_avatar: None,
-                    created_at: now,
-                    updated_at: now,
-                    likes: 0,
-                    comments_count: 0,
-                    tags: request.tags.clone(),
-                    irys_transaction_id: None,
-                    image: request.image.clone(),
-                    blockchain_post_id: request.blockchain_post_id,
-                    is_liked_by_user: false, 
-                    views: 0, 
-                    heat_score: None, 
-                };
-                
-                //Save post to database
-                database.create_post(&post).await
-                    .map_err(|e| format!("Database save failed: {}", e))?;
-                
-               
-                database.update_post_blockchain_hash(&post_id, &tx_hash).await
-                    .map_err(|e| format!("Failed to update transaction hash: {}", e))?;
-                
-                
-                let block_timestamp = chrono::DateTime::from_timestamp(
-                    verification.block_timestamp.as_u64() as i64, 0
-                ).unwrap_or_else(|| chrono::Utc::now());
-                
-                database.record_post_transaction(
-                    &tx_hash,
-                    &verification.sender,
-                    verification.block_number,
-                    block_timestamp,
-                    &post_id
-                ).await.map_err(|e| format!("Record transaction failure: {}", e))?;
-                
-                log::info!("✅ Post asynchronous creation completed: {}", post_id);
-                
-                return Ok(serde_json::json!({
-                    "success": true, 
-                    "message": "Post created successfully", 
-                    "post_id": post_id,
-                    "transaction_hash": tx_hash
-                }));
-            }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_store::MemoryStore;
+    use crate::services::ForumService;
+
+    fn config() -> QueueConfig {
+        QueueConfig {
+            worker_count: 1,
+            max_size: 2,
+            task_timeout: Duration::from_secs(5),
+            retry_attempts: 2,
+            retry_delay: Duration::from_millis(10),
+            status_retention: Duration::from_secs(3600),
+            poll_interval: Duration::from_millis(10),
         }
-        
-        Err("Blockchain service or database service unavailable".to_string())
     }
-    
-    async fn process_comment_creation(
-        request: CreateCommentRequest,
-        tx_hash: String,
-        blockchain_service: Option<Arc<crate::blockchain::BlockchainService>>,
-        database_service: Option<Arc<crate::database::DatabaseService>>,
-    ) -> Result<serde_json::Value, String> {
-        log::info!("🔄 Start asynchronous processing of comment creation: {}", tx_hash);
-        
-        
-        if let Some(blockchain) = blockchain_service {
-            let verification = blockchain
-                .verify_comment_transaction(&tx_hash, &request.author_address)
-                .await
-                .map_err(|e| format!("Blockchain verification failed: {}", e))?;
-            
-            log::info!("✅ Blockchain verification succeeded: {}", verification.transaction_hash);
-            
-         
-            if let Some(database) = database_service {
-                
-                match database.is_transaction_used(&tx_hash).await {
-                    Ok(true) => {
-                        return Err("The transaction hash has been used".to_string());
-                    }
-                    Ok(false) => {
-                        log::info!("✅ Transaction hash verification passed");
-                    }
-                    Err(e) => {
-                        log::warn!("⚠️ Transaction verification failed, continue processing: {}", e);
-                    }
-                }
-                
-               
-                match database.check_duplicate_comment(&request.author_address, &request.content, &request.post_id).await {
-                    Ok(true) => {
-                        return Err("You have posted a comment with the same content in the last 5 minutes".to_string());
-                    }
-                    Ok(false) => {
-                        log::info!("✅ Content re check passed");
-                    }
-                    Err(e) => {
-                        log::warn!("⚠️ Content re check failed, continue processing: {}", e);
-                    }
-                }
-                
-                
-                let comment_id = uuid::Uuid::new_v4().to_string();
-                let now = chrono::Utc::now();
-                let content_hash = {
-                    use sha2::{Sha256, Digest};
-                    let mut hasher = Sha256::new();
-                    hasher.update(request.content.as_bytes());
-                    format!("{:x}", hasher.finalize())
-                };
-                
-                let comment = crate::models::Comment {
-                    id: comment_id.clone(),
-                    post_id: request.post_id.clone(),
-                    content: request.content.clone(),
-                    author_address: request.author_address.clone(),
-                    author_id: None, 
-                    author_name: request.author_name.clone(),
-                    author_avatar: None,
-                    created_at: now,
-                    parent_id: request.parent_id.clone(),
-                    likes: 0,
-                    irys_transaction_id: None,
-                    image: request.image.clone(),
-                    content_hash,
-                    is_liked_by_user: false,
-                };
-                
-                
-                database.add_comment(&comment).await
-                    .map_err(|e| format!("Database save failed: {}", e))?;
-                
-               
-                database.update_comment_blockchain_hash(&comment_id, &tx_hash).await
-                    .map_err(|e| format!("Failed to update transaction hash: {}", e))?;
-                
-                
-                let block_timestamp = chrono::DateTime::from_timestamp(
-                    verification.block_timestamp.as_u64() as i64, 0
-                ).unwrap_or_else(|| chrono::Utc::now());
-                
-                database.record_comment_transaction(
-                    &tx_hash,
-                    &verification.sender,
-                    verification.block_number,
-                    block_timestamp,
-                    &comment_id
-                ).await.map_err(|e| format!("Record transaction failure: {}", e))?;
-                
-                log::info!("✅ Asynchronous creation of comments completed: {}", comment_id);
-                
-                return Ok(serde_json::json!({
-                    "success": true, 
-                    "message": "Comment created successfully", 
-                    "comment_id": comment_id,
-                    "post_id": request.post_id,
-                    "transaction_hash": tx_hash
-                }));
+
+    fn post_task() -> QueueTask {
+        QueueTask::PostCreation {
+            request: CreatePostRequest {
+                title: "Title".to_string(),
+                content: "Content".to_string(),
+                author_address: "0x1111111111111111111111111111111111111111".to_string(),
+                author_name: None,
+                tags: vec![],
+                image: None,
+                blockchain_transaction_hash: None,
+                blockchain_transaction_proof: None,
+            },
+            tx_hash: format!("0x{}", "a".repeat(64)),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let config = QueueConfig { retry_delay: Duration::from_secs(5), ..config() };
+        assert_eq!(config.backoff(1), Duration::from_secs(5));
+        assert_eq!(config.backoff(3), Duration::from_secs(20));
+        assert_eq!(config.backoff(40), Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn test_failing_task_is_retried_then_dead_lettered() {
+        let store: Arc<dyn ForumStore> = Arc::new(MemoryStore::new());
+        let queue = Arc::new(AsyncQueueService::with_config(store.clone(), config()));
+        let service = Arc::new(ForumService::from_parts(store.clone(), None, None, Some(queue.clone())));
+        service.start_queue_workers();
+
+        let task_id = queue.submit(post_task()).await.unwrap();
+        let mut task = None;
+        for _ in 0..200 {
+            task = queue.get_task_status(&task_id).await;
+            if matches!(task, Some(QueuedTask { status: TaskStatus::Failed(_), .. })) {
+                break;
             }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
-        
-        Err("Blockchain service or database service unavailable".to_string())
+
+        let task = task.unwrap();
+        assert_eq!(task.status, TaskStatus::Failed("Blockchain service unavailable".to_string()));
+        assert_eq!(task.attempts, 3);
+        // Dead-lettered tasks are not claimed again until replayed
+        assert!(store.claim_task(Duration::from_secs(60)).await.unwrap().is_none());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_only_pending_transactions_and_transient_failures_are_retried() {
+        let pending = TransactionVerificationError::Pending("Transaction has not been included in a block yet".to_string());
+        assert!(matches!(verification_task_error(pending), TaskError::Retryable(_)));
+        let mismatch = TransactionVerificationError::Invalid("Transaction sender mismatch".to_string());
+        assert!(matches!(verification_task_error(mismatch), TaskError::Permanent(_)));
+
+        let held = creation_task_result(Box::new(SpamError::Held { held_id: "h1".to_string() })).unwrap();
+        assert_eq!(held["held_id"], "h1");
+        let duplicate = ContentActionError::Invalid("You have posted the same content within the last 5 minutes".to_string());
+        assert!(matches!(creation_task_result(Box::new(duplicate)), Err(TaskError::Permanent(_))));
+        assert!(matches!(creation_task_result(Box::new(ContentActionError::Internal("Database error".to_string()))), Err(TaskError::Retryable(_))));
+        assert!(matches!(creation_task_result("Irys upload failed".into()), Err(TaskError::Retryable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_queue_rejects_tasks_beyond_max_size() {
+        let store = MemoryStore::new();
+        assert!(store.enqueue_task(&QueuedTask::new(post_task()), 2).await.unwrap());
+        assert!(store.enqueue_task(&QueuedTask::new(post_task()), 2).await.unwrap());
+        assert!(!store.enqueue_task(&QueuedTask::new(post_task()), 2).await.unwrap());
+
+        let claimed = store.claim_task(Duration::from_secs(60)).await.unwrap().unwrap();
+        assert_eq!((claimed.status, claimed.attempts), (TaskStatus::Processing, 1));
+        store.complete_task(&claimed.id, &serde_json::json!({})).await.unwrap();
+        assert!(store.enqueue_task(&QueuedTask::new(post_task()), 2).await.unwrap());
+        assert_eq!(store.evict_completed_tasks(Utc::now()).await.unwrap(), 1);
+    }
+}
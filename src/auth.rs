@@ -0,0 +1,444 @@
+use crate::cache::CacheService;
+use crate::models::ApiResponse;
+use actix_web::{dev::Payload, error::InternalError, web, FromRequest, HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use ethers::types::{Address, Signature};
+use futures::future::{ready, Ready};
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::str::FromStr;
+use std::sync::Arc;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SIWE_HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+const NONCE_TTL_SECONDS: i64 = 600;
+/// Allowed clock skew for an `Issued At` slightly in the future
+const ISSUED_AT_SKEW_SECONDS: i64 = 60;
+/// Irys testnet, the chain the forum contract is deployed on
+const DEFAULT_CHAIN_ID: u64 = 1270;
+const TOKEN_VERSION: &str = "v1";
+
+/// Parsed EIP-4361 (Sign-In with Ethereum) message
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+}
+
+impl SiweMessage {
+    /// Parse the plain-text EIP-4361 message format
+    pub fn parse(message: &str) -> Result<Self, String> {
+        let mut lines = message.lines();
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(SIWE_HEADER_SUFFIX))
+            .filter(|domain| !domain.is_empty())
+            .ok_or("Invalid SIWE message header")?
+            .to_string();
+
+        let address = lines.next().ok_or("Missing address line")?.trim().to_string();
+        Address::from_str(&address).map_err(|_| "Invalid address in SIWE message".to_string())?;
+
+        let mut statement_lines = Vec::new();
+        let mut uri = None;
+        let mut version = None;
+        let mut chain_id = None;
+        let mut nonce = None;
+        let mut issued_at = None;
+        let mut expiration_time = None;
+        let mut not_before = None;
+
+        for line in lines {
+            if line.trim().is_empty() || line == "Resources:" || line.starts_with("- ") {
+                continue;
+            }
+
+            let field = line.split_once(": ");
+            match field {
+                Some(("URI", value)) => uri = Some(value.to_string()),
+                Some(("Version", value)) => version = Some(value.to_string()),
+                Some(("Chain ID", value)) => {
+                    chain_id = Some(value.parse::<u64>().map_err(|_| "Invalid Chain ID".to_string())?)
+                }
+                Some(("Nonce", value)) => nonce = Some(value.to_string()),
+                Some(("Issued At", value)) => issued_at = Some(parse_timestamp(value)?),
+                Some(("Expiration Time", value)) => expiration_time = Some(parse_timestamp(value)?),
+                Some(("Not Before", value)) => not_before = Some(parse_timestamp(value)?),
+                Some(("Request ID", _)) => {}
+                _ if uri.is_none() => statement_lines.push(line.to_string()),
+                _ => return Err(format!("Unexpected line in SIWE message: {}", line)),
+            }
+        }
+
+        Ok(Self {
+            domain,
+            address,
+            statement: if statement_lines.is_empty() { None } else { Some(statement_lines.join("\n")) },
+            uri: uri.ok_or("Missing URI")?,
+            version: version.ok_or("Missing Version")?,
+            chain_id: chain_id.ok_or("Missing Chain ID")?,
+            nonce: nonce.ok_or("Missing Nonce")?,
+            issued_at: issued_at.ok_or("Missing Issued At")?,
+            expiration_time,
+            not_before,
+        })
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid timestamp: {}", value))
+}
+
+/// Signed session token handed out after a successful SIWE login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionToken {
+    pub token: String,
+    pub address: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Issues SIWE nonces and HMAC-signed session tokens keyed by SECRET_KEY.
+/// Nonces live in Redis when a cache is configured, so any instance can
+/// complete a login, and in process memory otherwise or when Redis is unreachable.
+pub struct AuthService {
+    secret: Vec<u8>,
+    /// Domain SIWE messages must be addressed to (`SIWE_DOMAIN`)
+    domain: String,
+    /// Chain SIWE messages must be signed for (`SIWE_CHAIN_ID`)
+    chain_id: u64,
+    session_ttl: Duration,
+    cache: Option<Arc<CacheService>>,
+    nonces: DashMap<String, DateTime<Utc>>,
+}
+
+impl AuthService {
+    pub fn new(cache: Option<Arc<CacheService>>) -> Self {
+        let secret = match std::env::var("SECRET_KEY") {
+            Ok(key) if !key.trim().is_empty() => key.into_bytes(),
+            _ => {
+                warn!("⚠️ SECRET_KEY not set, using a random key - sessions will not survive a restart");
+                rand::thread_rng().gen::<[u8; 32]>().to_vec()
+            }
+        };
+
+        let domain = match std::env::var("SIWE_DOMAIN") {
+            Ok(domain) if !domain.trim().is_empty() => domain.trim().to_string(),
+            _ => {
+                let domain = format!("localhost:{}", std::env::var("PORT").unwrap_or_else(|_| "8080".to_string()));
+                warn!("⚠️ SIWE_DOMAIN not set, only sign-ins addressed to {} are accepted", domain);
+                domain
+            }
+        };
+        let chain_id = std::env::var("SIWE_CHAIN_ID")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CHAIN_ID);
+
+        let session_ttl = std::env::var("SESSION_TTL")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse::<i64>()
+            .unwrap_or(86400);
+
+        info!("🔐 Auth service initialized - session TTL: {}s, domain: {}, chain ID: {}", session_ttl, domain, chain_id);
+        Self {
+            cache,
+            ..Self::with_secret(secret, domain, chain_id, Duration::seconds(session_ttl))
+        }
+    }
+
+    pub fn with_secret(secret: Vec<u8>, domain: String, chain_id: u64, session_ttl: Duration) -> Self {
+        Self {
+            secret,
+            domain,
+            chain_id,
+            session_ttl,
+            cache: None,
+            nonces: DashMap::new(),
+        }
+    }
+
+    /// Issue a single-use nonce for a SIWE message
+    pub async fn issue_nonce(&self) -> String {
+        let nonce: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(17)
+            .map(char::from)
+            .collect();
+
+        if let Some(cache) = &self.cache {
+            match cache.store_nonce(&nonce, NONCE_TTL_SECONDS as u64).await {
+                Ok(()) => return nonce,
+                Err(e) => warn!("⚠️ Failed to store nonce in Redis, keeping it in process: {}", e),
+            }
+        }
+
+        let now = Utc::now();
+        self.nonces.retain(|_, issued_at| now.signed_duration_since(*issued_at).num_seconds() < NONCE_TTL_SECONDS);
+        self.nonces.insert(nonce.clone(), now);
+        nonce
+    }
+
+    async fn consume_nonce(&self, nonce: &str) -> bool {
+        if let Some(cache) = &self.cache {
+            match cache.consume_nonce(nonce).await {
+                Ok(true) => return true,
+                // Fall through: the nonce may have been issued in process while Redis was down
+                Ok(false) => {}
+                Err(e) => warn!("⚠️ Failed to consume nonce in Redis, checking in-process nonces: {}", e),
+            }
+        }
+
+        match self.nonces.remove(nonce) {
+            Some((_, issued_at)) => Utc::now().signed_duration_since(issued_at).num_seconds() < NONCE_TTL_SECONDS,
+            None => false,
+        }
+    }
+
+    /// Verify a signed SIWE message and issue a session token for the recovered signer.
+    /// The message must be addressed to the configured domain and chain and be no older than a nonce.
+    pub async fn verify_login(&self, message: &str, signature: &str) -> Result<SessionToken, String> {
+        let siwe = SiweMessage::parse(message)?;
+
+        if siwe.domain != self.domain {
+            return Err(format!("SIWE domain mismatch: expected {}", self.domain));
+        }
+        if siwe.chain_id != self.chain_id {
+            return Err(format!("SIWE chain ID mismatch: expected {}", self.chain_id));
+        }
+
+        if siwe.version != "1" {
+            return Err("Unsupported SIWE version".to_string());
+        }
+
+        let now = Utc::now();
+        if now.signed_duration_since(siwe.issued_at).num_seconds() >= NONCE_TTL_SECONDS {
+            return Err("SIWE message is too old, please sign in again".to_string());
+        }
+        if siwe.issued_at.signed_duration_since(now).num_seconds() > ISSUED_AT_SKEW_SECONDS {
+            return Err("SIWE message is issued in the future".to_string());
+        }
+        if let Some(expiration_time) = siwe.expiration_time {
+            if expiration_time <= now {
+                return Err("SIWE message has expired".to_string());
+            }
+        }
+        if let Some(not_before) = siwe.not_before {
+            if not_before > now {
+                return Err("SIWE message is not yet valid".to_string());
+            }
+        }
+
+        let signature = Signature::from_str(signature.trim_start_matches("0x"))
+            .map_err(|e| format!("Invalid signature: {}", e))?;
+        let signer = signature
+            .recover(message)
+            .map_err(|e| format!("Failed to recover signer: {}", e))?;
+        let claimed: Address = siwe.address.parse().map_err(|_| "Invalid address in SIWE message".to_string())?;
+        if signer != claimed {
+            return Err("Signature does not match SIWE address".to_string());
+        }
+
+        if !self.consume_nonce(&siwe.nonce).await {
+            return Err("Invalid or expired nonce".to_string());
+        }
+
+        let token = self.issue_token(&format!("{:?}", signer));
+        info!("🔐 SIWE login succeeded: {}", token.address);
+        Ok(token)
+    }
+
+    /// Issue a session token for an already authenticated address
+    pub fn issue_token(&self, address: &str) -> SessionToken {
+        let address = address.to_lowercase();
+        let expires_at = Utc::now() + self.session_ttl;
+        let payload = format!("{}.{}.{}", TOKEN_VERSION, address, expires_at.timestamp());
+        let signature = hex::encode(self.sign(&payload));
+
+        SessionToken {
+            token: format!("{}.{}", payload, signature),
+            address,
+            expires_at,
+        }
+    }
+
+    /// Verify a session token and return the authenticated address
+    pub fn verify_token(&self, token: &str) -> Result<String, String> {
+        let (payload, signature) = token.rsplit_once('.').ok_or("Malformed session token")?;
+        let signature = hex::decode(signature).map_err(|_| "Malformed session token".to_string())?;
+
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).map_err(|_| "Invalid session token".to_string())?;
+
+        let mut parts = payload.split('.');
+        let (version, address, expires) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(version), Some(address), Some(expires), None) => (version, address, expires),
+            _ => return Err("Malformed session token".to_string()),
+        };
+
+        if version != TOKEN_VERSION {
+            return Err("Unsupported session token version".to_string());
+        }
+
+        let expires = expires.parse::<i64>().map_err(|_| "Malformed session token".to_string())?;
+        if expires <= Utc::now().timestamp() {
+            return Err("Session expired".to_string());
+        }
+
+        Ok(address.to_string())
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length")
+    }
+
+    fn sign(&self, payload: &str) -> Vec<u8> {
+        let mut mac = self.mac();
+        mac.update(payload.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+/// Extractor that resolves the caller's address from an `Authorization: Bearer` session token
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub address: String,
+}
+
+impl AuthenticatedUser {
+    fn from_http_request(req: &HttpRequest) -> Result<Self, actix_web::Error> {
+        let auth_service = req
+            .app_data::<web::Data<AuthService>>()
+            .ok_or_else(|| unauthorized("Authentication is not configured"))?;
+
+        let token = req
+            .headers()
+            .get(actix_web::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("Missing session token, please sign in"))?;
+
+        let address = auth_service.verify_token(token.trim()).map_err(unauthorized)?;
+        Ok(Self { address })
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::from_http_request(req))
+    }
+}
+
+fn unauthorized(message: impl Into<String>) -> actix_web::Error {
+    let message = message.into();
+    let response = HttpResponse::Unauthorized().json(ApiResponse::<()>::error(message.clone()));
+    InternalError::from_response(message, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::utils::hash_message;
+
+    fn siwe_message_with(domain: &str, address: &str, chain_id: u64, nonce: &str, issued_at: DateTime<Utc>) -> String {
+        format!(
+            "{} wants you to sign in with your Ethereum account:\n{}\n\nSign in to Irys Forum\n\nURI: http://{}\nVersion: 1\nChain ID: {}\nNonce: {}\nIssued At: {}",
+            domain,
+            address,
+            domain,
+            chain_id,
+            nonce,
+            issued_at.to_rfc3339()
+        )
+    }
+
+    fn siwe_message(address: &str, nonce: &str) -> String {
+        siwe_message_with("localhost:8080", address, 1270, nonce, Utc::now())
+    }
+
+    fn test_auth() -> AuthService {
+        AuthService::with_secret(b"test-secret".to_vec(), "localhost:8080".to_string(), 1270, Duration::hours(1))
+    }
+
+    fn sign(wallet: &LocalWallet, message: &str) -> String {
+        wallet.sign_hash(hash_message(message)).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_siwe_message() {
+        let message = siwe_message("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", "abc123");
+        let parsed = SiweMessage::parse(&message).unwrap();
+        assert_eq!(parsed.domain, "localhost:8080");
+        assert_eq!(parsed.statement.as_deref(), Some("Sign in to Irys Forum"));
+        assert_eq!(parsed.chain_id, 1270);
+        assert_eq!(parsed.nonce, "abc123");
+        assert!(SiweMessage::parse("hello world").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_login_and_token_roundtrip() {
+        let auth = test_auth();
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        let address = ethers::utils::to_checksum(&wallet.address(), None);
+
+        let message = siwe_message(&address, &auth.issue_nonce().await);
+        let session = auth.verify_login(&message, &sign(&wallet, &message)).await.unwrap();
+        assert_eq!(session.address, format!("{:?}", wallet.address()));
+        assert_eq!(auth.verify_token(&session.token).unwrap(), session.address);
+
+        // Nonces are single use
+        assert!(auth.verify_login(&message, &sign(&wallet, &message)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_signer_and_tampered_token() {
+        let auth = test_auth();
+        let wallet = LocalWallet::new(&mut rand::thread_rng());
+        let other = LocalWallet::new(&mut rand::thread_rng());
+        let address = format!("{:?}", wallet.address());
+
+        let message = siwe_message(&address, &auth.issue_nonce().await);
+        assert!(auth.verify_login(&message, &sign(&other, &message)).await.is_err());
+
+        // Wrong domain, wrong chain, stale or future-dated messages; none of them consume the nonce
+        let nonce = auth.issue_nonce().await;
+        for message in [
+            siwe_message_with("evil.example", &address, 1270, &nonce, Utc::now()),
+            siwe_message_with("localhost:8080", &address, 1, &nonce, Utc::now()),
+            siwe_message_with("localhost:8080", &address, 1270, &nonce, Utc::now() - Duration::seconds(NONCE_TTL_SECONDS + 1)),
+            siwe_message_with("localhost:8080", &address, 1270, &nonce, Utc::now() + Duration::hours(1)),
+        ] {
+            assert!(auth.verify_login(&message, &sign(&wallet, &message)).await.is_err());
+        }
+        let message = siwe_message_with("localhost:8080", &address, 1270, &nonce, Utc::now());
+        assert!(auth.verify_login(&message, &sign(&wallet, &message)).await.is_ok());
+
+        let token = auth.issue_token(&format!("{:?}", wallet.address())).token;
+        let tampered = token.replacen(&format!("{:?}", wallet.address()), &format!("{:?}", other.address()), 1);
+        assert!(auth.verify_token(&tampered).is_err());
+
+        let other_auth = AuthService::with_secret(b"other-secret".to_vec(), "localhost:8080".to_string(), 1270, Duration::hours(1));
+        assert!(other_auth.verify_token(&token).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use dotenv::dotenv;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Row};
+use std::env;
+use std::process;
+
+#[path = "../migrations.rs"]
+#[allow(dead_code)]
+mod migrations;
+
+const USAGE: &str = "Usage: admin <command>
+
+Commands:
+  migrate               Apply pending database migrations
+  status                Show applied and pending migrations
+  dead-letters          List async tasks that exhausted their retries
+  replay <task_id|all>  Re-queue dead-lettered tasks with a fresh retry budget;
+                        refused if it would take the queue past QUEUE_MAX_SIZE
+  set-role <address> <user|moderator|admin>
+                        Change a user's role; moderators can delete any post or comment
+                        and work the report queue, admins can also change roles over the API
+
+Reads DATABASE_URL from the environment (or .env).";
+
+/// Recorded as the moderator on moderation_log rows written by this tool
+const CLI_MODERATOR: &str = "admin-cli";
+
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+    env_logger::init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = args.first().cloned().unwrap_or_default();
+    let valid = match command.as_str() {
+        "migrate" | "status" | "dead-letters" => args.len() == 1,
+        "replay" => args.len() == 2,
+        "set-role" => args.len() == 3 && ["user", "moderator", "admin"].contains(&args[2].as_str()),
+        _ => false,
+    };
+    if !valid {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let database_url = env::var("DATABASE_URL").unwrap_or_else(|_| {
+        eprintln!("DATABASE_URL is not set");
+        process::exit(1);
+    });
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to connect to database: {}", e);
+            process::exit(1);
+        });
+
+    match command.as_str() {
+        "migrate" => match migrations::run(&pool).await {
+            Ok(applied) if applied.is_empty() => println!("Database is up to date (version {})", migrations::latest_version()),
+            Ok(applied) => {
+                for version in applied {
+                    println!("Applied migration {:04}", version);
+                }
+            }
+            Err(e) => {
+                eprintln!("Migration failed: {}", e);
+                process::exit(1);
+            }
+        },
+        "dead-letters" => list_dead_letters(&pool).await,
+        "replay" => replay_dead_letters(&pool, &args[1]).await,
+        "set-role" => set_role(&pool, &args[1], &args[2]).await,
+        _ => {
+            let applied = migrations::applied_migrations(&pool).await.unwrap_or_else(|e| {
+                eprintln!("Failed to read schema_migrations: {}", e);
+                process::exit(1);
+            });
+
+            for migration in migrations::MIGRATIONS {
+                match applied.iter().find(|a| a.version == migration.version) {
+                    Some(a) => println!("{:04}_{}  applied {}", migration.version, migration.name, a.applied_at),
+                    None => println!("{:04}_{}  pending", migration.version, migration.name),
+                }
+            }
+            for unknown in applied.iter().filter(|a| !migrations::MIGRATIONS.iter().any(|m| m.version == a.version)) {
+                println!("{:04}_{}  applied {} (unknown to this build)", unknown.version, unknown.name, unknown.applied_at);
+            }
+        }
+    }
+}
+
+async fn list_dead_letters(pool: &PgPool) {
+    let rows = sqlx::query(
+        "SELECT id, kind, attempts, last_error, completed_at FROM async_tasks WHERE status = 'dead' ORDER BY completed_at DESC"
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap_or_else(|e| {
+        eprintln!("Failed to read async_tasks: {}", e);
+        process::exit(1);
+    });
+
+    if rows.is_empty() {
+        println!("No dead-lettered tasks");
+    }
+    for row in rows {
+        let failed_at: Option<chrono::DateTime<chrono::Utc>> = row.get("completed_at");
+        println!(
+            "{}  {}  attempts={}  failed {}  {}",
+            row.get::<String, _>("id"),
+            row.get::<String, _>("kind"),
+            row.get::<i32, _>("attempts"),
+            failed_at.map(|at| at.to_string()).unwrap_or_default(),
+            row.get::<Option<String>, _>("last_error").unwrap_or_default(),
+        );
+    }
+}
+
+async fn replay_dead_letters(pool: &PgPool, task_id: &str) {
+    let fail = |e: sqlx::Error| -> ! {
+        eprintln!("Failed to replay tasks: {}", e);
+        process::exit(1);
+    };
+    // Same limit and default the server's queue applies to new submissions
+    let max_size = env::var("QUEUE_MAX_SIZE").ok().and_then(|v| v.parse::<i64>().ok()).unwrap_or(1000);
+
+    let mut tx = pool.begin().await.unwrap_or_else(|e| fail(e));
+    let counts = sqlx::query(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE status IN ('pending', 'processing')) AS queued,
+               COUNT(*) FILTER (WHERE status = 'dead' AND ($1 = 'all' OR id = $1)) AS replayable
+        FROM async_tasks
+        "#
+    )
+    .bind(task_id)
+    .fetch_one(&mut *tx)
+    .await
+    .unwrap_or_else(|e| fail(e));
+    let queued: i64 = counts.get("queued");
+    let replayable: i64 = counts.get("replayable");
+
+    if replayable == 0 && task_id != "all" {
+        eprintln!("No dead-lettered task with ID {}", task_id);
+        process::exit(1);
+    }
+    if queued + replayable > max_size {
+        eprintln!(
+            "Refusing to re-queue {} task(s): {} already queued and QUEUE_MAX_SIZE is {}",
+            replayable, queued, max_size
+        );
+        process::exit(1);
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE async_tasks
+        SET status = 'pending', attempts = 0, run_at = NOW(), completed_at = NULL, updated_at = NOW()
+        WHERE status = 'dead' AND ($1 = 'all' OR id = $1)
+        "#
+    )
+    .bind(task_id)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|e| fail(e));
+
+    tx.commit().await.unwrap_or_else(|e| fail(e));
+    println!("Re-queued {} task(s); a running server will pick them up", result.rows_affected());
+}
+
+async fn set_role(pool: &PgPool, address: &str, role: &str) {
+    let fail = |e: sqlx::Error| -> ! {
+        eprintln!("Failed to update role: {}", e);
+        process::exit(1);
+    };
+
+    let mut tx = pool.begin().await.unwrap_or_else(|e| fail(e));
+    let result = sqlx::query("UPDATE users SET role = $2 WHERE LOWER(ethereum_address) = LOWER($1)")
+        .bind(address)
+        .bind(role)
+        .execute(&mut *tx)
+        .await
+        .unwrap_or_else(|e| fail(e));
+
+    if result.rows_affected() == 0 {
+        eprintln!("No user with address {}; accounts are created on the first post, comment or username registration", address);
+        process::exit(1);
+    }
+
+    // Same row shape as the API's set_role entries, so CLI changes show up in the audit trail too
+    sqlx::query(
+        r#"
+        INSERT INTO moderation_log (moderator_address, action, target_type, target_id, reason)
+        VALUES ($1, 'set_role', 'user', LOWER($2), $3)
+        "#
+    )
+    .bind(CLI_MODERATOR)
+    .bind(address)
+    .bind(role)
+    .execute(&mut *tx)
+    .await
+    .unwrap_or_else(|e| fail(e));
+
+    tx.commit().await.unwrap_or_else(|e| fail(e));
+    println!("{} is now {}", address, role);
+}
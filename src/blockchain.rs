@@ -3,7 +3,6 @@ use ethers::providers::{Http, Provider};
 use ethers::types::{Address, U256, U64, TxHash};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use futures::StreamExt;
 
 
 abigen!(
@@ -14,6 +13,7 @@ abigen!(
         function likePost(uint256 _postId) external
         function likeComment(uint256 _commentId) external
         function getPost(uint256 _postId) external view returns (tuple(uint256 id, address author, string title, string content, string[] tags, uint256 timestamp, uint256 likes, uint256 comments, bool qualityPost, string irysTransactionId))
+        function getComment(uint256 _commentId) external view returns (tuple(uint256 id, uint256 postId, address author, string content, uint256 timestamp, uint256 likes, uint256 parentId, string irysTransactionId))
         function getUser(address _user) external view returns (tuple(uint256 postsCount, uint256 commentsCount, uint256 totalLikesReceived, uint256 reputationScore, uint256 totalEarned, uint256 totalSpent, bool isMiner, uint256 lastActivityTime))
         function postCost() external view returns (uint256)
         function commentCost() external view returns (uint256)
@@ -26,49 +26,32 @@ abigen!(
         event PostCreated(uint256 indexed postId, address indexed author, string title, uint256 reward)
         event CommentCreated(uint256 indexed commentId, uint256 indexed postId, address indexed author, uint256 reward)
         event PostLiked(uint256 indexed postId, address indexed liker, address indexed author, uint256 reward)
+        event CommentLiked(uint256 indexed commentId, address indexed liker, address indexed author, uint256 reward)
+        event QualityPostDetected(uint256 indexed postId, address indexed author, uint256 bonusPoints)
+        event PointsEarned(address indexed user, uint256 points, string reason)
         event MiningRewardDistributed(address indexed miner, uint256 reward)
+        event ReputationUpdated(address indexed user, uint256 newReputation)
         event UsernameRegistered(address indexed user, string username)
     ]"#
 );
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContractConfig {
-    pub network_name: String,
-    pub chain_id: u64,
-    pub contract_address: String,
-    pub rpc_url: String,
-}
-
 #[derive(Clone)]
 pub struct BlockchainService {
     provider: Arc<Provider<Http>>,
     contract_address: Address,
-    config: ContractConfig,
 }
 
 impl BlockchainService {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let config = ContractConfig {
-            network_name: "Irys Testnet".to_string(),
-            chain_id: 1270,
-            contract_address: std::env::var("CONTRACT_ADDRESS").unwrap_or_default(),
-            rpc_url: "https://testnet-rpc.irys.xyz/v1/execution-rpc".to_string(),
-        };
-
-        let provider = Provider::<Http>::try_from(&config.rpc_url)?;
-        let contract_address = config.contract_address.parse()?;
+        let provider = Provider::<Http>::try_from("https://testnet-rpc.irys.xyz/v1/execution-rpc")?;
+        let contract_address = std::env::var("CONTRACT_ADDRESS").unwrap_or_default().parse()?;
 
         Ok(Self {
             provider: Arc::new(provider),
             contract_address,
-            config,
         })
     }
 
-    pub fn get_contract_address(&self) -> &Address {
-        &self.contract_address
-    }
-
     /// Get post cost (wei)
     pub async fn get_post_cost(&self) -> Result<U256, Box<dyn std::error::Error>> {
         let contract = IrysForum::new(self.contract_address, self.provider.clone());
@@ -102,21 +85,20 @@ impl BlockchainService {
         })
     }
 
-    /// Get on-chain user information
-    pub async fn get_blockchain_user(&self, address: &str) -> Result<BlockchainUser, Box<dyn std::error::Error>> {
+    /// Get on-chain comment information
+    pub async fn get_blockchain_comment(&self, comment_id: U256) -> Result<BlockchainComment, Box<dyn std::error::Error>> {
         let contract = IrysForum::new(self.contract_address, self.provider.clone());
-        let user_address: Address = address.parse()?;
-        let user_data = contract.get_user(user_address).call().await?;
-        
-        Ok(BlockchainUser {
-            posts_count: user_data.0,
-            comments_count: user_data.1,
-            total_likes_received: user_data.2,
-            reputation_score: user_data.3,
-            total_earned: user_data.4,
-            total_spent: user_data.5,
-            is_miner: user_data.6,
-            last_activity_time: user_data.7,
+        let comment_data = contract.get_comment(comment_id).call().await?;
+
+        Ok(BlockchainComment {
+            id: comment_data.0,
+            post_id: comment_data.1,
+            author: format!("{:?}", comment_data.2),
+            content: comment_data.3,
+            timestamp: comment_data.4,
+            likes: comment_data.5,
+            parent_id: comment_data.6,
+            irys_transaction_id: comment_data.7,
         })
     }
 
@@ -143,83 +125,70 @@ impl BlockchainService {
         }
     }
 
-    /// Build createPost transaction payload for frontend
-    pub fn build_create_post_tx(&self, title: &str, content: &str, tags: Vec<String>, irys_tx_id: &str, value: U256) -> String {
-        
-        format!(
-            r#"{{
-                "to": "{}",
-                "value": "0x{:x}",
-                "data": "{}",
-                "gasLimit": "0x47b760"
-            }}"#,
-            self.contract_address,
-            value,
-            self.encode_create_post_call(title, content, tags, irys_tx_id)
-        )
+    /// Get the address that registered `username` on-chain
+    pub async fn get_address_by_username_on_chain(&self, username: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let contract = IrysForum::new(self.contract_address, self.provider.clone());
+
+        let address = contract.get_address_by_username(username.to_string()).call().await?;
+        if address.is_zero() {
+            Ok(None)
+        } else {
+            Ok(Some(format!("{:?}", address)))
+        }
     }
 
-    /// Encode createPost call data
-    fn encode_create_post_call(&self, _title: &str, _content: &str, _tags: Vec<String>, _irys_tx_id: &str) -> String {
-        
-        
-        "0x1234abcd".to_string()
+    /// Get the latest block number
+    pub async fn get_block_number(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        Ok(self.provider.get_block_number().await?.as_u64())
     }
 
-    /// Listen to on-chain events
-    pub async fn listen_to_events(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let contract = IrysForum::new(self.contract_address, self.provider.clone());
-        
-        
-        let events = contract.events().from_block(0u64);
-        let mut stream = events.stream().await?;
-        
-        while let Some(event) = stream.next().await {
-            match event {
-                Ok(event) => {
-                    println!("Received event: {:?}", event);
-                    
-                }
-                Err(e) => {
-                    eprintln!("Event listener error: {}", e);
-                }
-            }
-        }
-        
-        Ok(())
+    /// Get the hash of a block, or None if the node does not know the block
+    pub async fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, Box<dyn std::error::Error>> {
+        let block = self.provider.get_block(block_number).await?;
+        Ok(block.and_then(|block| block.hash))
+    }
+
+    /// Fetch all forum contract logs in an inclusive block range
+    pub async fn get_contract_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<Log>, Box<dyn std::error::Error>> {
+        let filter = Filter::new()
+            .address(self.contract_address)
+            .from_block(from_block)
+            .to_block(to_block);
+        Ok(self.provider.get_logs(&filter).await?)
     }
     
     /// Verify transaction exists on chain
-    pub async fn verify_transaction_exists(&self, tx_hash: &str) -> Result<TransactionDetails, Box<dyn std::error::Error>> {
-        let tx_hash: TxHash = tx_hash.parse()?;
+    pub async fn verify_transaction_exists(&self, tx_hash: &str) -> Result<TransactionDetails, TransactionVerificationError> {
+        let tx_hash: TxHash = tx_hash.parse()
+            .map_err(|_| TransactionVerificationError::Invalid("Invalid transaction hash".to_string()))?;
         
         
-        let receipt = self.provider.get_transaction_receipt(tx_hash).await?;
+        let receipt = self.provider.get_transaction_receipt(tx_hash).await.map_err(node_error)?;
         if receipt.is_none() {
-            return Err("Transaction does not exist or is not yet confirmed".into());
+            return Err(TransactionVerificationError::Pending("Transaction does not exist or is not yet confirmed".to_string()));
         }
         
         let receipt = receipt.unwrap();
         
         
-        let transaction = self.provider.get_transaction(tx_hash).await?;
+        let transaction = self.provider.get_transaction(tx_hash).await.map_err(node_error)?;
         if transaction.is_none() {
-            return Err("Unable to fetch transaction details".into());
+            return Err(TransactionVerificationError::Pending("Unable to fetch transaction details".to_string()));
         }
         
         let transaction = transaction.unwrap();
         
         
         let block = if let Some(block_number) = receipt.block_number {
-            self.provider.get_block(block_number).await?
+            self.provider.get_block(block_number).await.map_err(node_error)?
         } else {
-            return Err("Transaction has not been included in a block yet".into());
+            return Err(TransactionVerificationError::Pending("Transaction has not been included in a block yet".to_string()));
         };
         
         let block_timestamp = if let Some(block) = &block {
             block.timestamp
         } else {
-            return Err("Unable to fetch block information".into());
+            return Err(TransactionVerificationError::Pending("Unable to fetch block information".to_string()));
         };
         
         Ok(TransactionDetails {
@@ -235,49 +204,46 @@ impl BlockchainService {
         })
     }
     
-   
-    pub async fn verify_post_transaction(&self, tx_hash: &str, expected_sender: &str) -> Result<PostTransactionVerification, Box<dyn std::error::Error>> {
+
+    /// Verify a createPost transaction and decode its PostCreated event
+    pub async fn verify_post_transaction(&self, tx_hash: &str, expected_sender: &str) -> Result<PostTransactionVerification, TransactionVerificationError> {
         let tx_details = self.verify_transaction_exists(tx_hash).await?;
         
        
         if tx_details.status != U64::from(1) {
-            return Err("Transaction execution failed".into());
+            return Err(TransactionVerificationError::Invalid("Transaction execution failed".to_string()));
         }
         
       
-        let expected_sender: Address = expected_sender.parse()?;
-        let actual_sender: Address = tx_details.from.parse()?;
+        let expected_sender: Address = expected_sender.parse()
+            .map_err(|_| TransactionVerificationError::Invalid("Invalid sender address".to_string()))?;
+        let actual_sender: Address = tx_details.from.parse()
+            .map_err(|_| TransactionVerificationError::Invalid("Invalid transaction sender".to_string()))?;
         if actual_sender != expected_sender {
-            return Err("Transaction sender mismatch".into());
+            return Err(TransactionVerificationError::Invalid("Transaction sender mismatch".to_string()));
         }
         
         
         if let Some(to) = &tx_details.to {
-            let to_address: Address = to.parse()?;
+            let to_address: Address = to.parse()
+                .map_err(|_| TransactionVerificationError::Invalid("Invalid transaction target".to_string()))?;
             if to_address != self.contract_address {
-                return Err("Transaction target contract address incorrect".into());
+                return Err(TransactionVerificationError::Invalid("Transaction target contract address incorrect".to_string()));
             }
         } else {
-            return Err("Transaction has no target address".into());
+            return Err(TransactionVerificationError::Invalid("Transaction has no target address".to_string()));
         }
         
-
-        let has_post_event = tx_details.logs.iter().any(|log| {
-            log.address == self.contract_address
-        });
-        
-        if !has_post_event {
-            return Err("No contract event found in transaction".into());
+        let event: PostCreatedFilter = decode_contract_event(self.contract_address, &tx_details.logs)
+            .ok_or_else(|| TransactionVerificationError::Invalid("No PostCreated event found in transaction".to_string()))?;
+        if event.author != expected_sender {
+            return Err(TransactionVerificationError::Invalid("PostCreated event author mismatch".to_string()));
         }
         
-
-        let post_id = U256::from(1);
-        let points_earned = U256::from(100); 
-        
       
-        let required_cost = self.get_post_cost().await?;
+        let required_cost = self.get_post_cost().await.map_err(node_error)?;
         if tx_details.value < required_cost {
-            return Err("Insufficient payment amount".into());
+            return Err(TransactionVerificationError::Invalid("Insufficient payment amount".to_string()));
         }
         
         Ok(PostTransactionVerification {
@@ -285,58 +251,59 @@ impl BlockchainService {
             sender: tx_details.from,
             block_number: tx_details.block_number.as_u64(),
             block_timestamp: tx_details.block_timestamp,
-            post_id,
-            points_earned,
+            post_id: event.post_id,
+            points_earned: event.reward,
             value_paid: tx_details.value,
             gas_used: tx_details.gas_used,
             verified: true,
         })
     }
     
- 
-    pub async fn verify_comment_transaction(&self, tx_hash: &str, expected_sender: &str) -> Result<CommentTransactionVerification, Box<dyn std::error::Error>> {
+
+    /// Verify a createComment transaction. The decoded event must reference the
+    /// on-chain post `expected_post_id`.
+    pub async fn verify_comment_transaction(&self, tx_hash: &str, expected_sender: &str, expected_post_id: U256) -> Result<CommentTransactionVerification, TransactionVerificationError> {
         let tx_details = self.verify_transaction_exists(tx_hash).await?;
         
       
         if tx_details.status != U64::from(1) {
-            return Err("Transaction execution failed".into());
+            return Err(TransactionVerificationError::Invalid("Transaction execution failed".to_string()));
         }
         
     
-        let expected_sender: Address = expected_sender.parse()?;
-        let actual_sender: Address = tx_details.from.parse()?;
+        let expected_sender: Address = expected_sender.parse()
+            .map_err(|_| TransactionVerificationError::Invalid("Invalid sender address".to_string()))?;
+        let actual_sender: Address = tx_details.from.parse()
+            .map_err(|_| TransactionVerificationError::Invalid("Invalid transaction sender".to_string()))?;
         if actual_sender != expected_sender {
-            return Err("Transaction sender mismatch".into());
+            return Err(TransactionVerificationError::Invalid("Transaction sender mismatch".to_string()));
         }
         
       
         if let Some(to) = &tx_details.to {
-            let to_address: Address = to.parse()?;
+            let to_address: Address = to.parse()
+                .map_err(|_| TransactionVerificationError::Invalid("Invalid transaction target".to_string()))?;
             if to_address != self.contract_address {
-                return Err("Transaction target contract address incorrect".into());
+                return Err(TransactionVerificationError::Invalid("Transaction target contract address incorrect".to_string()));
             }
         } else {
-            return Err("Transaction has no target address".into());
+            return Err(TransactionVerificationError::Invalid("Transaction has no target address".to_string()));
         }
         
-      
-        let has_comment_event = tx_details.logs.iter().any(|log| {
-            log.address == self.contract_address
-        });
-        
-        if !has_comment_event {
-            return Err("No contract event found in transaction".into());
+
+        let event: CommentCreatedFilter = decode_contract_event(self.contract_address, &tx_details.logs)
+            .ok_or_else(|| TransactionVerificationError::Invalid("No CommentCreated event found in transaction".to_string()))?;
+        if event.author != expected_sender {
+            return Err(TransactionVerificationError::Invalid("CommentCreated event author mismatch".to_string()));
+        }
+        if event.post_id != expected_post_id {
+            return Err(TransactionVerificationError::Invalid("CommentCreated event post ID mismatch".to_string()));
         }
-        
-     
-        let comment_id = U256::from(1); 
-        let post_id = U256::from(1); 
-        let points_earned = U256::from(50); 
         
        
-        let required_cost = self.get_comment_cost().await?;
+        let required_cost = self.get_comment_cost().await.map_err(node_error)?;
         if tx_details.value < required_cost {
-            return Err("Insufficient payment amount".into());
+            return Err(TransactionVerificationError::Invalid("Insufficient payment amount".to_string()));
         }
         
         Ok(CommentTransactionVerification {
@@ -344,9 +311,9 @@ impl BlockchainService {
             sender: tx_details.from,
             block_number: tx_details.block_number.as_u64(),
             block_timestamp: tx_details.block_timestamp,
-            comment_id,
-            post_id,
-            points_earned,
+            comment_id: event.comment_id,
+            post_id: event.post_id,
+            points_earned: event.reward,
             value_paid: tx_details.value,
             gas_used: tx_details.gas_used,
             verified: true,
@@ -354,6 +321,36 @@ impl BlockchainService {
     }
 }
 
+/// Why a post or comment transaction did not verify
+#[derive(Debug)]
+pub enum TransactionVerificationError {
+    /// Not mined yet, or the node could not be asked; the same transaction may verify later
+    Pending(String),
+    /// The mined transaction does not match the submission and never will
+    Invalid(String),
+}
+
+impl std::fmt::Display for TransactionVerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionVerificationError::Pending(message) | TransactionVerificationError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TransactionVerificationError {}
+
+fn node_error(e: impl std::fmt::Display) -> TransactionVerificationError {
+    TransactionVerificationError::Pending(format!("Blockchain node request failed: {}", e))
+}
+
+/// Decode the first `E` event emitted by the forum contract in a receipt's logs
+fn decode_contract_event<E: EthEvent>(contract_address: Address, logs: &[Log]) -> Option<E> {
+    logs.iter()
+        .filter(|log| log.address == contract_address && log.topics.first() == Some(&E::signature()))
+        .find_map(|log| ethers::contract::parse_log::<E>(log.clone()).ok())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BlockchainPost {
     pub id: U256,
@@ -369,36 +366,17 @@ pub struct BlockchainPost {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct BlockchainUser {
-    pub posts_count: U256,
-    pub comments_count: U256,
-    pub total_likes_received: U256,
-    pub reputation_score: U256,
-    pub total_earned: U256,
-    pub total_spent: U256,
-    pub is_miner: bool,
-    pub last_activity_time: U256,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GasCostInfo {
-    pub post_cost_wei: String,
-    pub comment_cost_wei: String,
-    pub post_cost_irys: String,
-    pub comment_cost_irys: String,
+pub struct BlockchainComment {
+    pub id: U256,
+    pub post_id: U256,
+    pub author: String,
+    pub content: String,
+    pub timestamp: U256,
+    pub likes: U256,
+    pub parent_id: U256,
+    pub irys_transaction_id: String,
 }
 
-impl GasCostInfo {
-    pub fn new(post_cost: U256, comment_cost: U256) -> Self {
-        Self {
-            post_cost_wei: post_cost.to_string(),
-            comment_cost_wei: comment_cost.to_string(),
-            post_cost_irys: format!("{:.6}", post_cost.as_u128() as f64 / 1e18),
-            comment_cost_irys: format!("{:.6}", comment_cost.as_u128() as f64 / 1e18),
-        }
-    }
-} 
-
 //New Structure Definition
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionDetails {
@@ -413,7 +391,7 @@ pub struct TransactionDetails {
     pub logs: Vec<ethers::types::Log>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostTransactionVerification {
     pub transaction_hash: String,
     pub sender: String,
@@ -426,7 +404,7 @@ pub struct PostTransactionVerification {
     pub verified: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommentTransactionVerification {
     pub transaction_hash: String,
     pub sender: String,
@@ -440,3 +418,40 @@ pub struct CommentTransactionVerification {
     pub verified: bool,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{encode, Token};
+
+    fn post_created_log(contract: Address, post_id: u64, author: Address, reward: u64) -> Log {
+        Log {
+            address: contract,
+            topics: vec![
+                PostCreatedFilter::signature(),
+                H256::from_low_u64_be(post_id),
+                H256::from(author),
+            ],
+            data: encode(&[Token::String("Hello".to_string()), Token::Uint(U256::from(reward))]).into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_post_created_event() {
+        let contract = Address::from_low_u64_be(0xf0);
+        let author = Address::from_low_u64_be(0xa1);
+        let logs = vec![
+            post_created_log(Address::from_low_u64_be(0xbad), 99, author, 1),
+            post_created_log(contract, 42, author, 100),
+        ];
+
+        let event: PostCreatedFilter = decode_contract_event(contract, &logs).unwrap();
+        assert_eq!(event.post_id, U256::from(42));
+        assert_eq!(event.author, author);
+        assert_eq!(event.title, "Hello");
+        assert_eq!(event.reward, U256::from(100));
+
+        assert!(decode_contract_event::<CommentCreatedFilter>(contract, &logs).is_none());
+    }
+}
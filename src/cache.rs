@@ -1,40 +1,92 @@
-use redis::{Client, Connection, Commands, RedisResult};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Client, RedisResult};
+use tokio::sync::OnceCell;
 use crate::models::*;
+use crate::rate_limit::RateLimitStatus;
 
+const RATE_LIMIT_SCRIPT: &str = r#"
+local count = redis.call('INCR', KEYS[1])
+if count == 1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+local ttl = redis.call('TTL', KEYS[1])
+if ttl < 0 then
+    redis.call('EXPIRE', KEYS[1], ARGV[1])
+    ttl = tonumber(ARGV[1])
+end
+return {count, ttl}
+"#;
+
+/// Redis cache backed by a small pool of multiplexed async connections.
+///
+/// Each `ConnectionManager` pipelines concurrent commands over one socket and
+/// reconnects on its own after a dropped connection, so the pool size
+/// (`REDIS_MAX_CONNECTIONS`) caps the number of sockets opened to Redis.
+/// Slots are connected lazily and handed out round-robin.
 pub struct CacheService {
     client: Client,
+    connections: Vec<OnceCell<ConnectionManager>>,
+    next_connection: AtomicUsize,
 }
 
 impl CacheService {
-    pub fn new(redis_url: &str) -> Result<Self, redis::RedisError> {
+    pub async fn new(redis_url: &str) -> Result<Self, redis::RedisError> {
+        let max_connections = std::env::var("REDIS_MAX_CONNECTIONS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(20)
+            .max(1);
+
         let client = Client::open(redis_url)?;
-        Ok(Self { client })
+        let service = Self {
+            client,
+            connections: (0..max_connections).map(|_| OnceCell::new()).collect(),
+            next_connection: AtomicUsize::new(0),
+        };
+
+        // Connect the first slot up front so an unreachable server is reported at startup
+        service.get_connection().await?;
+        Ok(service)
     }
     
-    pub fn get_connection(&self) -> RedisResult<Connection> {
-        self.client.get_connection()
+    pub async fn get_connection(&self) -> RedisResult<ConnectionManager> {
+        let slot = self.next_connection.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        let connection = self.connections[slot]
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await?;
+        Ok(connection.clone())
     }
     
+    /// Key for one page of the post list. Cursor pages are keyed by their position, so a page
+    /// cached before new posts arrived still continues exactly after its cursor.
+    fn posts_page_key(page: &PageRequest) -> String {
+        match &page.after {
+            Some(after) => format!("posts:{}:after:{}", page.limit, after.encode()),
+            None => format!("posts:{}:{}", page.limit, page.offset),
+        }
+    }
+
    //Cache post list
-    pub fn cache_posts(&self, posts: &[Post], limit: u32, offset: u32) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
-        let key = format!("posts:{}:{}", limit, offset);
+    pub async fn cache_posts(&self, posts: &Page<Post>, page: &PageRequest) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        let key = Self::posts_page_key(page);
         let value = serde_json::to_string(posts).map_err(|e| {
             redis::RedisError::from((redis::ErrorKind::TypeError, "Serialization failed", e.to_string()))
         })?;
         
-        conn.set_ex::<_, _, ()>(&key, value, 300)?; 
+        conn.set_ex::<_, _, ()>(&key, value, 300).await?; 
         Ok(())
     }
     
-    pub fn get_cached_posts(&self, limit: u32, offset: u32) -> RedisResult<Option<Vec<Post>>> {
-        let mut conn = self.get_connection()?;
-        let key = format!("posts:{}:{}", limit, offset);
+    pub async fn get_cached_posts(&self, page: &PageRequest) -> RedisResult<Option<Page<Post>>> {
+        let mut conn = self.get_connection().await?;
+        let key = Self::posts_page_key(page);
         
-        let cached: RedisResult<String> = conn.get(&key);
+        let cached: RedisResult<String> = conn.get(&key).await;
         match cached {
             Ok(data) => {
-                let posts: Vec<Post> = serde_json::from_str(&data).map_err(|e| {
+                let posts: Page<Post> = serde_json::from_str(&data).map_err(|e| {
                     redis::RedisError::from((redis::ErrorKind::TypeError, "Desialization failed", e.to_string()))
                 })?;
                 Ok(Some(posts))
@@ -43,112 +95,71 @@ impl CacheService {
         }
     }
     
-   //Cache comment list
-    pub fn cache_comments(&self, post_id: &str, comments: &[Comment]) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
-        let key = format!("comments:{}", post_id);
-        let value = serde_json::to_string(comments).map_err(|e| {
-            redis::RedisError::from((redis::ErrorKind::TypeError, "Serialization failed", e.to_string()))
-        })?;
-        
-        conn.set_ex::<_, _, ()>(&key, value, 180)?; 
-        Ok(())
-    }
-    
-    pub fn get_cached_comments(&self, post_id: &str) -> RedisResult<Option<Vec<Comment>>> {
-        let mut conn = self.get_connection()?;
-        let key = format!("comments:{}", post_id);
-        
-        let cached: RedisResult<String> = conn.get(&key);
-        match cached {
-            Ok(data) => {
-                let comments: Vec<Comment> = serde_json::from_str(&data).map_err(|e| {
-                    redis::RedisError::from((redis::ErrorKind::TypeError, "Desialization failed", e.to_string()))
-                })?;
-                Ok(Some(comments))
-            }
-            Err(_) => Ok(None),
-        }
-    }
-    
    
-    pub fn cache_user_stats(&self, address: &str, stats: &UserStats) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
-        let key = format!("user_stats:{}", address);
-        let value = serde_json::to_string(stats).map_err(|e| {
-            redis::RedisError::from((redis::ErrorKind::TypeError, "Serialization failed", e.to_string()))
-        })?;
-        
-        conn.set_ex::<_, _, ()>(&key, value, 600)?; 
-        Ok(())
-    }
-    
-    // Cache popular users
-    pub fn cache_active_users(&self, users: &[UserStats]) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
-        let key = "active_users";
-        let value = serde_json::to_string(users).map_err(|e| {
-            redis::RedisError::from((redis::ErrorKind::TypeError, "Desialization failed", e.to_string()))
-        })?;
-        
-        conn.set_ex::<_, _, ()>(&key, value, 900)?; 
-        Ok(())
-    }
-    
-   
-    pub fn check_rate_limit(&self, user_address: &str, action: &str, limit: u32, window: u64) -> RedisResult<bool> {
-        let mut conn = self.get_connection()?;
-        let key = format!("rate_limit:{}:{}", user_address, action);
-        
-        let current: u32 = conn.get(&key).unwrap_or(0);
-        if current >= limit {
-            return Ok(false);
+    /// Count one request in a fixed window. INCR and EXPIRE run in a single
+    /// script, so concurrent requests can never overshoot `limit`.
+    pub async fn check_rate_limit(&self, key: &str, limit: u32, window: u64) -> RedisResult<RateLimitStatus> {
+        let mut conn = self.get_connection().await?;
+        let (count, ttl): (u32, i64) = redis::Script::new(RATE_LIMIT_SCRIPT)
+            .key(format!("rate_limit:{}", key))
+            .arg(window)
+            .invoke_async(&mut conn)
+            .await?;
+
+        if count > limit {
+            Ok(RateLimitStatus::Limited { retry_after: ttl.max(1) as u64 })
+        } else {
+            Ok(RateLimitStatus::Allowed)
         }
-        
-        let _: () = conn.incr(&key, 1)?;
-        let _: () = conn.expire(&key, window as usize)?;
-        Ok(true)
     }
     
-    
-    pub fn acquire_lock(&self, lock_key: &str, ttl_seconds: u64) -> RedisResult<bool> {
-        let mut conn = self.get_connection()?;
-        
-      
-        let result: RedisResult<String> = redis::cmd("SET")
-            .arg(&format!("lock:{}", lock_key))
-            .arg("locked")
-            .arg("NX")
-            .arg("EX")
-            .arg(ttl_seconds)
-            .query(&mut conn);
-        
-        match result {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
-        }
+   
+    /// Store a SIWE nonce until it is consumed or `ttl` seconds pass
+    pub async fn store_nonce(&self, nonce: &str, ttl: u64) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        conn.set_ex::<_, _, ()>(format!("siwe_nonce:{}", nonce), 1, ttl as usize).await
     }
-    
-    pub fn release_lock(&self, lock_key: &str) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
-        let _: () = conn.del(&format!("lock:{}", lock_key))?;
-        Ok(())
+
+    /// Remove a SIWE nonce, reporting whether it was still live. `GETDEL` is
+    /// atomic, so two logins racing on the same nonce cannot both succeed.
+    pub async fn consume_nonce(&self, nonce: &str) -> RedisResult<bool> {
+        let mut conn = self.get_connection().await?;
+        let value: Option<String> = redis::cmd("GETDEL")
+            .arg(format!("siwe_nonce:{}", nonce))
+            .query_async(&mut conn)
+            .await?;
+        Ok(value.is_some())
     }
-    
-   
-    pub fn invalidate_post_cache(&self) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
-        let keys: Vec<String> = conn.keys("posts:*")?;
-        if !keys.is_empty() {
-            let _: () = conn.del(&keys)?;
+
+    /// Drops every cached post page. Walks the keyspace with `SCAN` rather
+    /// than `KEYS` so Redis is never blocked, and frees each batch with
+    /// `UNLINK` so large values are reclaimed in the background.
+    pub async fn invalidate_post_cache(&self) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg("posts:*")
+                .arg("COUNT")
+                .arg(500)
+                .query_async(&mut conn)
+                .await?;
+            if !keys.is_empty() {
+                let _: () = redis::cmd("UNLINK").arg(&keys).query_async(&mut conn).await?;
+            }
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
         }
-        Ok(())
     }
     
-    pub fn invalidate_comment_cache(&self, post_id: &str) -> RedisResult<()> {
-        let mut conn = self.get_connection()?;
+    pub async fn invalidate_comment_cache(&self, post_id: &str) -> RedisResult<()> {
+        let mut conn = self.get_connection().await?;
         let key = format!("comments:{}", post_id);
-        let _: () = conn.del(&key)?;
+        let _: () = conn.del(&key).await?;
         Ok(())
     }
 } 
\ No newline at end of file
@@ -4,8 +4,8 @@ use sqlx::{PgPool, Row, postgres::PgPoolOptions};
 use uuid::Uuid;
 use log::{info, warn};
 use unicode_normalization::UnicodeNormalization;
-use md5;
-use std::time::{Duration, Instant};
+use std::collections::HashMap;
+use std::time::Duration;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
@@ -28,15 +28,6 @@ impl DatabaseStats {
         }
     }
     
-    pub fn record_query(&self, duration: Duration, success: bool) {
-        self.total_queries.fetch_add(1, Ordering::Relaxed);
-        self.total_query_time.fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
-        
-        if !success {
-            self.failed_queries.fetch_add(1, Ordering::Relaxed);
-        }
-    }
-    
     pub fn get_avg_query_time(&self) -> f64 {
         let total_queries = self.total_queries.load(Ordering::Relaxed);
         if total_queries == 0 {
@@ -53,9 +44,18 @@ pub struct DatabaseService {
     stats: Arc<DatabaseStats>,
 }
 
+/// The row's stored `content_html`, or its `content` rendered now for rows written before
+/// migration 0014 and queries that do not select the column
+fn content_html(row: &sqlx::postgres::PgRow) -> String {
+    match row.try_get::<Option<String>, _>("content_html") {
+        Ok(Some(html)) => html,
+        _ => crate::markdown::render(&row.try_get::<String, _>("content").unwrap_or_default()),
+    }
+}
+
 impl DatabaseService {
     pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
-        /// Build pool options from environment variables with sensible defaults
+        // Build pool options from environment variables with sensible defaults
         let max_connections = std::env::var("DATABASE_MAX_CONNECTIONS")
             .unwrap_or_else(|_| "50".to_string())
             .parse::<u32>()
@@ -66,7 +66,7 @@ impl DatabaseService {
             .parse::<u32>()
             .unwrap_or(10);
         
-        /// High performance pool configuration
+        // High performance pool configuration
         let pool = PgPoolOptions::new()
             .max_connections(max_connections)
             .min_connections(min_connections)
@@ -79,8 +79,11 @@ impl DatabaseService {
         
         info!("🗄️ Database pool initialized - max connections: {}, min connections: {}", max_connections, min_connections);
         
-        /// Connection pool health check
+        // Connection pool health check
         Self::health_check(&pool).await?;
+
+        let applied = crate::migrations::run(&pool).await?;
+        info!("🗄️ Database schema at version {} ({} migrations applied now)", crate::migrations::latest_version(), applied.len());
         
         Ok(Self { 
             pool,
@@ -211,26 +214,6 @@ impl DatabaseService {
         Ok(count > 0)
     }
     
-    /// Execute operation and record performance stats
-    async fn execute_with_stats<F, R>(&self, operation_name: &str, operation: F) -> R
-    where
-        F: std::future::Future<Output = R>,
-    {
-        let start = Instant::now();
-        let result = operation.await;
-        let duration = start.elapsed();
-        
-        
-        self.stats.record_query(duration, true);
-        
-        
-        if duration.as_millis() > 100 {
-            warn!("⚠️ Slow query detected - {}: {:?}", operation_name, duration);
-        }
-        
-        result
-    }
-    
     /// Get database performance stats
     pub fn get_database_stats(&self) -> serde_json::Value {
         serde_json::json!({
@@ -257,8 +240,8 @@ impl DatabaseService {
 
         sqlx::query(
             r#"
-            INSERT INTO posts (id, title, content, author_id, content_hash, category, tags, upvotes, irys_transaction_id, author_name, likes, created_at, updated_at, image, blockchain_post_id)
-            SELECT $1, $2, $3, u.id, $4, 'general', $5, $6, $7, $8, $9, $10, $11, $12, $13
+            INSERT INTO posts (id, title, content, author_id, content_hash, category, tags, upvotes, irys_transaction_id, author_name, likes, created_at, updated_at, image, blockchain_post_id, content_html)
+            SELECT $1, $2, $3, u.id, $4, 'general', $5, $6, $7, $8, $9, $10, $11, $12, $13, $15
             FROM users u WHERE u.ethereum_address = $14
             "#
         )
@@ -276,6 +259,7 @@ impl DatabaseService {
         .bind(&post.image)
         .bind(post.blockchain_post_id.map(|id| id as i32))
         .bind(&post.author_address)
+        .bind(&post.content_html)
         .execute(&mut *tx)
         .await?;
 
@@ -320,8 +304,8 @@ impl DatabaseService {
         
         sqlx::query(
             r#"
-            INSERT INTO comments (id, content, author_id, post_id, parent_id, created_at, likes, irys_transaction_id, author_name, image, content_hash, updated_at)
-            SELECT $1, $2, u.id, $3, $4, $5, $6, $7, $8, $9, $10, $11
+            INSERT INTO comments (id, content, author_id, post_id, parent_id, created_at, likes, irys_transaction_id, author_name, image, content_hash, updated_at, content_html)
+            SELECT $1, $2, u.id, $3, $4, $5, $6, $7, $8, $9, $10, $11, $13
             FROM users u WHERE u.ethereum_address = $12
             "#
         )
@@ -337,6 +321,7 @@ impl DatabaseService {
         .bind(&comment.content_hash)
         .bind(comment.created_at)
         .bind(&comment.author_address)
+        .bind(&comment.content_html)
         .execute(&mut *tx)
         .await?;
 
@@ -363,17 +348,13 @@ impl DatabaseService {
         Ok(())
     }
 
-    /// Simplified post query
-    pub async fn get_posts(&self) -> Result<Vec<Post>, sqlx::Error> {
-        self.get_posts_paginated(1000, 0).await
-    }
-    
-    /// Paginated posts query
-    pub async fn get_posts_paginated(&self, limit: u32, offset: u32) -> Result<Vec<Post>, sqlx::Error> {
+    /// Paginated posts query; `excluded_authors` are lowercase addresses filtered out before paging
+    pub async fn get_posts_paginated(&self, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
         let rows = sqlx::query(
             r#"
-            SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+            SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                    COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                    p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                    COALESCE(p.views, 0) as views,
@@ -381,12 +362,18 @@ impl DatabaseService {
                    COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar
             FROM posts p
             JOIN users u ON p.author_id = u.id
-            ORDER BY p.created_at DESC
+            WHERE p.deleted_at IS NULL
+              AND ($3::TIMESTAMPTZ IS NULL OR (p.created_at, p.id) < ($3, $4))
+              AND LOWER(u.ethereum_address) <> ALL($5)
+            ORDER BY p.created_at DESC, p.id DESC
             LIMIT $1 OFFSET $2
             "#
         )
-        .bind(limit as i64)
-        .bind(offset as i64)
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .bind(excluded_authors)
         .fetch_all(&self.pool)
         .await?;
 
@@ -396,6 +383,7 @@ impl DatabaseService {
                 id: row.try_get::<Uuid, _>("id")?.to_string(),
                 title: row.try_get("title")?,
                 content: row.try_get("content")?,
+                content_html: content_html(&row),
                 author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
                 author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
                 author_name: row.try_get("author_name")?,
@@ -410,20 +398,23 @@ impl DatabaseService {
                 image: row.try_get("image").ok(),
                 blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
                 is_liked_by_user: false,
+                is_bookmarked_by_user: false,
                 heat_score: None,
+                mentions: Vec::new(),
             };
             posts.push(post);
         }
 
-        Ok(posts)
+        Ok(Page::from_rows(posts, page.limit, Post::page_cursor))
     }
     
     /// Get posts by user (paginated)
-    pub async fn get_posts_by_user(&self, user_address: &str, limit: u32, offset: u32) -> Result<Vec<Post>, sqlx::Error> {
+    pub async fn get_posts_by_user(&self, user_address: &str, page: &PageRequest) -> Result<Page<Post>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
         let rows = sqlx::query(
             r#"
-            SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+            SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                    COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                    p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                    COALESCE(p.views, 0) as views,
@@ -431,14 +422,17 @@ impl DatabaseService {
                    COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar
             FROM posts p
             JOIN users u ON p.author_id = u.id
-            WHERE LOWER(u.ethereum_address) = LOWER($1)
-            ORDER BY p.created_at DESC
+            WHERE LOWER(u.ethereum_address) = LOWER($1) AND p.deleted_at IS NULL
+              AND ($4::TIMESTAMPTZ IS NULL OR (p.created_at, p.id) < ($4, $5))
+            ORDER BY p.created_at DESC, p.id DESC
             LIMIT $2 OFFSET $3
             "#
         )
         .bind(user_address)
-        .bind(limit as i64)
-        .bind(offset as i64)
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -448,6 +442,7 @@ impl DatabaseService {
                 id: row.try_get::<Uuid, _>("id")?.to_string(),
                 title: row.try_get("title")?,
                 content: row.try_get("content")?,
+                content_html: content_html(&row),
                 author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
                 author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
                 author_name: row.try_get("author_name")?,
@@ -462,22 +457,25 @@ impl DatabaseService {
                 image: row.try_get("image").ok(),
                 blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
                 is_liked_by_user: row.try_get::<bool, _>("is_liked_by_user").unwrap_or(false),
+                is_bookmarked_by_user: false,
                 heat_score: None,
+                mentions: Vec::new(),
             };
             posts.push(post);
         }
 
-        Ok(posts)
+        Ok(Page::from_rows(posts, page.limit, Post::page_cursor))
     }
 
     /// Get posts by user (paginated, with like status)
-    pub async fn get_posts_by_user_with_like_status(&self, user_address: &str, limit: u32, offset: u32, request_user_address: Option<&str>) -> Result<Vec<Post>, sqlx::Error> {
+    pub async fn get_posts_by_user_with_like_status(&self, user_address: &str, page: &PageRequest, request_user_address: Option<&str>) -> Result<Page<Post>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
         let rows = if let Some(req_addr) = request_user_address {
             // 包含点赞状态的查询
             let query_result = sqlx::query(
                 r#"
-                SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                        COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                        p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                        COALESCE(p.views, 0) as views,
@@ -487,15 +485,18 @@ impl DatabaseService {
                 FROM posts p
                 JOIN users u ON p.author_id = u.id
                 LEFT JOIN post_likes pl ON pl.post_id = p.id AND LOWER(pl.user_address) = LOWER($4)
-                WHERE LOWER(u.ethereum_address) = LOWER($1)
-                ORDER BY p.created_at DESC
+                WHERE LOWER(u.ethereum_address) = LOWER($1) AND p.deleted_at IS NULL
+                  AND ($5::TIMESTAMPTZ IS NULL OR (p.created_at, p.id) < ($5, $6))
+                ORDER BY p.created_at DESC, p.id DESC
                 LIMIT $2 OFFSET $3
                 "#
             )
             .bind(user_address)
-            .bind(limit as i64)
-            .bind(offset as i64)
+            .bind(page.fetch_limit())
+            .bind(page.offset as i64)
             .bind(req_addr)
+            .bind(after_at)
+            .bind(after_id)
             .fetch_all(&self.pool)
             .await?;
             
@@ -516,8 +517,8 @@ impl DatabaseService {
 
             sqlx::query(
                 r#"
-                SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                        COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                        p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                        COALESCE(p.views, 0) as views,
@@ -526,14 +527,17 @@ impl DatabaseService {
                        false as is_liked_by_user
                 FROM posts p
                 JOIN users u ON p.author_id = u.id
-                WHERE LOWER(u.ethereum_address) = LOWER($1)
-                ORDER BY p.created_at DESC
+                WHERE LOWER(u.ethereum_address) = LOWER($1) AND p.deleted_at IS NULL
+                  AND ($4::TIMESTAMPTZ IS NULL OR (p.created_at, p.id) < ($4, $5))
+                ORDER BY p.created_at DESC, p.id DESC
                 LIMIT $2 OFFSET $3
                 "#
             )
             .bind(user_address)
-            .bind(limit as i64)
-            .bind(offset as i64)
+            .bind(page.fetch_limit())
+            .bind(page.offset as i64)
+            .bind(after_at)
+            .bind(after_id)
             .fetch_all(&self.pool)
             .await?
         };
@@ -544,6 +548,7 @@ impl DatabaseService {
                 id: row.try_get::<Uuid, _>("id")?.to_string(),
                 title: row.try_get("title")?,
                 content: row.try_get("content")?,
+                content_html: content_html(&row),
                 author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
                 author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
                 author_name: row.try_get("author_name")?,
@@ -558,12 +563,91 @@ impl DatabaseService {
                 image: row.try_get("image").ok(),
                 blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
                 is_liked_by_user: row.try_get::<bool, _>("is_liked_by_user").unwrap_or(false),
+                is_bookmarked_by_user: false,
+                heat_score: None,
+                mentions: Vec::new(),
+            };
+            posts.push(post);
+        }
+
+        Ok(Page::from_rows(posts, page.limit, Post::page_cursor))
+    }
+
+    /// Posts by the users `user_address` follows, newest first, with its like status.
+    /// Fan-out on read: each followed author contributes at most one page of rows from the
+    /// (author_id, created_at, id) index before they are merged, so the cost is bounded by
+    /// the page size per followed author rather than by how much they have posted.
+    pub async fn get_following_feed(&self, user_address: &str, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        let rows = sqlx::query(
+            r#"
+            WITH followed AS (
+                SELECT u.id
+                FROM follows f
+                JOIN users u ON u.ethereum_address = f.following_address
+                WHERE f.follower_address = $1
+                  AND LOWER(u.ethereum_address) <> ALL($6)
+            )
+            SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes,
+                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
+                   COALESCE(p.tags, '{}') as tags, p.irys_transaction_id,
+                   p.created_at, p.updated_at, p.image, p.blockchain_post_id,
+                   COALESCE(p.views, 0) as views,
+                   u.id as user_id, u.ethereum_address,
+                   COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar,
+                   EXISTS (SELECT 1 FROM post_likes pl WHERE pl.post_id = p.id AND LOWER(pl.user_address) = LOWER($1)) as is_liked_by_user
+            FROM followed fu
+            CROSS JOIN LATERAL (
+                SELECT *
+                FROM posts p
+                WHERE p.author_id = fu.id AND p.deleted_at IS NULL
+                  AND ($4::TIMESTAMPTZ IS NULL OR (p.created_at, p.id) < ($4, $5))
+                ORDER BY p.created_at DESC, p.id DESC
+                LIMIT $2 + $3
+            ) p
+            JOIN users u ON p.author_id = u.id
+            ORDER BY p.created_at DESC, p.id DESC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(user_address)
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .bind(excluded_authors)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut posts = Vec::new();
+        for row in rows {
+            let post = Post {
+                id: row.try_get::<Uuid, _>("id")?.to_string(),
+                title: row.try_get("title")?,
+                content: row.try_get("content")?,
+                content_html: content_html(&row),
+                author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
+                author_name: row.try_get("author_name")?,
+                author_avatar: row.try_get("author_avatar").ok(),
+                created_at: row.try_get("created_at")?,
+                updated_at: row.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+                likes: row.try_get::<i32, _>("likes").unwrap_or(0) as u32,
+                comments_count: row.try_get::<i64, _>("comments_count").unwrap_or(0) as u32,
+                views: row.try_get::<i32, _>("views").unwrap_or(0) as u32,
+                tags: row.try_get::<Vec<String>, _>("tags").unwrap_or_default(),
+                irys_transaction_id: row.try_get("irys_transaction_id").ok(),
+                image: row.try_get("image").ok(),
+                blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
+                is_liked_by_user: row.try_get::<bool, _>("is_liked_by_user").unwrap_or(false),
+                is_bookmarked_by_user: false,
                 heat_score: None,
+                mentions: Vec::new(),
             };
             posts.push(post);
         }
 
-        Ok(posts)
+        Ok(Page::from_rows(posts, page.limit, Post::page_cursor))
     }
 
     /// Get single post
@@ -575,8 +659,8 @@ impl DatabaseService {
         
         let row = sqlx::query(
             r#"
-            SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+            SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                    COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                    p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                    COALESCE(p.views, 0) as views,
@@ -584,7 +668,7 @@ impl DatabaseService {
                    COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar
             FROM posts p
             JOIN users u ON p.author_id = u.id
-            WHERE p.id = $1
+            WHERE p.id = $1 AND p.deleted_at IS NULL
             "#
         )
         .bind(post_uuid)
@@ -595,6 +679,7 @@ impl DatabaseService {
             id: row.try_get::<Uuid, _>("id").unwrap().to_string(),
             title: row.try_get("title").unwrap(),
             content: row.try_get("content").unwrap(),
+            content_html: content_html(&row),
             author_address: row.try_get::<Option<String>, _>("ethereum_address").unwrap().unwrap_or_default(),
             author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
             author_name: row.try_get("author_name").unwrap(),
@@ -609,12 +694,14 @@ impl DatabaseService {
             image: row.try_get("image").ok(),
             blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
             is_liked_by_user: false, // 默认为false，后续由service层设置
+            is_bookmarked_by_user: false,
             heat_score: None,
+            mentions: Vec::new(),
         }))
     }
 
-    /// Get single post (with user's like status)
-    pub async fn get_post_by_id_with_like_status(&self, id: &str, user_address: Option<&str>) -> Result<Option<Post>, sqlx::Error> {
+    /// Get single post (with user's like status, and the bookmark status of the signed-in viewer)
+    pub async fn get_post_by_id_with_like_status(&self, id: &str, user_address: Option<&str>, viewer: Option<&str>) -> Result<Option<Post>, sqlx::Error> {
         let post_uuid = match Uuid::parse_str(id) {
             Ok(uuid) => uuid,
             Err(_) => return Ok(None),
@@ -623,39 +710,45 @@ impl DatabaseService {
         let row = if let Some(address) = user_address {
             sqlx::query(
                 r#"
-                SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                        COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                        p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                        u.id as user_id, u.ethereum_address, 
                        COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar,
-                       CASE WHEN pl.user_address IS NOT NULL THEN true ELSE false END as is_liked_by_user
+                       CASE WHEN pl.user_address IS NOT NULL THEN true ELSE false END as is_liked_by_user,
+                       CASE WHEN b.user_address IS NOT NULL THEN true ELSE false END as is_bookmarked_by_user
                 FROM posts p
                 JOIN users u ON p.author_id = u.id
                 LEFT JOIN post_likes pl ON pl.post_id = p.id AND pl.user_address = $2
-                WHERE p.id = $1
+                LEFT JOIN bookmarks b ON b.post_id = p.id AND b.user_address = LOWER($3)
+                WHERE p.id = $1 AND p.deleted_at IS NULL
                 "#
             )
             .bind(post_uuid)
             .bind(address)
+            .bind(viewer)
             .fetch_optional(&self.pool)
             .await?
         } else {
             sqlx::query(
                 r#"
-                SELECT p.id, p.title, p.content, COALESCE(p.likes, 0) as likes, 
-                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes, 
+                       (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                        COALESCE(p.tags, '{}') as tags, p.irys_transaction_id, 
                        p.created_at, p.updated_at, p.image, p.blockchain_post_id,
                        u.id as user_id, u.ethereum_address, 
                        COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar,
-                       false as is_liked_by_user
+                       false as is_liked_by_user,
+                       CASE WHEN b.user_address IS NOT NULL THEN true ELSE false END as is_bookmarked_by_user
                 FROM posts p
                 JOIN users u ON p.author_id = u.id
-                WHERE p.id = $1
+                LEFT JOIN bookmarks b ON b.post_id = p.id AND b.user_address = LOWER($2)
+                WHERE p.id = $1 AND p.deleted_at IS NULL
                 "#
             )
             .bind(post_uuid)
+            .bind(viewer)
             .fetch_optional(&self.pool)
             .await?
         };
@@ -664,6 +757,7 @@ impl DatabaseService {
             id: row.try_get::<Uuid, _>("id").unwrap().to_string(),
             title: row.try_get("title").unwrap(),
             content: row.try_get("content").unwrap(),
+            content_html: content_html(&row),
             author_address: row.try_get::<Option<String>, _>("ethereum_address").unwrap().unwrap_or_default(),
             author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
             author_name: row.try_get("author_name").unwrap(),
@@ -678,74 +772,41 @@ impl DatabaseService {
             image: row.try_get("image").ok(),
             blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
             is_liked_by_user: row.try_get::<bool, _>("is_liked_by_user").unwrap_or(false),
+            is_bookmarked_by_user: row.try_get::<bool, _>("is_bookmarked_by_user").unwrap_or(false),
             heat_score: None,
+            mentions: Vec::new(),
         }))
     }
 
-    /// Create comment
-    pub async fn create_comment(&self, comment: &Comment) -> Result<(), sqlx::Error> {
-
-        self.ensure_user_exists(&comment.author_address, &comment.author_name).await?;
-        
-        /// Get user ID和帖子UUID
-        let user_id = self.get_user_id_by_address(&comment.author_address).await?;
-        let post_uuid = Uuid::parse_str(&comment.post_id)
-            .map_err(|_| sqlx::Error::RowNotFound)?;
-        let comment_uuid = Uuid::parse_str(&comment.id)
-            .map_err(|_| sqlx::Error::RowNotFound)?;
-        
-
-        let content_hash = format!("{:x}", md5::compute(&comment.content));
-        
-        
-        sqlx::query(
-            r#"
-            INSERT INTO comments (id, post_id, author_id, content, content_hash, parent_id, upvotes, created_at, updated_at, author_name, image)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            "#
-        )
-        .bind(comment_uuid)
-        .bind(post_uuid)
-        .bind(user_id)
-        .bind(&comment.content)
-        .bind(content_hash)
-        .bind(comment.parent_id.as_ref().and_then(|id| Uuid::parse_str(id).ok()))
-        .bind(comment.likes as i32) 
-        // .bind(comment.created_at)
-        .bind(comment.created_at) 
-        .bind(&comment.author_name)
-        .bind(&comment.image)
-        .execute(&self.pool)
-        .await?;
-        
-
-        self.update_user_stats(&comment.author_address, false, true).await?;
-        
-        info!("📊 评论已保存到数据库: {}", comment.id);
-        Ok(())
-    }
-
-    /// Get comments for a post
-    pub async fn get_comments_by_post_id(&self, post_id: &str) -> Result<Vec<Comment>, sqlx::Error> {
+    /// Get comments for a post (paginated)
+    pub async fn get_comments_by_post_id_paginated(&self, post_id: &str, page: &PageRequest) -> Result<Page<Comment>, sqlx::Error> {
         let post_uuid = match Uuid::parse_str(post_id) {
             Ok(uuid) => uuid,
-            Err(_) => return Ok(vec![]), 
+            Err(_) => return Ok(Page::empty()),
         };
+        let (after_at, after_id) = cursor_params(page);
         
         let rows = sqlx::query(
             r#"
-            SELECT c.id, c.post_id, c.content, c.parent_id, COALESCE(c.likes, 0) as likes,
+            SELECT c.id, c.post_id, c.content, c.content_html, c.parent_id, COALESCE(c.likes, 0) as likes,
                    c.created_at, c.irys_transaction_id, 
                    COALESCE(c.author_name, u.username) as author_name, u.avatar as author_avatar, c.image,
                    COALESCE(c.content_hash, '') as content_hash,
-                   u.ethereum_address, u.id as user_id
+                   u.ethereum_address, u.id as user_id, c.deleted_at IS NOT NULL as is_deleted
             FROM comments c
             JOIN users u ON c.author_id = u.id
             WHERE c.post_id = $1
-            ORDER BY c.created_at DESC
+              AND (c.deleted_at IS NULL OR EXISTS (SELECT 1 FROM comments r WHERE r.parent_id = c.id))
+              AND ($4::TIMESTAMPTZ IS NULL OR (c.created_at, c.id) > ($4, $5))
+            ORDER BY c.created_at ASC, c.id ASC
+            LIMIT $2 OFFSET $3
             "#
         )
         .bind(post_uuid)
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -753,6 +814,7 @@ impl DatabaseService {
             id: row.try_get::<Uuid, _>("id").unwrap().to_string(),
             post_id: row.try_get::<Uuid, _>("post_id").unwrap().to_string(),
             content: row.try_get("content").unwrap(),
+            content_html: content_html(&row),
             author_address: row.try_get::<Option<String>, _>("ethereum_address").unwrap().unwrap_or_default(),
             author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
             author_name: row.try_get("author_name").unwrap(),
@@ -764,42 +826,41 @@ impl DatabaseService {
             image: row.try_get("image").ok(),
             content_hash: row.try_get("content_hash").unwrap_or_default(),
             is_liked_by_user: false,
-        }).collect();
+            is_deleted: row.try_get("is_deleted").unwrap_or(false),
+            mentions: Vec::new(),
+        }).map(|comment| if comment.is_deleted { comment.into_tombstone() } else { comment }).collect();
 
-        Ok(comments)
+        Ok(Page::from_rows(comments, page.limit, Comment::page_cursor))
     }
-
-    /// Get comments for a post (paginated)
-    pub async fn get_comments_by_post_id_paginated(&self, post_id: &str, limit: u32, offset: u32) -> Result<Vec<Comment>, sqlx::Error> {
-        let post_uuid = match Uuid::parse_str(post_id) {
+    
+    /// Get single comment
+    pub async fn get_comment_by_id(&self, comment_id: &str) -> Result<Option<Comment>, sqlx::Error> {
+        let comment_uuid = match Uuid::parse_str(comment_id) {
             Ok(uuid) => uuid,
-            Err(_) => return Ok(vec![]), 
+            Err(_) => return Ok(None),
         };
-        
-        let rows = sqlx::query(
+
+        let row = sqlx::query(
             r#"
-            SELECT c.id, c.post_id, c.content, c.parent_id, COALESCE(c.likes, 0) as likes,
+            SELECT c.id, c.post_id, c.content, c.content_html, c.parent_id, COALESCE(c.likes, 0) as likes,
                    c.created_at, c.irys_transaction_id, 
                    COALESCE(c.author_name, u.username) as author_name, u.avatar as author_avatar, c.image,
                    COALESCE(c.content_hash, '') as content_hash,
                    u.ethereum_address, u.id as user_id
             FROM comments c
             JOIN users u ON c.author_id = u.id
-            WHERE c.post_id = $1
-            ORDER BY c.created_at ASC
-            LIMIT $2 OFFSET $3
+            WHERE c.id = $1 AND c.deleted_at IS NULL
             "#
         )
-        .bind(post_uuid)
-        .bind(limit as i64)
-        .bind(offset as i64)
-        .fetch_all(&self.pool)
+        .bind(comment_uuid)
+        .fetch_optional(&self.pool)
         .await?;
 
-        let comments = rows.into_iter().map(|row| Comment {
+        Ok(row.map(|row| Comment {
             id: row.try_get::<Uuid, _>("id").unwrap().to_string(),
             post_id: row.try_get::<Uuid, _>("post_id").unwrap().to_string(),
             content: row.try_get("content").unwrap(),
+            content_html: content_html(&row),
             author_address: row.try_get::<Option<String>, _>("ethereum_address").unwrap().unwrap_or_default(),
             author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
             author_name: row.try_get("author_name").unwrap(),
@@ -811,183 +872,451 @@ impl DatabaseService {
             image: row.try_get("image").ok(),
             content_hash: row.try_get("content_hash").unwrap_or_default(),
             is_liked_by_user: false,
-        }).collect();
-
-        Ok(comments)
+            is_deleted: false,
+            mentions: Vec::new(),
+        }))
     }
-    
-    /// Check duplicate comment within a time window in the same post
-    pub async fn check_duplicate_comment(
+
+    /// Load a window of a comment thread with one recursive query: up to `limit + 1` visible
+    /// comments directly under `parent_id` (top level if `None`) after `after`, then up to
+    /// `limit` replies per comment, down to `max_depth` levels
+    pub async fn get_comment_tree(
         &self,
-        author_address: &str,
-        content: &str,
-        post_id: &str
-    ) -> Result<bool, sqlx::Error> {
-        let post_uuid = uuid::Uuid::parse_str(post_id)
-            .map_err(|e| sqlx::Error::TypeNotFound { type_name: format!("Invalid UUID: {}", e) })?;
-        
+        post_id: &str,
+        parent_id: Option<&str>,
+        after: Option<&PageCursor>,
+        limit: u32,
+        max_depth: u32,
+    ) -> Result<Vec<CommentTreeRow>, sqlx::Error> {
+        let (Ok(post_uuid), Ok(parent_uuid)) = (Uuid::parse_str(post_id), parent_id.map(Uuid::parse_str).transpose()) else {
+            return Ok(Vec::new());
+        };
+        let after_id = after.map(|c| Uuid::parse_str(&c.id)).transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Invalid cursor: {}", e)))?;
 
-        let count: i64 = sqlx::query_scalar(
+        let rows = sqlx::query(
             r#"
-            SELECT COUNT(*) 
-            FROM comments c
+            WITH RECURSIVE visible AS (
+                SELECT c.id, c.parent_id, c.created_at
+                FROM comments c
+                WHERE c.post_id = $1
+                  AND (c.deleted_at IS NULL OR EXISTS (SELECT 1 FROM comments r WHERE r.parent_id = c.id))
+            ), tree AS (
+                SELECT roots.id, 1 AS depth, roots.rn
+                FROM (
+                    SELECT v.id, ROW_NUMBER() OVER (ORDER BY v.created_at, v.id) AS rn
+                    FROM visible v
+                    WHERE v.parent_id IS NOT DISTINCT FROM $2
+                      AND ($3::TIMESTAMPTZ IS NULL OR (v.created_at, v.id) > ($3, $4))
+                    ORDER BY v.created_at, v.id
+                    LIMIT $5 + 1
+                ) roots
+                UNION ALL
+                -- The extra root only tells whether there is a next page, so its replies are skipped
+                SELECT child.id, t.depth + 1, 0::BIGINT
+                FROM tree t
+                CROSS JOIN LATERAL (
+                    SELECT v.id FROM visible v WHERE v.parent_id = t.id ORDER BY v.created_at, v.id LIMIT $5
+                ) child
+                WHERE t.depth < $6 AND t.rn <= $5
+            )
+            SELECT c.id, c.post_id, c.content, c.content_html, c.parent_id, COALESCE(c.likes, 0) as likes,
+                   c.created_at, c.irys_transaction_id,
+                   COALESCE(c.author_name, u.username) as author_name, u.avatar as author_avatar, c.image,
+                   COALESCE(c.content_hash, '') as content_hash,
+                   u.ethereum_address, u.id as user_id, c.deleted_at IS NOT NULL as is_deleted,
+                   (SELECT COUNT(*) FROM visible r WHERE r.parent_id = t.id) as reply_count
+            FROM tree t
+            JOIN comments c ON c.id = t.id
             JOIN users u ON c.author_id = u.id
-            WHERE u.ethereum_address = $1 
-              AND c.content = $2 
-              AND c.post_id = $3
-              AND c.created_at > NOW() - INTERVAL '5 minutes'
             "#
         )
-        .bind(author_address)
-        .bind(content)
         .bind(post_uuid)
-        .fetch_one(&self.pool)
+        .bind(parent_uuid)
+        .bind(after.map(|c| c.created_at))
+        .bind(after_id)
+        .bind(limit as i64)
+        .bind(max_depth as i32)
+        .fetch_all(&self.pool)
         .await?;
-        
-        Ok(count > 0)
-    }
-    
-    /// Check duplicate post within a time window
-    pub async fn check_duplicate_post(
-        &self,
-        author_address: &str,
-        content: &str
-    ) -> Result<bool, sqlx::Error> {
 
-        let count: i64 = sqlx::query_scalar(
-            r#"
-            SELECT COUNT(*) 
-            FROM posts p
-            JOIN users u ON p.author_id = u.id
-            WHERE u.ethereum_address = $1 
-              AND p.content = $2 
-              AND p.created_at > NOW() - INTERVAL '5 minutes'
-            "#
-        )
-        .bind(author_address)
-        .bind(content)
-        .fetch_one(&self.pool)
-        .await?;
-        
-        Ok(count > 0)
+        rows.into_iter()
+            .map(|row| {
+                let comment = Comment {
+                    id: row.try_get::<Uuid, _>("id")?.to_string(),
+                    post_id: row.try_get::<Uuid, _>("post_id")?.to_string(),
+                    content: row.try_get("content")?,
+                    content_html: content_html(&row),
+                    author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                    author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
+                    author_name: row.try_get("author_name")?,
+                    author_avatar: row.try_get("author_avatar")?,
+                    created_at: row.try_get("created_at")?,
+                    parent_id: row.try_get::<Option<Uuid>, _>("parent_id")?.map(|u| u.to_string()),
+                    likes: row.try_get::<i32, _>("likes")? as u32,
+                    irys_transaction_id: row.try_get("irys_transaction_id")?,
+                    image: row.try_get("image")?,
+                    content_hash: row.try_get("content_hash")?,
+                    is_liked_by_user: false,
+                    is_deleted: row.try_get("is_deleted")?,
+                    mentions: Vec::new(),
+                };
+                Ok(CommentTreeRow {
+                    comment: if comment.is_deleted { comment.into_tombstone() } else { comment },
+                    reply_count: row.try_get::<i64, _>("reply_count")? as u32,
+                })
+            })
+            .collect()
     }
 
-    /// Get user ID
-    pub async fn get_user_id_by_address(&self, address: &str) -> Result<Uuid, sqlx::Error> {
-        let row = sqlx::query("SELECT id FROM users WHERE ethereum_address = $1")
-            .bind(address)
-            .fetch_one(&self.pool)
-            .await?;
-        
-        Ok(row.try_get("id")?)
-    }
+    /// Get the content hash stored when a post was created
+    pub async fn get_post_content_hash(&self, post_id: &str) -> Result<Option<String>, sqlx::Error> {
+        let post_uuid = match Uuid::parse_str(post_id) {
+            Ok(uuid) => uuid,
+            Err(_) => return Ok(None),
+        };
 
-    /// Simplified user query
-    pub async fn get_user_by_address(&self, address: &str) -> Result<Option<User>, sqlx::Error> {
-        let row = sqlx::query(
-            "SELECT id, ethereum_address, username, bio, avatar, posts_count, comments_count, reputation, created_at FROM users WHERE LOWER(ethereum_address) = LOWER($1)"
+        let hash: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT content_hash FROM posts WHERE id = $1"
         )
-        .bind(address)
+        .bind(post_uuid)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(|r| User {
-            id: r.try_get::<uuid::Uuid, _>("id").unwrap_or_default().to_string(),
-            address: r.try_get::<Option<String>, _>("ethereum_address").unwrap_or_default().unwrap_or_default(),
-            name: r.try_get("username").ok(),
-            bio: r.try_get("bio").ok(),
-            avatar: r.try_get("avatar").ok(),
-            created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
-            posts_count: r.try_get::<Option<i32>, _>("posts_count").unwrap_or(Some(0)).unwrap_or(0) as u32,
-            comments_count: r.try_get::<Option<i32>, _>("comments_count").unwrap_or(Some(0)).unwrap_or(0) as u32,
-            reputation: r.try_get::<Option<i32>, _>("reputation").unwrap_or(Some(0)).unwrap_or(0) as u32,
-        }))
+        Ok(hash.flatten())
     }
 
-    /// Ensure user exists (transactional)
-    async fn ensure_user_exists_tx(&self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, address: &str, name: &Option<String>) -> Result<(), sqlx::Error> {
-
-        let username = match name {
-            Some(n) if !n.is_empty() => {
-
-                let short_addr = &address[..8];
-                format!("{}_{}", n, short_addr)
-            },
-            _ => {
+    /// Archive the current version of a post in `post_revisions` and apply an edit, in one transaction
+    pub async fn update_post(&self, post: &Post) -> Result<(), sqlx::Error> {
+        let post_uuid = Uuid::parse_str(&post.id).map_err(|_| sqlx::Error::RowNotFound)?;
+        let mut tx = self.pool.begin().await?;
 
-                let short_addr = &address[2..10]; // 去掉0x前缀，取8位
-                format!("user_{}", short_addr)
-            }
-        };
-        
-        sqlx::query(
+        // Lock the row so concurrent edits get consecutive revision numbers
+        let archived = sqlx::query(
             r#"
-            INSERT INTO users (ethereum_address, username, posts_count, comments_count, reputation)
-            VALUES ($1, $2, 0, 0, 0)
-            ON CONFLICT (ethereum_address) DO NOTHING
+            INSERT INTO post_revisions (post_id, revision, title, content, tags, image, content_hash, irys_transaction_id, created_at, replaced_at)
+            SELECT p.id,
+                   COALESCE((SELECT MAX(r.revision) FROM post_revisions r WHERE r.post_id = p.id), 0) + 1,
+                   p.title, p.content, COALESCE(p.tags, '{}'), p.image, p.content_hash, p.irys_transaction_id,
+                   COALESCE(p.updated_at, p.created_at, NOW()), $2
+            FROM (SELECT * FROM posts WHERE id = $1 AND deleted_at IS NULL FOR UPDATE) p
             "#
         )
-        .bind(address)
-        .bind(&username)
-        .execute(&mut **tx)
+        .bind(post_uuid)
+        .bind(post.updated_at)
+        .execute(&mut *tx)
         .await?;
-        
-        Ok(())
-    }
-
-    /// Helper methods
-    pub async fn ensure_user_exists(&self, address: &str, name: &Option<String>) -> Result<(), sqlx::Error> {
-
-        let username = match name {
-            Some(n) if !n.is_empty() => {
-
-                let short_addr = &address[..8];
-                format!("{}_{}", n, short_addr)
-            },
-            _ => {
+        if archived.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
 
-                let short_addr = &address[2..10]; 
-                format!("user_{}", short_addr)
-            }
-        };
-        
         sqlx::query(
             r#"
-            INSERT INTO users (ethereum_address, username, posts_count, comments_count, reputation)
-            VALUES ($1, $2, 0, 0, 0)
-            ON CONFLICT (ethereum_address) DO NOTHING
+            UPDATE posts
+            SET title = $2, content = $3, content_hash = $4, tags = $5, image = $6, irys_transaction_id = $7, updated_at = $8, content_html = $9
+            WHERE id = $1
             "#
         )
-        .bind(address)
-        .bind(&username)
-        .execute(&self.pool)
+        .bind(post_uuid)
+        .bind(&post.title)
+        .bind(&post.content)
+        .bind(format!("{:x}", md5::compute(&post.content)))
+        .bind(&post.tags)
+        .bind(&post.image)
+        .bind(&post.irys_transaction_id)
+        .bind(post.updated_at)
+        .bind(&post.content_html)
+        .execute(&mut *tx)
         .await?;
-        
+
+        tx.commit().await?;
         Ok(())
     }
 
-    async fn update_user_stats(&self, address: &str, is_post: bool, is_comment: bool) -> Result<(), sqlx::Error> {
-        if is_post {
+    /// Get the prior versions of a post, oldest first
+    pub async fn get_post_revisions(&self, post_id: &str) -> Result<Vec<PostRevision>, sqlx::Error> {
+        let post_uuid = match Uuid::parse_str(post_id) {
+            Ok(uuid) => uuid,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT revision, title, content, tags, image, content_hash, irys_transaction_id, created_at, replaced_at
+            FROM post_revisions
+            WHERE post_id = $1
+            ORDER BY revision
+            "#
+        )
+        .bind(post_uuid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PostRevision {
+                    post_id: post_id.to_string(),
+                    revision: row.try_get::<i32, _>("revision")? as u32,
+                    title: row.try_get("title")?,
+                    content: row.try_get("content")?,
+                    tags: row.try_get::<Option<Vec<String>>, _>("tags")?.unwrap_or_default(),
+                    image: row.try_get("image")?,
+                    content_hash: row.try_get("content_hash")?,
+                    irys_transaction_id: row.try_get("irys_transaction_id")?,
+                    created_at: row.try_get("created_at")?,
+                    replaced_at: row.try_get("replaced_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Get a user's role; unknown addresses are plain users
+    pub async fn get_user_role(&self, address: &str) -> Result<UserRole, sqlx::Error> {
+        let role: Option<String> = sqlx::query_scalar(
+            "SELECT role FROM users WHERE LOWER(ethereum_address) = LOWER($1)"
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(role.map(|r| UserRole::from_db(&r)).unwrap_or(UserRole::User))
+    }
 
-            let update_sql = format!(
-                "UPDATE users SET posts_count = COALESCE(posts_count, 0) + 1, reputation = COALESCE(reputation, 0) + 10 WHERE ethereum_address = '{}'",
-                address.replace("'", "''") 
-            );
-            sqlx::query(&update_sql)
+    /// Change a user's role; false if the user does not exist
+    pub async fn update_user_role(&self, address: &str, role: UserRole) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE users SET role = $2 WHERE LOWER(ethereum_address) = LOWER($1)")
+            .bind(address)
+            .bind(role.as_str())
             .execute(&self.pool)
             .await?;
-        }
-        
-        if is_comment {
+        Ok(result.rows_affected() > 0)
+    }
 
-            let update_sql = format!(
-                "UPDATE users SET comments_count = COALESCE(comments_count, 0) + 1, reputation = COALESCE(reputation, 0) + 5 WHERE ethereum_address = '{}'",
-                address.replace("'", "''")
-            );
-            sqlx::query(&update_sql)
-            .execute(&self.pool)
+    /// Soft delete a post and decrement the author's `posts_count`, in one transaction.
+    /// Returns false if the post does not exist or is already deleted.
+    pub async fn delete_post(&self, post_id: &str, deleted_by: &str) -> Result<bool, sqlx::Error> {
+        let post_uuid = match Uuid::parse_str(post_id) {
+            Ok(uuid) => uuid,
+            Err(_) => return Ok(false),
+        };
+        let mut tx = self.pool.begin().await?;
+
+        let author_id: Option<Uuid> = sqlx::query_scalar(
+            "UPDATE posts SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL RETURNING author_id"
+        )
+        .bind(post_uuid)
+        .bind(deleted_by.to_lowercase())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(author_id) = author_id else {
+            return Ok(false);
+        };
+
+        sqlx::query("UPDATE users SET posts_count = GREATEST(0, COALESCE(posts_count, 0) - 1) WHERE id = $1")
+            .bind(author_id)
+            .execute(&mut *tx)
             .await?;
-        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Soft delete a comment and decrement `posts.comments_count` and the author's
+    /// `comments_count`, in one transaction. Returns false if the comment does not
+    /// exist or is already deleted.
+    pub async fn delete_comment(&self, comment_id: &str, deleted_by: &str) -> Result<bool, sqlx::Error> {
+        let comment_uuid = match Uuid::parse_str(comment_id) {
+            Ok(uuid) => uuid,
+            Err(_) => return Ok(false),
+        };
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "UPDATE comments SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL RETURNING author_id, post_id"
+        )
+        .bind(comment_uuid)
+        .bind(deleted_by.to_lowercase())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+
+        sqlx::query("UPDATE posts SET comments_count = GREATEST(0, COALESCE(comments_count, 0) - 1) WHERE id = $1")
+            .bind(row.try_get::<Uuid, _>("post_id")?)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE users SET comments_count = GREATEST(0, COALESCE(comments_count, 0) - 1) WHERE id = $1")
+            .bind(row.try_get::<Uuid, _>("author_id")?)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Resolve a comment's on-chain ID from the indexed CommentCreated event of its transaction
+    pub async fn get_comment_chain_id(&self, comment_id: &str) -> Result<Option<u64>, sqlx::Error> {
+        let comment_uuid = match Uuid::parse_str(comment_id) {
+            Ok(uuid) => uuid,
+            Err(_) => return Ok(None),
+        };
+
+        let chain_id: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT cc.comment_id
+            FROM comments c
+            JOIN chain_comment_created cc ON cc.transaction_hash = LOWER(c.blockchain_transaction_hash)
+            WHERE c.id = $1
+            LIMIT 1
+            "#
+        )
+        .bind(comment_uuid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(chain_id.map(|id| id as u64))
+    }
+
+    /// Check duplicate comment within a time window in the same post
+    pub async fn check_duplicate_comment(
+        &self,
+        author_address: &str,
+        content: &str,
+        post_id: &str
+    ) -> Result<bool, sqlx::Error> {
+        let post_uuid = uuid::Uuid::parse_str(post_id)
+            .map_err(|e| sqlx::Error::TypeNotFound { type_name: format!("Invalid UUID: {}", e) })?;
+        
+
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) 
+            FROM comments c
+            JOIN users u ON c.author_id = u.id
+            WHERE u.ethereum_address = $1 
+              AND c.content = $2 
+              AND c.post_id = $3
+              AND c.created_at > NOW() - INTERVAL '5 minutes'
+              AND c.deleted_at IS NULL
+            "#
+        )
+        .bind(author_address)
+        .bind(content)
+        .bind(post_uuid)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(count > 0)
+    }
+    
+    /// Check duplicate post within a time window
+    pub async fn check_duplicate_post(
+        &self,
+        author_address: &str,
+        content: &str
+    ) -> Result<bool, sqlx::Error> {
+
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) 
+            FROM posts p
+            JOIN users u ON p.author_id = u.id
+            WHERE u.ethereum_address = $1 
+              AND p.content = $2 
+              AND p.created_at > NOW() - INTERVAL '5 minutes'
+              AND p.deleted_at IS NULL
+            "#
+        )
+        .bind(author_address)
+        .bind(content)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(count > 0)
+    }
+
+    /// Simplified user query
+    pub async fn get_user_by_address(&self, address: &str) -> Result<Option<User>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, ethereum_address, username, bio, avatar, posts_count, comments_count, reputation, role, created_at FROM users WHERE LOWER(ethereum_address) = LOWER($1)"
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| User {
+            id: r.try_get::<uuid::Uuid, _>("id").unwrap_or_default().to_string(),
+            address: r.try_get::<Option<String>, _>("ethereum_address").unwrap_or_default().unwrap_or_default(),
+            name: r.try_get("username").ok(),
+            bio: r.try_get("bio").ok(),
+            avatar: r.try_get("avatar").ok(),
+            created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+            posts_count: r.try_get::<Option<i32>, _>("posts_count").unwrap_or(Some(0)).unwrap_or(0) as u32,
+            comments_count: r.try_get::<Option<i32>, _>("comments_count").unwrap_or(Some(0)).unwrap_or(0) as u32,
+            reputation: r.try_get::<Option<i32>, _>("reputation").unwrap_or(Some(0)).unwrap_or(0) as u32,
+            role: r.try_get::<String, _>("role").map(|role| UserRole::from_db(&role)).unwrap_or_default(),
+        }))
+    }
+
+    /// Ensure user exists (transactional)
+    async fn ensure_user_exists_tx(&self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, address: &str, name: &Option<String>) -> Result<(), sqlx::Error> {
+
+        let username = match name {
+            Some(n) if !n.is_empty() => {
+
+                let short_addr = &address[..8];
+                format!("{}_{}", n, short_addr)
+            },
+            _ => {
+
+                let short_addr = &address[2..10]; // 去掉0x前缀，取8位
+                format!("user_{}", short_addr)
+            }
+        };
+        
+        sqlx::query(
+            r#"
+            INSERT INTO users (ethereum_address, username, posts_count, comments_count, reputation)
+            VALUES ($1, $2, 0, 0, 0)
+            ON CONFLICT (ethereum_address) DO NOTHING
+            "#
+        )
+        .bind(address)
+        .bind(&username)
+        .execute(&mut **tx)
+        .await?;
+        
+        Ok(())
+    }
+
+    /// Helper methods
+    pub async fn ensure_user_exists(&self, address: &str, name: &Option<String>) -> Result<(), sqlx::Error> {
+
+        let username = match name {
+            Some(n) if !n.is_empty() => {
+
+                let short_addr = &address[..8];
+                format!("{}_{}", n, short_addr)
+            },
+            _ => {
+
+                let short_addr = &address[2..10]; 
+                format!("user_{}", short_addr)
+            }
+        };
+        
+        sqlx::query(
+            r#"
+            INSERT INTO users (ethereum_address, username, posts_count, comments_count, reputation)
+            VALUES ($1, $2, 0, 0, 0)
+            ON CONFLICT (ethereum_address) DO NOTHING
+            "#
+        )
+        .bind(address)
+        .bind(&username)
+        .execute(&self.pool)
+        .await?;
         
         Ok(())
     }
@@ -996,7 +1325,7 @@ impl DatabaseService {
     pub async fn get_active_users_ranking(&self, limit: i64) -> Result<Vec<User>, sqlx::Error> {
         let rows = sqlx::query(
             r#"
-            SELECT id, ethereum_address, username, bio, avatar, posts_count, comments_count, reputation, created_at
+            SELECT id, ethereum_address, username, bio, avatar, posts_count, comments_count, reputation, role, created_at
             FROM users 
             WHERE posts_count > 0 OR comments_count > 0
             ORDER BY reputation DESC, posts_count DESC, comments_count DESC
@@ -1017,6 +1346,7 @@ impl DatabaseService {
             posts_count: r.try_get::<Option<i32>, _>("posts_count").unwrap_or(Some(0)).unwrap_or(0) as u32,
             comments_count: r.try_get::<Option<i32>, _>("comments_count").unwrap_or(Some(0)).unwrap_or(0) as u32,
             reputation: r.try_get::<Option<i32>, _>("reputation").unwrap_or(Some(0)).unwrap_or(0) as u32,
+            role: r.try_get::<String, _>("role").map(|role| UserRole::from_db(&role)).unwrap_or_default(),
         }).collect();
 
         Ok(users)
@@ -1031,19 +1361,19 @@ impl DatabaseService {
         .await?;
 
         let post_count = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM posts"
+            "SELECT COUNT(*) FROM posts WHERE deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
 
         let comment_count = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM comments"
+            "SELECT COUNT(*) FROM comments c JOIN posts p ON p.id = c.post_id WHERE c.deleted_at IS NULL AND p.deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
 
         let total_likes = sqlx::query_scalar::<_, i64>(
-            "SELECT COALESCE(SUM(likes), 0) FROM posts"
+            "SELECT COALESCE(SUM(likes), 0) FROM posts WHERE deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
@@ -1214,6 +1544,18 @@ impl DatabaseService {
         Ok(username)
     }
     
+    /// Address that registered `username` (NFC-normalized like `register_username`)
+    pub async fn get_address_by_username(&self, username: &str) -> Result<Option<String>, sqlx::Error> {
+        let normalized: String = username.nfc().collect::<String>().trim().to_string();
+        sqlx::query_scalar::<_, Option<String>>(
+            "SELECT ethereum_address FROM users WHERE username = $1 AND has_username = true"
+        )
+        .bind(&normalized)
+        .fetch_optional(&self.pool)
+        .await
+        .map(Option::flatten)
+    }
+
     /// Check whether the user has registered a username
     pub async fn user_has_username(&self, address: &str) -> Result<bool, sqlx::Error> {
         let has_username = sqlx::query_scalar::<_, bool>(
@@ -1228,10 +1570,10 @@ impl DatabaseService {
     }
     
     /// Validate username format (letters/digits/underscore, 3-20 chars)
-    fn is_valid_username(username: &str) -> bool {
+    pub(crate) fn is_valid_username(username: &str) -> bool {
        
         let char_count = username.chars().count();
-        if char_count < 2 || char_count > 20 {
+        if !(2..=20).contains(&char_count) {
             return false;
         }
         
@@ -1313,31 +1655,6 @@ impl DatabaseService {
         Ok(())
     }
     
-    /// Record used transaction (username registration)
-    pub async fn record_username_transaction(
-        &self, 
-        tx_hash: &str, 
-        user_address: &str, 
-        block_number: u64,
-        block_timestamp: chrono::DateTime<chrono::Utc>
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query(
-            r#"
-            INSERT INTO used_transactions 
-            (transaction_hash, transaction_type, user_address, block_number, block_timestamp)
-            VALUES ($1, 'USERNAME_REGISTER', $2, $3, $4)
-            "#
-        )
-        .bind(tx_hash)
-        .bind(user_address)
-        .bind(block_number as i64)
-        .bind(block_timestamp)
-        .execute(&self.pool)
-        .await?;
-        
-        Ok(())
-    }
-    
     /// Update post blockchain transaction hash
     pub async fn update_post_blockchain_hash(&self, post_id: &str, tx_hash: &str) -> Result<(), sqlx::Error> {
        
@@ -1372,30 +1689,6 @@ impl DatabaseService {
         Ok(())
     }
     
-    /// Get user's transaction records
-    pub async fn get_user_transactions(&self, user_address: &str) -> Result<Vec<UserTransaction>, sqlx::Error> {
-        let transactions = sqlx::query_as::<_, UserTransaction>(
-            r#"
-            SELECT 
-                transaction_hash,
-                transaction_type as transaction_type_str,
-                user_address,
-                block_number,
-                block_timestamp,
-                verified_at,
-                post_id,
-                comment_id
-            FROM used_transactions 
-            WHERE user_address = $1 
-            ORDER BY verified_at DESC
-            "#
-        )
-        .bind(user_address)
-        .fetch_all(&self.pool)
-        .await?;
-        
-        Ok(transactions)
-    }
 
     /// Follow system related methods
     pub async fn follow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
@@ -1485,7 +1778,8 @@ impl DatabaseService {
         Ok((following_count, followers_count, mutual_follows_count))
     }
 
-    pub async fn get_following_list(&self, user_address: &str, limit: i64, offset: i64) -> Result<Vec<crate::models::UserProfile>, sqlx::Error> {
+    pub async fn get_following_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<crate::models::UserProfile>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
         let users = sqlx::query!(
             r#"
             SELECT 
@@ -1497,22 +1791,28 @@ impl DatabaseService {
                 u.comments_count,
                 u.reputation,
                 u.created_at,
-                u.ethereum_address
+                u.ethereum_address,
+                f.id as follow_id,
+                f.created_at as followed_at
             FROM follows f
             JOIN users u ON f.following_address = u.ethereum_address
             WHERE f.follower_address = $1
-            ORDER BY f.created_at DESC
+              AND ($4::TIMESTAMPTZ IS NULL OR (f.created_at, f.id) < ($4, $5))
+            ORDER BY f.created_at DESC, f.id DESC
             LIMIT $2 OFFSET $3
             "#,
             user_address,
-            limit,
-            offset
+            page.fetch_limit(),
+            page.offset as i64,
+            after_at,
+            after_id
         )
         .fetch_all(&self.pool)
         .await?;
 
+        let page = Page::from_rows(users, page.limit, |user| PageCursor::new(user.followed_at, &user.follow_id.to_string()));
         let mut profiles = Vec::new();
-        for user in users {
+        for user in page.items {
             let user_id = user.id.to_string();
             let ethereum_address = user.ethereum_address.unwrap_or_default();
             let (following_count, followers_count, mutual_follows_count) = self.get_follow_counts(&ethereum_address).await.unwrap_or((0, 0, 0));
@@ -1540,10 +1840,11 @@ impl DatabaseService {
             });
         }
 
-        Ok(profiles)
+        Ok(Page { items: profiles, next_cursor: page.next_cursor, has_more: page.has_more })
     }
 
-    pub async fn get_followers_list(&self, user_address: &str, limit: i64, offset: i64) -> Result<Vec<crate::models::UserProfile>, sqlx::Error> {
+    pub async fn get_followers_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<crate::models::UserProfile>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
         let users = sqlx::query!(
             r#"
             SELECT 
@@ -1555,22 +1856,28 @@ impl DatabaseService {
                 u.comments_count,
                 u.reputation,
                 u.created_at,
-                u.ethereum_address
+                u.ethereum_address,
+                f.id as follow_id,
+                f.created_at as followed_at
             FROM follows f
             JOIN users u ON f.follower_address = u.ethereum_address
             WHERE f.following_address = $1
-            ORDER BY f.created_at DESC
+              AND ($4::TIMESTAMPTZ IS NULL OR (f.created_at, f.id) < ($4, $5))
+            ORDER BY f.created_at DESC, f.id DESC
             LIMIT $2 OFFSET $3
             "#,
             user_address,
-            limit,
-            offset
+            page.fetch_limit(),
+            page.offset as i64,
+            after_at,
+            after_id
         )
         .fetch_all(&self.pool)
         .await?;
 
+        let page = Page::from_rows(users, page.limit, |user| PageCursor::new(user.followed_at, &user.follow_id.to_string()));
         let mut profiles = Vec::new();
-        for user in users {
+        for user in page.items {
             let user_id = user.id.to_string();
             let ethereum_address = user.ethereum_address.unwrap_or_default();
             let (following_count, followers_count, mutual_follows_count) = self.get_follow_counts(&ethereum_address).await.unwrap_or((0, 0, 0));
@@ -1598,7 +1905,7 @@ impl DatabaseService {
             });
         }
 
-        Ok(profiles)
+        Ok(Page { items: profiles, next_cursor: page.next_cursor, has_more: page.has_more })
     }
 
     /// Get user address by user ID
@@ -1617,7 +1924,8 @@ impl DatabaseService {
         }
     }
 
-    pub async fn get_mutual_follows_list(&self, user_address: &str, limit: i64, offset: i64) -> Result<Vec<crate::models::UserProfile>, sqlx::Error> {
+    pub async fn get_mutual_follows_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<crate::models::UserProfile>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
         let users = sqlx::query!(
             r#"
             SELECT 
@@ -1629,24 +1937,30 @@ impl DatabaseService {
                 u.comments_count,
                 u.reputation,
                 u.created_at,
-                u.ethereum_address
+                u.ethereum_address,
+                f1.id as follow_id,
+                f1.created_at as followed_at
             FROM follows f1
             JOIN follows f2 ON f1.following_address = f2.follower_address 
                            AND f1.follower_address = f2.following_address
             JOIN users u ON f1.following_address = u.ethereum_address
             WHERE f1.follower_address = $1
-            ORDER BY f1.created_at DESC
+              AND ($4::TIMESTAMPTZ IS NULL OR (f1.created_at, f1.id) < ($4, $5))
+            ORDER BY f1.created_at DESC, f1.id DESC
             LIMIT $2 OFFSET $3
             "#,
             user_address,
-            limit,
-            offset
+            page.fetch_limit(),
+            page.offset as i64,
+            after_at,
+            after_id
         )
         .fetch_all(&self.pool)
         .await?;
 
+        let page = Page::from_rows(users, page.limit, |user| PageCursor::new(user.followed_at, &user.follow_id.to_string()));
         let mut profiles = Vec::new();
-        for user in users {
+        for user in page.items {
             let user_id = user.id.to_string();
             let ethereum_address = user.ethereum_address.unwrap_or_default();
             let (following_count, followers_count, mutual_follows_count) = self.get_follow_counts(&ethereum_address).await.unwrap_or((0, 0, 0));
@@ -1672,12 +1986,20 @@ impl DatabaseService {
             });
         }
 
-        Ok(profiles)
+        Ok(Page { items: profiles, next_cursor: page.next_cursor, has_more: page.has_more })
+    }
+}
+
+/// Bind values for a `($n::TIMESTAMPTZ IS NULL OR (created_at, id) < ($n, $m))` keyset predicate
+fn cursor_params(page: &PageRequest) -> (Option<chrono::DateTime<Utc>>, Option<Uuid>) {
+    match &page.after {
+        Some(cursor) => (Some(cursor.created_at), Uuid::parse_str(&cursor.id).ok()),
+        None => (None, None),
     }
 }
 
 /// User transaction record struct
-#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize, Debug)]
+#[derive(sqlx::FromRow, serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct UserTransaction {
     pub transaction_hash: String,
     pub transaction_type_str: String, 
@@ -1827,7 +2149,7 @@ impl DatabaseService {
                     p.id,
                     p.created_at,
                     COALESCE(p.likes, 0) as likes,
-                    (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                    (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                     COALESCE(p.views, 0) as views,
                     -- Time decay factor calculation
                     CASE 
@@ -1838,7 +2160,7 @@ impl DatabaseService {
                     END as time_decay,
                     -- Heat score calculation: (likes x 3+comments x 2+views x 0.1) x time decay factor
                     (COALESCE(p.likes, 0) * 3 + 
-                     (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) * 2 + 
+                     (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) * 2 + 
                      COALESCE(p.views, 0) * 0.1) * 
                     CASE 
                         WHEN EXTRACT(EPOCH FROM (NOW() - p.created_at)) / 3600 <= 24 THEN 1.0
@@ -1848,6 +2170,7 @@ impl DatabaseService {
                     END as heat_score
                 FROM posts p
                 WHERE p.created_at >= NOW() - INTERVAL '7 days'  -- Only consider posts from the past 7 days
+                AND p.deleted_at IS NULL
                 AND p.title IS NOT NULL 
                 AND p.content IS NOT NULL
             )
@@ -1902,13 +2225,13 @@ impl DatabaseService {
     }
 
     /// Get daily recommendations
-    pub async fn get_daily_recommendations(&self, user_address: Option<&str>) -> Result<crate::models::RecommendationResult, sqlx::Error> {
-        let query = if let Some(user_addr) = user_address {
+    pub async fn get_daily_recommendations(&self, user_address: Option<&str>, excluded_authors: &[String]) -> Result<crate::models::RecommendationResult, sqlx::Error> {
+        let query = if user_address.is_some() {
             r#"
             SELECT 
-                p.id, p.title, p.content, p.created_at, p.image,
+                p.id, p.title, p.content, p.content_html, p.created_at, p.image,
                 COALESCE(p.likes, 0) as likes,
-                (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                 COALESCE(p.views, 0) as views,
                 COALESCE(p.tags, '{}') as tags,
                 u.ethereum_address,
@@ -1917,7 +2240,7 @@ impl DatabaseService {
                 CASE WHEN pl.user_address IS NOT NULL THEN true ELSE false END as is_liked_by_user,
                 -- Recalculate heat score for display
                 (COALESCE(p.likes, 0) * 3 + 
-                 (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) * 2 + 
+                 (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) * 2 + 
                  COALESCE(p.views, 0) * 0.1) * 
                 CASE 
                     WHEN EXTRACT(EPOCH FROM (NOW() - p.created_at)) / 3600 <= 24 THEN 1.0
@@ -1930,15 +2253,16 @@ impl DatabaseService {
             JOIN posts p ON dr.post_id = p.id
             JOIN users u ON p.author_id = u.id
             LEFT JOIN post_likes pl ON pl.post_id = p.id AND LOWER(pl.user_address) = LOWER($1)
-            WHERE DATE(dr.created_at) = CURRENT_DATE
+            WHERE DATE(dr.created_at) = CURRENT_DATE AND p.deleted_at IS NULL
+              AND LOWER(u.ethereum_address) <> ALL($2)
             ORDER BY dr.rank_position ASC
             "#
         } else {
             r#"
             SELECT 
-                p.id, p.title, p.content, p.created_at, p.image,
+                p.id, p.title, p.content, p.content_html, p.created_at, p.image,
                 COALESCE(p.likes, 0) as likes,
-                (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comments_count,
+                (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
                 COALESCE(p.views, 0) as views,
                 COALESCE(p.tags, '{}') as tags,
                 u.ethereum_address,
@@ -1947,7 +2271,7 @@ impl DatabaseService {
                 false as is_liked_by_user,
                 -- Recalculate heat score for display
                 (COALESCE(p.likes, 0) * 3 + 
-                 (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) * 2 + 
+                 (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) * 2 + 
                  COALESCE(p.views, 0) * 0.1) * 
                 CASE 
                     WHEN EXTRACT(EPOCH FROM (NOW() - p.created_at)) / 3600 <= 24 THEN 1.0
@@ -1959,7 +2283,8 @@ impl DatabaseService {
             FROM daily_recommendations dr
             JOIN posts p ON dr.post_id = p.id
             JOIN users u ON p.author_id = u.id
-            WHERE DATE(dr.created_at) = CURRENT_DATE
+            WHERE DATE(dr.created_at) = CURRENT_DATE AND p.deleted_at IS NULL
+              AND LOWER(u.ethereum_address) <> ALL($1)
             ORDER BY dr.rank_position ASC
             "#
         };
@@ -1967,10 +2292,12 @@ impl DatabaseService {
         let rows = if let Some(user_addr) = user_address {
             sqlx::query(query)
                 .bind(user_addr)
+                .bind(excluded_authors)
                 .fetch_all(&self.pool)
                 .await?
         } else {
             sqlx::query(query)
+                .bind(excluded_authors)
                 .fetch_all(&self.pool)
                 .await?
         };
@@ -1987,6 +2314,7 @@ impl DatabaseService {
                 id: row.try_get::<uuid::Uuid, _>("id").unwrap().to_string(),
                 title: row.try_get("title").unwrap(),
                 content: row.try_get("content").unwrap(),
+                content_html: content_html(&row),
                 author_address: row.try_get("ethereum_address").unwrap(),
                 author_id: None, 
                 author_name: row.try_get("author_name").ok(),
@@ -2001,7 +2329,9 @@ impl DatabaseService {
                 image: row.try_get("image").ok(),
                 blockchain_post_id: None,
                 is_liked_by_user: row.try_get::<bool, _>("is_liked_by_user").unwrap_or(false),
+                is_bookmarked_by_user: false,
                 heat_score: Some(row.try_get::<f64, _>("heat_score").unwrap_or(0.0)),
+                mentions: Vec::new(),
             };
             posts.push(post);
         }
@@ -2012,3 +2342,1479 @@ impl DatabaseService {
         })
     }
 } 
+
+/// Number of chain indexer checkpoints kept for reorg detection
+pub(crate) const CHAIN_CHECKPOINT_HISTORY: i64 = 256;
+
+// Chain indexer storage
+impl DatabaseService {
+    /// Get stored indexer checkpoints (block number, block hash), newest first
+    pub async fn get_chain_checkpoints(&self) -> Result<Vec<(u64, String)>, sqlx::Error> {
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT block_number, block_hash FROM chain_indexer_checkpoints ORDER BY block_number DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(number, hash)| (number as u64, hash)).collect())
+    }
+
+    /// Store a batch of decoded contract events and advance the checkpoint atomically
+    pub async fn store_chain_events(
+        &self,
+        events: &[crate::indexer::IndexedEvent],
+        checkpoint_block: u64,
+        checkpoint_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        use crate::blockchain::IrysForumEvents;
+
+        let mut tx = self.pool.begin().await?;
+
+        for indexed in events {
+            let query = match &indexed.event {
+                IrysForumEvents::PostCreatedFilter(e) => sqlx::query(
+                    r#"
+                    INSERT INTO chain_post_created
+                    (transaction_hash, log_index, block_number, block_hash, post_id, author_address, title, points_earned)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC)
+                    ON CONFLICT (transaction_hash, log_index) DO NOTHING
+                    "#
+                )
+                .bind(&indexed.transaction_hash)
+                .bind(indexed.log_index)
+                .bind(indexed.block_number)
+                .bind(&indexed.block_hash)
+                .bind(e.post_id.low_u64() as i64)
+                .bind(format!("{:?}", e.author))
+                .bind(&e.title)
+                .bind(e.reward.to_string()),
+                IrysForumEvents::CommentCreatedFilter(e) => sqlx::query(
+                    r#"
+                    INSERT INTO chain_comment_created
+                    (transaction_hash, log_index, block_number, block_hash, comment_id, post_id, author_address, points_earned)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC)
+                    ON CONFLICT (transaction_hash, log_index) DO NOTHING
+                    "#
+                )
+                .bind(&indexed.transaction_hash)
+                .bind(indexed.log_index)
+                .bind(indexed.block_number)
+                .bind(&indexed.block_hash)
+                .bind(e.comment_id.low_u64() as i64)
+                .bind(e.post_id.low_u64() as i64)
+                .bind(format!("{:?}", e.author))
+                .bind(e.reward.to_string()),
+                IrysForumEvents::PostLikedFilter(e) => sqlx::query(
+                    r#"
+                    INSERT INTO chain_post_liked
+                    (transaction_hash, log_index, block_number, block_hash, post_id, liker_address, author_address, points_earned)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC)
+                    ON CONFLICT (transaction_hash, log_index) DO NOTHING
+                    "#
+                )
+                .bind(&indexed.transaction_hash)
+                .bind(indexed.log_index)
+                .bind(indexed.block_number)
+                .bind(&indexed.block_hash)
+                .bind(e.post_id.low_u64() as i64)
+                .bind(format!("{:?}", e.liker))
+                .bind(format!("{:?}", e.author))
+                .bind(e.reward.to_string()),
+                IrysForumEvents::CommentLikedFilter(e) => sqlx::query(
+                    r#"
+                    INSERT INTO chain_comment_liked
+                    (transaction_hash, log_index, block_number, block_hash, comment_id, liker_address, author_address, points_earned)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8::NUMERIC)
+                    ON CONFLICT (transaction_hash, log_index) DO NOTHING
+                    "#
+                )
+                .bind(&indexed.transaction_hash)
+                .bind(indexed.log_index)
+                .bind(indexed.block_number)
+                .bind(&indexed.block_hash)
+                .bind(e.comment_id.low_u64() as i64)
+                .bind(format!("{:?}", e.liker))
+                .bind(format!("{:?}", e.author))
+                .bind(e.reward.to_string()),
+                IrysForumEvents::UsernameRegisteredFilter(e) => sqlx::query(
+                    r#"
+                    INSERT INTO chain_username_registered
+                    (transaction_hash, log_index, block_number, block_hash, user_address, username)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (transaction_hash, log_index) DO NOTHING
+                    "#
+                )
+                .bind(&indexed.transaction_hash)
+                .bind(indexed.log_index)
+                .bind(indexed.block_number)
+                .bind(&indexed.block_hash)
+                .bind(format!("{:?}", e.user))
+                .bind(&e.username),
+                IrysForumEvents::PointsEarnedFilter(e) => sqlx::query(
+                    r#"
+                    INSERT INTO chain_points_earned
+                    (transaction_hash, log_index, block_number, block_hash, user_address, points, reason)
+                    VALUES ($1, $2, $3, $4, $5, $6::NUMERIC, $7)
+                    ON CONFLICT (transaction_hash, log_index) DO NOTHING
+                    "#
+                )
+                .bind(&indexed.transaction_hash)
+                .bind(indexed.log_index)
+                .bind(indexed.block_number)
+                .bind(&indexed.block_hash)
+                .bind(format!("{:?}", e.user))
+                .bind(e.points.to_string())
+                .bind(&e.reason),
+                // Other contract events are not mirrored
+                _ => continue,
+            };
+            query.execute(&mut *tx).await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO chain_indexer_checkpoints (block_number, block_hash)
+            VALUES ($1, $2)
+            ON CONFLICT (block_number) DO UPDATE SET block_hash = EXCLUDED.block_hash, created_at = NOW()
+            "#
+        )
+        .bind(checkpoint_block as i64)
+        .bind(checkpoint_hash)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM chain_indexer_checkpoints
+            WHERE block_number < (
+                SELECT block_number FROM chain_indexer_checkpoints
+                ORDER BY block_number DESC
+                OFFSET $1 LIMIT 1
+            )
+            "#
+        )
+        .bind(CHAIN_CHECKPOINT_HISTORY - 1)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete indexed events and checkpoints above `after_block` (everything if None)
+    pub async fn rollback_chain_events(&self, after_block: Option<u64>) -> Result<(), sqlx::Error> {
+        let after_block = after_block.map(|block| block as i64).unwrap_or(-1);
+        let mut tx = self.pool.begin().await?;
+
+        for table in [
+            "chain_post_created",
+            "chain_comment_created",
+            "chain_post_liked",
+            "chain_comment_liked",
+            "chain_username_registered",
+            "chain_points_earned",
+            "chain_indexer_checkpoints",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} WHERE block_number > $1", table))
+                .bind(after_block)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+const TASK_COLUMNS: &str = "id, payload::TEXT AS payload, status, attempts, last_error, result::TEXT AS result, run_at, created_at, updated_at, completed_at";
+
+fn task_from_row(row: &sqlx::postgres::PgRow) -> Result<crate::async_queue::QueuedTask, sqlx::Error> {
+    use crate::async_queue::{QueuedTask, TaskStatus};
+
+    let payload: String = row.try_get("payload")?;
+    let result: Option<String> = row.try_get("result")?;
+    let status: String = row.try_get("status")?;
+    let last_error: Option<String> = row.try_get("last_error")?;
+
+    Ok(QueuedTask {
+        id: row.try_get("id")?,
+        task: serde_json::from_str(&payload).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        status: TaskStatus::from_db(&status, last_error.as_deref()),
+        attempts: row.try_get::<i32, _>("attempts")? as u32,
+        last_error,
+        result: result.and_then(|r| serde_json::from_str(&r).ok()),
+        run_at: row.try_get("run_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        completed_at: row.try_get("completed_at")?,
+    })
+}
+
+// Async task queue storage
+impl DatabaseService {
+    /// Insert a task if fewer than `max_queued` tasks are pending or processing
+    pub async fn enqueue_task(&self, task: &crate::async_queue::QueuedTask, max_queued: u64) -> Result<bool, sqlx::Error> {
+        let payload = serde_json::to_string(&task.task).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO async_tasks (id, kind, payload, status, attempts, run_at, created_at, updated_at)
+            SELECT $1, $2, $3::JSONB, 'pending', 0, $4, $5, $5
+            WHERE (SELECT COUNT(*) FROM async_tasks WHERE status IN ('pending', 'processing')) < $6
+            "#
+        )
+        .bind(&task.id)
+        .bind(task.task.kind())
+        .bind(payload)
+        .bind(task.run_at)
+        .bind(task.created_at)
+        .bind(max_queued.min(i64::MAX as u64) as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(inserted.rows_affected() == 1)
+    }
+
+    /// Claim the oldest due task; `SKIP LOCKED` lets several workers (and instances) poll concurrently
+    pub async fn claim_task(&self, lease: Duration) -> Result<Option<crate::async_queue::QueuedTask>, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE async_tasks
+            SET status = 'processing',
+                attempts = attempts + 1,
+                locked_until = NOW() + $1 * INTERVAL '1 second',
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM async_tasks
+                WHERE (status = 'pending' AND run_at <= NOW())
+                   OR (status = 'processing' AND locked_until < NOW())
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            TASK_COLUMNS
+        ))
+        .bind(lease.as_secs_f64())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(task_from_row).transpose()
+    }
+
+    pub async fn complete_task(&self, task_id: &str, result: &serde_json::Value) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE async_tasks
+            SET status = 'completed', result = $2::JSONB, last_error = NULL, locked_until = NULL,
+                completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(task_id)
+        .bind(result.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn reschedule_task(&self, task_id: &str, error: &str, run_at: chrono::DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE async_tasks
+            SET status = 'pending', last_error = $2, run_at = $3, locked_until = NULL, updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(task_id)
+        .bind(error)
+        .bind(run_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn dead_letter_task(&self, task_id: &str, error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE async_tasks
+            SET status = 'dead', last_error = $2, locked_until = NULL, completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(task_id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_task(&self, task_id: &str) -> Result<Option<crate::async_queue::QueuedTask>, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM async_tasks WHERE id = $1", TASK_COLUMNS))
+            .bind(task_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(task_from_row).transpose()
+    }
+
+    /// Delete completed tasks; dead-lettered tasks are kept until replayed or removed by an operator
+    pub async fn evict_completed_tasks(&self, before: chrono::DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM async_tasks WHERE status = 'completed' AND completed_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+// Full-text search over the generated `search_vector` columns (migration 0006)
+impl DatabaseService {
+    pub async fn search(&self, request: &SearchRequest) -> Result<SearchResults, sqlx::Error> {
+        let mut results = SearchResults::default();
+        if request.include_posts {
+            results.posts = self.search_posts(request).await?;
+        }
+        if request.include_comments {
+            results.comments = self.search_comments(request).await?;
+        }
+        if request.include_users {
+            results.users = self.search_users(request).await?;
+        }
+        Ok(results)
+    }
+
+    async fn search_posts(&self, request: &SearchRequest) -> Result<Vec<PostSearchHit>, sqlx::Error> {
+        // Rank and paginate first so `ts_headline` only runs on the returned page
+        let rows = sqlx::query(
+            r#"
+            WITH q AS (SELECT websearch_to_tsquery('simple', $1) AS query),
+            hits AS (
+                SELECT p.id, ts_rank_cd(p.search_vector, q.query) AS rank
+                FROM posts p
+                JOIN users u ON p.author_id = u.id
+                CROSS JOIN q
+                WHERE p.search_vector @@ q.query
+                  AND p.deleted_at IS NULL
+                  AND ($2::TEXT IS NULL OR $2 = ANY(p.tags))
+                  AND ($3::TEXT IS NULL OR LOWER(u.ethereum_address) = LOWER($3))
+                  AND ($4::TIMESTAMPTZ IS NULL OR p.created_at >= $4)
+                  AND ($5::TIMESTAMPTZ IS NULL OR p.created_at < $5)
+                ORDER BY rank DESC, p.created_at DESC
+                LIMIT $6 OFFSET $7
+            )
+            SELECT p.id, p.title, COALESCE(p.tags, '{}') as tags, p.created_at, hits.rank,
+                   u.ethereum_address, COALESCE(u.username, p.author_name) as author_name,
+                   ts_headline('simple', p.content, q.query, $8) as snippet
+            FROM hits
+            JOIN posts p ON p.id = hits.id
+            JOIN users u ON p.author_id = u.id
+            CROSS JOIN q
+            ORDER BY hits.rank DESC, p.created_at DESC
+            "#
+        )
+        .bind(&request.q)
+        .bind(&request.tag)
+        .bind(&request.author)
+        .bind(request.from)
+        .bind(request.to)
+        .bind(request.limit as i64)
+        .bind(request.offset as i64)
+        .bind(crate::search::headline_options())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(PostSearchHit {
+                    post_id: row.try_get::<Uuid, _>("id")?.to_string(),
+                    title: row.try_get("title")?,
+                    snippet: crate::search::render_snippet(&row.try_get::<String, _>("snippet")?),
+                    author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                    author_name: row.try_get("author_name")?,
+                    tags: row.try_get("tags")?,
+                    created_at: row.try_get("created_at")?,
+                    rank: row.try_get("rank")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn search_comments(&self, request: &SearchRequest) -> Result<Vec<CommentSearchHit>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            WITH q AS (SELECT websearch_to_tsquery('simple', $1) AS query),
+            hits AS (
+                SELECT c.id, ts_rank_cd(c.search_vector, q.query) AS rank
+                FROM comments c
+                JOIN posts p ON c.post_id = p.id
+                JOIN users u ON c.author_id = u.id
+                CROSS JOIN q
+                WHERE c.search_vector @@ q.query
+                  AND c.deleted_at IS NULL
+                  AND p.deleted_at IS NULL
+                  AND ($2::TEXT IS NULL OR $2 = ANY(p.tags))
+                  AND ($3::TEXT IS NULL OR LOWER(u.ethereum_address) = LOWER($3))
+                  AND ($4::TIMESTAMPTZ IS NULL OR c.created_at >= $4)
+                  AND ($5::TIMESTAMPTZ IS NULL OR c.created_at < $5)
+                ORDER BY rank DESC, c.created_at DESC
+                LIMIT $6 OFFSET $7
+            )
+            SELECT c.id, c.post_id, p.title as post_title, c.created_at, hits.rank,
+                   u.ethereum_address, COALESCE(c.author_name, u.username) as author_name,
+                   ts_headline('simple', c.content, q.query, $8) as snippet
+            FROM hits
+            JOIN comments c ON c.id = hits.id
+            JOIN posts p ON c.post_id = p.id
+            JOIN users u ON c.author_id = u.id
+            CROSS JOIN q
+            ORDER BY hits.rank DESC, c.created_at DESC
+            "#
+        )
+        .bind(&request.q)
+        .bind(&request.tag)
+        .bind(&request.author)
+        .bind(request.from)
+        .bind(request.to)
+        .bind(request.limit as i64)
+        .bind(request.offset as i64)
+        .bind(crate::search::headline_options())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(CommentSearchHit {
+                    comment_id: row.try_get::<Uuid, _>("id")?.to_string(),
+                    post_id: row.try_get::<Uuid, _>("post_id")?.to_string(),
+                    post_title: row.try_get("post_title")?,
+                    snippet: crate::search::render_snippet(&row.try_get::<String, _>("snippet")?),
+                    author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                    author_name: row.try_get("author_name")?,
+                    created_at: row.try_get("created_at")?,
+                    rank: row.try_get("rank")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn search_users(&self, request: &SearchRequest) -> Result<Vec<UserSearchHit>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT u.ethereum_address, u.username, u.avatar, ts_rank_cd(u.search_vector, q.query) AS rank
+            FROM users u, websearch_to_tsquery('simple', $1) q(query)
+            WHERE u.search_vector @@ q.query
+            ORDER BY rank DESC, u.reputation DESC NULLS LAST
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(&request.q)
+        .bind(request.limit as i64)
+        .bind(request.offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(UserSearchHit {
+                    address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                    username: row.try_get("username")?,
+                    avatar: row.try_get("avatar")?,
+                    rank: row.try_get("rank")?,
+                })
+            })
+            .collect()
+    }
+}
+
+// Notifications (migration 0008); addresses are stored lowercase
+impl DatabaseService {
+    pub async fn create_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error> {
+        let post_id = notification.post_id.as_deref().map(Uuid::parse_str).transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Invalid post id: {}", e)))?;
+        let comment_id = notification.comment_id.as_deref().map(Uuid::parse_str).transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Invalid comment id: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (recipient_address, actor_address, kind, post_id, comment_id, group_key)
+            VALUES (LOWER($1), LOWER($2), $3, $4, $5, $6)
+            ON CONFLICT (recipient_address, group_key, actor_address) DO UPDATE
+            SET kind = EXCLUDED.kind,
+                post_id = EXCLUDED.post_id,
+                comment_id = EXCLUDED.comment_id,
+                read_at = NULL,
+                created_at = NOW()
+            "#
+        )
+        .bind(&notification.recipient_address)
+        .bind(&notification.actor_address)
+        .bind(notification.kind.as_str())
+        .bind(post_id)
+        .bind(comment_id)
+        .bind(&notification.group_key)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM notifications WHERE recipient_address = LOWER($1) AND group_key = $2 AND actor_address = LOWER($3)")
+            .bind(&notification.recipient_address)
+            .bind(&notification.group_key)
+            .bind(&notification.actor_address)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_notification_groups(&self, recipient_address: &str, page: &PageRequest, unread_only: bool) -> Result<Page<NotificationGroup>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        let rows = sqlx::query(
+            r#"
+            WITH grouped AS (
+                SELECT n.group_key,
+                       (array_agg(n.id ORDER BY n.created_at DESC, n.id DESC))[1] AS latest_id,
+                       MAX(n.created_at) AS latest_at,
+                       COUNT(*) AS actor_count,
+                       COUNT(*) FILTER (WHERE n.read_at IS NULL) AS unread_count,
+                       (array_agg(n.actor_address ORDER BY n.created_at DESC, n.id DESC))[1:$7] AS actor_addresses
+                FROM notifications n
+                WHERE n.recipient_address = LOWER($1)
+                GROUP BY n.group_key
+            )
+            SELECT g.group_key, g.latest_id, g.latest_at, g.actor_count, g.unread_count, g.actor_addresses,
+                   ARRAY(
+                       SELECT COALESCE(
+                           (SELECT u.username FROM users u WHERE LOWER(u.ethereum_address) = a.address LIMIT 1), ''
+                       )
+                       FROM unnest(g.actor_addresses) WITH ORDINALITY AS a(address, ord)
+                       ORDER BY a.ord
+                   ) AS actor_names,
+                   n.kind, n.post_id, n.comment_id, p.title AS post_title
+            FROM grouped g
+            JOIN notifications n ON n.id = g.latest_id
+            LEFT JOIN posts p ON p.id = n.post_id AND p.deleted_at IS NULL
+            WHERE (NOT $4 OR g.unread_count > 0)
+              AND ($5::TIMESTAMPTZ IS NULL OR (g.latest_at, g.latest_id) < ($5, $6))
+            ORDER BY g.latest_at DESC, g.latest_id DESC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(recipient_address)
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(unread_only)
+        .bind(after_at)
+        .bind(after_id)
+        .bind(NotificationGroup::MAX_ACTORS as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut groups = Vec::new();
+        for row in rows {
+            let kind: String = row.try_get("kind")?;
+            let Some(kind) = NotificationKind::from_db(&kind) else {
+                warn!("⚠️ Skipping notification group of unknown kind: {}", kind);
+                continue;
+            };
+            let addresses: Vec<String> = row.try_get("actor_addresses")?;
+            let names: Vec<String> = row.try_get("actor_names")?;
+            groups.push(NotificationGroup {
+                id: row.try_get::<Uuid, _>("latest_id")?.to_string(),
+                group_key: row.try_get("group_key")?,
+                kind,
+                post_id: row.try_get::<Option<Uuid>, _>("post_id")?.map(|id| id.to_string()),
+                comment_id: row.try_get::<Option<Uuid>, _>("comment_id")?.map(|id| id.to_string()),
+                post_title: row.try_get("post_title")?,
+                actors: addresses.into_iter()
+                    .zip(names)
+                    .map(|(address, name)| NotificationActor { address, name: Some(name).filter(|n| !n.is_empty()) })
+                    .collect(),
+                actor_count: row.try_get::<i64, _>("actor_count")? as u32,
+                unread: row.try_get::<i64, _>("unread_count")? > 0,
+                message: String::new(),
+                created_at: row.try_get("latest_at")?,
+            });
+        }
+
+        Ok(Page::from_rows(groups, page.limit, NotificationGroup::page_cursor))
+    }
+
+    pub async fn mark_notifications_read(&self, recipient_address: &str, group_keys: Option<&[String]>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications SET read_at = NOW()
+            WHERE recipient_address = LOWER($1) AND read_at IS NULL
+              AND ($2::TEXT[] IS NULL OR group_key = ANY($2))
+            "#
+        )
+        .bind(recipient_address)
+        .bind(group_keys)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn count_unread_notifications(&self, recipient_address: &str) -> Result<u32, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT group_key) FROM notifications WHERE recipient_address = LOWER($1) AND read_at IS NULL"
+        )
+        .bind(recipient_address)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u32)
+    }
+}
+
+// Mentions (migration 0009)
+impl DatabaseService {
+    pub async fn replace_mentions(&self, post_id: &str, comment_id: Option<&str>, mentions: &[Mention]) -> Result<(), sqlx::Error> {
+        let post_uuid = Uuid::parse_str(post_id).map_err(|e| sqlx::Error::Protocol(format!("Invalid post id: {}", e)))?;
+        let comment_uuid = comment_id.map(Uuid::parse_str).transpose()
+            .map_err(|e| sqlx::Error::Protocol(format!("Invalid comment id: {}", e)))?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM mentions WHERE post_id = $1 AND comment_id IS NOT DISTINCT FROM $2")
+            .bind(post_uuid)
+            .bind(comment_uuid)
+            .execute(&mut *tx)
+            .await?;
+        if !mentions.is_empty() {
+            sqlx::query(
+                r#"
+                INSERT INTO mentions (post_id, comment_id, mentioned_address, username, start_offset, end_offset)
+                SELECT $1, $2, m.address, m.username, m.start_offset, m.end_offset
+                FROM UNNEST($3::TEXT[], $4::TEXT[], $5::INTEGER[], $6::INTEGER[]) AS m(address, username, start_offset, end_offset)
+                "#
+            )
+            .bind(post_uuid)
+            .bind(comment_uuid)
+            .bind(mentions.iter().map(|m| m.address.clone()).collect::<Vec<_>>())
+            .bind(mentions.iter().map(|m| m.username.clone()).collect::<Vec<_>>())
+            .bind(mentions.iter().map(|m| m.start as i32).collect::<Vec<_>>())
+            .bind(mentions.iter().map(|m| m.end as i32).collect::<Vec<_>>())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Mentions keyed by comment id when `comments`, otherwise by post id (post content only)
+    pub async fn get_mentions(&self, ids: &[String], comments: bool) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error> {
+        let uuids: Vec<Uuid> = ids.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect();
+        if uuids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT CASE WHEN $2 THEN comment_id ELSE post_id END AS owner_id,
+                   username, mentioned_address, start_offset, end_offset
+            FROM mentions
+            WHERE ($2 AND comment_id = ANY($1)) OR (NOT $2 AND comment_id IS NULL AND post_id = ANY($1))
+            ORDER BY owner_id, start_offset
+            "#
+        )
+        .bind(&uuids)
+        .bind(comments)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut mentions: HashMap<String, Vec<Mention>> = HashMap::new();
+        for row in rows {
+            let owner_id: Uuid = row.try_get("owner_id")?;
+            mentions.entry(owner_id.to_string()).or_default().push(Mention {
+                username: row.try_get("username")?,
+                address: row.try_get("mentioned_address")?,
+                start: row.try_get::<i32, _>("start_offset")? as u32,
+                end: row.try_get::<i32, _>("end_offset")? as u32,
+            });
+        }
+        Ok(mentions)
+    }
+}
+
+// Tags (migration 0010); `tags.post_count` is maintained by a trigger on posts
+impl DatabaseService {
+    pub async fn get_posts_by_tag(&self, tag: &str, page: &PageRequest, viewer: Option<&str>) -> Result<Page<Post>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        // `tags @> ARRAY[tag]` rather than `tag = ANY(tags)` so idx_posts_tags (GIN) applies
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes,
+                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
+                   p.tags, p.irys_transaction_id,
+                   p.created_at, p.updated_at, p.image, p.blockchain_post_id,
+                   COALESCE(p.views, 0) as views,
+                   u.id as user_id, u.ethereum_address,
+                   COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar,
+                   ($6::TEXT IS NOT NULL AND EXISTS (
+                       SELECT 1 FROM post_likes pl WHERE pl.post_id = p.id AND LOWER(pl.user_address) = LOWER($6)
+                   )) as is_liked_by_user
+            FROM posts p
+            JOIN users u ON p.author_id = u.id
+            WHERE p.tags @> ARRAY[$1]::TEXT[] AND p.deleted_at IS NULL
+              AND ($4::TIMESTAMPTZ IS NULL OR (p.created_at, p.id) < ($4, $5))
+            ORDER BY p.created_at DESC, p.id DESC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(tag)
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .bind(viewer)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut posts = Vec::new();
+        for row in rows {
+            posts.push(Post {
+                id: row.try_get::<Uuid, _>("id")?.to_string(),
+                title: row.try_get("title")?,
+                content: row.try_get("content")?,
+                content_html: content_html(&row),
+                author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
+                author_name: row.try_get("author_name")?,
+                author_avatar: row.try_get("author_avatar").ok(),
+                created_at: row.try_get("created_at")?,
+                updated_at: row.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+                likes: row.try_get::<i32, _>("likes").unwrap_or(0) as u32,
+                comments_count: row.try_get::<i64, _>("comments_count").unwrap_or(0) as u32,
+                views: row.try_get::<i32, _>("views").unwrap_or(0) as u32,
+                tags: row.try_get("tags")?,
+                irys_transaction_id: row.try_get("irys_transaction_id").ok(),
+                image: row.try_get("image").ok(),
+                blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
+                is_liked_by_user: row.try_get("is_liked_by_user")?,
+                is_bookmarked_by_user: false,
+                heat_score: None,
+                mentions: Vec::new(),
+            });
+        }
+
+        Ok(Page::from_rows(posts, page.limit, Post::page_cursor))
+    }
+
+    pub async fn get_tag(&self, tag: &str) -> Result<Option<TagSummary>, sqlx::Error> {
+        let row = sqlx::query("SELECT name, post_count, last_used_at FROM tags WHERE name = $1 AND post_count > 0")
+            .bind(tag)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| {
+            Ok(TagSummary {
+                name: row.try_get("name")?,
+                post_count: row.try_get::<i32, _>("post_count")? as u32,
+                last_used_at: row.try_get("last_used_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    pub async fn get_trending_tags(&self, since: chrono::DateTime<Utc>, limit: u32) -> Result<Vec<TrendingTag>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.name, COUNT(*) AS recent_posts, COALESCE(SUM(p.likes), 0) AS recent_likes
+            FROM posts p
+            CROSS JOIN LATERAL unnest(p.tags) AS t(name)
+            WHERE p.deleted_at IS NULL AND p.created_at >= $1
+            GROUP BY t.name
+            ORDER BY recent_posts DESC, recent_likes DESC, t.name
+            LIMIT $2
+            "#
+        )
+        .bind(since)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(TrendingTag {
+                    name: row.try_get("name")?,
+                    recent_posts: row.try_get::<i64, _>("recent_posts")? as u32,
+                    recent_likes: row.try_get::<i64, _>("recent_likes")? as u32,
+                })
+            })
+            .collect()
+    }
+}
+
+// Bookmarks (migration 0011)
+impl DatabaseService {
+    pub async fn add_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        let post_uuid = Uuid::parse_str(post_id).map_err(|_| sqlx::Error::RowNotFound)?;
+        let result = sqlx::query("INSERT INTO bookmarks (user_address, post_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(user_address.to_lowercase())
+            .bind(post_uuid)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn remove_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        let Ok(post_uuid) = Uuid::parse_str(post_id) else {
+            return Ok(false);
+        };
+        let result = sqlx::query("DELETE FROM bookmarks WHERE user_address = $1 AND post_id = $2")
+            .bind(user_address.to_lowercase())
+            .bind(post_uuid)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_bookmarks(&self, user_address: &str, page: &PageRequest) -> Result<Page<BookmarkedPost>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, p.title, p.content, p.content_html, COALESCE(p.likes, 0) as likes,
+                   (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id AND c.deleted_at IS NULL) as comments_count,
+                   p.tags, p.irys_transaction_id,
+                   p.created_at, p.updated_at, p.image, p.blockchain_post_id,
+                   COALESCE(p.views, 0) as views,
+                   u.id as user_id, u.ethereum_address,
+                   COALESCE(p.author_name, u.username) as author_name, u.avatar as author_avatar,
+                   EXISTS (
+                       SELECT 1 FROM post_likes pl WHERE pl.post_id = p.id AND LOWER(pl.user_address) = b.user_address
+                   ) as is_liked_by_user,
+                   b.created_at as bookmarked_at
+            FROM bookmarks b
+            JOIN posts p ON p.id = b.post_id
+            JOIN users u ON p.author_id = u.id
+            WHERE b.user_address = $1 AND p.deleted_at IS NULL
+              AND ($4::TIMESTAMPTZ IS NULL OR (b.created_at, b.post_id) < ($4, $5))
+            ORDER BY b.created_at DESC, b.post_id DESC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(user_address.to_lowercase())
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut bookmarks = Vec::new();
+        for row in rows {
+            bookmarks.push(BookmarkedPost {
+                post: Post {
+                    id: row.try_get::<Uuid, _>("id")?.to_string(),
+                    title: row.try_get("title")?,
+                    content: row.try_get("content")?,
+                    content_html: content_html(&row),
+                    author_address: row.try_get::<Option<String>, _>("ethereum_address")?.unwrap_or_default(),
+                    author_id: row.try_get::<Uuid, _>("user_id").ok().map(|id| id.to_string()),
+                    author_name: row.try_get("author_name")?,
+                    author_avatar: row.try_get("author_avatar").ok(),
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+                    likes: row.try_get::<i32, _>("likes").unwrap_or(0) as u32,
+                    comments_count: row.try_get::<i64, _>("comments_count").unwrap_or(0) as u32,
+                    views: row.try_get::<i32, _>("views").unwrap_or(0) as u32,
+                    tags: row.try_get("tags")?,
+                    irys_transaction_id: row.try_get("irys_transaction_id").ok(),
+                    image: row.try_get("image").ok(),
+                    blockchain_post_id: row.try_get::<Option<i32>, _>("blockchain_post_id").ok().flatten().map(|id| id as u32),
+                    is_liked_by_user: row.try_get("is_liked_by_user")?,
+                    is_bookmarked_by_user: true,
+                    heat_score: None,
+                    mentions: Vec::new(),
+                },
+                bookmarked_at: row.try_get("bookmarked_at")?,
+            });
+        }
+
+        Ok(Page::from_rows(bookmarks, page.limit, BookmarkedPost::page_cursor))
+    }
+}
+
+const REPORT_COLUMNS: &str = "id, reporter_address, target_type, target_id, reason, details, status, resolution, resolved_by, resolved_at, created_at";
+
+fn report_from_row(row: &sqlx::postgres::PgRow) -> Result<Report, sqlx::Error> {
+    let target_type: String = row.try_get("target_type")?;
+    let reason: String = row.try_get("reason")?;
+    let status: String = row.try_get("status")?;
+    let resolution: Option<String> = row.try_get("resolution")?;
+
+    Ok(Report {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        reporter_address: row.try_get("reporter_address")?,
+        target_type: ModerationTarget::from_db(&target_type)
+            .ok_or_else(|| sqlx::Error::Decode(format!("unknown report target: {}", target_type).into()))?,
+        target_id: row.try_get("target_id")?,
+        reason: ReportReason::from_db(&reason),
+        details: row.try_get("details")?,
+        status: ReportStatus::from_db(&status),
+        resolution: resolution.as_deref().and_then(ModerationAction::from_db),
+        resolved_by: row.try_get("resolved_by")?,
+        resolved_at: row.try_get("resolved_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn moderation_log_from_row(row: &sqlx::postgres::PgRow) -> Result<ModerationLogEntry, sqlx::Error> {
+    let action: String = row.try_get("action")?;
+    let target_type: String = row.try_get("target_type")?;
+
+    Ok(ModerationLogEntry {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        moderator_address: row.try_get("moderator_address")?,
+        action: ModerationAction::from_db(&action)
+            .ok_or_else(|| sqlx::Error::Decode(format!("unknown moderation action: {}", action).into()))?,
+        target_type: ModerationTarget::from_db(&target_type)
+            .ok_or_else(|| sqlx::Error::Decode(format!("unknown moderation target: {}", target_type).into()))?,
+        target_id: row.try_get("target_id")?,
+        report_id: row.try_get::<Option<Uuid>, _>("report_id")?.map(|id| id.to_string()),
+        reason: row.try_get("reason")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+// Reports, locks, suspensions and the moderation log (migration 0012)
+impl DatabaseService {
+    pub async fn create_report(&self, report: &Report) -> Result<bool, sqlx::Error> {
+        let id = Uuid::parse_str(&report.id).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reports (id, reporter_address, target_type, target_id, reason, details, status, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, 'open', $7)
+            ON CONFLICT (reporter_address, target_type, target_id) WHERE status = 'open' DO NOTHING
+            "#
+        )
+        .bind(id)
+        .bind(report.reporter_address.to_lowercase())
+        .bind(report.target_type.as_str())
+        .bind(&report.target_id)
+        .bind(report.reason.as_str())
+        .bind(&report.details)
+        .bind(report.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_report(&self, report_id: &str) -> Result<Option<Report>, sqlx::Error> {
+        let Ok(id) = Uuid::parse_str(report_id) else {
+            return Ok(None);
+        };
+        let row = sqlx::query(&format!("SELECT {} FROM reports WHERE id = $1", REPORT_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(report_from_row).transpose()
+    }
+
+    pub async fn get_open_reports(&self, page: &PageRequest) -> Result<Page<Report>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {} FROM reports
+            WHERE status = 'open' AND ($3::TIMESTAMPTZ IS NULL OR (created_at, id) > ($3, $4))
+            ORDER BY created_at, id
+            LIMIT $1 OFFSET $2
+            "#,
+            REPORT_COLUMNS
+        ))
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let reports = rows.iter().map(report_from_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page::from_rows(reports, page.limit, Report::page_cursor))
+    }
+
+    pub async fn close_reports(
+        &self,
+        target_type: ModerationTarget,
+        target_id: &str,
+        status: ReportStatus,
+        resolution: ModerationAction,
+        resolved_by: &str,
+    ) -> Result<u32, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE reports SET status = $3, resolution = $4, resolved_by = $5, resolved_at = NOW()
+            WHERE target_type = $1 AND target_id = $2 AND status = 'open'
+            "#
+        )
+        .bind(target_type.as_str())
+        .bind(target_id)
+        .bind(status.as_str())
+        .bind(resolution.as_str())
+        .bind(resolved_by.to_lowercase())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() as u32)
+    }
+
+    pub async fn set_post_locked(&self, post_id: &str, locked_by: Option<&str>) -> Result<bool, sqlx::Error> {
+        let Ok(post_uuid) = Uuid::parse_str(post_id) else {
+            return Ok(false);
+        };
+        let result = match locked_by {
+            Some(moderator) => sqlx::query(
+                "UPDATE posts SET locked_at = NOW(), locked_by = $2 WHERE id = $1 AND deleted_at IS NULL AND locked_at IS NULL"
+            )
+            .bind(post_uuid)
+            .bind(moderator.to_lowercase())
+            .execute(&self.pool)
+            .await?,
+            None => sqlx::query(
+                "UPDATE posts SET locked_at = NULL, locked_by = NULL WHERE id = $1 AND deleted_at IS NULL AND locked_at IS NOT NULL"
+            )
+            .bind(post_uuid)
+            .execute(&self.pool)
+            .await?,
+        };
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn is_post_locked(&self, post_id: &str) -> Result<bool, sqlx::Error> {
+        let Ok(post_uuid) = Uuid::parse_str(post_id) else {
+            return Ok(false);
+        };
+        let locked: Option<bool> = sqlx::query_scalar("SELECT locked_at IS NOT NULL FROM posts WHERE id = $1")
+            .bind(post_uuid)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(locked.unwrap_or(false))
+    }
+
+    pub async fn suspend_address(&self, suspension: &Suspension) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO suspensions (address, reason, suspended_by, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (address) DO UPDATE
+            SET reason = EXCLUDED.reason, suspended_by = EXCLUDED.suspended_by,
+                created_at = EXCLUDED.created_at, expires_at = EXCLUDED.expires_at
+            "#
+        )
+        .bind(suspension.address.to_lowercase())
+        .bind(&suspension.reason)
+        .bind(suspension.suspended_by.to_lowercase())
+        .bind(suspension.created_at)
+        .bind(suspension.expires_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn lift_suspension(&self, address: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM suspensions WHERE address = $1")
+            .bind(address.to_lowercase())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_active_suspension(&self, address: &str) -> Result<Option<Suspension>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT address, reason, suspended_by, created_at, expires_at FROM suspensions
+            WHERE address = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            "#
+        )
+        .bind(address.to_lowercase())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(Suspension {
+                address: row.try_get("address")?,
+                reason: row.try_get("reason")?,
+                suspended_by: row.try_get("suspended_by")?,
+                created_at: row.try_get("created_at")?,
+                expires_at: row.try_get("expires_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    pub async fn append_moderation_log(&self, entry: &ModerationLogEntry) -> Result<(), sqlx::Error> {
+        let id = Uuid::parse_str(&entry.id).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let report_id = entry.report_id.as_deref().and_then(|id| Uuid::parse_str(id).ok());
+        sqlx::query(
+            r#"
+            INSERT INTO moderation_log (id, moderator_address, action, target_type, target_id, report_id, reason, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(id)
+        .bind(entry.moderator_address.to_lowercase())
+        .bind(entry.action.as_str())
+        .bind(entry.target_type.as_str())
+        .bind(&entry.target_id)
+        .bind(report_id)
+        .bind(&entry.reason)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_moderation_log(&self, page: &PageRequest) -> Result<Page<ModerationLogEntry>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        let rows = sqlx::query(
+            r#"
+            SELECT id, moderator_address, action, target_type, target_id, report_id, reason, created_at
+            FROM moderation_log
+            WHERE ($3::TIMESTAMPTZ IS NULL OR (created_at, id) < ($3, $4))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $1 OFFSET $2
+            "#
+        )
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let entries = rows.iter().map(moderation_log_from_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page::from_rows(entries, page.limit, ModerationLogEntry::page_cursor))
+    }
+}
+
+fn restricted_user_from_row(row: &sqlx::postgres::PgRow) -> Result<RestrictedUser, sqlx::Error> {
+    Ok(RestrictedUser {
+        address: row.try_get("address")?,
+        username: row.try_get("username")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+// Blocks and mutes (migration 0013)
+impl DatabaseService {
+    pub async fn block_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        let (blocker, blocked) = (blocker_address.to_lowercase(), blocked_address.to_lowercase());
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query("INSERT INTO blocks (blocker_address, blocked_address) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(&blocker)
+            .bind(&blocked)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            r#"
+            DELETE FROM follows
+            WHERE (LOWER(follower_address) = $1 AND LOWER(following_address) = $2)
+               OR (LOWER(follower_address) = $2 AND LOWER(following_address) = $1)
+            "#
+        )
+        .bind(&blocker)
+        .bind(&blocked)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn unblock_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM blocks WHERE blocker_address = $1 AND blocked_address = $2")
+            .bind(blocker_address.to_lowercase())
+            .bind(blocked_address.to_lowercase())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn is_blocked(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM blocks WHERE blocker_address = $1 AND blocked_address = $2)")
+            .bind(blocker_address.to_lowercase())
+            .bind(blocked_address.to_lowercase())
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn get_blocked_users(&self, blocker_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT b.blocked_address AS address, u.username, b.created_at
+            FROM blocks b
+            LEFT JOIN users u ON LOWER(u.ethereum_address) = b.blocked_address
+            WHERE b.blocker_address = $1
+            ORDER BY b.created_at DESC
+            "#
+        )
+        .bind(blocker_address.to_lowercase())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(restricted_user_from_row).collect()
+    }
+
+    pub async fn mute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("INSERT INTO mutes (muter_address, muted_address) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(muter_address.to_lowercase())
+            .bind(muted_address.to_lowercase())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn unmute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM mutes WHERE muter_address = $1 AND muted_address = $2")
+            .bind(muter_address.to_lowercase())
+            .bind(muted_address.to_lowercase())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_muted_users(&self, muter_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.muted_address AS address, u.username, m.created_at
+            FROM mutes m
+            LEFT JOIN users u ON LOWER(u.ethereum_address) = m.muted_address
+            WHERE m.muter_address = $1
+            ORDER BY m.created_at DESC
+            "#
+        )
+        .bind(muter_address.to_lowercase())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(restricted_user_from_row).collect()
+    }
+}
+
+const HELD_COLUMNS: &str = "id, author_address, submission::TEXT AS submission, score, signals::TEXT AS signals, status, content_id, resolved_by, resolved_at, created_at";
+
+fn held_content_from_row(row: &sqlx::postgres::PgRow) -> Result<HeldContent, sqlx::Error> {
+    let submission: String = row.try_get("submission")?;
+    let signals: String = row.try_get("signals")?;
+    let status: String = row.try_get("status")?;
+
+    Ok(HeldContent {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        author_address: row.try_get("author_address")?,
+        submission: serde_json::from_str(&submission).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        score: row.try_get::<i32, _>("score")?.max(0) as u32,
+        signals: serde_json::from_str(&signals).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        status: HeldStatus::from_db(&status),
+        content_id: row.try_get("content_id")?,
+        resolved_by: row.try_get("resolved_by")?,
+        resolved_at: row.try_get("resolved_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+// Spam filter fingerprints and held submissions (migration 0015)
+impl DatabaseService {
+    pub async fn record_fingerprint(&self, fingerprint: &ContentFingerprint) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_fingerprints (content_id, content_type, author_address, simhash, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (content_id) DO NOTHING
+            "#
+        )
+        .bind(&fingerprint.content_id)
+        .bind(fingerprint.content_type.as_str())
+        .bind(fingerprint.author_address.to_lowercase())
+        // Stored bit for bit; BIGINT is signed
+        .bind(fingerprint.simhash.map(|hash| hash as i64))
+        .bind(fingerprint.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_recent_fingerprints(&self, since: chrono::DateTime<Utc>, limit: u32) -> Result<Vec<ContentFingerprint>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT content_id, content_type, author_address, simhash, created_at
+            FROM content_fingerprints
+            WHERE simhash IS NOT NULL AND created_at >= $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#
+        )
+        .bind(since)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let content_type: String = row.try_get("content_type")?;
+                Ok(ContentFingerprint {
+                    content_id: row.try_get("content_id")?,
+                    content_type: ModerationTarget::from_db(&content_type)
+                        .ok_or_else(|| sqlx::Error::Decode(format!("unknown content type: {}", content_type).into()))?,
+                    author_address: row.try_get("author_address")?,
+                    simhash: row.try_get::<Option<i64>, _>("simhash")?.map(|hash| hash as u64),
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn count_recent_fingerprints(&self, author_address: &str, since: chrono::DateTime<Utc>) -> Result<u32, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM content_fingerprints WHERE author_address = $1 AND created_at >= $2")
+            .bind(author_address.to_lowercase())
+            .bind(since)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as u32)
+    }
+
+    pub async fn hold_content(&self, held: &HeldContent) -> Result<(), sqlx::Error> {
+        let id = Uuid::parse_str(&held.id).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let submission = serde_json::to_string(&held.submission).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let signals = serde_json::to_string(&held.signals).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        sqlx::query(
+            r#"
+            INSERT INTO held_content (id, content_type, author_address, submission, score, signals, status, created_at)
+            VALUES ($1, $2, $3, $4::JSONB, $5, $6::JSONB, 'held', $7)
+            "#
+        )
+        .bind(id)
+        .bind(held.submission.content_type().as_str())
+        .bind(held.author_address.to_lowercase())
+        .bind(submission)
+        .bind(held.score.min(i32::MAX as u32) as i32)
+        .bind(signals)
+        .bind(held.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_held_content(&self, held_id: &str) -> Result<Option<HeldContent>, sqlx::Error> {
+        let Ok(id) = Uuid::parse_str(held_id) else {
+            return Ok(None);
+        };
+        let row = sqlx::query(&format!("SELECT {} FROM held_content WHERE id = $1", HELD_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(held_content_from_row).transpose()
+    }
+
+    pub async fn get_held_queue(&self, page: &PageRequest) -> Result<Page<HeldContent>, sqlx::Error> {
+        let (after_at, after_id) = cursor_params(page);
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {} FROM held_content
+            WHERE status = 'held' AND ($3::TIMESTAMPTZ IS NULL OR (created_at, id) > ($3, $4))
+            ORDER BY created_at, id
+            LIMIT $1 OFFSET $2
+            "#,
+            HELD_COLUMNS
+        ))
+        .bind(page.fetch_limit())
+        .bind(page.offset as i64)
+        .bind(after_at)
+        .bind(after_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let held = rows.iter().map(held_content_from_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(Page::from_rows(held, page.limit, HeldContent::page_cursor))
+    }
+
+    pub async fn resolve_held_content(
+        &self,
+        held_id: &str,
+        status: HeldStatus,
+        content_id: Option<&str>,
+        resolved_by: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let Ok(id) = Uuid::parse_str(held_id) else {
+            return Ok(false);
+        };
+        let result = sqlx::query(
+            r#"
+            UPDATE held_content SET status = $2, content_id = $3, resolved_by = $4, resolved_at = NOW()
+            WHERE id = $1 AND status = 'held'
+            "#
+        )
+        .bind(id)
+        .bind(status.as_str())
+        .bind(content_id)
+        .bind(resolved_by.to_lowercase())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+const MEDIA_COLUMNS: &str = "id, kind, owner_address, source_format, width, height, variants::TEXT AS variants, created_at";
+
+fn media_from_row(row: &sqlx::postgres::PgRow) -> Result<Media, sqlx::Error> {
+    let kind: String = row.try_get("kind")?;
+    let variants: String = row.try_get("variants")?;
+
+    Ok(Media {
+        id: row.try_get("id")?,
+        kind: MediaKind::from_db(&kind),
+        owner_address: row.try_get("owner_address")?,
+        source_format: row.try_get("source_format")?,
+        width: row.try_get::<i32, _>("width")?.max(0) as u32,
+        height: row.try_get::<i32, _>("height")?.max(0) as u32,
+        variants: serde_json::from_str(&variants).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+// Uploaded images (migration 0016)
+impl DatabaseService {
+    pub async fn create_media(&self, media: &Media) -> Result<(), sqlx::Error> {
+        let variants = serde_json::to_string(&media.variants).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        sqlx::query(
+            r#"
+            INSERT INTO media (id, kind, owner_address, source_format, width, height, variants, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7::JSONB, $8)
+            ON CONFLICT (id) DO NOTHING
+            "#
+        )
+        .bind(&media.id)
+        .bind(media.kind.as_str())
+        .bind(media.owner_address.to_lowercase())
+        .bind(&media.source_format)
+        .bind(media.width as i32)
+        .bind(media.height as i32)
+        .bind(variants)
+        .bind(media.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_media(&self, media_id: &str) -> Result<Option<Media>, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM media WHERE id = $1", MEDIA_COLUMNS))
+            .bind(media_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(media_from_row).transpose()
+    }
+
+    pub async fn delete_media(&self, media_id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM media WHERE id = $1")
+            .bind(media_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn count_users_with_avatar(&self, avatar_url: &str) -> Result<u32, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE avatar = $1")
+            .bind(avatar_url)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as u32)
+    }
+}
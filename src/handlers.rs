@@ -1,5 +1,7 @@
+use crate::auth::{AuthService, AuthenticatedUser};
 use crate::models::*;
-use crate::services::ForumService;
+use crate::services::{ContentActionError, ForumService};
+use crate::spam::SpamError;
 use actix_web::{web, HttpResponse, Result, Responder};
 use log::{error, info};
 use serde_json::{Value, json};
@@ -7,38 +9,68 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use actix_multipart::Multipart;
 use futures::TryStreamExt;
-use std::io::Write;
-use uuid::Uuid;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+/// Paging for list endpoints. A `cursor` parameter (empty for the first page) selects keyset
+/// pagination and the `{ items, next_cursor, has_more }` envelope; without it the legacy
+/// `offset` pagination and a bare array are used.
+fn page_request(query: &HashMap<String, String>, default_limit: u32) -> Result<(PageRequest, bool), String> {
+    let limit = query.get("limit")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(default_limit);
+
+    match query.get("cursor").map(|s| s.as_str()) {
+        Some("") => Ok((PageRequest::offset(limit, 0), true)),
+        Some(cursor) => match PageCursor::decode(cursor) {
+            Some(after) => Ok((PageRequest { limit, offset: 0, after: Some(after) }, true)),
+            None => Err("Invalid cursor".to_string()),
+        },
+        None => {
+            let offset = query.get("offset")
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(0);
+            Ok((PageRequest::offset(limit, offset), false))
+        }
+    }
+}
+
+fn page_response<T: Serialize>(page: Page<T>, keyset: bool) -> HttpResponse {
+    if keyset {
+        HttpResponse::Ok().json(ApiResponse::success(page))
+    } else {
+        HttpResponse::Ok().json(ApiResponse::success(page.items))
+    }
+}
 
 pub async fn get_posts(
     service: web::Data<Arc<ForumService>>,
+    viewer: Option<AuthenticatedUser>,
     query: web::Query<HashMap<String, String>>,
 ) -> Result<HttpResponse> {
     
-    let limit = query.get("limit")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(15); 
-    
-    let offset = query.get("offset")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(0); 
+    let (page, keyset) = match page_request(&query, 15) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
     
     
     let user_address = query.get("user_address").map(|s| s.as_str());
     
-    let posts = service.get_posts_paginated_with_like_status(limit, offset, user_address).await;
-    info!("Retrieved {} posts (limit: {}, offset: {}, user: {:?})", posts.len(), limit, offset, user_address);
-    Ok(HttpResponse::Ok().json(ApiResponse::success(posts)))
+    let viewer = viewer.as_ref().map(|user| user.address.as_str());
+    let posts = service.get_posts_paginated_with_like_status(&page, user_address, viewer).await;
+    info!("Retrieved {} posts (limit: {}, offset: {}, user: {:?})", posts.items.len(), page.limit, page.offset, user_address);
+    Ok(page_response(posts, keyset))
 }
 
 pub async fn create_post(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<CreatePostRequest>,
 ) -> Result<HttpResponse> {
     info!("Creating new post: {}", request.title);
     
-    let request_data = request.into_inner();
+    let mut request_data = request.into_inner();
+    request_data.author_address = user.address;
     
     
     if let Some(tx_hash) = &request_data.blockchain_transaction_hash {
@@ -78,7 +110,7 @@ pub async fn create_post(
         }
         Err(e) => {
             error!("Failed to create post: {}", e);
-            Ok(HttpResponse::InternalServerError().json(ApiResponse::<Post>::error(e.to_string())))
+            Ok(creation_error(e))
         }
                 }
             }
@@ -90,20 +122,22 @@ pub async fn create_post(
         
     } else {
         error!("Missing smart contract transaction hash");
-        return Ok(HttpResponse::BadRequest().json(ApiResponse::<Post>::error("Smart contract transaction hash is required".to_string())));
+        Ok(HttpResponse::BadRequest().json(ApiResponse::<Post>::error("Smart contract transaction hash is required".to_string())))
     }
 }
 
 pub async fn get_post(
     service: web::Data<Arc<ForumService>>,
+    viewer: Option<AuthenticatedUser>,
     path: web::Path<String>,
     query: web::Query<HashMap<String, String>>,
 ) -> Result<HttpResponse> {
     let post_id = path.into_inner();
     let user_address = query.get("user_address").map(|s| s.as_str());
+    let viewer = viewer.as_ref().map(|user| user.address.as_str());
     info!("Getting post with ID: {} for user: {:?}", post_id, user_address);
     
-    match service.get_post_with_like_status(&post_id, user_address).await {
+    match service.get_post_with_like_status(&post_id, user_address, viewer).await {
         Some(post) => {
             info!("Found post: {}", post.title);
             Ok(HttpResponse::Ok().json(ApiResponse::success(post)))
@@ -115,14 +149,146 @@ pub async fn get_post(
     }
 }
 
+/// Map a failed content action to its HTTP status
+fn content_action_error(e: ContentActionError) -> HttpResponse {
+    let body = ApiResponse::<()>::error(e.to_string());
+    match e {
+        ContentActionError::NotFound(_) => HttpResponse::NotFound().json(body),
+        ContentActionError::Forbidden(_) => HttpResponse::Forbidden().json(body),
+        ContentActionError::Invalid(_) => HttpResponse::BadRequest().json(body),
+        ContentActionError::Internal(_) => HttpResponse::InternalServerError().json(body),
+    }
+}
+
+/// Rejections such as a missing parent comment, a locked thread, a suspended author or a block
+/// are client errors, and content (or an edit) held by the spam filter is accepted for review
+/// (202); anything else is a 500
+fn creation_error(e: Box<dyn std::error::Error>) -> HttpResponse {
+    let e = match e.downcast::<SpamError>() {
+        Ok(e) => {
+            let message = e.to_string();
+            return match *e {
+                SpamError::Held { held_id } => HttpResponse::Accepted().json(ApiResponse::success(json!({
+                    "held": true,
+                    "held_id": held_id,
+                    "message": message
+                }))),
+                SpamError::Rejected => HttpResponse::Forbidden().json(ApiResponse::<()>::error(message)),
+                SpamError::Internal(_) => HttpResponse::InternalServerError().json(ApiResponse::<()>::error(message)),
+            };
+        }
+        Err(e) => e,
+    };
+    match e.downcast::<ContentActionError>() {
+        Ok(e) => content_action_error(*e),
+        Err(e) => HttpResponse::InternalServerError().json(ApiResponse::<()>::error(e.to_string())),
+    }
+}
+
+pub async fn update_post(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<UpdatePostRequest>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    info!("Editing post {} by {}", post_id, user.address);
+
+    match service.update_post(&post_id, &user.address, request.into_inner()).await {
+        Ok(post) => Ok(HttpResponse::Ok().json(ApiResponse::success(post))),
+        Err(e) => {
+            error!("Failed to edit post {}: {}", post_id, e);
+            Ok(creation_error(e))
+        }
+    }
+}
+
+pub async fn get_post_revisions(
+    service: web::Data<Arc<ForumService>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+
+    match service.get_post_revisions(&post_id).await {
+        Ok(Some(revisions)) => Ok(HttpResponse::Ok().json(ApiResponse::success(revisions))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(ApiResponse::<()>::error("Post not found".to_string()))),
+        Err(e) => {
+            error!("Failed to get revisions of post {}: {}", post_id, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(e.to_string())))
+        }
+    }
+}
+
+pub async fn delete_post(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    info!("Deleting post {} by {}", post_id, user.address);
+
+    match service.delete_post(&post_id, &user.address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success("✅ Post deleted"))),
+        Err(e) => {
+            error!("Failed to delete post {}: {}", post_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+pub async fn delete_comment(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let comment_id = path.into_inner();
+    info!("Deleting comment {} by {}", comment_id, user.address);
+
+    match service.delete_comment(&comment_id, &user.address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success("✅ Comment deleted"))),
+        Err(e) => {
+            error!("Failed to delete comment {}: {}", comment_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+pub async fn verify_post(
+    service: web::Data<Arc<ForumService>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    info!("Verifying content integrity of post: {}", post_id);
+
+    match service.verify_post_integrity(&post_id).await {
+        Some(report) => Ok(HttpResponse::Ok().json(ApiResponse::success(report))),
+        None => Ok(HttpResponse::NotFound().json(ApiResponse::<ContentVerificationReport>::error("Post not found".to_string()))),
+    }
+}
+
+pub async fn verify_comment(
+    service: web::Data<Arc<ForumService>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let comment_id = path.into_inner();
+    info!("Verifying content integrity of comment: {}", comment_id);
+
+    match service.verify_comment_integrity(&comment_id).await {
+        Some(report) => Ok(HttpResponse::Ok().json(ApiResponse::success(report))),
+        None => Ok(HttpResponse::NotFound().json(ApiResponse::<ContentVerificationReport>::error("Comment not found".to_string()))),
+    }
+}
+
 pub async fn add_comment(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     path: web::Path<String>,
     request: web::Json<CreateCommentRequest>,
 ) -> Result<HttpResponse> {
     let post_id = path.into_inner();
     let mut comment_request = request.into_inner();
     comment_request.post_id = post_id.clone();
+    comment_request.author_address = user.address;
     
     info!("Adding comment to post: {}", post_id);
     
@@ -152,7 +318,7 @@ pub async fn add_comment(
         }
         
      
-        match service.verify_blockchain_comment_transaction(tx_hash, &comment_request.author_address).await {
+        match service.verify_blockchain_comment_transaction(tx_hash, &comment_request.author_address, &post_id).await {
             Ok(verification) => {
                 info!("Comment blockchain transaction verification succeeded: {:?}", verification);
                 
@@ -164,7 +330,7 @@ pub async fn add_comment(
                     }
                     Err(e) => {
                         error!("Failed to add comment: {}", e);
-                        Ok(HttpResponse::InternalServerError().json(ApiResponse::<Comment>::error(e.to_string())))
+                        Ok(creation_error(e))
                     }
                 }
             }
@@ -182,7 +348,7 @@ pub async fn add_comment(
         }
         Err(e) => {
             error!("Failed to add comment: {}", e);
-            Ok(HttpResponse::InternalServerError().json(ApiResponse::<Comment>::error(e.to_string())))
+            Ok(creation_error(e))
             }
         }
     }
@@ -197,20 +363,17 @@ pub async fn get_post_comments(
     let user_address = query.get("user_address").map(|s| s.as_str());
     
     
-    let limit = query.get("limit")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(50); 
-    
-    let offset = query.get("offset")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(0);
+    let (page, keyset) = match page_request(&query, 50) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
     
-    info!("Getting comments for post: {} (user: {:?}, limit: {}, offset: {})", post_id, user_address, limit, offset);
+    info!("Getting comments for post: {} (user: {:?}, limit: {}, offset: {})", post_id, user_address, page.limit, page.offset);
     
-    match service.get_comments_with_like_status_paginated(&post_id, user_address, limit, offset).await {
+    match service.get_comments_with_like_status_paginated(&post_id, user_address, &page).await {
         Ok(comments) => {
-            info!("Retrieved {} comments for post: {}", comments.len(), post_id);
-            Ok(HttpResponse::Ok().json(ApiResponse::success(comments)))
+            info!("Retrieved {} comments for post: {}", comments.items.len(), post_id);
+            Ok(page_response(comments, keyset))
         }
         Err(e) => {
             error!("Failed to get comments for post {}: {}", post_id, e);
@@ -219,6 +382,49 @@ pub async fn get_post_comments(
     }
 }
 
+pub async fn get_comment_tree(
+    service: web::Data<Arc<ForumService>>,
+    path: web::Path<String>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+    let user_address = query.get("user_address").map(|s| s.as_str());
+    let parent_id = query.get("parent_id").map(|s| s.as_str());
+    let cursor = query.get("cursor").map(|s| s.as_str());
+    let limit = query.get("limit")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(20);
+    let max_depth = query.get("max_depth")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(3);
+
+    match service.get_comment_tree(&post_id, parent_id, cursor, limit, max_depth, user_address).await {
+        Ok(tree) => Ok(HttpResponse::Ok().json(ApiResponse::success(tree))),
+        Err(e) => {
+            error!("Failed to get comment tree for post {}: {}", post_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+pub async fn search(
+    service: web::Data<Arc<ForumService>>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let request = match crate::search::parse_search_request(&query) {
+        Ok(request) => request,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+
+    match service.search(&request).await {
+        Ok(results) => Ok(HttpResponse::Ok().json(ApiResponse::success(results))),
+        Err(e) => {
+            error!("Search for {:?} failed: {}", request.q, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error(format!("Search failed: {}", e))))
+        }
+    }
+}
+
 pub async fn get_user_profile(
     service: web::Data<Arc<ForumService>>,
     path: web::Path<String>,
@@ -240,11 +446,14 @@ pub async fn get_user_profile(
 
 pub async fn upload_to_irys(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<IrysUploadRequest>,
 ) -> Result<HttpResponse> {
+    let mut request = request.into_inner();
+    request.address = user.address;
     info!("Uploading data to Irys for address: {}", request.address);
     
-    match service.upload_to_irys(request.into_inner()).await {
+    match service.upload_to_irys(request).await {
         Ok(tx_id) => {
             info!("Successfully uploaded to Irys with transaction ID: {}", tx_id);
             Ok(HttpResponse::Ok().json(ApiResponse::success(tx_id)))
@@ -307,13 +516,13 @@ pub async fn get_global_stats(
 // 点赞帖子
 pub async fn like_post(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     path: web::Path<String>,
-    request: web::Json<LikeRequest>,
 ) -> Result<HttpResponse> {
     let post_id = path.into_inner();
-    info!("Liking post: {}, user: {}", post_id, request.user_address);
+    info!("Liking post: {}, user: {}", post_id, user.address);
     
-    match service.like_post(&post_id, &request.user_address).await {
+    match service.like_post(&post_id, &user.address).await {
         Ok(new_likes_count) => {
             info!("Post {} liked successfully, new count: {}", post_id, new_likes_count);
             Ok(HttpResponse::Ok().json(ApiResponse::success(new_likes_count)))
@@ -325,20 +534,75 @@ pub async fn like_post(
     }
 }
 
+/// `POST /api/posts/{id}/bookmark` - save a post for later; idempotent
+pub async fn bookmark_post(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+
+    match service.bookmark_post(&post_id, &user.address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "bookmarked": true })))),
+        Err(e) => {
+            error!("Failed to bookmark post {}: {}", post_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `DELETE /api/posts/{id}/bookmark` - remove a saved post; idempotent
+pub async fn remove_bookmark(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+
+    match service.remove_bookmark(&post_id, &user.address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "bookmarked": false })))),
+        Err(e) => {
+            error!("❌ Failed to remove bookmark on post {}: {}", post_id, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to remove bookmark".to_string())))
+        }
+    }
+}
+
+/// `GET /api/bookmarks` - the caller's saved posts, most recently bookmarked first, with `limit` and `cursor`
+pub async fn get_bookmarks(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let (page, _) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+
+    match service.get_bookmarks(&user.address, &page).await {
+        Ok(bookmarks) => Ok(HttpResponse::Ok().json(ApiResponse::success(bookmarks))),
+        Err(e) => {
+            error!("❌ Failed to get bookmarks for {}: {}", user.address, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to get bookmarks".to_string())))
+        }
+    }
+}
+
 // 注册用户名
 pub async fn register_username(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<RegisterUsernameRequest>,
 ) -> Result<HttpResponse> {
-    info!("Registering username: {} for address: {}", request.username, request.user_address);
+    info!("Registering username: {} for address: {}", request.username, user.address);
     
-    match service.register_username(&request.user_address, &request.username).await {
+    match service.register_username(&user.address, &request.username).await {
         Ok(true) => {
-            info!("Username {} registered successfully for {}", request.username, request.user_address);
+            info!("Username {} registered successfully for {}", request.username, user.address);
             Ok(HttpResponse::Ok().json(ApiResponse::success("✅ Username registered successfully")))
         }
         Ok(false) => {
-            info!("Username {} registration failed for {}", request.username, request.user_address);
+            info!("Username {} registration failed for {}", request.username, user.address);
             Ok(HttpResponse::BadRequest().json(ApiResponse::<String>::error("Username already exists or you already have a username".to_string())))
         }
         Err(e) => {
@@ -445,11 +709,9 @@ pub async fn debug_static_files() -> Result<HttpResponse> {
     let mut files = Vec::new();
     
     if let Ok(entries) = fs::read_dir(static_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                if let Some(file_name) = entry.file_name().to_str() {
-                    files.push(file_name.to_string());
-                }
+        for entry in entries.flatten() {
+            if let Some(file_name) = entry.file_name().to_str() {
+                files.push(file_name.to_string());
             }
         }
     }
@@ -467,9 +729,7 @@ pub async fn get_performance_stats(
     });
     
 
-    if let Some(db_stats) = service.get_database_performance() {
-        stats["database"] = db_stats;
-    }
+    stats["storage"] = service.get_storage_stats();
     
 
     if service.has_cache_service() {
@@ -479,18 +739,17 @@ pub async fn get_performance_stats(
         });
     }
     
-
-    stats["memory"] = service.get_memory_stats();
-    
     HttpResponse::Ok().json(ApiResponse::success(stats))
 }
 
 
 pub async fn create_post_async(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<CreatePostRequest>,
 ) -> impl Responder {
-    let request_data = request.into_inner();
+    let mut request_data = request.into_inner();
+    request_data.author_address = user.address;
     
  
     if request_data.title.trim().is_empty() {
@@ -517,7 +776,7 @@ pub async fn create_post_async(
         },
         Err(e) => {
             error!("Failed to submit post creation task: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(e.to_string()))
+            creation_error(e)
         }
     }
 }
@@ -525,9 +784,11 @@ pub async fn create_post_async(
 
 pub async fn create_comment_async(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<CreateCommentRequest>,
 ) -> impl Responder {
-    let request_data = request.into_inner();
+    let mut request_data = request.into_inner();
+    request_data.author_address = user.address;
     
    
     if request_data.content.trim().is_empty() {
@@ -554,7 +815,7 @@ pub async fn create_comment_async(
         },
         Err(e) => {
             error!("Failed to submit comment creation task: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(e.to_string()))
+            creation_error(e)
         }
     }
 }
@@ -562,13 +823,11 @@ pub async fn create_comment_async(
 
 pub async fn like_comment(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     path: web::Path<String>,
-    req: web::Json<serde_json::Value>,
 ) -> impl Responder {
     let comment_id = path.into_inner();
-    let user_address = req.get("user_address")
-        .and_then(|v| v.as_str())
-        .unwrap_or("");
+    let user_address = user.address.as_str();
     
     info!("❤️ User liked comment: {} -> {}", user_address, comment_id);
     
@@ -632,21 +891,18 @@ pub async fn get_user_posts(
     }
     
     
-    let limit = query.get("limit")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(20);
-    
-    let offset = query.get("offset")
-        .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(0);
+    let (page, keyset) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return HttpResponse::BadRequest().json(ApiResponse::<()>::error(message)),
+    };
     
   
     let request_user_address = query.get("user_address");
     
-    match service.get_user_posts_with_like_status(&user_address, limit, offset, request_user_address.map(|s| s.as_str())).await {
+    match service.get_user_posts_with_like_status(&user_address, &page, request_user_address.map(|s| s.as_str())).await {
         Ok(posts) => {
-            info!("👤 Retrieved user posts: {} (count: {})", user_address, posts.len());
-            HttpResponse::Ok().json(ApiResponse::success(posts))
+            info!("👤 Retrieved user posts: {} (count: {})", user_address, posts.items.len());
+            page_response(posts, keyset)
         },
         Err(e) => {
             error!("Failed to get user posts: {}", e);
@@ -658,18 +914,19 @@ pub async fn get_user_posts(
 
 pub async fn follow_user(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<FollowRequest>,
 ) -> impl Responder {
-    info!("👥 Follow request: {:?} -> {:?}", request.follower_address, request.following_address);
+    info!("👥 Follow request: {} -> {:?}", user.address, request.following_address.as_ref().or(request.following_id.as_ref()));
     
-    match service.follow_user(request.into_inner()).await {
+    match service.follow_user(&user.address, request.into_inner()).await {
         Ok(response) => {
             info!("✅ Follow operation completed: success={}", response.success);
             HttpResponse::Ok().json(ApiResponse::success(response))
         },
         Err(e) => {
             error!("❌ Follow operation failed: {}", e);
-            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(e.to_string()))
+            creation_error(e)
         }
     }
 }
@@ -677,11 +934,12 @@ pub async fn follow_user(
 
 pub async fn unfollow_user(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     request: web::Json<FollowRequest>,
 ) -> impl Responder {
-    info!("👥 Unfollow request: {:?} -> {:?}", request.follower_address, request.following_address);
+    info!("👥 Unfollow request: {} -> {:?}", user.address, request.following_address.as_ref().or(request.following_id.as_ref()));
     
-    match service.unfollow_user(request.into_inner()).await {
+    match service.unfollow_user(&user.address, request.into_inner()).await {
         Ok(response) => {
             info!("✅ Unfollow operation completed: success={}", response.success);
             HttpResponse::Ok().json(ApiResponse::success(response))
@@ -700,15 +958,17 @@ pub async fn get_following_list(
     query: web::Query<HashMap<String, String>>,
 ) -> impl Responder {
     let user_address = path.into_inner();
-    let limit = query.get("limit").and_then(|s| s.parse::<u32>().ok()).unwrap_or(20);
-    let offset = query.get("offset").and_then(|s| s.parse::<u32>().ok()).unwrap_or(0);
+    let (page, keyset) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return HttpResponse::BadRequest().json(ApiResponse::<()>::error(message)),
+    };
     
-    info!("📋 Get following list: {} (limit: {}, offset: {})", user_address, limit, offset);
+    info!("📋 Get following list: {} (limit: {}, offset: {})", user_address, page.limit, page.offset);
     
-    match service.get_following_list(&user_address, limit, offset).await {
+    match service.get_following_list(&user_address, &page).await {
         Ok(profiles) => {
-            info!("✅ Following list fetched: {} (count: {})", user_address, profiles.len());
-            HttpResponse::Ok().json(ApiResponse::success(profiles))
+            info!("✅ Following list fetched: {} (count: {})", user_address, profiles.items.len());
+            page_response(profiles, keyset)
         },
         Err(e) => {
             error!("❌ Failed to get following list: {}", e);
@@ -724,15 +984,17 @@ pub async fn get_followers_list(
     query: web::Query<HashMap<String, String>>,
 ) -> impl Responder {
     let user_address = path.into_inner();
-    let limit = query.get("limit").and_then(|s| s.parse::<u32>().ok()).unwrap_or(20);
-    let offset = query.get("offset").and_then(|s| s.parse::<u32>().ok()).unwrap_or(0);
+    let (page, keyset) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return HttpResponse::BadRequest().json(ApiResponse::<()>::error(message)),
+    };
     
-    info!("📋 Get followers list: {} (limit: {}, offset: {})", user_address, limit, offset);
+    info!("📋 Get followers list: {} (limit: {}, offset: {})", user_address, page.limit, page.offset);
     
-    match service.get_followers_list(&user_address, limit, offset).await {
+    match service.get_followers_list(&user_address, &page).await {
         Ok(profiles) => {
-            info!("✅ Followers list fetched: {} (count: {})", user_address, profiles.len());
-            HttpResponse::Ok().json(ApiResponse::success(profiles))
+            info!("✅ Followers list fetched: {} (count: {})", user_address, profiles.items.len());
+            page_response(profiles, keyset)
         },
         Err(e) => {
             error!("❌ Failed to get followers list: {}", e);
@@ -748,15 +1010,17 @@ pub async fn get_mutual_follows_list(
     query: web::Query<HashMap<String, String>>,
 ) -> impl Responder {
     let user_address = path.into_inner();
-    let limit = query.get("limit").and_then(|s| s.parse::<u32>().ok()).unwrap_or(20);
-    let offset = query.get("offset").and_then(|s| s.parse::<u32>().ok()).unwrap_or(0);
+    let (page, keyset) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return HttpResponse::BadRequest().json(ApiResponse::<()>::error(message)),
+    };
     
-    info!("📋 Get mutual follows list: {} (limit: {}, offset: {})", user_address, limit, offset);
+    info!("📋 Get mutual follows list: {} (limit: {}, offset: {})", user_address, page.limit, page.offset);
     
-    match service.get_mutual_follows_list(&user_address, limit, offset).await {
+    match service.get_mutual_follows_list(&user_address, &page).await {
         Ok(profiles) => {
-            info!("✅ Mutual follows list fetched: {} (count: {})", user_address, profiles.len());
-            HttpResponse::Ok().json(ApiResponse::success(profiles))
+            info!("✅ Mutual follows list fetched: {} (count: {})", user_address, profiles.items.len());
+            page_response(profiles, keyset)
         },
         Err(e) => {
             error!("❌ Failed to get mutual follows list: {}", e);
@@ -824,114 +1088,102 @@ pub async fn get_follow_stats(
 }
 
 
-#[derive(Deserialize)]
-pub struct AvatarUploadData {
-    pub user_address: String,
-}
-
-
 #[derive(Deserialize)]
 pub struct BioUpdateRequest {
-    pub user_address: String,
     pub bio: String,
 }
 
 
-pub async fn upload_avatar(
-    service: web::Data<Arc<ForumService>>,
-    mut payload: Multipart,
-) -> Result<HttpResponse> {
-    let mut user_address = String::new();
-    let mut file_data = Vec::new();
-    let mut file_name = String::new();
-    let mut content_type = String::new();
+/// Read the file in multipart field `field_name`, refusing it with 413 as soon as it exceeds
+/// `max_bytes`. Other fields are skipped.
+async fn read_upload(mut payload: Multipart, field_name: &str, max_bytes: usize) -> std::result::Result<Vec<u8>, HttpResponse> {
+    let upload_error = |e: actix_multipart::MultipartError| {
+        HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!("Invalid upload: {}", e)))
+    };
+    let mut data = None;
 
- 
-    while let Some(mut field) = payload.try_next().await? {
-        let field_name = field.name().to_string();
-        
-        match field_name.as_str() {
-            "user_address" => {
-                while let Some(chunk) = field.try_next().await? {
-                    user_address.push_str(&String::from_utf8_lossy(&chunk));
-                }
-            }
-            "avatar" => {
-             
-                let content_disposition = field.content_disposition();
-                if let Some(filename) = content_disposition.get_filename() {
-                    file_name = filename.to_string();
-                }
-                
-                if let Some(ct) = field.content_type() {
-                    content_type = ct.to_string();
-                }
-                
-           
-                if !["image/jpeg", "image/jpg", "image/png"].contains(&content_type.as_str()) {
-                    return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error("Only JPG and PNG images are supported".to_string())));
-                }
-                
-          
-                while let Some(chunk) = field.try_next().await? {
-                    file_data.extend_from_slice(&chunk);
-                }
-                
-              
-                if file_data.len() > 5 * 1024 * 1024 {
-                    return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error("Image size must not exceed 5MB".to_string())));
-                }
-            }
-            _ => {
-            
-                while let Some(_chunk) = field.try_next().await? {}
-            }
+    while let Some(mut field) = payload.try_next().await.map_err(upload_error)? {
+        if field.name() != field_name || data.is_some() {
+            while field.try_next().await.map_err(upload_error)?.is_some() {}
+            continue;
         }
-    }
 
-    if user_address.is_empty() || file_data.is_empty() {
-        return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error("Missing required parameters".to_string())));
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(upload_error)? {
+            if bytes.len() + chunk.len() > max_bytes {
+                return Err(HttpResponse::PayloadTooLarge().json(ApiResponse::<()>::error(format!("Images may not exceed {} bytes", max_bytes))));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        data = Some(bytes);
     }
 
-    info!("📤 Avatar upload request: user={}, file_size={} bytes", user_address, file_data.len());
-
+    data.filter(|bytes| !bytes.is_empty())
+        .ok_or_else(|| HttpResponse::BadRequest().json(ApiResponse::<()>::error(format!("Missing \"{}\" file", field_name))))
+}
 
-    let file_extension = if content_type == "image/png" { "png" } else { "jpg" };
-    let new_filename = format!("avatar_{}_{}.{}", user_address, Uuid::new_v4(), file_extension);
-    
- 
-    std::fs::create_dir_all("static/avatars").map_err(|e| {
-        error!("Failed to create avatars directory: {}", e);
-        actix_web::error::ErrorInternalServerError("Filesystem error")
-    })?;
-    
- 
-    let file_path = format!("static/avatars/{}", new_filename);
-    let mut file = std::fs::File::create(&file_path).map_err(|e| {
-        error!("Failed to create avatar file: {}", e);
-        actix_web::error::ErrorInternalServerError("File save failed")
-    })?;
-    
-    file.write_all(&file_data).map_err(|e| {
-        error!("Failed to write avatar file: {}", e);
-        actix_web::error::ErrorInternalServerError("File write failed")
-    })?;
+/// `POST /api/users/avatar/upload` - multipart field `avatar`; processed like `upload_media`
+pub async fn upload_avatar(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    payload: Multipart,
+) -> Result<HttpResponse> {
+    let data = match read_upload(payload, "avatar", service.media_config().max_upload_bytes).await {
+        Ok(data) => data,
+        Err(response) => return Ok(response),
+    };
+    info!("📤 Avatar upload request: user={}, file_size={} bytes", user.address, data.len());
 
-    let avatar_url = format!("/avatars/{}", new_filename);
-    
-   
-    match service.update_user_avatar(&user_address, &avatar_url).await {
-        Ok(_) => {
+    match service.update_avatar(&user.address, data).await {
+        Ok(media) => {
+            let avatar_url = media.display_url();
             info!("✅ Avatar uploaded successfully: {}", avatar_url);
             Ok(HttpResponse::Ok().json(ApiResponse::success(json!({
-                "avatar_url": avatar_url
+                "avatar_url": avatar_url,
+                "media": media
             }))))
         }
         Err(e) => {
             error!("❌ Failed to update user avatar: {}", e);
-        
-            let _ = std::fs::remove_file(&file_path);
-            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to update avatar".to_string())))
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `POST /api/media` - multipart field `image`. The file is identified by its magic bytes,
+/// decoded and re-encoded into thumbnail sizes and WebP; the returned `id` is what posts and
+/// comments send as `image`.
+pub async fn upload_media(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    payload: Multipart,
+) -> Result<HttpResponse> {
+    let data = match read_upload(payload, "image", service.media_config().max_upload_bytes).await {
+        Ok(data) => data,
+        Err(response) => return Ok(response),
+    };
+    info!("📤 Image upload request: user={}, file_size={} bytes", user.address, data.len());
+
+    match service.upload_media(&user.address, MediaKind::Post, data).await {
+        Ok(media) => Ok(HttpResponse::Created().json(ApiResponse::success(media))),
+        Err(e) => {
+            error!("❌ Image upload failed: {}", e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/media/{id}` - an uploaded image and the URLs of its variants
+pub async fn get_media(
+    service: web::Data<Arc<ForumService>>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    match service.get_media(&path.into_inner()).await {
+        Ok(Some(media)) => Ok(HttpResponse::Ok().json(ApiResponse::success(media))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(ApiResponse::<()>::error("Media not found".to_string()))),
+        Err(e) => {
+            error!("❌ Failed to load media: {}", e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to load media".to_string())))
         }
     }
 }
@@ -939,9 +1191,10 @@ pub async fn upload_avatar(
 
 pub async fn update_bio(
     service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
     bio_data: web::Json<BioUpdateRequest>,
 ) -> Result<HttpResponse> {
-    let user_address = &bio_data.user_address;
+    let user_address = &user.address;
     let bio = &bio_data.bio;
     
   
@@ -965,16 +1218,439 @@ pub async fn update_bio(
     }
 }
 
+// Posts from followed users for the signed-in user, cursor paginated
+pub async fn get_following_feed(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let (page, _) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+    let include_recommended = query.get("include_recommended").map(|v| v == "true").unwrap_or(false);
+
+    match service.get_following_feed(&user.address, &page, include_recommended).await {
+        Ok(feed) => Ok(HttpResponse::Ok().json(ApiResponse::success(feed))),
+        Err(e) => {
+            error!("❌ Failed to get following feed for {}: {}", user.address, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to get following feed".to_string())))
+        }
+    }
+}
+
+/// `GET /api/tags/{tag}/posts` - posts carrying the tag, newest first, with `limit`, `cursor` and
+/// optional `user_address` for like status
+pub async fn get_tag_posts(
+    service: web::Data<Arc<ForumService>>,
+    path: web::Path<String>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let tag = path.into_inner();
+    let (page, _) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+    let user_address = query.get("user_address").map(|s| s.as_str());
+
+    match service.get_tag_posts(&tag, &page, user_address).await {
+        Ok(Some(tag_posts)) => Ok(HttpResponse::Ok().json(ApiResponse::success(tag_posts))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(ApiResponse::<()>::error("Tag not found".to_string()))),
+        Err(e) => {
+            error!("Failed to get posts for tag {}: {}", tag, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/tags/trending` - tags used most by posts created in the `window` (`24h`, `7d` or `30d`; default `7d`)
+pub async fn get_trending_tags(
+    service: web::Data<Arc<ForumService>>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let window = match query.get("window").map(|w| w.as_str()) {
+        None => TrendingWindow::Week,
+        Some(value) => match TrendingWindow::parse(value) {
+            Some(window) => window,
+            None => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error("window must be 24h, 7d or 30d".to_string()))),
+        },
+    };
+    let limit = query.get("limit")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(10);
+
+    match service.get_trending_tags(window, limit).await {
+        Ok(tags) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({
+            "window": window.as_str(),
+            "tags": tags
+        })))),
+        Err(e) => {
+            error!("❌ Failed to get trending tags: {}", e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to get trending tags".to_string())))
+        }
+    }
+}
+
+async fn create_report(
+    service: &ForumService,
+    user: &AuthenticatedUser,
+    target_type: ModerationTarget,
+    target_id: &str,
+    request: CreateReportRequest,
+) -> HttpResponse {
+    match service.report(&user.address, target_type, target_id, request).await {
+        Ok(report) => HttpResponse::Created().json(ApiResponse::success(report)),
+        Err(e) => {
+            error!("Failed to report {} {}: {}", target_type.as_str(), target_id, e);
+            content_action_error(e)
+        }
+    }
+}
+
+/// `POST /api/posts/{id}/report` - body `{ "reason": "spam" | "harassment" | "illegal" | "nsfw" | "other", "details" }`
+pub async fn report_post(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<CreateReportRequest>,
+) -> Result<HttpResponse> {
+    Ok(create_report(&service, &user, ModerationTarget::Post, &path.into_inner(), request.into_inner()).await)
+}
+
+/// `POST /api/comments/{comment_id}/report`
+pub async fn report_comment(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<CreateReportRequest>,
+) -> Result<HttpResponse> {
+    Ok(create_report(&service, &user, ModerationTarget::Comment, &path.into_inner(), request.into_inner()).await)
+}
+
+/// `POST /api/users/{address}/report`
+pub async fn report_user(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<CreateReportRequest>,
+) -> Result<HttpResponse> {
+    Ok(create_report(&service, &user, ModerationTarget::User, &path.into_inner(), request.into_inner()).await)
+}
+
+/// `GET /api/moderation/reports` - open reports, oldest first, with `limit` and `cursor` (moderators only)
+pub async fn get_report_queue(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let (page, _) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+
+    match service.get_report_queue(&user.address, &page).await {
+        Ok(reports) => Ok(HttpResponse::Ok().json(ApiResponse::success(reports))),
+        Err(e) => {
+            error!("Failed to get report queue for {}: {}", user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `POST /api/moderation/reports/{id}/resolve` - body `{ "action": "dismiss" | "hide" | "lock" | "suspend", "reason", "duration_hours" }`
+pub async fn resolve_report(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<ResolveReportRequest>,
+) -> Result<HttpResponse> {
+    let report_id = path.into_inner();
+
+    match service.resolve_report(&user.address, &report_id, request.into_inner()).await {
+        Ok(report) => Ok(HttpResponse::Ok().json(ApiResponse::success(report))),
+        Err(e) => {
+            error!("Failed to resolve report {}: {}", report_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/moderation/held` - submissions held by the spam filter, oldest first (moderators only)
+pub async fn get_held_queue(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let (page, _) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+
+    match service.get_held_queue(&user.address, &page).await {
+        Ok(held) => Ok(HttpResponse::Ok().json(ApiResponse::success(held))),
+        Err(e) => {
+            error!("Failed to get held queue for {}: {}", user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `POST /api/moderation/held/{id}/resolve` - body `{ "action": "approve" | "reject", "reason" }`
+pub async fn resolve_held(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<ResolveHeldRequest>,
+) -> Result<HttpResponse> {
+    let held_id = path.into_inner();
+
+    match service.resolve_held(&user.address, &held_id, request.into_inner()).await {
+        Ok(held) => Ok(HttpResponse::Ok().json(ApiResponse::success(held))),
+        Err(e) => {
+            error!("Failed to resolve held submission {}: {}", held_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `DELETE /api/moderation/posts/{id}/lock` - reopen a locked thread (moderators only)
+pub async fn unlock_post(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let post_id = path.into_inner();
+
+    match service.unlock_post(&user.address, &post_id).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "locked": false })))),
+        Err(e) => {
+            error!("Failed to unlock post {}: {}", post_id, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `DELETE /api/moderation/suspensions/{address}` - lift a suspension (moderators only)
+pub async fn lift_suspension(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let address = path.into_inner();
+
+    match service.lift_suspension(&user.address, &address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "suspended": false })))),
+        Err(e) => {
+            error!("Failed to lift suspension of {}: {}", address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `PUT /api/moderation/users/{address}/role` - body `{ "role": "user" | "moderator" | "admin" }` (admins only)
+pub async fn set_user_role(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<SetRoleRequest>,
+) -> Result<HttpResponse> {
+    let address = path.into_inner();
+
+    match service.set_user_role(&user.address, &address, request.role).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "role": request.role })))),
+        Err(e) => {
+            error!("Failed to set role of {}: {}", address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/moderation/log` - moderator actions, newest first, with `limit` and `cursor` (moderators only)
+pub async fn get_moderation_log(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let (page, _) = match page_request(&query, 50) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+
+    match service.get_moderation_log(&user.address, &page).await {
+        Ok(entries) => Ok(HttpResponse::Ok().json(ApiResponse::success(entries))),
+        Err(e) => {
+            error!("Failed to get moderation log for {}: {}", user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `POST /api/users/{address}/block` - stop a user from following, replying to or mentioning the caller
+pub async fn block_user(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let address = path.into_inner();
+
+    match service.block_user(&user.address, &address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "blocked": true })))),
+        Err(e) => {
+            error!("Failed to block {} for {}: {}", address, user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `DELETE /api/users/{address}/block` - idempotent
+pub async fn unblock_user(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let address = path.into_inner();
+
+    match service.unblock_user(&user.address, &address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "blocked": false })))),
+        Err(e) => {
+            error!("Failed to unblock {} for {}: {}", address, user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/blocks` - users the caller has blocked, most recent first
+pub async fn get_blocked_users(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse> {
+    match service.get_blocked_users(&user.address).await {
+        Ok(users) => Ok(HttpResponse::Ok().json(ApiResponse::success(users))),
+        Err(e) => {
+            error!("Failed to get blocked users of {}: {}", user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `POST /api/users/{address}/mute` - hide a user's posts from the caller's post list, feed and recommendations
+pub async fn mute_user(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let address = path.into_inner();
+
+    match service.mute_user(&user.address, &address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "muted": true })))),
+        Err(e) => {
+            error!("Failed to mute {} for {}: {}", address, user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `DELETE /api/users/{address}/mute` - idempotent
+pub async fn unmute_user(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let address = path.into_inner();
+
+    match service.unmute_user(&user.address, &address).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "muted": false })))),
+        Err(e) => {
+            error!("Failed to unmute {} for {}: {}", address, user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/mutes` - users the caller has muted, most recent first
+pub async fn get_muted_users(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse> {
+    match service.get_muted_users(&user.address).await {
+        Ok(users) => Ok(HttpResponse::Ok().json(ApiResponse::success(users))),
+        Err(e) => {
+            error!("Failed to get muted users of {}: {}", user.address, e);
+            Ok(content_action_error(e))
+        }
+    }
+}
+
+/// `GET /api/notifications` - the caller's notifications grouped per post, comment or follow,
+/// newest first. Accepts `limit`, `cursor` and `unread_only=true`.
+pub async fn get_notifications(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse> {
+    let (page, _) = match page_request(&query, 20) {
+        Ok(page) => page,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error(message))),
+    };
+    let unread_only = query.get("unread_only").map(|v| v == "true").unwrap_or(false);
+
+    match service.get_notifications(&user.address, &page, unread_only).await {
+        Ok(groups) => Ok(HttpResponse::Ok().json(ApiResponse::success(groups))),
+        Err(e) => {
+            error!("❌ Failed to get notifications for {}: {}", user.address, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to get notifications".to_string())))
+        }
+    }
+}
+
+/// `POST /api/notifications/read` - mark the listed `group_keys`, or everything with `all: true`, as read
+pub async fn mark_notifications_read(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+    request: web::Json<MarkNotificationsReadRequest>,
+) -> Result<HttpResponse> {
+    let group_keys = match (request.all, request.group_keys.is_empty()) {
+        (true, _) => None,
+        (false, false) => Some(request.group_keys.as_slice()),
+        (false, true) => {
+            return Ok(HttpResponse::BadRequest().json(ApiResponse::<()>::error("Provide group_keys or set all to true".to_string())));
+        }
+    };
+
+    match service.mark_notifications_read(&user.address, group_keys).await {
+        Ok(marked) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "marked": marked })))),
+        Err(e) => {
+            error!("❌ Failed to mark notifications read for {}: {}", user.address, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to mark notifications read".to_string())))
+        }
+    }
+}
+
+pub async fn get_unread_notification_count(
+    service: web::Data<Arc<ForumService>>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse> {
+    match service.count_unread_notifications(&user.address).await {
+        Ok(unread) => Ok(HttpResponse::Ok().json(ApiResponse::success(json!({ "unread": unread })))),
+        Err(e) => {
+            error!("❌ Failed to count unread notifications for {}: {}", user.address, e);
+            Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to count unread notifications".to_string())))
+        }
+    }
+}
+
 //Get daily recommendations
 pub async fn get_daily_recommendations(
     service: web::Data<Arc<ForumService>>,
+    viewer: Option<AuthenticatedUser>,
     query: web::Query<HashMap<String, String>>,
 ) -> Result<HttpResponse> {
     let user_address = query.get("user_address").map(|s| s.as_str());
+    let viewer = viewer.as_ref().map(|user| user.address.as_str());
     
     info!("📊 Get daily recommendations request (user: {:?})", user_address);
     
-    match service.get_daily_recommendations(user_address).await {
+    match service.get_daily_recommendations(user_address, viewer).await {
         Ok(result) => {
             info!("✅ Daily recommendations fetched, {} posts returned", result.posts.len());
             Ok(HttpResponse::Ok().json(ApiResponse::success(json!({
@@ -1017,4 +1693,41 @@ pub async fn get_amplifiers(
             Ok(HttpResponse::InternalServerError().json(ApiResponse::<()>::error("Failed to fetch data".to_string())))
         }
     }
-}
\ No newline at end of file
+}
+
+
+// Issue a nonce for a Sign-In with Ethereum message
+pub async fn get_auth_nonce(
+    auth: web::Data<AuthService>,
+) -> Result<HttpResponse> {
+    let nonce = auth.issue_nonce().await;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({
+        "nonce": nonce
+    }))))
+}
+
+// Verify a signed SIWE message and return a session token
+pub async fn verify_auth(
+    auth: web::Data<AuthService>,
+    request: web::Json<SiweVerifyRequest>,
+) -> Result<HttpResponse> {
+    match auth.verify_login(&request.message, &request.signature).await {
+        Ok(session) => {
+            info!("🔐 Session issued for {}", session.address);
+            Ok(HttpResponse::Ok().json(ApiResponse::success(session)))
+        }
+        Err(e) => {
+            error!("❌ SIWE verification failed: {}", e);
+            Ok(HttpResponse::Unauthorized().json(ApiResponse::<()>::error(e)))
+        }
+    }
+}
+
+// Return the address bound to the current session token
+pub async fn get_auth_session(
+    user: AuthenticatedUser,
+) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::success(json!({
+        "address": user.address
+    }))))
+}
@@ -0,0 +1,388 @@
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use ethers::types::{Log, H256};
+use log::{info, warn, error};
+use crate::blockchain::{BlockchainService, IrysForumEvents};
+use crate::store::ForumStore;
+
+/// A decoded contract event together with its position on chain
+#[derive(Debug, Clone)]
+pub struct IndexedEvent {
+    pub event: IrysForumEvents,
+    pub transaction_hash: String,
+    pub log_index: i64,
+    pub block_number: i64,
+    pub block_hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainIndexerConfig {
+    pub start_block: u64,
+    pub confirmations: u64,
+    pub batch_size: u64,
+    pub poll_interval: Duration,
+}
+
+impl ChainIndexerConfig {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            start_block: env_u64("CHAIN_INDEXER_START_BLOCK", 0),
+            confirmations: env_u64("CHAIN_INDEXER_CONFIRMATIONS", 12),
+            batch_size: env_u64("CHAIN_INDEXER_BATCH_SIZE", 1000).max(1),
+            poll_interval: Duration::from_secs(env_u64("CHAIN_INDEXER_POLL_INTERVAL", 5).max(1)),
+        }
+    }
+}
+
+/// The chain reads the indexer needs, so it can run against something other than a live node
+#[async_trait]
+pub trait ChainSource: Send + Sync {
+    async fn get_block_number(&self) -> Result<u64, Box<dyn std::error::Error>>;
+    async fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, Box<dyn std::error::Error>>;
+    async fn get_contract_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<Log>, Box<dyn std::error::Error>>;
+}
+
+#[async_trait]
+impl ChainSource for BlockchainService {
+    async fn get_block_number(&self) -> Result<u64, Box<dyn std::error::Error>> {
+        BlockchainService::get_block_number(self).await
+    }
+
+    async fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, Box<dyn std::error::Error>> {
+        BlockchainService::get_block_hash(self, block_number).await
+    }
+
+    async fn get_contract_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<Log>, Box<dyn std::error::Error>> {
+        BlockchainService::get_contract_logs(self, from_block, to_block).await
+    }
+}
+
+/// Background indexer that mirrors forum contract events into Postgres.
+///
+/// Only blocks at least `confirmations` deep are indexed. Each batch stores a
+/// checkpoint (block number + hash); if a stored checkpoint hash no longer
+/// matches the chain, events above the newest still-valid checkpoint are
+/// deleted and re-indexed.
+pub struct ChainIndexer {
+    blockchain: Arc<dyn ChainSource>,
+    store: Arc<dyn ForumStore>,
+    config: ChainIndexerConfig,
+}
+
+impl ChainIndexer {
+    pub fn new(
+        blockchain: Arc<dyn ChainSource>,
+        store: Arc<dyn ForumStore>,
+        config: ChainIndexerConfig,
+    ) -> Self {
+        Self { blockchain, store, config }
+    }
+
+    /// Spawn the indexer loop on the current runtime
+    pub fn start(self) {
+        tokio::spawn(async move {
+            info!(
+                "⛓️ Chain indexer started - start block: {}, confirmations: {}, batch size: {}",
+                self.config.start_block, self.config.confirmations, self.config.batch_size
+            );
+
+            loop {
+                match self.index_next_batch().await {
+                    // Keep going without sleeping while we are catching up
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(e) => error!("❌ Chain indexer error: {}", e),
+                }
+                tokio::time::sleep(self.config.poll_interval).await;
+            }
+        });
+    }
+
+    /// Index the next confirmed block range. Returns true if more confirmed
+    /// blocks remain to be indexed.
+    async fn index_next_batch(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let last_indexed = match self.handle_reorg().await? {
+            Some(block_number) => block_number + 1,
+            None => self.config.start_block,
+        };
+
+        let head = self.blockchain.get_block_number().await?;
+        let confirmed_head = match head.checked_sub(self.config.confirmations) {
+            Some(block_number) => block_number,
+            None => return Ok(false),
+        };
+        if last_indexed > confirmed_head {
+            return Ok(false);
+        }
+
+        let from_block = last_indexed;
+        let to_block = confirmed_head.min(from_block + self.config.batch_size - 1);
+
+        let to_block_hash = self.blockchain.get_block_hash(to_block).await?
+            .ok_or_else(|| format!("Block {} not found", to_block))?;
+        let logs = self.blockchain.get_contract_logs(from_block, to_block).await?;
+
+        let mut events = Vec::with_capacity(logs.len());
+        for log in logs {
+            if log.removed == Some(true) {
+                continue;
+            }
+            let (Some(transaction_hash), Some(log_index), Some(block_number), Some(block_hash)) =
+                (log.transaction_hash, log.log_index, log.block_number, log.block_hash)
+            else {
+                warn!("⚠️ Skipping pending log without block metadata");
+                continue;
+            };
+
+            match ethers::contract::parse_log::<IrysForumEvents>(log) {
+                Ok(event) => events.push(IndexedEvent {
+                    event,
+                    transaction_hash: format!("{:?}", transaction_hash),
+                    log_index: log_index.low_u64() as i64,
+                    block_number: block_number.as_u64() as i64,
+                    block_hash: format!("{:?}", block_hash),
+                }),
+                Err(e) => warn!("⚠️ Skipping undecodable contract log in {:?}: {}", transaction_hash, e),
+            }
+        }
+
+        self.store
+            .store_chain_events(&events, to_block, &format!("{:?}", to_block_hash))
+            .await?;
+
+        info!("⛓️ Indexed blocks {}-{} ({} events)", from_block, to_block, events.len());
+        Ok(to_block < confirmed_head)
+    }
+
+    /// Compare stored checkpoints with the chain and roll back past any reorg.
+    /// Returns the last block that is still safely indexed.
+    async fn handle_reorg(&self) -> Result<Option<u64>, Box<dyn std::error::Error>> {
+        let checkpoints = self.store.get_chain_checkpoints().await?;
+        let Some((latest_block, _)) = checkpoints.first().cloned() else {
+            return Ok(None);
+        };
+
+        for (block_number, block_hash) in &checkpoints {
+            let chain_hash = self.blockchain.get_block_hash(*block_number).await?;
+            if chain_hash.map(|hash| format!("{:?}", hash)).as_deref() == Some(block_hash.as_str()) {
+                if *block_number != latest_block {
+                    warn!("🔀 Chain reorg detected, rolling back indexed events after block {}", block_number);
+                    self.store.rollback_chain_events(Some(*block_number)).await?;
+                }
+                return Ok(Some(*block_number));
+            }
+        }
+
+        warn!("🔀 Chain reorg deeper than all stored checkpoints, re-indexing from block {}", self.config.start_block);
+        self.store.rollback_chain_events(None).await?;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{CommentCreatedFilter, PostCreatedFilter};
+    use crate::memory_store::MemoryStore;
+    use ethers::abi::{encode, Token};
+    use ethers::contract::EthEvent;
+    use ethers::types::{Address, U256, U64};
+    use std::sync::Mutex;
+
+    /// An in-memory chain: one hash per block, and logs that only count while their block hash is canonical
+    #[derive(Default)]
+    struct FakeChain {
+        state: Mutex<FakeChainState>,
+    }
+
+    #[derive(Default)]
+    struct FakeChainState {
+        hashes: Vec<H256>,
+        logs: Vec<Log>,
+        requested: Vec<(u64, u64)>,
+    }
+
+    fn block_hash(fork: u64, block_number: u64) -> H256 {
+        H256::from_low_u64_be(fork * 1_000_000 + block_number)
+    }
+
+    impl FakeChain {
+        fn new(blocks: u64) -> Self {
+            let chain = Self::default();
+            chain.extend(blocks);
+            chain
+        }
+
+        fn extend(&self, blocks: u64) {
+            let mut state = self.state.lock().unwrap();
+            let fork = state.hashes.last().map_or(0, |hash| hash.to_low_u64_be() / 1_000_000);
+            let start = state.hashes.len() as u64;
+            state.hashes.extend((start..start + blocks).map(|n| block_hash(fork, n)));
+        }
+
+        /// Replace every block from `from_block` on with a new fork of the same length
+        fn reorg(&self, from_block: u64, fork: u64) {
+            let mut state = self.state.lock().unwrap();
+            for (n, hash) in state.hashes.iter_mut().enumerate().skip(from_block as usize) {
+                *hash = block_hash(fork, n as u64);
+            }
+        }
+
+        fn emit(&self, block_number: u64, tx: u64, topics: Vec<H256>, data: Vec<Token>) {
+            let mut state = self.state.lock().unwrap();
+            let block_hash = state.hashes[block_number as usize];
+            state.logs.push(Log {
+                topics,
+                data: encode(&data).into(),
+                block_hash: Some(block_hash),
+                block_number: Some(U64::from(block_number)),
+                transaction_hash: Some(H256::from_low_u64_be(tx)),
+                log_index: Some(U256::zero()),
+                ..Default::default()
+            });
+        }
+
+        fn post_created(&self, block_number: u64, tx: u64, post_id: u64) {
+            let topics = vec![PostCreatedFilter::signature(), H256::from_low_u64_be(post_id), H256::from(Address::from_low_u64_be(0xa1))];
+            self.emit(block_number, tx, topics, vec![Token::String("Hello".to_string()), Token::Uint(U256::from(10))]);
+        }
+
+        fn comment_created(&self, block_number: u64, tx: u64, comment_id: u64, post_id: u64) {
+            let topics = vec![
+                CommentCreatedFilter::signature(),
+                H256::from_low_u64_be(comment_id),
+                H256::from_low_u64_be(post_id),
+                H256::from(Address::from_low_u64_be(0xa1)),
+            ];
+            self.emit(block_number, tx, topics, vec![Token::Uint(U256::from(5))]);
+        }
+
+        fn take_requested(&self) -> Vec<(u64, u64)> {
+            std::mem::take(&mut self.state.lock().unwrap().requested)
+        }
+    }
+
+    #[async_trait]
+    impl ChainSource for FakeChain {
+        async fn get_block_number(&self) -> Result<u64, Box<dyn std::error::Error>> {
+            Ok(self.state.lock().unwrap().hashes.len() as u64 - 1)
+        }
+
+        async fn get_block_hash(&self, block_number: u64) -> Result<Option<H256>, Box<dyn std::error::Error>> {
+            Ok(self.state.lock().unwrap().hashes.get(block_number as usize).copied())
+        }
+
+        async fn get_contract_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<Log>, Box<dyn std::error::Error>> {
+            let mut state = self.state.lock().unwrap();
+            state.requested.push((from_block, to_block));
+            let state = &*state;
+            Ok(state.logs.iter()
+                .filter(|log| {
+                    let block_number = log.block_number.unwrap().as_u64();
+                    (from_block..=to_block).contains(&block_number)
+                        && state.hashes.get(block_number as usize) == log.block_hash.as_ref()
+                })
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn indexer(chain: &Arc<FakeChain>, store: &Arc<MemoryStore>) -> ChainIndexer {
+        let config = ChainIndexerConfig {
+            start_block: 0,
+            confirmations: 2,
+            batch_size: 10,
+            poll_interval: Duration::from_secs(1),
+        };
+        ChainIndexer::new(chain.clone(), store.clone(), config)
+    }
+
+    fn indexed_events(store: &MemoryStore) -> u64 {
+        store.get_database_stats()["chain_events"].as_u64().unwrap()
+    }
+
+    async fn latest_checkpoint(store: &MemoryStore) -> Option<(u64, String)> {
+        store.get_chain_checkpoints().await.unwrap().first().cloned()
+    }
+
+    #[tokio::test]
+    async fn test_indexes_confirmed_blocks_in_batches() {
+        let chain = Arc::new(FakeChain::new(25));
+        chain.post_created(3, 1, 1);
+        chain.post_created(12, 2, 2);
+        chain.comment_created(20, 3, 1, 2);
+        // Still within the confirmation depth of head 24
+        chain.post_created(23, 4, 3);
+        let store = Arc::new(MemoryStore::new());
+        let indexer = indexer(&chain, &store);
+
+        assert!(indexer.index_next_batch().await.unwrap());
+        assert!(indexer.index_next_batch().await.unwrap());
+        assert!(!indexer.index_next_batch().await.unwrap());
+        assert_eq!(chain.take_requested(), vec![(0, 9), (10, 19), (20, 22)]);
+        assert_eq!(indexed_events(&store), 3);
+        assert_eq!(latest_checkpoint(&store).await, Some((22, format!("{:?}", block_hash(0, 22)))));
+
+        // Nothing new is confirmed
+        assert!(!indexer.index_next_batch().await.unwrap());
+        assert!(chain.take_requested().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let chain = Arc::new(FakeChain::new(15));
+        chain.post_created(3, 1, 1);
+        chain.post_created(8, 2, 2);
+        let store = Arc::new(MemoryStore::new());
+
+        assert!(indexer(&chain, &store).index_next_batch().await.unwrap());
+        assert_eq!(chain.take_requested(), vec![(0, 9)]);
+
+        // A restarted indexer carries on after the stored checkpoint
+        chain.extend(7);
+        chain.post_created(18, 3, 3);
+        let restarted = indexer(&chain, &store);
+        assert!(!restarted.index_next_batch().await.unwrap());
+        assert_eq!(chain.take_requested(), vec![(10, 19)]);
+        assert_eq!(indexed_events(&store), 3);
+        assert_eq!(latest_checkpoint(&store).await.map(|(block, _)| block), Some(19));
+    }
+
+    #[tokio::test]
+    async fn test_rolls_back_reorg_past_latest_checkpoint() {
+        let chain = Arc::new(FakeChain::new(25));
+        chain.post_created(3, 1, 1);
+        chain.post_created(12, 2, 2);
+        chain.comment_created(20, 3, 1, 2);
+        let store = Arc::new(MemoryStore::new());
+        let indexer = indexer(&chain, &store);
+        while indexer.index_next_batch().await.unwrap() {}
+        assert_eq!(indexed_events(&store), 3);
+        chain.take_requested();
+
+        // Blocks 15 and up are replaced: checkpoints 19 and 22 are stale, 9 still holds. The
+        // comment moves to block 17 and a new post lands in block 21.
+        chain.reorg(15, 1);
+        chain.extend(3);
+        chain.comment_created(17, 3, 1, 2);
+        chain.post_created(21, 5, 3);
+
+        assert!(indexer.index_next_batch().await.unwrap());
+        assert_eq!(chain.take_requested(), vec![(10, 19)]);
+        assert_eq!(latest_checkpoint(&store).await, Some((19, format!("{:?}", block_hash(1, 19)))));
+        // Posts 1 and 2 and the moved comment; the orphaned copy in block 20 is gone
+        assert_eq!(indexed_events(&store), 3);
+
+        assert!(!indexer.index_next_batch().await.unwrap());
+        assert_eq!(chain.take_requested(), vec![(20, 25)]);
+        assert_eq!(indexed_events(&store), 4);
+    }
+}
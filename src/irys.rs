@@ -0,0 +1,393 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ethers::signers::{LocalWallet, Signer};
+use log::{info, warn};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha384};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// ANS-104 signature type for Ethereum (secp256k1, EIP-191) signers
+const SIGNATURE_TYPE_ETHEREUM: u16 = 3;
+const SIGNATURE_LENGTH: usize = 65;
+const OWNER_LENGTH: usize = 65;
+const ANCHOR_LENGTH: usize = 32;
+const APP_NAME: &str = "Irys-Forum";
+
+/// A signed ANS-104 data item ready to be posted to an Irys bundler
+#[derive(Debug, Clone)]
+pub struct DataItem {
+    pub id: String,
+    pub bytes: Vec<u8>,
+}
+
+impl DataItem {
+    /// Build and sign a data item with an Ethereum key
+    pub async fn sign(
+        wallet: &LocalWallet,
+        data: &[u8],
+        tags: &[(String, String)],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let owner = wallet.signer().verifying_key().to_encoded_point(false);
+        let owner = owner.as_bytes();
+        let anchor: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(ANCHOR_LENGTH)
+            .map(char::from)
+            .collect();
+        let tag_bytes = encode_tags(tags);
+
+        let signature_type = SIGNATURE_TYPE_ETHEREUM.to_string();
+        let message = deep_hash(&[
+            b"dataitem",
+            b"1",
+            signature_type.as_bytes(),
+            owner,
+            &[],
+            anchor.as_bytes(),
+            &tag_bytes,
+            data,
+        ]);
+        let signature = wallet.sign_message(message).await?.to_vec();
+
+        let mut bytes = Vec::with_capacity(
+            2 + SIGNATURE_LENGTH + OWNER_LENGTH + 2 + ANCHOR_LENGTH + 16 + tag_bytes.len() + data.len(),
+        );
+        bytes.extend_from_slice(&SIGNATURE_TYPE_ETHEREUM.to_le_bytes());
+        bytes.extend_from_slice(&signature);
+        bytes.extend_from_slice(owner);
+        // No target
+        bytes.push(0);
+        bytes.push(1);
+        bytes.extend_from_slice(anchor.as_bytes());
+        bytes.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(tag_bytes.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&tag_bytes);
+        bytes.extend_from_slice(data);
+
+        Ok(Self {
+            id: URL_SAFE_NO_PAD.encode(Sha256::digest(&signature)),
+            bytes,
+        })
+    }
+}
+
+/// Arweave deep hash (SHA-384) over a list of blobs
+fn deep_hash(chunks: &[&[u8]]) -> [u8; 48] {
+    let mut acc: [u8; 48] = Sha384::digest(format!("list{}", chunks.len())).into();
+    for chunk in chunks {
+        let tag = Sha384::digest(format!("blob{}", chunk.len()));
+        let data = Sha384::digest(chunk);
+        let blob_hash = Sha384::new().chain_update(tag).chain_update(data).finalize();
+        acc = Sha384::new().chain_update(acc).chain_update(blob_hash).finalize().into();
+    }
+    acc
+}
+
+/// Avro-encode tags as an array of `{name: bytes, value: bytes}` records
+fn encode_tags(tags: &[(String, String)]) -> Vec<u8> {
+    fn write_long(buf: &mut Vec<u8>, n: i64) {
+        let mut zigzag = ((n << 1) ^ (n >> 63)) as u64;
+        while zigzag >= 0x80 {
+            buf.push((zigzag as u8 & 0x7f) | 0x80);
+            zigzag >>= 7;
+        }
+        buf.push(zigzag as u8);
+    }
+
+    let mut buf = Vec::new();
+    if tags.is_empty() {
+        return buf;
+    }
+    write_long(&mut buf, tags.len() as i64);
+    for (name, value) in tags {
+        write_long(&mut buf, name.len() as i64);
+        buf.extend_from_slice(name.as_bytes());
+        write_long(&mut buf, value.len() as i64);
+        buf.extend_from_slice(value.as_bytes());
+    }
+    write_long(&mut buf, 0);
+    buf
+}
+
+/// Irys bundler client: signs data items with the server key and uploads them
+pub struct IrysService {
+    client: Client,
+    bundler_url: String,
+    currency: String,
+    gateway_url: String,
+    explorer_url: String,
+    wallet: Option<LocalWallet>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl IrysService {
+    pub fn new() -> Self {
+        let timeout = std::env::var("IRYS_UPLOAD_TIMEOUT")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        let max_retries = std::env::var("IRYS_UPLOAD_RETRIES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(3);
+
+        let wallet = match std::env::var("IRYS_PRIVATE_KEY") {
+            Ok(key) if !key.trim().is_empty() => match key.trim().trim_start_matches("0x").parse::<LocalWallet>() {
+                Ok(wallet) => {
+                    info!("✅ Irys uploads enabled, signer: {:?}", wallet.address());
+                    Some(wallet)
+                }
+                Err(e) => {
+                    warn!("⚠️ Invalid IRYS_PRIVATE_KEY, Irys uploads disabled: {}", e);
+                    None
+                }
+            },
+            _ => {
+                warn!("⚠️ IRYS_PRIVATE_KEY not set, Irys uploads disabled");
+                None
+            }
+        };
+
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(timeout))
+                .build()
+                .unwrap_or_default(),
+            bundler_url: std::env::var("IRYS_TESTNET_URL")
+                .unwrap_or_else(|_| "https://devnet.irys.xyz".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            currency: std::env::var("IRYS_CURRENCY").unwrap_or_else(|_| "ethereum".to_string()),
+            gateway_url: std::env::var("IRYS_GATEWAY_URL")
+                .unwrap_or_else(|_| "https://gateway.irys.xyz".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            explorer_url: std::env::var("IRYS_EXPLORER_URL")
+                .unwrap_or_else(|_| "https://explorer.irys.xyz".to_string()),
+            wallet,
+            max_retries,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+
+    /// Upload data to Irys and return its transaction ID, or None when uploads are not configured
+    pub async fn upload_data(&self, data: &str, tags: Vec<String>, address: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let Some(wallet) = &self.wallet else {
+            return Ok(None);
+        };
+
+        let mut item_tags = vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("App-Name".to_string(), APP_NAME.to_string()),
+            ("Author".to_string(), address.to_lowercase()),
+        ];
+        item_tags.extend(tags.into_iter().map(|tag| ("Tag".to_string(), tag)));
+
+        let item = DataItem::sign(wallet, data.as_bytes(), &item_tags).await?;
+        let tx_id = self.post_data_item(&item).await?;
+        info!("Successfully uploaded to Irys with transaction ID: {}", tx_id);
+        Ok(Some(tx_id))
+    }
+
+    /// POST a signed data item, retrying timeouts, connection errors, 429 and 5xx responses
+    async fn post_data_item(&self, item: &DataItem) -> Result<String, Box<dyn std::error::Error>> {
+        let url = format!("{}/tx/{}", self.bundler_url, self.currency);
+        let mut attempt = 0;
+
+        loop {
+            let error = match self.client
+                .post(&url)
+                .header("Content-Type", "application/octet-stream")
+                .body(item.bytes.clone())
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => {
+                    // 202 means the bundler already has this item; the body may be empty
+                    let body: Value = response.json().await.unwrap_or(Value::Null);
+                    let id = body.get("id").and_then(|id| id.as_str()).unwrap_or(&item.id);
+                    return Ok(id.to_string());
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let body = response.text().await.unwrap_or_default();
+                    if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+                        return Err(format!("Irys upload rejected ({}): {}", status, body).into());
+                    }
+                    format!("Irys bundler returned {}: {}", status, body)
+                }
+                Err(e) => format!("Irys upload request failed: {}", e),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error.into());
+            }
+            let delay = self.retry_delay * 2u32.pow(attempt);
+            attempt += 1;
+            warn!("⚠️ {}, retry {}/{} in {:?}", error, attempt, self.max_retries, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Fetch the raw payload of an uploaded transaction from the gateway
+    pub async fn fetch_data(&self, tx_id: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let url = format!("{}/{}", self.gateway_url, tx_id);
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(format!("Irys gateway returned {}", response.status()).into());
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    pub async fn query_data(&self, address: Option<&str>, tags: Option<Vec<String>>, limit: Option<u32>) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
+        let mut params = HashMap::new();
+
+        if let Some(addr) = address {
+            params.insert("address".to_string(), addr.to_string());
+        }
+
+        if let Some(tags) = tags {
+            let tags_str = serde_json::to_string(&tags)?;
+            params.insert("tags".to_string(), tags_str);
+        }
+
+        if let Some(limit) = limit {
+            let limit_str = limit.to_string();
+            params.insert("limit".to_string(), limit_str);
+        }
+
+        let query_string = serde_urlencoded::to_string(&params)?;
+        let url = format!("{}/query?{}", self.explorer_url, query_string);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await?;
+
+        let result: Value = response.json().await?;
+
+        if let Some(data) = result.get("data") {
+            if let Some(transactions) = data.as_array() {
+                return Ok(transactions.clone());
+            }
+        }
+
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Signature;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal bundler stand-in: answers 503 to the first request, then 200 with an ID
+    async fn spawn_bundler() -> (String, Arc<AtomicUsize>, tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let (body_tx, body_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let counter = attempts.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                let body = loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else { continue };
+                    let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                    let length: usize = headers.lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map(|v| v.trim().parse().unwrap())
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + length {
+                        break request[end + 4..end + 4 + length].to_vec();
+                    }
+                };
+
+                let response = if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    body_tx.send(body).unwrap();
+                    let json = r#"{"id":"bundler-tx-id"}"#;
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", json.len(), json)
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, attempts, body_rx)
+    }
+
+    #[tokio::test]
+    async fn test_upload_signs_data_item_and_retries() {
+        let (url, attempts, mut bodies) = spawn_bundler().await;
+        let wallet: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap();
+        let service = IrysService {
+            client: Client::new(),
+            bundler_url: url,
+            currency: "ethereum".to_string(),
+            gateway_url: String::new(),
+            explorer_url: String::new(),
+            wallet: Some(wallet.clone()),
+            max_retries: 2,
+            retry_delay: Duration::from_millis(10),
+        };
+
+        let tx_id = service.upload_data(r#"{"title":"hi"}"#, vec!["post".to_string()], "0xABC").await.unwrap();
+        assert_eq!(tx_id.as_deref(), Some("bundler-tx-id"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        let item = bodies.recv().await.unwrap();
+        assert_eq!(u16::from_le_bytes([item[0], item[1]]), SIGNATURE_TYPE_ETHEREUM);
+        let signature = &item[2..67];
+        let owner = &item[67..132];
+        assert_eq!((item[132], item[133]), (0, 1));
+        let anchor = &item[134..166];
+        let tag_count = u64::from_le_bytes(item[166..174].try_into().unwrap());
+        let tag_len = u64::from_le_bytes(item[174..182].try_into().unwrap()) as usize;
+        let tag_bytes = &item[182..182 + tag_len];
+        let data = &item[182 + tag_len..];
+        assert_eq!(tag_count, 4);
+        assert_eq!(data, br#"{"title":"hi"}"#);
+
+        let message = deep_hash(&[b"dataitem", b"1", b"3", owner, &[], anchor, tag_bytes, data]);
+        let recovered = Signature::try_from(signature).unwrap().recover(&message[..]).unwrap();
+        assert_eq!(recovered, wallet.address());
+    }
+
+    #[tokio::test]
+    async fn test_upload_disabled_without_key() {
+        let service = IrysService {
+            client: Client::new(),
+            bundler_url: "http://127.0.0.1:9".to_string(),
+            currency: "ethereum".to_string(),
+            gateway_url: String::new(),
+            explorer_url: String::new(),
+            wallet: None,
+            max_retries: 0,
+            retry_delay: Duration::from_millis(10),
+        };
+        assert_eq!(service.upload_data("{}", vec![], "0xabc").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_encode_tags() {
+        assert!(encode_tags(&[]).is_empty());
+        let encoded = encode_tags(&[("a".to_string(), "bc".to_string())]);
+        // count 1 (zigzag 2), "a" (len 2), "bc" (len 4), end of array
+        assert_eq!(encoded, vec![2, 2, b'a', 4, b'b', b'c', 0]);
+    }
+}
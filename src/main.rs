@@ -1,15 +1,28 @@
 use actix_cors::Cors;
 use actix_files::Files;
-use actix_web::{web, App, HttpServer};
+use actix_web::{middleware, web, App, HttpServer};
 use dotenv::dotenv;
 use log::info;
 use std::time::Duration;
 
+mod auth;
 mod blockchain;
 mod database;
 mod handlers;
+mod indexer;
+mod irys;
+mod migrations;
 mod models;
+mod notifications;
+mod rate_limit;
+mod search;
 mod services;
+mod store;
+mod memory_store;
+mod mentions;
+mod markdown;
+mod media;
+mod spam;
 mod utils;
 mod cache;
 mod async_queue;
@@ -28,6 +41,14 @@ async fn main() -> std::io::Result<()> {
 
     // Initialize ForumService
     let forum_service = web::Data::new(std::sync::Arc::new(services::ForumService::new().await));
+    forum_service.start_queue_workers();
+    let auth_service = web::Data::new(auth::AuthService::new(forum_service.cache_service()));
+    let rate_limiter = web::Data::new(rate_limit::RateLimiter::new(
+        rate_limit::RateLimitConfig::from_env(),
+        forum_service.cache_service(),
+    ));
+
+    let media_dir = forum_service.media_config().dir.clone();
 
     HttpServer::new(move || {
         let cors = Cors::default()
@@ -39,16 +60,30 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .wrap(cors)
             .app_data(forum_service.clone())
-          
-            .app_data(web::JsonConfig::default().limit(10 * 1024 * 1024))
+            .app_data(auth_service.clone())
+            .app_data(rate_limiter.clone())
+            // Images are uploaded through /api/media, so JSON bodies only carry text
+            .app_data(web::JsonConfig::default().limit(1024 * 1024))
             .service(
                 web::scope("/api")
+                    .wrap(middleware::from_fn(rate_limit::rate_limit_middleware))
+                    .route("/auth/nonce", web::get().to(handlers::get_auth_nonce))
+                    .route("/auth/verify", web::post().to(handlers::verify_auth))
+                    .route("/auth/session", web::get().to(handlers::get_auth_session))
                     .route("/posts", web::get().to(handlers::get_posts))
                     .route("/posts", web::post().to(handlers::create_post))
                     .route("/posts/{id}", web::get().to(handlers::get_post))
+                    .route("/posts/{id}", web::put().to(handlers::update_post))
+                    .route("/posts/{id}", web::delete().to(handlers::delete_post))
+                    .route("/posts/{id}/revisions", web::get().to(handlers::get_post_revisions))
                     .route("/posts/{id}/like", web::post().to(handlers::like_post))
+                    .route("/posts/{id}/bookmark", web::post().to(handlers::bookmark_post))
+                    .route("/posts/{id}/bookmark", web::delete().to(handlers::remove_bookmark))
+                    .route("/posts/{id}/report", web::post().to(handlers::report_post))
+                    .route("/posts/{id}/verify", web::get().to(handlers::verify_post))
                     .route("/posts/{id}/comments", web::get().to(handlers::get_post_comments))
                     .route("/posts/{id}/comments", web::post().to(handlers::add_comment))
+                    .route("/posts/{id}/comments/tree", web::get().to(handlers::get_comment_tree))
                     .route("/users/{address}", web::get().to(handlers::get_user_profile))
                     .route("/users/{address}/username", web::get().to(handlers::get_username))
                     .route("/users/{address}/has-username", web::get().to(handlers::check_user_has_username))
@@ -65,7 +100,10 @@ async fn main() -> std::io::Result<()> {
                     .route("/comments/async", web::post().to(handlers::create_comment_async))
                     .route("/tasks/{task_id}", web::get().to(handlers::get_task_status))
                     .route("/users/{address}/posts", web::get().to(handlers::get_user_posts))
+                    .route("/comments/{comment_id}", web::delete().to(handlers::delete_comment))
                     .route("/comments/{comment_id}/like", web::post().to(handlers::like_comment))
+                    .route("/comments/{comment_id}/report", web::post().to(handlers::report_comment))
+                    .route("/comments/{comment_id}/verify", web::get().to(handlers::verify_comment))
                     
                     .route("/follow", web::post().to(handlers::follow_user))
                     .route("/unfollow", web::post().to(handlers::unfollow_user))
@@ -74,16 +112,46 @@ async fn main() -> std::io::Result<()> {
                     .route("/users/{address}/friends", web::get().to(handlers::get_mutual_follows_list))
                     .route("/follow/status", web::get().to(handlers::check_follow_status))
                     .route("/users/{address}/follow-stats", web::get().to(handlers::get_follow_stats))
+                    .route("/users/{address}/block", web::post().to(handlers::block_user))
+                    .route("/users/{address}/block", web::delete().to(handlers::unblock_user))
+                    .route("/users/{address}/mute", web::post().to(handlers::mute_user))
+                    .route("/users/{address}/mute", web::delete().to(handlers::unmute_user))
+                    .route("/blocks", web::get().to(handlers::get_blocked_users))
+                    .route("/mutes", web::get().to(handlers::get_muted_users))
                     
                     .route("/users/avatar/upload", web::post().to(handlers::upload_avatar))
+                    .route("/media", web::post().to(handlers::upload_media))
+                    .route("/media/{id}", web::get().to(handlers::get_media))
                     .route("/users/bio/update", web::post().to(handlers::update_bio))
 
                     .route("/recommendations/daily", web::get().to(handlers::get_daily_recommendations))
+                    .route("/feed/following", web::get().to(handlers::get_following_feed))
+                    .route("/bookmarks", web::get().to(handlers::get_bookmarks))
+
+                    .route("/notifications", web::get().to(handlers::get_notifications))
+                    .route("/notifications/read", web::post().to(handlers::mark_notifications_read))
+                    .route("/notifications/unread-count", web::get().to(handlers::get_unread_notification_count))
+
+                    .route("/search", web::get().to(handlers::search))
+
+                    .route("/users/{address}/report", web::post().to(handlers::report_user))
+                    .route("/moderation/reports", web::get().to(handlers::get_report_queue))
+                    .route("/moderation/reports/{id}/resolve", web::post().to(handlers::resolve_report))
+                    .route("/moderation/held", web::get().to(handlers::get_held_queue))
+                    .route("/moderation/held/{id}/resolve", web::post().to(handlers::resolve_held))
+                    .route("/moderation/posts/{id}/lock", web::delete().to(handlers::unlock_post))
+                    .route("/moderation/suspensions/{address}", web::delete().to(handlers::lift_suspension))
+                    .route("/moderation/users/{address}/role", web::put().to(handlers::set_user_role))
+                    .route("/moderation/log", web::get().to(handlers::get_moderation_log))
+
+                    .route("/tags/trending", web::get().to(handlers::get_trending_tags))
+                    .route("/tags/{tag}/posts", web::get().to(handlers::get_tag_posts))
 
                     .route("/amplifiers", web::get().to(handlers::get_amplifiers))
             )
             .service(Files::new("/icon", "./icon"))
             .service(Files::new("/avatars", "./static/avatars"))
+            .service(Files::new(media::MEDIA_URL_PREFIX, &media_dir))
             .service(Files::new("/", "./static").index_file("index.html"))
     })
     .workers(num_cpus::get().max(4))
@@ -0,0 +1,263 @@
+use ammonia::Builder;
+use pulldown_cmark::{html, Options, Parser};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Tags that may appear in rendered content: what CommonMark with tables and strikethrough
+/// produces, minus anything that can run script or load active content
+pub const ALLOWED_TAGS: &[&str] = &[
+    "a", "blockquote", "br", "code", "del", "em", "h1", "h2", "h3", "h4", "h5", "h6", "hr", "img",
+    "li", "ol", "p", "pre", "strong", "table", "tbody", "td", "th", "thead", "tr", "ul",
+];
+
+/// Attributes allowed per tag; `a` additionally gets `rel` from the sanitizer
+pub const ALLOWED_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href", "title"]),
+    ("img", &["src", "alt", "title"]),
+    ("code", &["class"]),
+    ("ol", &["start"]),
+];
+
+/// Schemes allowed in `href` and `src`; relative URLs are kept
+pub const ALLOWED_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Tags removed together with their content rather than unwrapped
+const REMOVED_WITH_CONTENT: &[&str] = &["script", "style", "iframe", "object", "embed", "svg", "math", "template", "noscript", "textarea"];
+
+fn is_language_class(value: &str) -> bool {
+    value.strip_prefix("language-")
+        .is_some_and(|lang| !lang.is_empty() && lang.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '#')))
+}
+
+fn sanitizer() -> &'static Builder<'static> {
+    static SANITIZER: OnceLock<Builder<'static>> = OnceLock::new();
+    SANITIZER.get_or_init(|| {
+        let attributes: HashMap<&str, HashSet<&str>> = ALLOWED_ATTRIBUTES.iter()
+            .map(|(tag, attributes)| (*tag, attributes.iter().copied().collect()))
+            .collect();
+        let mut builder = Builder::empty();
+        builder
+            .tags(ALLOWED_TAGS.iter().copied().collect())
+            .clean_content_tags(REMOVED_WITH_CONTENT.iter().copied().collect())
+            .tag_attributes(attributes)
+            .generic_attributes(HashSet::new())
+            .url_schemes(ALLOWED_URL_SCHEMES.iter().copied().collect())
+            .link_rel(Some("noopener noreferrer nofollow ugc"))
+            .strip_comments(true)
+            // Fenced code blocks carry their language as `class="language-x"`; nothing else may set a class
+            .attribute_filter(|tag, attribute, value| match (tag, attribute) {
+                ("code", "class") if !is_language_class(value) => None,
+                _ => Some(Cow::Borrowed(value)),
+            });
+        builder
+    })
+}
+
+/// Reduce an HTML fragment to the allowlisted tags, attributes and URL schemes. Disallowed
+/// tags are unwrapped (script-like ones dropped with their content), so the result is
+/// always safe to insert into a page.
+pub fn sanitize_html(input: &str) -> String {
+    sanitizer().clean(input).to_string()
+}
+
+/// Render Markdown source (CommonMark plus tables and strikethrough) to sanitized HTML.
+/// Raw HTML in the source goes through the same allowlist.
+pub fn render(source: &str) -> String {
+    let parser = Parser::new_ext(source, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+    let mut rendered = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut rendered, parser);
+    sanitize_html(&rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Payloads from common XSS filter evasion lists
+    const XSS_CORPUS: &[&str] = &[
+        "<script>alert(1)</script>",
+        "<SCRIPT>alert(1)</SCRIPT>",
+        "<ScRiPt src=//evil.example/x.js></sCrIpT>",
+        "<script\n>alert(1)</script\n>",
+        "<<script>script>alert(1)<</script>/script>",
+        "<img src=x onerror=alert(1)>",
+        "<IMG SRC=x ONERROR=alert(1)>",
+        "<img src=x\tonerror\t=\talert(1)>",
+        "<img src=x onerror\n=alert(1)//",
+        "<img/src=x/onerror=alert(1)>",
+        "<img src=\"javascript:alert(1)\">",
+        "<img src=\"jav&#x09;ascript:alert(1)\">",
+        "<img src=\"&#106;&#97;&#118;&#97;&#115;&#99;&#114;&#105;&#112;&#116;&#58;alert(1)\">",
+        "<img src=`javascript:alert(1)`>",
+        "<img \"\"\"><script>alert(1)</script>\">",
+        "<svg onload=alert(1)>",
+        "<svg><script>alert(1)</script></svg>",
+        "<math><mtext><table><mglyph><style><img src=x onerror=alert(1)>",
+        "<iframe src=\"javascript:alert(1)\"></iframe>",
+        "<iframe srcdoc=\"<script>alert(1)</script>\"></iframe>",
+        "<object data=\"data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==\"></object>",
+        "<embed src=\"javascript:alert(1)\">",
+        "<a href=\"javascript:alert(1)\">x</a>",
+        "<a href=\"JaVaScRiPt:alert(1)\">x</a>",
+        "<a href=\" javascript:alert(1)\">x</a>",
+        "<a href=\"java\nscript:alert(1)\">x</a>",
+        "<a href=\"vbscript:msgbox(1)\">x</a>",
+        "<a href=\"data:text/html,<script>alert(1)</script>\">x</a>",
+        "<a href=\"#\" onclick=\"alert(1)\">x</a>",
+        "<a href=x onmouseover=alert(1)>x</a>",
+        "<a href=\"https://ok.example\" style=\"position:fixed;inset:0\">x</a>",
+        "<div style=\"background:url(javascript:alert(1))\">x</div>",
+        "<body onload=alert(1)>",
+        "<input autofocus onfocus=alert(1)>",
+        "<form action=javascript:alert(1)><button>x</button></form>",
+        "<details open ontoggle=alert(1)>",
+        "<video><source onerror=alert(1)></video>",
+        "<audio src=x onerror=alert(1)>",
+        "<marquee onstart=alert(1)>",
+        "<meta http-equiv=\"refresh\" content=\"0;url=javascript:alert(1)\">",
+        "<link rel=stylesheet href=javascript:alert(1)>",
+        "<base href=\"javascript:alert(1)//\">",
+        "<style>@import 'javascript:alert(1)';</style>",
+        "<!--<img src=\"--><img src=x onerror=alert(1)//\">",
+        "<![CDATA[<script>alert(1)</script>]]>",
+        "<noscript><p title=\"</noscript><img src=x onerror=alert(1)>\">",
+        "<textarea><script>alert(1)</script></textarea>",
+        "<template><script>alert(1)</script></template>",
+        "<p id=x class=evil onpointerover=alert(1)>x</p>",
+        "<code class=\"language-rust onclick=alert(1)\">x</code>",
+        "[x](javascript:alert(1))",
+        "[x](JAVASCRIPT:alert(1))",
+        "[x](javascript&#58;alert(1))",
+        "[x](<javascript:alert(1)>)",
+        "[x](data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==)",
+        "[x](vbscript:msgbox(1))",
+        "[x][ref]\n\n[ref]: javascript:alert(1)",
+        "![x](javascript:alert(1))",
+        "![x\" onerror=\"alert(1)](https://ok.example/a.png)",
+        "[x](https://ok.example \"title\\\" onmouseover=\\\"alert(1)\")",
+        "<javascript:alert(1)>",
+        "```\"><script>alert(1)</script>\nx\n```",
+        "``` onclick=alert(1)\nx\n```",
+        "| a |\n|---|\n| <img src=x onerror=alert(1)> |",
+        "> <script>alert(1)</script>",
+        "*<img src=x onerror=alert(1)>*",
+    ];
+
+    /// Every tag in sanitizer output as (name, attributes). The serializer escapes `<` in text,
+    /// so each `<` starts a tag; attribute values are always double-quoted.
+    fn tags(html: &str) -> Vec<(String, Vec<(String, String)>)> {
+        let chars: Vec<char> = html.chars().collect();
+        let mut tags = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] != '<' {
+                i += 1;
+                continue;
+            }
+            i += 1;
+            let mut name = String::new();
+            while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '>' {
+                name.push(chars[i]);
+                i += 1;
+            }
+            let mut attributes = Vec::new();
+            while i < chars.len() && chars[i] != '>' {
+                if chars[i].is_whitespace() || chars[i] == '/' {
+                    i += 1;
+                    continue;
+                }
+                let mut attribute = String::new();
+                while i < chars.len() && !matches!(chars[i], '=' | '>' | '/') && !chars[i].is_whitespace() {
+                    attribute.push(chars[i]);
+                    i += 1;
+                }
+                let mut value = String::new();
+                if chars.get(i) == Some(&'=') {
+                    assert_eq!(chars.get(i + 1), Some(&'"'), "unquoted attribute in {}", html);
+                    i += 2;
+                    while i < chars.len() && chars[i] != '"' {
+                        value.push(chars[i]);
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                attributes.push((attribute, value));
+            }
+            tags.push((name.trim_start_matches('/').to_string(), attributes));
+        }
+        tags
+    }
+
+    fn assert_safe(input: &str) {
+        let output = render(input);
+        for (tag, attributes) in tags(&output) {
+            assert!(ALLOWED_TAGS.contains(&tag.as_str()), "tag <{}> from {:?}: {}", tag, input, output);
+            for (attribute, value) in attributes {
+                let allowed = ALLOWED_ATTRIBUTES.iter()
+                    .any(|(t, attrs)| *t == tag && attrs.contains(&attribute.as_str()))
+                    || (tag == "a" && attribute == "rel");
+                assert!(allowed && !attribute.starts_with("on"), "attribute {}={:?} on <{}> from {:?}", attribute, value, tag, input);
+                if attribute == "href" || attribute == "src" {
+                    let url: String = value.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect::<String>().to_lowercase();
+                    let scheme = url.split_once(':').map(|(scheme, _)| scheme).filter(|s| !s.contains(['/', '?', '#']));
+                    assert!(scheme.is_none_or(|s| ALLOWED_URL_SCHEMES.contains(&s)), "{}={:?} from {:?}", attribute, value, input);
+                }
+                if attribute == "class" {
+                    assert!(is_language_class(&value), "class {:?} from {:?}", value, input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_xss_corpus_renders_to_allowlisted_html() {
+        for payload in XSS_CORPUS {
+            // Each payload on its own, with its case flipped and inside common Markdown contexts
+            let variants = [
+                payload.to_string(),
+                payload.to_uppercase(),
+                format!("# {}", payload),
+                format!("- {}", payload),
+                format!("**{}**", payload),
+                format!("[{}](https://ok.example)", payload),
+                format!("text {} text", payload.replace(' ', "\n")),
+            ];
+            for variant in &variants {
+                assert_safe(variant);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_fragment_soup_renders_to_allowlisted_html() {
+        const FRAGMENTS: &[&str] = &[
+            "<", ">", "/", "script", "img", "a", "svg", " src=", " href=", " onerror=", " onclick=", "alert(1)",
+            "javascript:", "java\tscript:", "data:", "\"", "'", "`", "=", "[", "]", "(", ")", "!", "*", "#",
+            "\n", "\n\n", "&#", "&#x6a;", "<!--", "-->", "<![CDATA[", "```", "|", " ", "x",
+        ];
+        // Deterministic LCG so failures reproduce
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..2000 {
+            let mut input = String::new();
+            for _ in 0..24 {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                input.push_str(FRAGMENTS[(state >> 33) as usize % FRAGMENTS.len()]);
+            }
+            assert_safe(&input);
+        }
+    }
+
+    #[test]
+    fn test_renders_markdown() {
+        assert_eq!(render("**bold** and _em_ ~~gone~~"), "<p><strong>bold</strong> and <em>em</em> <del>gone</del></p>\n");
+        assert_eq!(
+            render("[site](https://irys.xyz \"Irys\")"),
+            "<p><a href=\"https://irys.xyz\" title=\"Irys\" rel=\"noopener noreferrer nofollow ugc\">site</a></p>\n"
+        );
+        assert_eq!(render("```rust\nfn main() {}\n```"), "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n");
+        assert_eq!(render("a < b & <b>c</b>"), "<p>a &lt; b &amp; c</p>\n");
+        assert_eq!(render("<script>alert(1)</script>hello"), "hello");
+        assert_eq!(render("[x](javascript:alert(1))"), "<p><a rel=\"noopener noreferrer nofollow ugc\">x</a></p>\n");
+    }
+}
@@ -0,0 +1,416 @@
+use crate::models::{Media, MediaKind, MediaVariant};
+use chrono::Utc;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader, Limits};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// URL prefix the media directory is served under
+pub const MEDIA_URL_PREFIX: &str = "/media";
+
+const JPEG_QUALITY: u8 = 85;
+
+/// A size generated for every upload of a kind. `max` bounds the longer side; `square`
+/// center-crops first. Images are never upscaled.
+struct VariantSpec {
+    name: &'static str,
+    max: u32,
+    square: bool,
+}
+
+// Largest first: each variant is scaled down from the previous one
+const AVATAR_VARIANTS: &[VariantSpec] = &[
+    VariantSpec { name: "medium", max: 256, square: true },
+    VariantSpec { name: "small", max: 64, square: true },
+];
+
+const POST_VARIANTS: &[VariantSpec] = &[
+    VariantSpec { name: "full", max: 2560, square: false },
+    VariantSpec { name: "large", max: 1280, square: false },
+    VariantSpec { name: "thumb", max: 320, square: false },
+];
+
+fn variant_specs(kind: MediaKind) -> &'static [VariantSpec] {
+    match kind {
+        MediaKind::Avatar => AVATAR_VARIANTS,
+        MediaKind::Post => POST_VARIANTS,
+    }
+}
+
+/// Variant whose WebP file is used as the avatar or post image URL
+pub fn display_variant(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Avatar => "medium",
+        MediaKind::Post => "large",
+    }
+}
+
+/// Upload limits and storage location, from `MEDIA_*` environment variables
+#[derive(Debug, Clone)]
+pub struct MediaConfig {
+    /// Files are written to `<dir>/<media id>/<variant>.<ext>` and served under `/media`
+    pub dir: PathBuf,
+    pub max_upload_bytes: usize,
+    /// Larger images are refused before their pixels are decoded
+    pub max_pixels: u64,
+    pub max_dimension: u32,
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("static/media"),
+            max_upload_bytes: 10 * 1024 * 1024,
+            max_pixels: 40_000_000,
+            max_dimension: 12_000,
+        }
+    }
+}
+
+impl MediaConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env_u64 = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            dir: std::env::var("MEDIA_DIR").map(PathBuf::from).unwrap_or(defaults.dir),
+            max_upload_bytes: env_u64("MEDIA_MAX_UPLOAD_BYTES", defaults.max_upload_bytes as u64) as usize,
+            max_pixels: env_u64("MEDIA_MAX_PIXELS", defaults.max_pixels).max(1),
+            max_dimension: env_u64("MEDIA_MAX_DIMENSION", u64::from(defaults.max_dimension)).clamp(1, u64::from(u32::MAX)) as u32,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MediaError {
+    TooLarge(String),
+    /// Not a JPEG, PNG, GIF or WebP file, whatever the declared content type
+    Unsupported,
+    Invalid(String),
+    Internal(String),
+}
+
+impl fmt::Display for MediaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaError::TooLarge(message) | MediaError::Invalid(message) | MediaError::Internal(message) => write!(f, "{}", message),
+            MediaError::Unsupported => write!(f, "Only JPEG, PNG, GIF and WebP images are supported"),
+        }
+    }
+}
+
+impl std::error::Error for MediaError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceFormat {
+    Jpeg,
+    Png,
+    Gif,
+    WebP,
+}
+
+impl SourceFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+            Self::Gif => "gif",
+            Self::WebP => "webp",
+        }
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Png => ImageFormat::Png,
+            Self::Gif => ImageFormat::Gif,
+            Self::WebP => ImageFormat::WebP,
+        }
+    }
+}
+
+/// Identify an upload by its magic bytes
+fn sniff(data: &[u8]) -> Option<SourceFormat> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(SourceFormat::Jpeg)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(SourceFormat::Png)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some(SourceFormat::Gif)
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some(SourceFormat::WebP)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    WebP,
+    Jpeg,
+    Png,
+}
+
+impl OutputFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::WebP => "webp",
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::WebP => "webp",
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+        }
+    }
+}
+
+fn decode_error(e: ImageError) -> MediaError {
+    match e {
+        ImageError::Limits(e) => MediaError::TooLarge(format!("Image exceeds the size limits: {}", e)),
+        e => MediaError::Invalid(format!("The image could not be decoded: {}", e)),
+    }
+}
+
+/// Decode with the sniffed format (never a guessed one), refusing oversized images from their
+/// header alone, and apply the EXIF orientation that re-encoding would otherwise drop
+fn decode(data: &[u8], format: SourceFormat, config: &MediaConfig) -> Result<DynamicImage, MediaError> {
+    let mut reader = ImageReader::with_format(Cursor::new(data), format.image_format());
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(config.max_dimension);
+    limits.max_image_height = Some(config.max_dimension);
+    // 16-bit RGBA at the pixel limit
+    limits.max_alloc = Some(config.max_pixels.saturating_mul(8));
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    let (width, height) = decoder.dimensions();
+    if u64::from(width) * u64::from(height) > config.max_pixels {
+        return Err(MediaError::TooLarge(format!(
+            "Image is {}x{}, more than {} pixels",
+            width, height, config.max_pixels
+        )));
+    }
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    // Animated GIF and WebP keep their first frame
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Content address: the decoded pixels, so the same picture sent as a different file (or with
+/// different metadata) maps to the same media
+fn content_id(kind: MediaKind, image: &DynamicImage) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(kind.as_str().as_bytes());
+    hasher.update(image.width().to_be_bytes());
+    hasher.update(image.height().to_be_bytes());
+    hasher.update(format!("{:?}", image.color()).as_bytes());
+    hasher.update(image.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn scale(image: &DynamicImage, spec: &VariantSpec) -> DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    if spec.square {
+        let side = spec.max.min(width).min(height);
+        if width == side && height == side {
+            image.clone()
+        } else {
+            image.resize_to_fill(side, side, FilterType::CatmullRom)
+        }
+    } else if width <= spec.max && height <= spec.max {
+        image.clone()
+    } else {
+        image.resize(spec.max, spec.max, FilterType::CatmullRom)
+    }
+}
+
+fn encode(image: &DynamicImage, format: OutputFormat) -> Result<Vec<u8>, MediaError> {
+    let mut out = Vec::new();
+    let result = match format {
+        OutputFormat::Jpeg => image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)),
+        // Both encoders only take 8-bit RGB(A)
+        OutputFormat::Png | OutputFormat::WebP => {
+            let image = if image.color().has_alpha() {
+                DynamicImage::ImageRgba8(image.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(image.to_rgb8())
+            };
+            match format {
+                OutputFormat::Png => image.write_with_encoder(PngEncoder::new(&mut out)),
+                _ => image.write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+            }
+        }
+    };
+    result.map_err(|e| MediaError::Internal(format!("Failed to encode {}: {}", format.as_str(), e)))?;
+    Ok(out)
+}
+
+pub fn variant_url(id: &str, name: &str, extension: &str) -> String {
+    format!("{}/{}/{}.{}", MEDIA_URL_PREFIX, id, name, extension)
+}
+
+fn is_media_id(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// The media ID a post or comment `image` refers to: a bare ID or one of its `/media/` URLs
+pub fn parse_reference(value: &str) -> Option<&str> {
+    let value = value.trim();
+    let id = match value.strip_prefix(MEDIA_URL_PREFIX).and_then(|rest| rest.strip_prefix('/')) {
+        Some(rest) => rest.split('/').next().unwrap_or_default(),
+        None => value,
+    };
+    is_media_id(id).then_some(id)
+}
+
+/// Write files atomically and skip ones already present; with content addressing an existing
+/// file already holds the same bytes
+fn write_files(dir: &Path, files: &[(String, Vec<u8>)]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (name, bytes) in files {
+        let path = dir.join(name);
+        if path.exists() {
+            continue;
+        }
+        let tmp = dir.join(format!(".{}.{}", name, uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &path)?;
+    }
+    Ok(())
+}
+
+/// Validate an upload and store its variants. The upload's own bytes are never written: every
+/// file is re-encoded from the decoded pixels, which drops EXIF and anything appended to or
+/// embedded in the original. Each size is stored as lossless WebP plus a JPEG (for JPEG
+/// uploads) or PNG fallback. CPU bound; call from a blocking thread.
+pub fn ingest(config: &MediaConfig, kind: MediaKind, owner_address: &str, data: &[u8]) -> Result<Media, MediaError> {
+    if data.len() > config.max_upload_bytes {
+        return Err(MediaError::TooLarge(format!("Images may not exceed {} bytes", config.max_upload_bytes)));
+    }
+    let source_format = sniff(data).ok_or(MediaError::Unsupported)?;
+    let image = decode(data, source_format, config)?;
+    let id = content_id(kind, &image);
+    let fallback = if source_format == SourceFormat::Jpeg { OutputFormat::Jpeg } else { OutputFormat::Png };
+
+    let mut files = Vec::new();
+    let mut variants = Vec::new();
+    let mut source = image.clone();
+    for spec in variant_specs(kind) {
+        source = scale(&source, spec);
+        for format in [OutputFormat::WebP, fallback] {
+            let bytes = encode(&source, format)?;
+            variants.push(MediaVariant {
+                name: spec.name.to_string(),
+                format: format.as_str().to_string(),
+                width: source.width(),
+                height: source.height(),
+                bytes: bytes.len() as u64,
+                url: variant_url(&id, spec.name, format.extension()),
+            });
+            files.push((format!("{}.{}", spec.name, format.extension()), bytes));
+        }
+    }
+
+    write_files(&config.dir.join(&id), &files)
+        .map_err(|e| MediaError::Internal(format!("Failed to store media {}: {}", id, e)))?;
+
+    Ok(Media {
+        id,
+        kind,
+        owner_address: owner_address.to_lowercase(),
+        source_format: source_format.as_str().to_string(),
+        width: image.width(),
+        height: image.height(),
+        variants,
+        created_at: Utc::now(),
+    })
+}
+
+/// Delete the stored files of a media
+pub fn remove_files(config: &MediaConfig, id: &str) -> std::io::Result<()> {
+    if !is_media_id(id) {
+        return Ok(());
+    }
+    match std::fs::remove_dir_all(config.dir.join(id)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| Rgb([(x % 256) as u8, (y % 256) as u8, 128]));
+        let mut out = Vec::new();
+        image.write_with_encoder(PngEncoder::new(&mut out)).unwrap();
+        out
+    }
+
+    fn test_config(max_pixels: u64) -> MediaConfig {
+        MediaConfig {
+            dir: std::env::temp_dir().join(format!("irys-media-{}", uuid::Uuid::new_v4())),
+            max_pixels,
+            ..MediaConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_ingest_reencodes_and_dedupes() {
+        let config = test_config(1_000_000);
+        let mut upload = png(400, 300);
+        // A polyglot payload after IEND must not survive
+        upload.extend_from_slice(b"<script>alert(1)</script>");
+
+        let media = ingest(&config, MediaKind::Post, "0xABC", &upload).unwrap();
+        assert_eq!((media.width, media.height, media.source_format.as_str()), (400, 300, "png"));
+        assert_eq!(media.owner_address, "0xabc");
+        let thumb = media.variants.iter().find(|v| v.name == "thumb" && v.format == "webp").unwrap();
+        assert_eq!((thumb.width, thumb.height), (320, 240));
+        assert_eq!(media.display_url(), format!("/media/{}/large.webp", media.id));
+
+        for variant in &media.variants {
+            let file = config.dir.join(&media.id).join(variant.url.rsplit('/').next().unwrap());
+            let bytes = std::fs::read(file).unwrap();
+            assert!(!bytes.windows(8).any(|w| w == b"<script>"));
+        }
+        assert_eq!(ingest(&config, MediaKind::Post, "0xdef", &png(400, 300)).unwrap().id, media.id);
+        assert_eq!(parse_reference(&media.display_url()), Some(media.id.as_str()));
+        assert_eq!(parse_reference("data:image/png;base64,AAAA"), None);
+
+        remove_files(&config, &media.id).unwrap();
+        assert!(!config.dir.join(&media.id).exists());
+    }
+
+    #[test]
+    fn test_ingest_rejects_unsupported_and_oversized() {
+        let config = test_config(10_000);
+        assert!(matches!(ingest(&config, MediaKind::Avatar, "0xabc", b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), Err(MediaError::Unsupported)));
+        assert!(matches!(ingest(&config, MediaKind::Avatar, "0xabc", &png(200, 100)), Err(MediaError::TooLarge(_))));
+        assert!(matches!(ingest(&config, MediaKind::Avatar, "0xabc", &png(10, 10)[..40]), Err(MediaError::Invalid(_))));
+
+        let avatar = ingest(&config, MediaKind::Avatar, "0xabc", &png(90, 60)).unwrap();
+        let sizes: Vec<(u32, u32)> = avatar.variants.iter().map(|v| (v.width, v.height)).collect();
+        assert_eq!(sizes, vec![(60, 60), (60, 60), (60, 60), (60, 60)]);
+        remove_files(&config, &avatar.id).unwrap();
+    }
+}
@@ -0,0 +1,1606 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use unicode_normalization::UnicodeNormalization;
+use crate::async_queue::{QueuedTask, TaskStatus};
+use crate::blockchain::IrysForumEvents;
+use crate::database::{DatabaseService, UserTransaction, CHAIN_CHECKPOINT_HISTORY};
+use crate::indexer::IndexedEvent;
+use crate::models::*;
+use crate::search::TextMatcher;
+use crate::store::ForumStore;
+
+/// Same window as the `INTERVAL '5 minutes'` duplicate checks in SQL
+const DUPLICATE_WINDOW_MINUTES: i64 = 5;
+
+struct StoredPost {
+    post: Post,
+    content_hash: String,
+    blockchain_transaction_hash: Option<String>,
+    /// Prior versions, oldest first
+    revisions: Vec<PostRevision>,
+}
+
+struct StoredComment {
+    comment: Comment,
+    blockchain_transaction_hash: Option<String>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+struct StoredUser {
+    user: User,
+    has_username: bool,
+}
+
+struct StoredTask {
+    task: QueuedTask,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+struct StoredFollow {
+    id: String,
+    follower: String,
+    following: String,
+    created_at: DateTime<Utc>,
+}
+
+struct StoredNotification {
+    id: String,
+    notification: NewNotification,
+    created_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+}
+
+struct StoredMention {
+    post_id: String,
+    comment_id: Option<String>,
+    mention: Mention,
+}
+
+/// All tables behind one lock, so every operation is atomic like a SQL transaction.
+/// Addresses are compared case-insensitively; users are keyed by lowercase address.
+#[derive(Default)]
+struct MemoryState {
+    posts: HashMap<String, StoredPost>,
+    /// Soft-deleted posts, moved out of `posts` so every lookup skips them
+    deleted_posts: HashMap<String, StoredPost>,
+    comments: HashMap<String, StoredComment>,
+    users: HashMap<String, StoredUser>,
+    post_likes: HashSet<(String, String)>,
+    comment_likes: HashSet<(String, String)>,
+    /// Bookmark times keyed by (post id, lowercase address)
+    bookmarks: HashMap<(String, String), DateTime<Utc>>,
+    follows: Vec<StoredFollow>,
+    notifications: Vec<StoredNotification>,
+    mentions: Vec<StoredMention>,
+    reports: Vec<Report>,
+    locked_posts: HashSet<String>,
+    /// Keyed by lowercase address
+    suspensions: HashMap<String, Suspension>,
+    moderation_log: Vec<ModerationLogEntry>,
+    /// Block and mute times keyed by (lowercase owner, lowercase target)
+    blocks: HashMap<(String, String), DateTime<Utc>>,
+    mutes: HashMap<(String, String), DateTime<Utc>>,
+    fingerprints: Vec<ContentFingerprint>,
+    held_content: Vec<HeldContent>,
+    media: HashMap<String, Media>,
+    transactions: Vec<UserTransaction>,
+    recommendations: Vec<String>,
+    recommendations_at: Option<DateTime<Utc>>,
+    chain_events: Vec<IndexedEvent>,
+    chain_checkpoints: BTreeMap<u64, String>,
+    tasks: HashMap<String, StoredTask>,
+}
+
+/// In-memory `ForumStore`, used when no database is configured and in tests.
+/// Data is lost on restart.
+#[derive(Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    pub fn set_user_role(&self, address: &str, role: UserRole) {
+        self.state.lock().unwrap().ensure_user(address, &None).user.role = role;
+    }
+}
+
+fn address_key(address: &str) -> String {
+    address.to_lowercase()
+}
+
+fn paginate<T>(items: Vec<T>, limit: impl TryInto<usize>, offset: impl TryInto<usize>) -> Vec<T> {
+    let limit = limit.try_into().unwrap_or(0);
+    let offset = offset.try_into().unwrap_or(0);
+    items.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Apply `page` to rows sorted in page order (newest first if `descending`), `cursor` giving
+/// each row's position
+fn page_rows<T>(rows: Vec<T>, page: &PageRequest, descending: bool, cursor: impl Fn(&T) -> PageCursor) -> Page<T> {
+    let rows = rows.into_iter()
+        .filter(|row| page.after.as_ref().is_none_or(|after| {
+            let position = cursor(row);
+            if descending { position < *after } else { position > *after }
+        }))
+        .skip(page.offset as usize)
+        .take(page.limit as usize + 1)
+        .collect();
+    Page::from_rows(rows, page.limit, cursor)
+}
+
+impl MemoryState {
+    /// Create the user row if missing, with the same default usernames as `DatabaseService`
+    fn ensure_user(&mut self, address: &str, name: &Option<String>) -> &mut StoredUser {
+        self.users.entry(address_key(address)).or_insert_with(|| {
+            let username = match name {
+                Some(n) if !n.is_empty() => format!("{}_{}", n, address.chars().take(8).collect::<String>()),
+                _ => format!("user_{}", address.trim_start_matches("0x").chars().take(8).collect::<String>()),
+            };
+            StoredUser {
+                user: User {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    address: address.to_string(),
+                    name: Some(username),
+                    avatar: None,
+                    bio: None,
+                    created_at: Utc::now(),
+                    posts_count: 0,
+                    comments_count: 0,
+                    reputation: 0,
+                    role: UserRole::User,
+                },
+                has_username: false,
+            }
+        })
+    }
+
+    fn user(&self, address: &str) -> Option<&StoredUser> {
+        self.users.get(&address_key(address))
+    }
+
+    fn post_view(&self, stored: &StoredPost, viewer: Option<&str>) -> Post {
+        let mut post = stored.post.clone();
+        if let Some(author) = self.user(&post.author_address) {
+            post.author_id = Some(author.user.id.clone());
+            post.author_name = post.author_name.or_else(|| author.user.name.clone());
+            post.author_avatar = author.user.avatar.clone();
+        }
+        post.comments_count = self.comments.values()
+            .filter(|c| c.comment.post_id == post.id && c.deleted_at.is_none())
+            .count() as u32;
+        post.is_liked_by_user = viewer
+            .map(|addr| self.post_likes.contains(&(post.id.clone(), address_key(addr))))
+            .unwrap_or(false);
+        post
+    }
+
+    fn comment_view(&self, stored: &StoredComment) -> Comment {
+        let mut comment = stored.comment.clone();
+        if let Some(author) = self.user(&comment.author_address) {
+            comment.author_id = Some(author.user.id.clone());
+            comment.author_name = comment.author_name.or_else(|| author.user.name.clone());
+            comment.author_avatar = author.user.avatar.clone();
+        }
+        comment
+    }
+
+    /// Posts by an optional author, newest first
+    fn posts_newest_first(&self, author: Option<&str>, viewer: Option<&str>) -> Vec<Post> {
+        let mut posts: Vec<Post> = self.posts.values()
+            .filter(|p| author.is_none_or(|a| p.post.author_address.eq_ignore_ascii_case(a)))
+            .map(|p| self.post_view(p, viewer))
+            .collect();
+        posts.sort_by_key(|p| std::cmp::Reverse(p.page_cursor()));
+        posts
+    }
+
+    fn has_replies(&self, comment_id: &str) -> bool {
+        self.comments.values().any(|c| c.comment.parent_id.as_deref() == Some(comment_id))
+    }
+
+    /// Deleted comments with replies become tombstones; ones without are hidden
+    fn visible_comment(&self, stored: &StoredComment) -> Option<Comment> {
+        match stored.deleted_at {
+            None => Some(self.comment_view(stored)),
+            Some(_) if self.has_replies(&stored.comment.id) => Some(self.comment_view(stored).into_tombstone()),
+            Some(_) => None,
+        }
+    }
+
+    /// Comments of a post, oldest first
+    fn comments_for_post(&self, post_id: &str) -> Vec<Comment> {
+        let mut comments: Vec<Comment> = self.comments.values()
+            .filter(|c| c.comment.post_id == post_id)
+            .filter_map(|c| self.visible_comment(c))
+            .collect();
+        comments.sort_by_key(Comment::page_cursor);
+        comments
+    }
+
+    /// (likes x 3 + comments x 2 + views x 0.1) x time decay, as in `calculate_hot_posts`
+    fn heat_score(&self, post: &Post) -> f64 {
+        let age_hours = (Utc::now() - post.created_at).num_seconds() as f64 / 3600.0;
+        let time_decay = if age_hours <= 24.0 {
+            1.0
+        } else if age_hours <= 48.0 {
+            0.8
+        } else if age_hours <= 72.0 {
+            0.6
+        } else {
+            0.4
+        };
+        (post.likes as f64 * 3.0 + post.comments_count as f64 * 2.0 + post.views as f64 * 0.1) * time_decay
+    }
+
+    fn record_stats(&mut self, address: &str, is_post: bool) {
+        if let Some(stored) = self.users.get_mut(&address_key(address)) {
+            if is_post {
+                stored.user.posts_count += 1;
+                stored.user.reputation += 10;
+            } else {
+                stored.user.comments_count += 1;
+                stored.user.reputation += 5;
+            }
+        }
+    }
+
+    fn insert_comment(&mut self, comment: &Comment) -> Result<(), sqlx::Error> {
+        if !self.posts.contains_key(&comment.post_id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        self.ensure_user(&comment.author_address, &comment.author_name);
+        self.comments.insert(comment.id.clone(), StoredComment {
+            comment: comment.clone(),
+            blockchain_transaction_hash: None,
+            deleted_at: None,
+        });
+        self.record_stats(&comment.author_address, false);
+        Ok(())
+    }
+
+    /// Visible direct replies to `parent_id` (top-level comments if `None`) in (created_at, id) order,
+    /// compared at the microsecond precision of Postgres and `PageCursor`
+    fn thread_children(&self, post_id: &str, parent_id: Option<&str>) -> Vec<Comment> {
+        let mut children: Vec<Comment> = self.comments.values()
+            .filter(|c| c.comment.post_id == post_id && c.comment.parent_id.as_deref() == parent_id)
+            .filter_map(|c| self.visible_comment(c))
+            .collect();
+        children.sort_by_key(Comment::page_cursor);
+        children
+    }
+
+    /// Entries of `owner`'s block or mute list, most recent first
+    fn restricted_users(&self, list: &HashMap<(String, String), DateTime<Utc>>, owner: &str) -> Vec<RestrictedUser> {
+        let owner = address_key(owner);
+        let mut users: Vec<RestrictedUser> = list.iter()
+            .filter(|((o, _), _)| *o == owner)
+            .map(|((_, target), at)| RestrictedUser {
+                address: target.clone(),
+                username: self.user(target).and_then(|u| u.user.name.clone()),
+                created_at: *at,
+            })
+            .collect();
+        users.sort_by_key(|u| std::cmp::Reverse(u.created_at));
+        users
+    }
+
+    fn is_following(&self, follower: &str, following: &str) -> bool {
+        self.follows.iter().any(|f| f.follower.eq_ignore_ascii_case(follower) && f.following.eq_ignore_ascii_case(following))
+    }
+
+    fn follow_counts(&self, address: &str) -> (u32, u32, u32) {
+        let following: Vec<&str> = self.follows.iter()
+            .filter(|f| f.follower.eq_ignore_ascii_case(address))
+            .map(|f| f.following.as_str())
+            .collect();
+        let followers = self.follows.iter().filter(|f| f.following.eq_ignore_ascii_case(address)).count();
+        let mutual = following.iter().filter(|other| self.is_following(other, address)).count();
+        (following.len() as u32, followers as u32, mutual as u32)
+    }
+
+    /// Follow edges touching `address`, newest first, mapped to the other side's address
+    /// Addresses on the other end of `address`'s follows, newest first with each follow's position.
+    /// Addresses without a user row are skipped, as by the join in `DatabaseService`.
+    fn follow_edges(&self, address: &str, outgoing: bool) -> Vec<(String, PageCursor)> {
+        let mut edges: Vec<(String, PageCursor)> = self.follows.iter()
+            .filter(|f| if outgoing { f.follower.eq_ignore_ascii_case(address) } else { f.following.eq_ignore_ascii_case(address) })
+            .map(|f| (if outgoing { f.following.clone() } else { f.follower.clone() }, PageCursor::new(f.created_at, &f.id)))
+            .filter(|(other, _)| self.user(other).is_some())
+            .collect();
+        edges.sort_by_key(|(_, position)| std::cmp::Reverse(position.clone()));
+        edges
+    }
+
+    fn profile_page(&self, edges: Vec<(String, PageCursor)>, page: &PageRequest, viewer: &str) -> Page<UserProfile> {
+        page_rows(edges, page, true, |(_, position)| position.clone())
+            .map(|edges| self.profiles(edges.into_iter().map(|(address, _)| address).collect(), viewer))
+    }
+
+    /// Build profiles for `addresses` as seen by `viewer`; addresses without a user row are skipped
+    fn profiles(&self, addresses: Vec<String>, viewer: &str) -> Vec<UserProfile> {
+        addresses.into_iter()
+            .filter_map(|address| self.user(&address))
+            .map(|stored| {
+                let user = &stored.user;
+                let (following_count, followers_count, mutual_follows_count) = self.follow_counts(&user.address);
+                let is_following = self.is_following(viewer, &user.address);
+                let is_followed_by = self.is_following(&user.address, viewer);
+                UserProfile {
+                    id: user.id.clone(),
+                    ethereum_address: user.address.clone(),
+                    username: user.name.clone(),
+                    bio: user.bio.clone(),
+                    avatar: user.avatar.clone(),
+                    posts_count: user.posts_count,
+                    comments_count: user.comments_count,
+                    reputation: user.reputation,
+                    following_count,
+                    followers_count,
+                    mutual_follows_count,
+                    is_following,
+                    is_followed_by,
+                    is_mutual: is_following && is_followed_by,
+                    is_self: user.address.eq_ignore_ascii_case(viewer),
+                    created_at: user.created_at,
+                }
+            })
+            .collect()
+    }
+
+    fn task_mut(&mut self, task_id: &str) -> Result<&mut QueuedTask, sqlx::Error> {
+        self.tasks.get_mut(task_id).map(|stored| {
+            stored.locked_until = None;
+            stored.task.updated_at = Utc::now();
+            &mut stored.task
+        }).ok_or(sqlx::Error::RowNotFound)
+    }
+
+    fn record_transaction(&mut self, transaction: UserTransaction) -> Result<(), sqlx::Error> {
+        if self.transactions.iter().any(|t| t.transaction_hash == transaction.transaction_hash) {
+            return Err(sqlx::Error::Protocol(format!("Transaction {} has already been recorded", transaction.transaction_hash)));
+        }
+        self.transactions.push(transaction);
+        Ok(())
+    }
+}
+
+fn new_transaction(tx_hash: &str, transaction_type: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>) -> UserTransaction {
+    UserTransaction {
+        transaction_hash: tx_hash.to_string(),
+        transaction_type_str: transaction_type.to_string(),
+        user_address: user_address.to_string(),
+        block_number: Some(block_number as i64),
+        block_timestamp: Some(block_timestamp),
+        verified_at: Utc::now(),
+        post_id: None,
+        comment_id: None,
+    }
+}
+
+#[async_trait]
+impl ForumStore for MemoryStore {
+    fn backend_name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get_database_stats(&self) -> serde_json::Value {
+        let state = self.state.lock().unwrap();
+        serde_json::json!({
+            "posts": state.posts.len(),
+            "comments": state.comments.len(),
+            "users": state.users.len(),
+            "follows": state.follows.len(),
+            "chain_events": state.chain_events.len(),
+            "tasks": state.tasks.len()
+        })
+    }
+
+    async fn create_post(&self, post: &Post) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.ensure_user(&post.author_address, &post.author_name);
+        state.posts.insert(post.id.clone(), StoredPost {
+            post: post.clone(),
+            content_hash: format!("{:x}", md5::compute(&post.content)),
+            blockchain_transaction_hash: None,
+            revisions: Vec::new(),
+        });
+        state.record_stats(&post.author_address, true);
+        Ok(())
+    }
+
+    async fn get_posts_paginated(&self, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let posts = state.posts_newest_first(None, None)
+            .into_iter()
+            .filter(|p| !excluded_authors.contains(&address_key(&p.author_address)))
+            .collect();
+        Ok(page_rows(posts, page, true, Post::page_cursor))
+    }
+
+    async fn get_posts_by_user(&self, user_address: &str, page: &PageRequest) -> Result<Page<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(page_rows(state.posts_newest_first(Some(user_address), None), page, true, Post::page_cursor))
+    }
+
+    async fn get_posts_by_user_with_like_status(&self, user_address: &str, page: &PageRequest, request_user_address: Option<&str>) -> Result<Page<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(page_rows(state.posts_newest_first(Some(user_address), request_user_address), page, true, Post::page_cursor))
+    }
+
+    async fn get_following_feed(&self, user_address: &str, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let posts = state.posts_newest_first(None, Some(user_address))
+            .into_iter()
+            .filter(|p| state.is_following(user_address, &p.author_address))
+            .filter(|p| !excluded_authors.contains(&address_key(&p.author_address)))
+            .collect();
+        Ok(page_rows(posts, page, true, Post::page_cursor))
+    }
+
+    async fn get_post_by_id(&self, id: &str) -> Result<Option<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.posts.get(id).map(|p| state.post_view(p, None)))
+    }
+
+    async fn get_post_by_id_with_like_status(&self, id: &str, user_address: Option<&str>, viewer: Option<&str>) -> Result<Option<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.posts.get(id).map(|p| {
+            let mut post = state.post_view(p, user_address);
+            post.is_bookmarked_by_user = viewer
+                .is_some_and(|addr| state.bookmarks.contains_key(&(post.id.clone(), address_key(addr))));
+            post
+        }))
+    }
+
+    async fn get_post_content_hash(&self, post_id: &str) -> Result<Option<String>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.posts.get(post_id).map(|p| p.content_hash.clone()))
+    }
+
+    async fn check_duplicate_post(&self, author_address: &str, content: &str) -> Result<bool, sqlx::Error> {
+        let since = Utc::now() - Duration::minutes(DUPLICATE_WINDOW_MINUTES);
+        let state = self.state.lock().unwrap();
+        Ok(state.posts.values().any(|p| {
+            p.post.author_address.eq_ignore_ascii_case(author_address) && p.post.content == content && p.post.created_at > since
+        }))
+    }
+
+    async fn update_post_blockchain_hash(&self, post_id: &str, tx_hash: &str) -> Result<(), sqlx::Error> {
+        if let Some(stored) = self.state.lock().unwrap().posts.get_mut(post_id) {
+            stored.blockchain_transaction_hash = Some(tx_hash.to_string());
+        }
+        Ok(())
+    }
+
+    async fn update_post(&self, post: &Post) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let stored = state.posts.get_mut(&post.id).ok_or(sqlx::Error::RowNotFound)?;
+
+        stored.revisions.push(PostRevision {
+            post_id: post.id.clone(),
+            revision: stored.revisions.len() as u32 + 1,
+            title: stored.post.title.clone(),
+            content: stored.post.content.clone(),
+            tags: stored.post.tags.clone(),
+            image: stored.post.image.clone(),
+            content_hash: Some(stored.content_hash.clone()),
+            irys_transaction_id: stored.post.irys_transaction_id.clone(),
+            created_at: stored.post.updated_at,
+            replaced_at: post.updated_at,
+        });
+        stored.post.title = post.title.clone();
+        stored.post.content = post.content.clone();
+        stored.post.content_html = post.content_html.clone();
+        stored.post.tags = post.tags.clone();
+        stored.post.image = post.image.clone();
+        stored.post.irys_transaction_id = post.irys_transaction_id.clone();
+        stored.post.updated_at = post.updated_at;
+        stored.content_hash = format!("{:x}", md5::compute(&post.content));
+        Ok(())
+    }
+
+    async fn get_post_revisions(&self, post_id: &str) -> Result<Vec<PostRevision>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.posts.get(post_id).map(|stored| stored.revisions.clone()).unwrap_or_default())
+    }
+
+    async fn delete_post(&self, post_id: &str, _deleted_by: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let Some(stored) = state.posts.remove(post_id) else {
+            return Ok(false);
+        };
+        if let Some(author) = state.users.get_mut(&address_key(&stored.post.author_address)) {
+            author.user.posts_count = author.user.posts_count.saturating_sub(1);
+        }
+        state.deleted_posts.insert(post_id.to_string(), stored);
+        Ok(true)
+    }
+
+    async fn like_post(&self, post_id: &str, user_address: &str) -> Result<u32, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let like = (post_id.to_string(), address_key(user_address));
+        let liked = !state.post_likes.remove(&like);
+        let stored = state.posts.get_mut(post_id).ok_or(sqlx::Error::RowNotFound)?;
+        stored.post.likes = if liked { stored.post.likes + 1 } else { stored.post.likes.saturating_sub(1) };
+        let likes = stored.post.likes;
+        if liked {
+            state.post_likes.insert(like);
+        }
+        Ok(likes)
+    }
+
+    async fn has_user_liked_post(&self, post_id: &str, user_address: &str) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.post_likes.contains(&(post_id.to_string(), address_key(user_address))))
+    }
+
+    async fn add_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        if !state.posts.contains_key(post_id) && !state.deleted_posts.contains_key(post_id) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        let key = (post_id.to_string(), address_key(user_address));
+        if state.bookmarks.contains_key(&key) {
+            return Ok(false);
+        }
+        state.bookmarks.insert(key, Utc::now());
+        Ok(true)
+    }
+
+    async fn remove_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.bookmarks.remove(&(post_id.to_string(), address_key(user_address))).is_some())
+    }
+
+    async fn get_bookmarks(&self, user_address: &str, page: &PageRequest) -> Result<Page<BookmarkedPost>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let user = address_key(user_address);
+        let mut bookmarks: Vec<BookmarkedPost> = state.bookmarks.iter()
+            .filter(|((_, address), _)| *address == user)
+            .filter_map(|((post_id, _), at)| {
+                state.posts.get(post_id).map(|stored| BookmarkedPost {
+                    post: Post { is_bookmarked_by_user: true, ..state.post_view(stored, Some(user_address)) },
+                    bookmarked_at: *at,
+                })
+            })
+            .collect();
+        bookmarks.sort_by_key(|b| std::cmp::Reverse(b.page_cursor()));
+        Ok(page_rows(bookmarks, page, true, BookmarkedPost::page_cursor))
+    }
+
+    async fn add_comment(&self, comment: &Comment) -> Result<(), sqlx::Error> {
+        self.state.lock().unwrap().insert_comment(comment)
+    }
+
+    async fn get_comments_by_post_id_paginated(&self, post_id: &str, page: &PageRequest) -> Result<Page<Comment>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(page_rows(state.comments_for_post(post_id), page, false, Comment::page_cursor))
+    }
+
+    async fn get_comment_by_id(&self, comment_id: &str) -> Result<Option<Comment>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.comments.get(comment_id).filter(|c| c.deleted_at.is_none()).map(|c| state.comment_view(c)))
+    }
+
+    async fn get_comment_tree(&self, post_id: &str, parent_id: Option<&str>, after: Option<&PageCursor>, limit: u32, max_depth: u32) -> Result<Vec<CommentTreeRow>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let limit = limit as usize;
+        let row = |comment: Comment| CommentTreeRow {
+            reply_count: state.thread_children(post_id, Some(&comment.id)).len() as u32,
+            comment,
+        };
+
+        let mut roots: Vec<Comment> = state.thread_children(post_id, parent_id).into_iter()
+            .filter(|c| after.is_none_or(|a| c.page_cursor() > *a))
+            .take(limit + 1)
+            .collect();
+        let extra = (roots.len() > limit).then(|| roots.pop()).flatten();
+
+        let mut rows: Vec<CommentTreeRow> = extra.into_iter().map(row).collect();
+        let mut level = roots;
+        for depth in 1..=max_depth {
+            let next: Vec<Comment> = if depth < max_depth {
+                level.iter().flat_map(|c| state.thread_children(post_id, Some(&c.id)).into_iter().take(limit)).collect()
+            } else {
+                Vec::new()
+            };
+            rows.extend(level.into_iter().map(row));
+            level = next;
+        }
+        Ok(rows)
+    }
+
+    async fn get_comment_chain_id(&self, comment_id: &str) -> Result<Option<u64>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let Some(tx_hash) = state.comments.get(comment_id).and_then(|c| c.blockchain_transaction_hash.as_deref()) else {
+            return Ok(None);
+        };
+        Ok(state.chain_events.iter().find_map(|indexed| match &indexed.event {
+            IrysForumEvents::CommentCreatedFilter(e) if indexed.transaction_hash.eq_ignore_ascii_case(tx_hash) => {
+                Some(e.comment_id.low_u64())
+            }
+            _ => None,
+        }))
+    }
+
+    async fn check_duplicate_comment(&self, author_address: &str, content: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        let since = Utc::now() - Duration::minutes(DUPLICATE_WINDOW_MINUTES);
+        let state = self.state.lock().unwrap();
+        Ok(state.comments.values().any(|c| {
+            c.comment.author_address.eq_ignore_ascii_case(author_address)
+                && c.comment.content == content
+                && c.comment.post_id == post_id
+                && c.comment.created_at > since
+                && c.deleted_at.is_none()
+        }))
+    }
+
+    async fn update_comment_blockchain_hash(&self, comment_id: &str, tx_hash: &str) -> Result<(), sqlx::Error> {
+        if let Some(stored) = self.state.lock().unwrap().comments.get_mut(comment_id) {
+            stored.blockchain_transaction_hash = Some(tx_hash.to_string());
+        }
+        Ok(())
+    }
+
+    async fn like_comment(&self, comment_id: &str, user_address: &str) -> Result<(u32, bool), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let like = (comment_id.to_string(), address_key(user_address));
+        let liked = !state.comment_likes.remove(&like);
+        let stored = state.comments.get_mut(comment_id).ok_or(sqlx::Error::RowNotFound)?;
+        stored.comment.likes = if liked { stored.comment.likes + 1 } else { stored.comment.likes.saturating_sub(1) };
+        let likes = stored.comment.likes;
+        if liked {
+            state.comment_likes.insert(like);
+        }
+        Ok((likes, liked))
+    }
+
+    async fn check_comment_liked(&self, comment_id: &str, user_address: &str) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.comment_likes.contains(&(comment_id.to_string(), address_key(user_address))))
+    }
+
+    async fn delete_comment(&self, comment_id: &str, _deleted_by: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let Some(stored) = state.comments.get_mut(comment_id).filter(|c| c.deleted_at.is_none()) else {
+            return Ok(false);
+        };
+        stored.deleted_at = Some(Utc::now());
+        let author = address_key(&stored.comment.author_address);
+        if let Some(author) = state.users.get_mut(&author) {
+            author.user.comments_count = author.user.comments_count.saturating_sub(1);
+        }
+        Ok(true)
+    }
+
+    async fn get_user_by_address(&self, address: &str) -> Result<Option<User>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.user(address).map(|u| u.user.clone()))
+    }
+
+    async fn get_user_address_by_id(&self, user_id: &str) -> Result<String, sqlx::Error> {
+        uuid::Uuid::parse_str(user_id)
+            .map_err(|e| sqlx::Error::Protocol(format!("Invalid UUID: {}", e)))?;
+        let state = self.state.lock().unwrap();
+        state.users.values()
+            .find(|u| u.user.id == user_id)
+            .map(|u| u.user.address.clone())
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_user_role(&self, address: &str) -> Result<UserRole, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.user(address).map(|u| u.user.role).unwrap_or(UserRole::User))
+    }
+
+    async fn update_user_role(&self, address: &str, role: UserRole) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.users.get_mut(&address_key(address)).map(|u| u.user.role = role).is_some())
+    }
+
+    async fn ensure_user_exists(&self, address: &str, name: &Option<String>) -> Result<(), sqlx::Error> {
+        self.state.lock().unwrap().ensure_user(address, name);
+        Ok(())
+    }
+
+    async fn get_active_users_ranking(&self, limit: i64) -> Result<Vec<User>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut users: Vec<User> = state.users.values()
+            .map(|u| u.user.clone())
+            .filter(|u| u.posts_count > 0 || u.comments_count > 0)
+            .collect();
+        users.sort_by(|a, b| {
+            b.reputation.cmp(&a.reputation)
+                .then(b.posts_count.cmp(&a.posts_count))
+                .then(b.comments_count.cmp(&a.comments_count))
+        });
+        Ok(paginate(users, limit, 0))
+    }
+
+    async fn get_global_stats(&self) -> Result<GlobalStats, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(GlobalStats {
+            total_users: state.users.values().filter(|u| u.user.posts_count > 0 || u.user.comments_count > 0).count() as u32,
+            total_posts: state.posts.len() as u32,
+            total_comments: state.comments.values()
+                .filter(|c| c.deleted_at.is_none() && state.posts.contains_key(&c.comment.post_id))
+                .count() as u32,
+            total_likes: state.posts.values().map(|p| p.post.likes).sum(),
+        })
+    }
+
+    async fn update_user_avatar(&self, user_address: &str, avatar_url: &str) -> Result<(), sqlx::Error> {
+        self.state.lock().unwrap().ensure_user(user_address, &None).user.avatar = Some(avatar_url.to_string());
+        Ok(())
+    }
+
+    async fn update_user_bio(&self, user_address: &str, bio: &str) -> Result<(), sqlx::Error> {
+        self.state.lock().unwrap().ensure_user(user_address, &None).user.bio = Some(bio.to_string());
+        Ok(())
+    }
+
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, sqlx::Error> {
+        let matcher = TextMatcher::new(&request.q);
+        let state = self.state.lock().unwrap();
+        let in_range = |at: DateTime<Utc>| request.from.is_none_or(|from| at >= from) && request.to.is_none_or(|to| at < to);
+        let by_author = |address: &str| request.author.as_deref().is_none_or(|author| author.eq_ignore_ascii_case(address));
+        let has_tag = |tags: &[String]| request.tag.as_deref().is_none_or(|tag| tags.iter().any(|t| t == tag));
+        let mut results = SearchResults::default();
+
+        if request.include_posts {
+            let mut hits: Vec<PostSearchHit> = state.posts.values()
+                .filter(|p| in_range(p.post.created_at) && by_author(&p.post.author_address) && has_tag(&p.post.tags))
+                .filter_map(|p| {
+                    let score = matcher.score(&[&p.post.title, &p.post.content])?;
+                    let post = state.post_view(p, None);
+                    Some(PostSearchHit {
+                        snippet: matcher.snippet(&post.content),
+                        post_id: post.id,
+                        title: post.title,
+                        author_address: post.author_address,
+                        author_name: post.author_name,
+                        tags: post.tags,
+                        created_at: post.created_at,
+                        rank: score as f32,
+                    })
+                })
+                .collect();
+            hits.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(b.created_at.cmp(&a.created_at)));
+            results.posts = paginate(hits, request.limit, request.offset);
+        }
+
+        if request.include_comments {
+            let mut hits: Vec<CommentSearchHit> = state.comments.values()
+                .filter(|c| c.deleted_at.is_none() && in_range(c.comment.created_at) && by_author(&c.comment.author_address))
+                .filter_map(|c| {
+                    let post = state.posts.get(&c.comment.post_id).filter(|p| has_tag(&p.post.tags))?;
+                    let score = matcher.score(&[&c.comment.content])?;
+                    let comment = state.comment_view(c);
+                    Some(CommentSearchHit {
+                        snippet: matcher.snippet(&comment.content),
+                        comment_id: comment.id,
+                        post_id: comment.post_id,
+                        post_title: post.post.title.clone(),
+                        author_address: comment.author_address,
+                        author_name: comment.author_name,
+                        created_at: comment.created_at,
+                        rank: score as f32,
+                    })
+                })
+                .collect();
+            hits.sort_by(|a, b| b.rank.total_cmp(&a.rank).then(b.created_at.cmp(&a.created_at)));
+            results.comments = paginate(hits, request.limit, request.offset);
+        }
+
+        if request.include_users {
+            let mut hits: Vec<(UserSearchHit, u32)> = state.users.values()
+                .filter_map(|u| {
+                    let score = matcher.score(&[u.user.name.as_deref().unwrap_or_default()])?;
+                    Some((UserSearchHit {
+                        address: u.user.address.clone(),
+                        username: u.user.name.clone(),
+                        avatar: u.user.avatar.clone(),
+                        rank: score as f32,
+                    }, u.user.reputation))
+                })
+                .collect();
+            hits.sort_by(|(a, a_reputation), (b, b_reputation)| b.rank.total_cmp(&a.rank).then(b_reputation.cmp(a_reputation)));
+            results.users = paginate(hits.into_iter().map(|(hit, _)| hit).collect(), request.limit, request.offset);
+        }
+
+        Ok(results)
+    }
+
+    async fn register_username(&self, address: &str, username: &str) -> Result<bool, sqlx::Error> {
+        let normalized: String = username.nfc().collect::<String>().trim().to_string();
+        let mut state = self.state.lock().unwrap();
+        if state.users.values().any(|u| u.user.name.as_deref() == Some(normalized.as_str())) {
+            return Ok(false);
+        }
+
+        let stored = state.ensure_user(address, &None);
+        if stored.has_username {
+            return Ok(false);
+        }
+        stored.user.name = Some(normalized);
+        stored.has_username = true;
+        Ok(true)
+    }
+
+    async fn is_username_available(&self, username: &str) -> Result<bool, sqlx::Error> {
+        if !DatabaseService::is_valid_username(username) {
+            return Ok(false);
+        }
+        let normalized: String = username.nfc().collect::<String>().trim().to_string();
+        let state = self.state.lock().unwrap();
+        Ok(!state.users.values().any(|u| u.user.name.as_deref() == Some(normalized.as_str())))
+    }
+
+    async fn get_username_by_address(&self, address: &str) -> Result<Option<String>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.user(address).filter(|u| u.has_username).and_then(|u| u.user.name.clone()))
+    }
+
+    async fn user_has_username(&self, address: &str) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.user(address).map(|u| u.has_username).unwrap_or(false))
+    }
+
+    async fn get_address_by_username(&self, username: &str) -> Result<Option<String>, sqlx::Error> {
+        let normalized: String = username.nfc().collect::<String>().trim().to_string();
+        let state = self.state.lock().unwrap();
+        Ok(state.users.values()
+            .find(|u| u.has_username && u.user.name.as_deref() == Some(normalized.as_str()))
+            .map(|u| u.user.address.clone()))
+    }
+
+    async fn is_transaction_used(&self, tx_hash: &str) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.transactions.iter().any(|t| t.transaction_hash == tx_hash))
+    }
+
+    async fn record_post_transaction(&self, tx_hash: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>, post_id: &str) -> Result<(), sqlx::Error> {
+        self.state.lock().unwrap().record_transaction(UserTransaction {
+            post_id: Some(post_id.to_string()),
+            ..new_transaction(tx_hash, "POST", user_address, block_number, block_timestamp)
+        })
+    }
+
+    async fn record_comment_transaction(&self, tx_hash: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>, comment_id: &str) -> Result<(), sqlx::Error> {
+        self.state.lock().unwrap().record_transaction(UserTransaction {
+            comment_id: Some(comment_id.to_string()),
+            ..new_transaction(tx_hash, "COMMENT", user_address, block_number, block_timestamp)
+        })
+    }
+
+    async fn follow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        if state.is_following(follower_address, following_address) {
+            return Ok(false);
+        }
+        state.follows.push(StoredFollow {
+            id: uuid::Uuid::new_v4().to_string(),
+            follower: follower_address.to_string(),
+            following: following_address.to_string(),
+            created_at: Utc::now(),
+        });
+        Ok(true)
+    }
+
+    async fn unfollow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.follows.len();
+        state.follows.retain(|f| !(f.follower.eq_ignore_ascii_case(follower_address) && f.following.eq_ignore_ascii_case(following_address)));
+        Ok(state.follows.len() < before)
+    }
+
+    async fn is_following(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
+        Ok(self.state.lock().unwrap().is_following(follower_address, following_address))
+    }
+
+    async fn get_follow_counts(&self, user_address: &str) -> Result<(u32, u32, u32), sqlx::Error> {
+        Ok(self.state.lock().unwrap().follow_counts(user_address))
+    }
+
+    async fn get_following_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let edges = state.follow_edges(user_address, true);
+        Ok(state.profile_page(edges, page, user_address))
+    }
+
+    async fn get_followers_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let edges = state.follow_edges(user_address, false);
+        Ok(state.profile_page(edges, page, user_address))
+    }
+
+    async fn get_mutual_follows_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let edges = state.follow_edges(user_address, true)
+            .into_iter()
+            .filter(|(other, _)| state.is_following(other, user_address))
+            .collect();
+        Ok(state.profile_page(edges, page, user_address))
+    }
+
+    async fn should_refresh_daily_recommendations(&self) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.recommendations_at.map(|at| at.date_naive()) != Some(Utc::now().date_naive()))
+    }
+
+    async fn calculate_hot_posts(&self) -> Result<Vec<String>, sqlx::Error> {
+        let since = Utc::now() - Duration::days(7);
+        let state = self.state.lock().unwrap();
+        let mut scored: Vec<(String, f64)> = state.posts.values()
+            .filter(|p| p.post.created_at >= since)
+            .map(|p| {
+                let post = state.post_view(p, None);
+                let score = state.heat_score(&post);
+                (post.id, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(scored.into_iter().take(10).map(|(id, _)| id).collect())
+    }
+
+    async fn update_daily_recommendations(&self, post_ids: &[String]) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.recommendations = post_ids.to_vec();
+        state.recommendations_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn get_daily_recommendations(&self, user_address: Option<&str>, excluded_authors: &[String]) -> Result<RecommendationResult, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let today = Utc::now().date_naive();
+        let Some(refreshed_at) = state.recommendations_at.filter(|at| at.date_naive() == today) else {
+            return Ok(RecommendationResult { posts: vec![], last_refresh_time: None });
+        };
+
+        let posts = state.recommendations.iter()
+            .filter_map(|id| state.posts.get(id))
+            .filter(|p| !excluded_authors.contains(&address_key(&p.post.author_address)))
+            .map(|p| {
+                let mut post = state.post_view(p, user_address);
+                post.heat_score = Some(state.heat_score(&post));
+                post
+            })
+            .collect();
+        Ok(RecommendationResult { posts, last_refresh_time: Some(refreshed_at) })
+    }
+
+    async fn create_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let same = |n: &StoredNotification| {
+            n.notification.recipient_address == notification.recipient_address
+                && n.notification.group_key == notification.group_key
+                && n.notification.actor_address == notification.actor_address
+        };
+        match state.notifications.iter_mut().find(|n| same(n)) {
+            Some(existing) => {
+                existing.notification = notification.clone();
+                existing.created_at = Utc::now();
+                existing.read_at = None;
+            }
+            None => state.notifications.push(StoredNotification {
+                id: uuid::Uuid::new_v4().to_string(),
+                notification: notification.clone(),
+                created_at: Utc::now(),
+                read_at: None,
+            }),
+        }
+        Ok(())
+    }
+
+    async fn delete_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.notifications.retain(|n| {
+            !(n.notification.recipient_address == notification.recipient_address
+                && n.notification.group_key == notification.group_key
+                && n.notification.actor_address == notification.actor_address)
+        });
+        Ok(())
+    }
+
+    async fn get_notification_groups(&self, recipient_address: &str, page: &PageRequest, unread_only: bool) -> Result<Page<NotificationGroup>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let recipient = address_key(recipient_address);
+        let mut by_key: HashMap<&str, Vec<&StoredNotification>> = HashMap::new();
+        for stored in state.notifications.iter().filter(|n| n.notification.recipient_address == recipient) {
+            by_key.entry(stored.notification.group_key.as_str()).or_default().push(stored);
+        }
+
+        let mut groups: Vec<NotificationGroup> = by_key.into_values()
+            .filter_map(|mut members| {
+                members.sort_by_key(|n| std::cmp::Reverse(PageCursor::new(n.created_at, &n.id)));
+                let unread = members.iter().any(|n| n.read_at.is_none());
+                let latest = members.first().filter(|_| unread || !unread_only)?;
+                Some(NotificationGroup {
+                    id: latest.id.clone(),
+                    group_key: latest.notification.group_key.clone(),
+                    kind: latest.notification.kind,
+                    post_id: latest.notification.post_id.clone(),
+                    comment_id: latest.notification.comment_id.clone(),
+                    post_title: latest.notification.post_id.as_ref()
+                        .and_then(|id| state.posts.get(id))
+                        .map(|p| p.post.title.clone()),
+                    actors: members.iter()
+                        .take(NotificationGroup::MAX_ACTORS)
+                        .map(|n| NotificationActor {
+                            address: n.notification.actor_address.clone(),
+                            name: state.user(&n.notification.actor_address).and_then(|u| u.user.name.clone()),
+                        })
+                        .collect(),
+                    actor_count: members.len() as u32,
+                    unread,
+                    message: String::new(),
+                    created_at: latest.created_at,
+                })
+            })
+            .collect();
+        groups.sort_by_key(|g| std::cmp::Reverse(g.page_cursor()));
+        Ok(page_rows(groups, page, true, NotificationGroup::page_cursor))
+    }
+
+    async fn mark_notifications_read(&self, recipient_address: &str, group_keys: Option<&[String]>) -> Result<u64, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let recipient = address_key(recipient_address);
+        let now = Utc::now();
+        let mut marked = 0;
+        for stored in state.notifications.iter_mut()
+            .filter(|n| n.notification.recipient_address == recipient && n.read_at.is_none())
+            .filter(|n| group_keys.is_none_or(|keys| keys.contains(&n.notification.group_key)))
+        {
+            stored.read_at = Some(now);
+            marked += 1;
+        }
+        Ok(marked)
+    }
+
+    async fn get_posts_by_tag(&self, tag: &str, page: &PageRequest, viewer: Option<&str>) -> Result<Page<Post>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let posts = state.posts_newest_first(None, viewer)
+            .into_iter()
+            .filter(|p| p.tags.iter().any(|t| t == tag))
+            .collect();
+        Ok(page_rows(posts, page, true, Post::page_cursor))
+    }
+
+    async fn get_tag(&self, tag: &str) -> Result<Option<TagSummary>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let tagged: Vec<&Post> = state.posts.values()
+            .map(|p| &p.post)
+            .filter(|p| p.tags.iter().any(|t| t == tag))
+            .collect();
+        Ok((!tagged.is_empty()).then(|| TagSummary {
+            name: tag.to_string(),
+            post_count: tagged.len() as u32,
+            last_used_at: tagged.iter().map(|p| p.updated_at.max(p.created_at)).max(),
+        }))
+    }
+
+    async fn get_trending_tags(&self, since: DateTime<Utc>, limit: u32) -> Result<Vec<TrendingTag>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut counts: HashMap<&str, (u32, u32)> = HashMap::new();
+        for post in state.posts.values().map(|p| &p.post).filter(|p| p.created_at >= since) {
+            for tag in &post.tags {
+                let entry = counts.entry(tag.as_str()).or_default();
+                entry.0 += 1;
+                entry.1 += post.likes;
+            }
+        }
+        let mut trending: Vec<TrendingTag> = counts.into_iter()
+            .map(|(name, (recent_posts, recent_likes))| TrendingTag { name: name.to_string(), recent_posts, recent_likes })
+            .collect();
+        trending.sort_by(|a, b| {
+            (b.recent_posts, b.recent_likes).cmp(&(a.recent_posts, a.recent_likes)).then_with(|| a.name.cmp(&b.name))
+        });
+        trending.truncate(limit as usize);
+        Ok(trending)
+    }
+
+    async fn replace_mentions(&self, post_id: &str, comment_id: Option<&str>, mentions: &[Mention]) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.mentions.retain(|m| !(m.post_id == post_id && m.comment_id.as_deref() == comment_id));
+        state.mentions.extend(mentions.iter().map(|mention| StoredMention {
+            post_id: post_id.to_string(),
+            comment_id: comment_id.map(str::to_string),
+            mention: mention.clone(),
+        }));
+        Ok(())
+    }
+
+    async fn get_post_mentions(&self, post_ids: &[String]) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut mentions: HashMap<String, Vec<Mention>> = HashMap::new();
+        for stored in state.mentions.iter().filter(|m| m.comment_id.is_none() && post_ids.contains(&m.post_id)) {
+            mentions.entry(stored.post_id.clone()).or_default().push(stored.mention.clone());
+        }
+        Ok(mentions)
+    }
+
+    async fn get_comment_mentions(&self, comment_ids: &[String]) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut mentions: HashMap<String, Vec<Mention>> = HashMap::new();
+        for stored in &state.mentions {
+            if let Some(comment_id) = stored.comment_id.as_ref().filter(|id| comment_ids.contains(id)) {
+                mentions.entry(comment_id.clone()).or_default().push(stored.mention.clone());
+            }
+        }
+        Ok(mentions)
+    }
+
+    async fn create_report(&self, report: &Report) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let reporter = address_key(&report.reporter_address);
+        let duplicate = state.reports.iter().any(|r| {
+            r.status == ReportStatus::Open && r.reporter_address == reporter
+                && r.target_type == report.target_type && r.target_id == report.target_id
+        });
+        if duplicate {
+            return Ok(false);
+        }
+        state.reports.push(Report { reporter_address: reporter, status: ReportStatus::Open, ..report.clone() });
+        Ok(true)
+    }
+
+    async fn get_report(&self, report_id: &str) -> Result<Option<Report>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.reports.iter().find(|r| r.id == report_id).cloned())
+    }
+
+    async fn get_open_reports(&self, page: &PageRequest) -> Result<Page<Report>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut reports: Vec<Report> = state.reports.iter()
+            .filter(|r| r.status == ReportStatus::Open)
+            .cloned()
+            .collect();
+        reports.sort_by_key(Report::page_cursor);
+        Ok(page_rows(reports, page, false, Report::page_cursor))
+    }
+
+    async fn close_reports(&self, target_type: ModerationTarget, target_id: &str, status: ReportStatus, resolution: ModerationAction, resolved_by: &str) -> Result<u32, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let mut closed = 0;
+        for report in state.reports.iter_mut() {
+            if report.status == ReportStatus::Open && report.target_type == target_type && report.target_id == target_id {
+                report.status = status;
+                report.resolution = Some(resolution);
+                report.resolved_by = Some(address_key(resolved_by));
+                report.resolved_at = Some(Utc::now());
+                closed += 1;
+            }
+        }
+        Ok(closed)
+    }
+
+    async fn set_post_locked(&self, post_id: &str, locked_by: Option<&str>) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        if !state.posts.contains_key(post_id) {
+            return Ok(false);
+        }
+        Ok(match locked_by {
+            Some(_) => state.locked_posts.insert(post_id.to_string()),
+            None => state.locked_posts.remove(post_id),
+        })
+    }
+
+    async fn is_post_locked(&self, post_id: &str) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.locked_posts.contains(post_id))
+    }
+
+    async fn suspend_address(&self, suspension: &Suspension) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let address = address_key(&suspension.address);
+        state.suspensions.insert(address.clone(), Suspension {
+            address,
+            suspended_by: address_key(&suspension.suspended_by),
+            ..suspension.clone()
+        });
+        Ok(())
+    }
+
+    async fn lift_suspension(&self, address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.suspensions.remove(&address_key(address)).is_some())
+    }
+
+    async fn get_active_suspension(&self, address: &str) -> Result<Option<Suspension>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.suspensions.get(&address_key(address))
+            .filter(|s| s.expires_at.is_none_or(|expires_at| expires_at > Utc::now()))
+            .cloned())
+    }
+
+    async fn append_moderation_log(&self, entry: &ModerationLogEntry) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.moderation_log.push(ModerationLogEntry { moderator_address: address_key(&entry.moderator_address), ..entry.clone() });
+        Ok(())
+    }
+
+    async fn get_moderation_log(&self, page: &PageRequest) -> Result<Page<ModerationLogEntry>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut entries = state.moderation_log.clone();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.page_cursor()));
+        Ok(page_rows(entries, page, true, ModerationLogEntry::page_cursor))
+    }
+
+    async fn block_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let key = (address_key(blocker_address), address_key(blocked_address));
+        if state.blocks.contains_key(&key) {
+            return Ok(false);
+        }
+        state.follows.retain(|f| {
+            let (follower, following) = (address_key(&f.follower), address_key(&f.following));
+            !((follower == key.0 && following == key.1) || (follower == key.1 && following == key.0))
+        });
+        state.blocks.insert(key, Utc::now());
+        Ok(true)
+    }
+
+    async fn unblock_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.blocks.remove(&(address_key(blocker_address), address_key(blocked_address))).is_some())
+    }
+
+    async fn is_blocked(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.blocks.contains_key(&(address_key(blocker_address), address_key(blocked_address))))
+    }
+
+    async fn get_blocked_users(&self, blocker_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.restricted_users(&state.blocks, blocker_address))
+    }
+
+    async fn mute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let key = (address_key(muter_address), address_key(muted_address));
+        if state.mutes.contains_key(&key) {
+            return Ok(false);
+        }
+        state.mutes.insert(key, Utc::now());
+        Ok(true)
+    }
+
+    async fn unmute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        Ok(state.mutes.remove(&(address_key(muter_address), address_key(muted_address))).is_some())
+    }
+
+    async fn get_muted_users(&self, muter_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.restricted_users(&state.mutes, muter_address))
+    }
+
+    async fn record_fingerprint(&self, fingerprint: &ContentFingerprint) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        if !state.fingerprints.iter().any(|f| f.content_id == fingerprint.content_id) {
+            state.fingerprints.push(ContentFingerprint {
+                author_address: address_key(&fingerprint.author_address),
+                ..fingerprint.clone()
+            });
+        }
+        Ok(())
+    }
+
+    async fn get_recent_fingerprints(&self, since: DateTime<Utc>, limit: u32) -> Result<Vec<ContentFingerprint>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut fingerprints: Vec<ContentFingerprint> = state.fingerprints.iter()
+            .filter(|f| f.simhash.is_some() && f.created_at >= since)
+            .cloned()
+            .collect();
+        fingerprints.sort_by_key(|f| std::cmp::Reverse(f.created_at));
+        fingerprints.truncate(limit as usize);
+        Ok(fingerprints)
+    }
+
+    async fn count_recent_fingerprints(&self, author_address: &str, since: DateTime<Utc>) -> Result<u32, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let author = address_key(author_address);
+        Ok(state.fingerprints.iter().filter(|f| f.author_address == author && f.created_at >= since).count() as u32)
+    }
+
+    async fn hold_content(&self, held: &HeldContent) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.held_content.push(HeldContent {
+            author_address: address_key(&held.author_address),
+            status: HeldStatus::Held,
+            ..held.clone()
+        });
+        Ok(())
+    }
+
+    async fn get_held_content(&self, held_id: &str) -> Result<Option<HeldContent>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.held_content.iter().find(|h| h.id == held_id).cloned())
+    }
+
+    async fn get_held_queue(&self, page: &PageRequest) -> Result<Page<HeldContent>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let mut held: Vec<HeldContent> = state.held_content.iter()
+            .filter(|h| h.status == HeldStatus::Held)
+            .cloned()
+            .collect();
+        held.sort_by_key(HeldContent::page_cursor);
+        Ok(page_rows(held, page, false, HeldContent::page_cursor))
+    }
+
+    async fn resolve_held_content(&self, held_id: &str, status: HeldStatus, content_id: Option<&str>, resolved_by: &str) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let Some(held) = state.held_content.iter_mut().find(|h| h.id == held_id && h.status == HeldStatus::Held) else {
+            return Ok(false);
+        };
+        held.status = status;
+        held.content_id = content_id.map(str::to_string);
+        held.resolved_by = Some(address_key(resolved_by));
+        held.resolved_at = Some(Utc::now());
+        Ok(true)
+    }
+
+    async fn create_media(&self, media: &Media) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.media.entry(media.id.clone()).or_insert_with(|| Media {
+            owner_address: address_key(&media.owner_address),
+            ..media.clone()
+        });
+        Ok(())
+    }
+
+    async fn get_media(&self, media_id: &str) -> Result<Option<Media>, sqlx::Error> {
+        Ok(self.state.lock().unwrap().media.get(media_id).cloned())
+    }
+
+    async fn delete_media(&self, media_id: &str) -> Result<bool, sqlx::Error> {
+        Ok(self.state.lock().unwrap().media.remove(media_id).is_some())
+    }
+
+    async fn count_users_with_avatar(&self, avatar_url: &str) -> Result<u32, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.users.values().filter(|u| u.user.avatar.as_deref() == Some(avatar_url)).count() as u32)
+    }
+
+    async fn count_unread_notifications(&self, recipient_address: &str) -> Result<u32, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        let recipient = address_key(recipient_address);
+        let unread: HashSet<&str> = state.notifications.iter()
+            .filter(|n| n.notification.recipient_address == recipient && n.read_at.is_none())
+            .map(|n| n.notification.group_key.as_str())
+            .collect();
+        Ok(unread.len() as u32)
+    }
+
+    async fn get_chain_checkpoints(&self) -> Result<Vec<(u64, String)>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.chain_checkpoints.iter().rev().map(|(number, hash)| (*number, hash.clone())).collect())
+    }
+
+    async fn store_chain_events(&self, events: &[IndexedEvent], checkpoint_block: u64, checkpoint_hash: &str) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        for indexed in events {
+            let exists = state.chain_events.iter().any(|e| {
+                e.transaction_hash == indexed.transaction_hash && e.log_index == indexed.log_index
+            });
+            if !exists {
+                state.chain_events.push(indexed.clone());
+            }
+        }
+
+        state.chain_checkpoints.insert(checkpoint_block, checkpoint_hash.to_string());
+        while state.chain_checkpoints.len() > CHAIN_CHECKPOINT_HISTORY as usize {
+            state.chain_checkpoints.pop_first();
+        }
+        Ok(())
+    }
+
+    async fn rollback_chain_events(&self, after_block: Option<u64>) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        match after_block {
+            Some(block) => {
+                state.chain_events.retain(|e| e.block_number as u64 <= block);
+                state.chain_checkpoints.split_off(&(block + 1));
+            }
+            None => {
+                state.chain_events.clear();
+                state.chain_checkpoints.clear();
+            }
+        }
+        Ok(())
+    }
+
+    async fn enqueue_task(&self, task: &QueuedTask, max_queued: u64) -> Result<bool, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let queued = state.tasks.values()
+            .filter(|t| matches!(t.task.status, TaskStatus::Pending | TaskStatus::Processing))
+            .count() as u64;
+        if queued >= max_queued {
+            return Ok(false);
+        }
+        state.tasks.insert(task.id.clone(), StoredTask { task: task.clone(), locked_until: None });
+        Ok(true)
+    }
+
+    async fn claim_task(&self, lease: std::time::Duration) -> Result<Option<QueuedTask>, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let now = Utc::now();
+        let due = state.tasks.values_mut()
+            .filter(|t| match t.task.status {
+                TaskStatus::Pending => t.task.run_at <= now,
+                TaskStatus::Processing => t.locked_until.is_some_and(|until| until < now),
+                _ => false,
+            })
+            .min_by_key(|t| t.task.run_at);
+
+        Ok(due.map(|stored| {
+            stored.locked_until = Some(now + Duration::from_std(lease).unwrap_or_else(|_| Duration::hours(1)));
+            stored.task.status = TaskStatus::Processing;
+            stored.task.attempts += 1;
+            stored.task.updated_at = now;
+            stored.task.clone()
+        }))
+    }
+
+    async fn complete_task(&self, task_id: &str, result: &serde_json::Value) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let task = state.task_mut(task_id)?;
+        task.status = TaskStatus::Completed;
+        task.result = Some(result.clone());
+        task.last_error = None;
+        task.completed_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn reschedule_task(&self, task_id: &str, error: &str, run_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let task = state.task_mut(task_id)?;
+        task.status = TaskStatus::Pending;
+        task.last_error = Some(error.to_string());
+        task.run_at = run_at;
+        Ok(())
+    }
+
+    async fn dead_letter_task(&self, task_id: &str, error: &str) -> Result<(), sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let task = state.task_mut(task_id)?;
+        task.status = TaskStatus::Failed(error.to_string());
+        task.last_error = Some(error.to_string());
+        task.completed_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn get_task(&self, task_id: &str) -> Result<Option<QueuedTask>, sqlx::Error> {
+        let state = self.state.lock().unwrap();
+        Ok(state.tasks.get(task_id).map(|stored| stored.task.clone()))
+    }
+
+    async fn evict_completed_tasks(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        let mut state = self.state.lock().unwrap();
+        let count = state.tasks.len();
+        state.tasks.retain(|_, t| !(t.task.status == TaskStatus::Completed && t.task.completed_at.is_some_and(|at| at < before)));
+        Ok((count - state.tasks.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "0x1111111111111111111111111111111111111111";
+    const BOB: &str = "0x2222222222222222222222222222222222222222";
+
+    fn post(id: &str, author: &str) -> Post {
+        Post {
+            id: id.to_string(),
+            title: "Title".to_string(),
+            content: format!("Content of {}", id),
+            content_html: String::new(),
+            author_address: author.to_string(),
+            author_id: None,
+            author_name: None,
+            author_avatar: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            likes: 0,
+            comments_count: 0,
+            tags: vec![],
+            irys_transaction_id: None,
+            image: None,
+            blockchain_post_id: None,
+            is_liked_by_user: false,
+            is_bookmarked_by_user: false,
+            views: 0,
+            heat_score: None,
+            mentions: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_likes_toggle_and_update_stats() {
+        let store = MemoryStore::new();
+        store.create_post(&post("p1", ALICE)).await.unwrap();
+
+        assert_eq!(store.like_post("p1", BOB).await.unwrap(), 1);
+        assert!(store.has_user_liked_post("p1", &BOB.to_uppercase()).await.unwrap());
+        assert!(store.get_post_by_id_with_like_status("p1", Some(BOB), None).await.unwrap().unwrap().is_liked_by_user);
+        assert_eq!(store.like_post("p1", BOB).await.unwrap(), 0);
+        assert!(matches!(store.like_post("missing", BOB).await, Err(sqlx::Error::RowNotFound)));
+
+        let author = store.get_user_by_address(ALICE).await.unwrap().unwrap();
+        assert_eq!((author.posts_count, author.reputation), (1, 10));
+        assert_eq!(store.get_global_stats().await.unwrap().total_posts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_keyset_pages_survive_new_posts_and_timestamp_ties() {
+        let store = MemoryStore::new();
+        let at = Utc::now() - Duration::hours(1);
+        for i in 0..5 {
+            // Two posts share each timestamp, so only the id breaks the tie
+            let id = uuid::Uuid::new_v4().to_string();
+            store.create_post(&Post { created_at: at + Duration::minutes(i / 2), ..post(&id, ALICE) }).await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut page = PageRequest::offset(2, 0);
+        loop {
+            let result = store.get_posts_paginated(&page, &[]).await.unwrap();
+            seen.extend(result.items.iter().map(|p| p.id.clone()));
+            if seen.len() == 2 {
+                // A post arriving mid-scroll would shift every later offset page by one
+                store.create_post(&post(&uuid::Uuid::new_v4().to_string(), BOB)).await.unwrap();
+            }
+            match result.next_cursor {
+                Some(cursor) => page.after = Some(PageCursor::decode(&cursor).unwrap()),
+                None => {
+                    assert!(!result.has_more);
+                    break;
+                }
+            }
+        }
+
+        let expected: Vec<String> = store.state.lock().unwrap().posts_newest_first(Some(ALICE), None).into_iter().map(|p| p.id).collect();
+        assert_eq!(seen, expected);
+        assert!(PageCursor::decode("not a cursor").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_follows_and_mutual_lists() {
+        let store = MemoryStore::new();
+        store.ensure_user_exists(ALICE, &None).await.unwrap();
+        store.ensure_user_exists(BOB, &None).await.unwrap();
+
+        assert!(store.follow_user(ALICE, BOB).await.unwrap());
+        assert!(!store.follow_user(ALICE, BOB).await.unwrap());
+        assert!(store.get_mutual_follows_list(ALICE, &PageRequest::offset(10, 0)).await.unwrap().items.is_empty());
+
+        store.follow_user(BOB, ALICE).await.unwrap();
+        assert_eq!(store.get_follow_counts(ALICE).await.unwrap(), (1, 1, 1));
+        let mutual = store.get_mutual_follows_list(ALICE, &PageRequest::offset(10, 0)).await.unwrap().items;
+        assert_eq!(mutual.len(), 1);
+        assert!(mutual[0].is_mutual && !mutual[0].is_self);
+
+        assert!(store.unfollow_user(ALICE, BOB).await.unwrap());
+        assert_eq!(store.get_follow_counts(ALICE).await.unwrap(), (0, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_usernames_are_unique_and_set_once() {
+        let store = MemoryStore::new();
+        assert!(store.register_username(ALICE, "alice").await.unwrap());
+        assert!(!store.register_username(BOB, "alice").await.unwrap());
+        assert!(!store.register_username(ALICE, "alice2").await.unwrap());
+        assert!(!store.is_username_available("alice").await.unwrap());
+        assert_eq!(store.get_username_by_address(ALICE).await.unwrap().as_deref(), Some("alice"));
+        assert_eq!(store.get_username_by_address(BOB).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::blockchain::BlockchainService;
+use crate::models::{Comment, Mention, NewNotification, Post};
+use crate::notifications;
+use crate::store::ForumStore;
+use crate::utils::extract_mentions_from_content;
+use log::info;
+use std::collections::{HashMap, HashSet};
+
+/// Address that registered `username`: storage first, then the contract's `getAddressByUsername`.
+/// Names only found on-chain are synced to storage so the next lookup stays local.
+async fn resolve_username(store: &dyn ForumStore, blockchain: Option<&BlockchainService>, username: &str) -> Option<String> {
+    match store.get_address_by_username(username).await {
+        Ok(Some(address)) => return Some(address),
+        Ok(None) => {}
+        Err(e) => info!("⚠️ Failed to look up username {} in storage: {}", username, e),
+    }
+
+    let address = match blockchain?.get_address_by_username_on_chain(username).await {
+        Ok(address) => address?,
+        Err(e) => {
+            info!("⚠️ Failed to look up username {} on chain: {}", username, e);
+            return None;
+        }
+    };
+    info!("📊 Resolved mention @{} on chain and synced to storage: {}", username, address);
+    if store.ensure_user_exists(&address, &None).await.is_ok() {
+        let _ = store.register_username(&address, username).await;
+    }
+    Some(address)
+}
+
+/// Parse `@username` mentions in `content`, store the resolved spans for the post (`comment_id`
+/// None) or comment in place of earlier ones, and notify users who were not mentioned before.
+/// Unknown usernames and users who blocked the author are left as plain text. Failures are
+/// logged, never returned.
+async fn record_mentions(
+    store: &dyn ForumStore,
+    blockchain: Option<&BlockchainService>,
+    author_address: &str,
+    post_id: &str,
+    comment_id: Option<&str>,
+    content: &str,
+) -> Vec<Mention> {
+    let previous = match comment_id {
+        Some(comment_id) => store.get_comment_mentions(&[comment_id.to_string()]).await,
+        None => store.get_post_mentions(&[post_id.to_string()]).await,
+    };
+    let previous: HashSet<String> = previous
+        .unwrap_or_default()
+        .into_values()
+        .flatten()
+        .map(|m| m.address.to_lowercase())
+        .collect();
+
+    let tokens = extract_mentions_from_content(content);
+    if tokens.is_empty() && previous.is_empty() {
+        return Vec::new();
+    }
+
+    let mut resolved: HashMap<String, Option<String>> = HashMap::new();
+    let mut mentions = Vec::new();
+    for token in tokens {
+        if !resolved.contains_key(&token.username) {
+            let mut address = resolve_username(store, blockchain, &token.username).await;
+            if let Some(mentioned) = &address {
+                if store.is_blocked(mentioned, author_address).await.unwrap_or(false) {
+                    info!("🚫 Skipping mention of @{}: {} is blocked", token.username, author_address);
+                    address = None;
+                }
+            }
+            resolved.insert(token.username.clone(), address);
+        }
+        if let Some(Some(address)) = resolved.get(&token.username) {
+            mentions.push(Mention {
+                address: address.clone(),
+                username: token.username,
+                start: token.start as u32,
+                end: token.end as u32,
+            });
+        }
+    }
+
+    if let Err(e) = store.replace_mentions(post_id, comment_id, &mentions).await {
+        info!("⚠️ Failed to store mentions for {}: {}", comment_id.unwrap_or(post_id), e);
+        return mentions;
+    }
+
+    let mut notified = previous;
+    for mention in &mentions {
+        if notified.insert(mention.address.to_lowercase()) {
+            notifications::notify(store, NewNotification::mentioned(&mention.address, author_address, post_id, comment_id)).await;
+        }
+    }
+    mentions
+}
+
+/// Record the mentions in a new or edited post and set `post.mentions`
+pub async fn record_post_mentions(store: &dyn ForumStore, blockchain: Option<&BlockchainService>, post: &mut Post) {
+    post.mentions = record_mentions(store, blockchain, &post.author_address, &post.id, None, &post.content).await;
+}
+
+/// Record the mentions in a new comment and set `comment.mentions`
+pub async fn record_comment_mentions(store: &dyn ForumStore, blockchain: Option<&BlockchainService>, comment: &mut Comment) {
+    comment.mentions = record_mentions(store, blockchain, &comment.author_address, &comment.post_id, Some(&comment.id), &comment.content).await;
+}
+
+/// Fill `mentions` on posts read from storage
+pub async fn attach_to_posts(store: &dyn ForumStore, posts: &mut [Post]) {
+    if posts.is_empty() {
+        return;
+    }
+    let ids: Vec<String> = posts.iter().map(|p| p.id.clone()).collect();
+    match store.get_post_mentions(&ids).await {
+        Ok(mut mentions) => {
+            for post in posts {
+                post.mentions = mentions.remove(&post.id).unwrap_or_default();
+            }
+        }
+        Err(e) => info!("⚠️ Failed to load post mentions: {}", e),
+    }
+}
+
+/// Fill `mentions` on comments read from storage; deleted placeholders get none
+pub async fn attach_to_comments<'a>(store: &dyn ForumStore, comments: impl IntoIterator<Item = &'a mut Comment>) {
+    let mut comments: Vec<&mut Comment> = comments.into_iter().filter(|c| !c.is_deleted).collect();
+    if comments.is_empty() {
+        return;
+    }
+    let ids: Vec<String> = comments.iter().map(|c| c.id.clone()).collect();
+    match store.get_comment_mentions(&ids).await {
+        Ok(mut mentions) => {
+            for comment in comments.iter_mut() {
+                comment.mentions = mentions.remove(&comment.id).unwrap_or_default();
+            }
+        }
+        Err(e) => info!("⚠️ Failed to load comment mentions: {}", e),
+    }
+}
@@ -0,0 +1,245 @@
+use chrono::{DateTime, Utc};
+use log::info;
+use sha2::{Digest, Sha256};
+use sqlx::migrate::MigrateError;
+use sqlx::{Connection, Executor, PgConnection, PgPool, Row};
+
+/// An embedded SQL migration. Versions are applied in ascending order and
+/// must never be edited once released; add a new migration instead.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("../migrations/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "chain_indexer",
+        sql: include_str!("../migrations/0002_chain_indexer.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "async_tasks",
+        sql: include_str!("../migrations/0003_async_tasks.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "post_revisions",
+        sql: include_str!("../migrations/0004_post_revisions.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "soft_delete",
+        sql: include_str!("../migrations/0005_soft_delete.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "search",
+        sql: include_str!("../migrations/0006_search.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "keyset_pagination",
+        sql: include_str!("../migrations/0007_keyset_pagination.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "notifications",
+        sql: include_str!("../migrations/0008_notifications.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "mentions",
+        sql: include_str!("../migrations/0009_mentions.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "tags",
+        sql: include_str!("../migrations/0010_tags.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "bookmarks",
+        sql: include_str!("../migrations/0011_bookmarks.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "moderation",
+        sql: include_str!("../migrations/0012_moderation.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "blocks_mutes",
+        sql: include_str!("../migrations/0013_blocks_mutes.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "rendered_content",
+        sql: include_str!("../migrations/0014_rendered_content.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "spam",
+        sql: include_str!("../migrations/0015_spam.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "media",
+        sql: include_str!("../migrations/0016_media.sql"),
+    },
+];
+
+/// Postgres advisory lock key, so concurrently starting instances migrate one at a time
+const MIGRATION_LOCK_KEY: i64 = 0x4952_5953_464f_5255;
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)] // `name` and `applied_at` are only displayed by the admin binary
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+async fn ensure_migrations_table(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum VARCHAR(64) NOT NULL,
+            applied_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn fetch_applied(conn: &mut PgConnection) -> Result<Vec<AppliedMigration>, sqlx::Error> {
+    let rows = sqlx::query("SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            name: row.get("name"),
+            checksum: row.get("checksum"),
+            applied_at: row.get("applied_at"),
+        })
+        .collect())
+}
+
+/// Fail if the database has migrations this build does not know about (schema
+/// newer than the code) or if an applied migration was modified afterwards
+fn check_applied(applied: &[AppliedMigration]) -> Result<(), MigrateError> {
+    for applied in applied {
+        match MIGRATIONS.iter().find(|m| m.version == applied.version) {
+            None => return Err(MigrateError::VersionMissing(applied.version)),
+            Some(migration) if migration.checksum() != applied.checksum => {
+                return Err(MigrateError::VersionMismatch(applied.version))
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// List migrations recorded in `schema_migrations`
+#[allow(dead_code)] // used by the admin binary
+pub async fn applied_migrations(pool: &PgPool) -> Result<Vec<AppliedMigration>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    ensure_migrations_table(&mut conn).await?;
+    fetch_applied(&mut conn).await
+}
+
+/// Apply all pending migrations, each in its own transaction. Returns the versions applied.
+pub async fn run(pool: &PgPool) -> Result<Vec<i64>, MigrateError> {
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+    let result = apply_pending(&mut conn).await;
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    result
+}
+
+async fn apply_pending(conn: &mut PgConnection) -> Result<Vec<i64>, MigrateError> {
+    ensure_migrations_table(conn).await?;
+    let applied = fetch_applied(conn).await?;
+    check_applied(&applied)?;
+
+    let mut newly_applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| !applied.iter().any(|a| a.version == m.version)) {
+        info!("🗄️ Applying migration {:04}_{}", migration.version, migration.name);
+
+        let mut tx = conn.begin().await?;
+        tx.execute(migration.sql).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        newly_applied.push(migration.version);
+    }
+
+    Ok(newly_applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered_and_unique() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert_eq!(latest_version(), MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn test_check_applied_rejects_unknown_and_modified() {
+        let applied = |version: i64, checksum: String| AppliedMigration {
+            version,
+            name: "x".to_string(),
+            checksum,
+            applied_at: Utc::now(),
+        };
+
+        assert!(check_applied(&[applied(1, MIGRATIONS[0].checksum())]).is_ok());
+        assert!(matches!(
+            check_applied(&[applied(latest_version() + 1, String::new())]),
+            Err(MigrateError::VersionMissing(_))
+        ));
+        assert!(matches!(
+            check_applied(&[applied(1, "0".repeat(64))]),
+            Err(MigrateError::VersionMismatch(1))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -5,7 +7,11 @@ use serde::{Deserialize, Serialize};
 pub struct Post {
     pub id: String,
     pub title: String,
+    /// Markdown source as written by the author
     pub content: String,
+    /// `content` rendered by `markdown::render`; safe to insert into a page as is
+    #[serde(default)]
+    pub content_html: String,
     pub author_address: String,
     pub author_id: Option<String>,
     pub author_name: Option<String>,
@@ -21,15 +27,29 @@ pub struct Post {
     #[serde(default)]
     pub is_liked_by_user: bool,
     #[serde(default)]
+    pub is_bookmarked_by_user: bool,
+    #[serde(default)]
     pub views: u32,
     pub heat_score: Option<f64>,
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
+}
+
+impl Post {
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, &self.id)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Comment {
     pub id: String,
     pub post_id: String,
+    /// Markdown source as written by the author
     pub content: String,
+    /// `content` rendered by `markdown::render`; safe to insert into a page as is
+    #[serde(default)]
+    pub content_html: String,
     pub author_address: String,
     pub author_id: Option<String>,
     pub author_name: Option<String>,
@@ -42,6 +62,48 @@ pub struct Comment {
     pub content_hash: String,
     #[serde(default)]
     pub is_liked_by_user: bool,
+    /// Deleted comment kept as a "[deleted]" placeholder because it still has replies
+    #[serde(default)]
+    pub is_deleted: bool,
+    #[serde(default)]
+    pub mentions: Vec<Mention>,
+}
+
+/// A resolved `@username` in a post or comment. `start`/`end` are char offsets of the
+/// whole `@username` in the content, so clients can turn the span into a profile link.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mention {
+    pub username: String,
+    pub address: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Comment {
+    pub const DELETED_PLACEHOLDER: &'static str = "[deleted]";
+
+    /// Strip content and authorship from a deleted comment, keeping its place in the thread
+    pub fn into_tombstone(self) -> Self {
+        Comment {
+            content: Self::DELETED_PLACEHOLDER.to_string(),
+            content_html: crate::markdown::render(Self::DELETED_PLACEHOLDER),
+            author_address: String::new(),
+            author_id: None,
+            author_name: None,
+            author_avatar: None,
+            image: None,
+            irys_transaction_id: None,
+            content_hash: String::new(),
+            is_liked_by_user: false,
+            is_deleted: true,
+            mentions: Vec::new(),
+            ..self
+        }
+    }
+
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, &self.id)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -55,6 +117,8 @@ pub struct User {
     pub posts_count: u32,
     pub comments_count: u32,
     pub reputation: u32,
+    #[serde(default)]
+    pub role: UserRole,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,8 +131,235 @@ pub struct CreatePostRequest {
     pub image: Option<String>,
     pub blockchain_transaction_hash: Option<String>,
     pub blockchain_transaction_proof: Option<String>,
-    #[serde(default, deserialize_with = "de_opt_u32")]
-    pub blockchain_post_id: Option<u32>,
+}
+
+/// Replaces the editable fields of a post (author only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePostRequest {
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub image: Option<String>,
+}
+
+/// A superseded version of a post. Revision 1 is the version created with the on-chain `createPost`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostRevision {
+    pub post_id: String,
+    pub revision: u32,
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub image: Option<String>,
+    pub content_hash: Option<String>,
+    pub irys_transaction_id: Option<String>,
+    /// When this version was published
+    pub created_at: DateTime<Utc>,
+    /// When the next edit replaced it
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// Keyset position of a row in (created_at, id) order. Timestamps are kept at the microsecond
+/// precision of Postgres; clients see it as an opaque base64url token.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PageCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl PageCursor {
+    pub fn new(created_at: DateTime<Utc>, id: &str) -> Self {
+        Self {
+            created_at: DateTime::from_timestamp_micros(created_at.timestamp_micros()).unwrap_or(created_at),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}_{}", self.created_at.timestamp_micros(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let (micros, id) = raw.split_once('_')?;
+        let created_at = DateTime::from_timestamp_micros(micros.parse().ok()?)?;
+        uuid::Uuid::parse_str(id).ok()?;
+        Some(Self { created_at, id: id.to_string() })
+    }
+}
+
+/// Paging parameters for list queries: rows strictly after `after` (when set), skipping `offset`
+#[derive(Debug, Clone, Default)]
+pub struct PageRequest {
+    pub limit: u32,
+    pub offset: u32,
+    pub after: Option<PageCursor>,
+}
+
+impl PageRequest {
+    pub fn offset(limit: u32, offset: u32) -> Self {
+        Self { limit, offset, after: None }
+    }
+
+    /// Rows to fetch: one more than `limit` tells whether another page follows
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit as i64 + 1
+    }
+}
+
+/// One page of a list endpoint. `next_cursor` is set when `has_more` is; pass it back as `cursor`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// Build a page from up to `limit + 1` rows in page order, `cursor` giving each row's position
+    pub fn from_rows(mut rows: Vec<T>, limit: u32, cursor: impl Fn(&T) -> PageCursor) -> Self {
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        let next_cursor = if has_more { rows.last().map(|last| cursor(last).encode()) } else { None };
+        Self { items: rows, next_cursor, has_more }
+    }
+
+    pub fn empty() -> Self {
+        Self { items: Vec::new(), next_cursor: None, has_more: false }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(Vec<T>) -> Vec<U>) -> Page<U> {
+        Page {
+            items: f(self.items),
+            next_cursor: self.next_cursor,
+            has_more: self.has_more,
+        }
+    }
+}
+
+/// A comment in a thread window returned by `ForumStore::get_comment_tree`
+#[derive(Debug, Clone)]
+pub struct CommentTreeRow {
+    pub comment: Comment,
+    /// Visible direct replies, including ones outside the window
+    pub reply_count: u32,
+}
+
+/// A comment with the replies loaded so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentNode {
+    #[serde(flatten)]
+    pub comment: Comment,
+    /// 1 for the comments directly under the requested parent
+    pub depth: u32,
+    pub reply_count: u32,
+    pub replies: Vec<CommentNode>,
+    /// Some replies were left out by `limit` or `max_depth`; fetch them with
+    /// `parent_id` = this comment and `cursor` = `replies_cursor`
+    pub has_more_replies: bool,
+    /// Position after the last included reply (`None`: start from the first reply)
+    pub replies_cursor: Option<String>,
+}
+
+/// Response of `GET /api/posts/{id}/comments/tree`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentTree {
+    pub comments: Vec<CommentNode>,
+    /// Pass as `cursor` (with the same `parent_id`) to load the next page of top-level comments
+    pub next_cursor: Option<String>,
+}
+
+/// Parsed `GET /api/search` parameters. Tag, author and date filters apply to posts and comments
+/// (a comment's tag is its post's); users are matched by username only.
+#[derive(Debug, Clone)]
+pub struct SearchRequest {
+    pub q: String,
+    pub include_posts: bool,
+    pub include_comments: bool,
+    pub include_users: bool,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    /// Inclusive lower bound on `created_at`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`
+    pub to: Option<DateTime<Utc>>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// `snippet` fields are HTML-escaped, with matches wrapped in `<mark>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostSearchHit {
+    pub post_id: String,
+    pub title: String,
+    pub snippet: String,
+    pub author_address: String,
+    pub author_name: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentSearchHit {
+    pub comment_id: String,
+    pub post_id: String,
+    pub post_title: String,
+    pub snippet: String,
+    pub author_address: String,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserSearchHit {
+    pub address: String,
+    pub username: Option<String>,
+    pub avatar: Option<String>,
+    pub rank: f32,
+}
+
+/// Each list is ranked best match first and paginated independently
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub posts: Vec<PostSearchHit>,
+    pub comments: Vec<CommentSearchHit>,
+    pub users: Vec<UserSearchHit>,
+}
+
+/// Stored in `users.role`; moderators and admins may delete other users' content and work
+/// the report queue, and admins may change roles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    #[default]
+    User,
+    Moderator,
+    Admin,
+}
+
+impl UserRole {
+    pub fn from_db(role: &str) -> Self {
+        match role {
+            "moderator" => UserRole::Moderator,
+            "admin" => UserRole::Admin,
+            _ => UserRole::User,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserRole::User => "user",
+            UserRole::Moderator => "moderator",
+            UserRole::Admin => "admin",
+        }
+    }
+
+    pub fn can_moderate(self) -> bool {
+        matches!(self, UserRole::Moderator | UserRole::Admin)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,15 +395,9 @@ pub struct GlobalStats {
     pub total_likes: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LikeRequest {
-    pub user_address: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterUsernameRequest {
     pub username: String,
-    pub user_address: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -125,6 +410,12 @@ pub struct SyncUsernameRequest {
     pub user_address: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SiweVerifyRequest {
+    pub message: String,
+    pub signature: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UsernameCheckResponse {
     pub available: bool,
@@ -159,32 +450,9 @@ impl<T> ApiResponse<T> {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct UserStats {
-    pub ethereum_address: String,
-    pub username: Option<String>,
-    pub posts_count: u32,
-    pub comments_count: u32,
-    pub reputation: u32,
-    pub following_count: u32,
-    pub followers_count: u32,
-    pub mutual_follows_count: u32,
-    pub created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Follow {
-    pub id: String,
-    pub follower_address: String,
-    pub following_address: String,
-    pub created_at: DateTime<Utc>,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FollowRequest {
-    pub follower_id: Option<String>,
     pub following_id: Option<String>,
-    pub follower_address: Option<String>,
     pub following_address: Option<String>,
 }
 
@@ -217,36 +485,656 @@ pub struct UserProfile {
 }
 
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    Match,
+    Mismatch,
+    Unavailable,
+    Error,
+}
+
+/// Result of comparing stored content with one source of truth
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntegrityCheck {
+    pub source: String,
+    pub status: IntegrityStatus,
+    pub content_hash: Option<String>,
+    pub detail: Option<String>,
+}
+
+/// Integrity report for a post or comment: the SHA-256 of the content served
+/// by this server, and whether each independent source agrees with it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContentVerificationReport {
+    pub content_type: String,
+    pub id: String,
+    pub content_hash: String,
+    pub verified: bool,
+    pub checks: Vec<IntegrityCheck>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RecommendationResult {
     pub posts: Vec<Post>,
     pub last_refresh_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-// Custom deserializer: accept number or numeric string (or null) for Option<u32>
-fn de_opt_u32<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
-where
-    D: serde::de::Deserializer<'de>,
-{
-    let opt = Option::<serde_json::Value>::deserialize(deserializer)?;
-    match opt {
-        None | Some(serde_json::Value::Null) => Ok(None),
-        Some(serde_json::Value::Number(n)) => {
-            n.as_u64()
-                .and_then(|v| if v <= u32::MAX as u64 { Some(v as u32) } else { None })
-                .map(Some)
-                .ok_or_else(|| serde::de::Error::custom("invalid u32 number"))
-        }
-        Some(serde_json::Value::String(s)) => {
-            let s = s.trim();
-            if s.is_empty() {
-                Ok(None)
-            } else {
-                s.parse::<u32>()
-                    .map(Some)
-                    .map_err(|_| serde::de::Error::custom("invalid u32 string"))
-            }
-        }
-        Some(_) => Err(serde::de::Error::custom("expected number or string or null")),
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedSource {
+    /// Written by a followed user
+    Following,
+    /// Mixed in from the daily recommendations
+    Recommended,
+}
+
+/// A post in the following feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItem {
+    #[serde(flatten)]
+    pub post: Post,
+    pub source: FeedSource,
+}
+
+
+/// Kinds of notification. Stored by name in `notifications.kind`, so a new kind needs a variant
+/// here (with its name and verb) and a `NewNotification` constructor, but no migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    PostLike,
+    CommentLike,
+    /// A top-level comment on the recipient's post
+    Comment,
+    /// A reply to the recipient's comment
+    Reply,
+    Follow,
+    Mention,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PostLike => "post_like",
+            Self::CommentLike => "comment_like",
+            Self::Comment => "comment",
+            Self::Reply => "reply",
+            Self::Follow => "follow",
+            Self::Mention => "mention",
+        }
+    }
+
+    pub fn from_db(kind: &str) -> Option<Self> {
+        [Self::PostLike, Self::CommentLike, Self::Comment, Self::Reply, Self::Follow, Self::Mention]
+            .into_iter()
+            .find(|k| k.as_str() == kind)
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            Self::PostLike => "liked your post",
+            Self::CommentLike => "liked your comment",
+            Self::Comment => "commented on your post",
+            Self::Reply => "replied to your comment",
+            Self::Follow => "followed you",
+            Self::Mention => "mentioned you",
+        }
+    }
+}
+
+/// A notification to record. The ones a recipient has with the same `group_key` are shown
+/// together ("Alice and 4 others liked your post"); an actor appears once per group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewNotification {
+    pub recipient_address: String,
+    pub actor_address: String,
+    pub kind: NotificationKind,
+    pub post_id: Option<String>,
+    pub comment_id: Option<String>,
+    pub group_key: String,
+}
+
+impl NewNotification {
+    fn new(kind: NotificationKind, recipient: &str, actor: &str, post_id: Option<&str>, comment_id: Option<&str>, group_key: String) -> Self {
+        Self {
+            recipient_address: recipient.to_lowercase(),
+            actor_address: actor.to_lowercase(),
+            kind,
+            post_id: post_id.map(str::to_string),
+            comment_id: comment_id.map(str::to_string),
+            group_key,
+        }
+    }
+
+    pub fn post_liked(recipient: &str, actor: &str, post_id: &str) -> Self {
+        Self::new(NotificationKind::PostLike, recipient, actor, Some(post_id), None, format!("post_like:{}", post_id))
+    }
+
+    pub fn comment_liked(recipient: &str, actor: &str, comment: &Comment) -> Self {
+        Self::new(NotificationKind::CommentLike, recipient, actor, Some(&comment.post_id), Some(&comment.id), format!("comment_like:{}", comment.id))
+    }
+
+    /// `comment` was posted on the recipient's post (grouped per post) or, with a parent,
+    /// in reply to the recipient's comment (grouped per parent)
+    pub fn commented(recipient: &str, comment: &Comment) -> Self {
+        let (kind, group_key) = match &comment.parent_id {
+            Some(parent_id) => (NotificationKind::Reply, format!("reply:{}", parent_id)),
+            None => (NotificationKind::Comment, format!("comment:{}", comment.post_id)),
+        };
+        Self::new(kind, recipient, &comment.author_address, Some(&comment.post_id), Some(&comment.id), group_key)
+    }
+
+    pub fn followed(recipient: &str, actor: &str) -> Self {
+        Self::new(NotificationKind::Follow, recipient, actor, None, None, "follow".to_string())
+    }
+
+    /// Mentions are grouped per post, whether they were in the post itself or in its comments
+    pub fn mentioned(recipient: &str, actor: &str, post_id: &str, comment_id: Option<&str>) -> Self {
+        Self::new(NotificationKind::Mention, recipient, actor, Some(post_id), comment_id, format!("mention:{}", post_id))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationActor {
+    pub address: String,
+    pub name: Option<String>,
+}
+
+/// A recipient's notifications sharing one group key, positioned by the newest of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationGroup {
+    /// Newest notification in the group
+    pub id: String,
+    pub group_key: String,
+    pub kind: NotificationKind,
+    pub post_id: Option<String>,
+    pub comment_id: Option<String>,
+    pub post_title: Option<String>,
+    /// The most recent actors, newest first
+    pub actors: Vec<NotificationActor>,
+    pub actor_count: u32,
+    /// Some notification in the group is unread
+    pub unread: bool,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl NotificationGroup {
+    /// Actors listed by `ForumStore::get_notification_groups`
+    pub const MAX_ACTORS: usize = 3;
+
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, &self.id)
+    }
+
+    /// "Alice liked your post", "Alice and Bob liked your post", "Alice and 4 others liked your post"
+    pub fn describe(&self) -> String {
+        let name = |actor: &NotificationActor| actor.name.clone().unwrap_or_else(|| actor.address.clone());
+        let subject = match (self.actors.first(), self.actor_count) {
+            (None, _) => "Someone".to_string(),
+            (Some(first), 0 | 1) => name(first),
+            (Some(first), 2) => match self.actors.get(1) {
+                Some(second) => format!("{} and {}", name(first), name(second)),
+                None => format!("{} and 1 other", name(first)),
+            },
+            (Some(first), count) => format!("{} and {} others", name(first), count - 1),
+        };
+        format!("{} {}", subject, self.kind.verb())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkNotificationsReadRequest {
+    #[serde(default)]
+    pub group_keys: Vec<String>,
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// A tag and the number of live posts carrying it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSummary {
+    pub name: String,
+    pub post_count: u32,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Response of `GET /api/tags/{tag}/posts`: the tag and one page of its posts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagPosts {
+    pub tag: TagSummary,
+    #[serde(flatten)]
+    pub posts: Page<Post>,
+}
+
+/// A tag ranked by how many posts used it within a trending window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingTag {
+    pub name: String,
+    /// Posts with this tag created within the window
+    pub recent_posts: u32,
+    /// Likes on those posts, used to break ties
+    pub recent_likes: u32,
+}
+
+/// Time windows offered by `GET /api/tags/trending`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendingWindow {
+    Day,
+    Week,
+    Month,
+}
+
+impl TrendingWindow {
+    /// `24h`, `7d` or `30d`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "24h" | "1d" => Some(Self::Day),
+            "7d" => Some(Self::Week),
+            "30d" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "24h",
+            Self::Week => "7d",
+            Self::Month => "30d",
+        }
+    }
+
+    pub fn duration(self) -> chrono::Duration {
+        match self {
+            Self::Day => chrono::Duration::hours(24),
+            Self::Week => chrono::Duration::days(7),
+            Self::Month => chrono::Duration::days(30),
+        }
+    }
+}
+
+/// A post in the caller's bookmarks; pages are ordered by `bookmarked_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkedPost {
+    #[serde(flatten)]
+    pub post: Post,
+    pub bookmarked_at: DateTime<Utc>,
+}
+
+impl BookmarkedPost {
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.bookmarked_at, &self.post.id)
+    }
+}
+
+/// What a report or moderator action is about. `target_id` is the post or comment id, or the
+/// lowercase address for users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationTarget {
+    Post,
+    Comment,
+    User,
+}
+
+impl ModerationTarget {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Post => "post",
+            Self::Comment => "comment",
+            Self::User => "user",
+        }
+    }
+
+    pub fn from_db(target: &str) -> Option<Self> {
+        [Self::Post, Self::Comment, Self::User].into_iter().find(|t| t.as_str() == target)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Spam,
+    Harassment,
+    Illegal,
+    Nsfw,
+    Other,
+}
+
+impl ReportReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Spam => "spam",
+            Self::Harassment => "harassment",
+            Self::Illegal => "illegal",
+            Self::Nsfw => "nsfw",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn from_db(reason: &str) -> Self {
+        [Self::Spam, Self::Harassment, Self::Illegal, Self::Nsfw]
+            .into_iter()
+            .find(|r| r.as_str() == reason)
+            .unwrap_or(Self::Other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Open,
+    Resolved,
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Resolved => "resolved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+
+    pub fn from_db(status: &str) -> Self {
+        match status {
+            "resolved" => Self::Resolved,
+            "dismissed" => Self::Dismissed,
+            _ => Self::Open,
+        }
+    }
+}
+
+/// Moderator actions, as recorded in the audit log. Resolving a report takes one of
+/// `Dismiss`, `Hide`, `Lock` or `Suspend`; the others are separate endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    Dismiss,
+    Hide,
+    Lock,
+    Unlock,
+    Suspend,
+    Unsuspend,
+    SetRole,
+    /// Publish a submission held by the spam filter
+    Approve,
+    /// Discard a submission held by the spam filter
+    Reject,
+}
+
+impl ModerationAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dismiss => "dismiss",
+            Self::Hide => "hide",
+            Self::Lock => "lock",
+            Self::Unlock => "unlock",
+            Self::Suspend => "suspend",
+            Self::Unsuspend => "unsuspend",
+            Self::SetRole => "set_role",
+            Self::Approve => "approve",
+            Self::Reject => "reject",
+        }
+    }
+
+    pub fn from_db(action: &str) -> Option<Self> {
+        [
+            Self::Dismiss, Self::Hide, Self::Lock, Self::Unlock, Self::Suspend, Self::Unsuspend, Self::SetRole,
+            Self::Approve, Self::Reject,
+        ]
+            .into_iter()
+            .find(|a| a.as_str() == action)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub id: String,
+    pub reporter_address: String,
+    pub target_type: ModerationTarget,
+    pub target_id: String,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+    pub status: ReportStatus,
+    pub resolution: Option<ModerationAction>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Report {
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, &self.id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReportRequest {
+    pub reason: ReportReason,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveReportRequest {
+    pub action: ModerationAction,
+    /// Shown in the audit log and, for suspensions, stored with the suspension
+    pub reason: Option<String>,
+    /// Suspension length; without it the suspension lasts until lifted
+    pub duration_hours: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRoleRequest {
+    pub role: UserRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suspension {
+    pub address: String,
+    pub reason: Option<String>,
+    pub suspended_by: String,
+    pub created_at: DateTime<Utc>,
+    /// `None` until lifted
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// One row of the append-only moderation audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationLogEntry {
+    pub id: String,
+    pub moderator_address: String,
+    pub action: ModerationAction,
+    pub target_type: ModerationTarget,
+    pub target_id: String,
+    pub report_id: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ModerationLogEntry {
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, &self.id)
+    }
+}
+
+/// An entry in the caller's block or mute list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestrictedUser {
+    pub address: String,
+    pub username: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One heuristic that added to a spam score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpamSignal {
+    pub name: String,
+    pub points: u32,
+    pub detail: String,
+}
+
+/// Simhash of a live post or comment; `simhash` is `None` for text too short to compare
+#[derive(Debug, Clone)]
+pub struct ContentFingerprint {
+    pub content_id: String,
+    pub content_type: ModerationTarget,
+    pub author_address: String,
+    pub simhash: Option<u64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A post, comment or post edit as submitted, with its verified transaction if it had one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HeldSubmission {
+    Post {
+        request: CreatePostRequest,
+        verification: Option<crate::blockchain::PostTransactionVerification>,
+    },
+    Comment {
+        request: CreateCommentRequest,
+        verification: Option<crate::blockchain::CommentTransactionVerification>,
+    },
+    PostEdit {
+        post_id: String,
+        author_address: String,
+        request: UpdatePostRequest,
+    },
+}
+
+impl HeldSubmission {
+    pub fn content_type(&self) -> ModerationTarget {
+        match self {
+            Self::Post { .. } | Self::PostEdit { .. } => ModerationTarget::Post,
+            Self::Comment { .. } => ModerationTarget::Comment,
+        }
+    }
+
+    pub fn author_address(&self) -> &str {
+        match self {
+            Self::Post { request, .. } => &request.author_address,
+            Self::Comment { request, .. } => &request.author_address,
+            Self::PostEdit { author_address, .. } => author_address,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeldStatus {
+    Held,
+    Approved,
+    Rejected,
+}
+
+impl HeldStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Held => "held",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    pub fn from_db(status: &str) -> Self {
+        match status {
+            "approved" => Self::Approved,
+            "rejected" => Self::Rejected,
+            _ => Self::Held,
+        }
+    }
+}
+
+/// A submission the spam filter held for moderator review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldContent {
+    pub id: String,
+    pub author_address: String,
+    pub submission: HeldSubmission,
+    pub score: u32,
+    pub signals: Vec<SpamSignal>,
+    pub status: HeldStatus,
+    /// The post or comment created on approval
+    pub content_id: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl HeldContent {
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor::new(self.created_at, &self.id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveHeldRequest {
+    /// `approve` or `reject`
+    pub action: ModerationAction,
+    /// Shown in the audit log
+    pub reason: Option<String>,
+}
+
+/// What an uploaded image is for; decides which sizes `media::ingest` generates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Avatar,
+    Post,
+}
+
+impl MediaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Avatar => "avatar",
+            Self::Post => "post",
+        }
+    }
+
+    pub fn from_db(kind: &str) -> Self {
+        match kind {
+            "avatar" => Self::Avatar,
+            _ => Self::Post,
+        }
+    }
+}
+
+/// One re-encoded file of an uploaded image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaVariant {
+    /// `small`/`medium` for avatars, `thumb`/`large`/`full` for post images
+    pub name: String,
+    /// `webp`, `jpeg` or `png`
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: u64,
+    pub url: String,
+}
+
+/// An uploaded image. `id` is the SHA-256 of its decoded pixels, so uploading the same picture
+/// again returns the same media and files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Media {
+    pub id: String,
+    pub kind: MediaKind,
+    /// First uploader
+    pub owner_address: String,
+    /// Format the upload was sniffed as: `jpeg`, `png`, `gif` or `webp`
+    pub source_format: String,
+    /// Decoded size, after EXIF orientation
+    pub width: u32,
+    pub height: u32,
+    pub variants: Vec<MediaVariant>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Media {
+    /// URL stored with the post, comment or profile that uses this image
+    pub fn display_url(&self) -> String {
+        crate::media::variant_url(&self.id, crate::media::display_variant(self.kind), "webp")
     }
 }
@@ -0,0 +1,34 @@
+use crate::models::{Comment, NewNotification};
+use crate::store::ForumStore;
+use log::info;
+
+/// Record a notification. Self-notifications are skipped, and failures are only logged so
+/// the action that triggered the notification never fails because of it.
+pub async fn notify(store: &dyn ForumStore, notification: NewNotification) {
+    if notification.recipient_address.is_empty() || notification.recipient_address == notification.actor_address {
+        return;
+    }
+    if let Err(e) = store.create_notification(&notification).await {
+        info!("⚠️ Failed to record {} notification for {}: {}", notification.kind.as_str(), notification.recipient_address, e);
+    }
+}
+
+/// Remove a notification again once its action is undone (unlike, unfollow)
+pub async fn retract(store: &dyn ForumStore, notification: NewNotification) {
+    if let Err(e) = store.delete_notification(&notification).await {
+        info!("⚠️ Failed to retract {} notification for {}: {}", notification.kind.as_str(), notification.recipient_address, e);
+    }
+}
+
+/// Notify the author of the parent comment for a reply, otherwise the author of the post
+pub async fn notify_comment(store: &dyn ForumStore, comment: &Comment) {
+    let recipient = match &comment.parent_id {
+        Some(parent_id) => store.get_comment_by_id(parent_id).await.map(|c| c.map(|c| c.author_address)),
+        None => store.get_post_by_id(&comment.post_id).await.map(|p| p.map(|p| p.author_address)),
+    };
+    match recipient {
+        Ok(Some(recipient)) => notify(store, NewNotification::commented(&recipient, comment)).await,
+        Ok(None) => {}
+        Err(e) => info!("⚠️ Failed to look up recipient for comment {} notification: {}", comment.id, e),
+    }
+}
@@ -0,0 +1,285 @@
+use crate::auth::AuthService;
+use crate::cache::CacheService;
+use crate::models::ApiResponse;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::InternalError;
+use actix_web::http::{header, Method};
+use actix_web::middleware::Next;
+use actix_web::{web, HttpResponse};
+use dashmap::DashMap;
+use log::warn;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Route classes that are rate limited independently of each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitClass {
+    Posts,
+    Comments,
+    Likes,
+    Follows,
+    Uploads,
+}
+
+impl RateLimitClass {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Posts => "posts",
+            Self::Comments => "comments",
+            Self::Likes => "likes",
+            Self::Follows => "follows",
+            Self::Uploads => "uploads",
+        }
+    }
+
+    /// Classify a write request by its matched route pattern (e.g. `/api/posts/{id}/like`).
+    /// Reads are never limited.
+    pub fn for_route(method: &Method, pattern: &str) -> Option<Self> {
+        if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+            return None;
+        }
+
+        let route = pattern.strip_prefix("/api").unwrap_or(pattern);
+        match route {
+            "/posts" | "/posts/async" | "/posts/{id}" => Some(Self::Posts),
+            "/posts/{id}/comments" | "/comments/async" | "/comments/{comment_id}" => Some(Self::Comments),
+            "/posts/{id}/like" | "/comments/{comment_id}/like" => Some(Self::Likes),
+            "/follow" | "/unfollow" => Some(Self::Follows),
+            "/irys/upload" | "/users/avatar/upload" | "/media" => Some(Self::Uploads),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStatus {
+    Allowed,
+    Limited { retry_after: u64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub posts: u32,
+    pub comments: u32,
+    pub likes: u32,
+    pub follows: u32,
+    pub uploads: u32,
+    /// Length of the fixed counting window
+    pub window: Duration,
+    /// Per-IP limit is the per-address limit times this factor, so several users behind one NAT are not starved
+    pub ip_multiplier: u32,
+    /// Take the client IP from `Forwarded`/`X-Forwarded-For` instead of the socket peer
+    pub trust_proxy: bool,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        let env_u32 = |name: &str, default: u32| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            posts: env_u32("RATE_LIMIT_POSTS", 10),
+            comments: env_u32("RATE_LIMIT_COMMENTS", 30),
+            likes: env_u32("RATE_LIMIT_LIKES", 120),
+            follows: env_u32("RATE_LIMIT_FOLLOWS", 60),
+            uploads: env_u32("RATE_LIMIT_UPLOADS", 20),
+            window: Duration::from_secs(env_u32("RATE_LIMIT_WINDOW", 3600).max(1) as u64),
+            ip_multiplier: env_u32("RATE_LIMIT_IP_MULTIPLIER", 3).max(1),
+            trust_proxy: std::env::var("RATE_LIMIT_TRUST_PROXY").map(|v| v == "true").unwrap_or(false),
+        }
+    }
+
+    /// Per-address limit for a class; 0 disables limiting for that class
+    pub fn limit(&self, class: RateLimitClass) -> u32 {
+        match class {
+            RateLimitClass::Posts => self.posts,
+            RateLimitClass::Comments => self.comments,
+            RateLimitClass::Likes => self.likes,
+            RateLimitClass::Follows => self.follows,
+            RateLimitClass::Uploads => self.uploads,
+        }
+    }
+}
+
+/// Fixed-window request counter for one key in the in-process fallback
+struct LocalWindow {
+    count: u32,
+    resets_at: Instant,
+}
+
+/// Fixed-window rate limiter. Counters live in Redis when a cache is
+/// configured (shared by all instances) and in process memory otherwise,
+/// or when Redis is unreachable.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    cache: Option<Arc<CacheService>>,
+    local: DashMap<String, LocalWindow>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, cache: Option<Arc<CacheService>>) -> Self {
+        Self {
+            config,
+            cache,
+            local: DashMap::new(),
+        }
+    }
+
+    /// Count one request against `key` and report whether it is within `limit`
+    pub async fn check(&self, key: &str, limit: u32) -> RateLimitStatus {
+        if let Some(cache) = &self.cache {
+            match cache.check_rate_limit(key, limit, self.config.window.as_secs()).await {
+                Ok(status) => return status,
+                Err(e) => warn!("⚠️ Redis rate limit check failed, using in-process counters: {}", e),
+            }
+        }
+        self.check_local(key, limit)
+    }
+
+    fn check_local(&self, key: &str, limit: u32) -> RateLimitStatus {
+        let now = Instant::now();
+        if self.local.len() > 10_000 {
+            self.local.retain(|_, window| window.resets_at > now);
+        }
+
+        // The entry guard holds the shard lock, so increment and compare are atomic per key
+        let mut window = self.local.entry(key.to_string()).or_insert_with(|| LocalWindow {
+            count: 0,
+            resets_at: now + self.config.window,
+        });
+        if window.resets_at <= now {
+            window.count = 0;
+            window.resets_at = now + self.config.window;
+        }
+
+        window.count += 1;
+        if window.count > limit {
+            let remaining = window.resets_at.saturating_duration_since(now);
+            RateLimitStatus::Limited { retry_after: remaining.as_secs().max(1) }
+        } else {
+            RateLimitStatus::Allowed
+        }
+    }
+
+    /// Apply the per-address (when signed in) and per-IP limits for a route class
+    async fn check_request(&self, class: RateLimitClass, address: Option<&str>, ip: Option<&str>) -> RateLimitStatus {
+        let limit = self.config.limit(class);
+        if limit == 0 {
+            return RateLimitStatus::Allowed;
+        }
+
+        if let Some(address) = address {
+            let status = self.check(&format!("{}:address:{}", class.name(), address), limit).await;
+            if status != RateLimitStatus::Allowed {
+                return status;
+            }
+        }
+        if let Some(ip) = ip {
+            let ip_limit = limit.saturating_mul(self.config.ip_multiplier);
+            return self.check(&format!("{}:ip:{}", class.name(), ip), ip_limit).await;
+        }
+        RateLimitStatus::Allowed
+    }
+}
+
+/// Middleware (`middleware::from_fn`) enforcing [`RateLimiter`] on write routes.
+/// Over-limit requests get `429 Too Many Requests` with a `Retry-After` header.
+pub async fn rate_limit_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let class = req
+        .match_pattern()
+        .and_then(|pattern| RateLimitClass::for_route(req.method(), &pattern));
+    let limiter = req.app_data::<web::Data<RateLimiter>>().cloned();
+
+    if let (Some(class), Some(limiter)) = (class, limiter) {
+        let address = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .zip(req.app_data::<web::Data<AuthService>>())
+            .and_then(|(token, auth)| auth.verify_token(token.trim()).ok());
+        let ip = {
+            let info = req.connection_info();
+            if limiter.config.trust_proxy {
+                info.realip_remote_addr().map(str::to_string)
+            } else {
+                info.peer_addr().map(str::to_string)
+            }
+        };
+
+        if let RateLimitStatus::Limited { retry_after } = limiter.check_request(class, address.as_deref(), ip.as_deref()).await {
+            let message = format!("Too many {} requests, please retry in {} seconds", class.name(), retry_after);
+            let response = HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                .json(ApiResponse::<()>::error(message.clone()));
+            return Err(InternalError::from_response(message, response).into());
+        }
+    }
+
+    next.call(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(window: Duration) -> RateLimiter {
+        let config = RateLimitConfig {
+            posts: 10,
+            comments: 30,
+            likes: 120,
+            follows: 60,
+            uploads: 20,
+            window,
+            ip_multiplier: 2,
+            trust_proxy: false,
+        };
+        RateLimiter::new(config, None)
+    }
+
+    #[test]
+    fn test_route_classification() {
+        assert_eq!(RateLimitClass::for_route(&Method::POST, "/api/posts"), Some(RateLimitClass::Posts));
+        assert_eq!(RateLimitClass::for_route(&Method::POST, "/api/comments/{comment_id}/like"), Some(RateLimitClass::Likes));
+        assert_eq!(RateLimitClass::for_route(&Method::POST, "/api/users/avatar/upload"), Some(RateLimitClass::Uploads));
+        assert_eq!(RateLimitClass::for_route(&Method::POST, "/api/media"), Some(RateLimitClass::Uploads));
+        assert_eq!(RateLimitClass::for_route(&Method::GET, "/api/posts"), None);
+        assert_eq!(RateLimitClass::for_route(&Method::POST, "/api/auth/verify"), None);
+    }
+
+    #[tokio::test]
+    async fn test_local_limits_per_address_and_ip() {
+        let limiter = limiter(Duration::from_secs(60));
+
+        for _ in 0..3 {
+            assert_eq!(limiter.check("k", 3).await, RateLimitStatus::Allowed);
+        }
+        assert!(matches!(limiter.check("k", 3).await, RateLimitStatus::Limited { retry_after } if retry_after <= 60));
+        assert_eq!(limiter.check("other", 3).await, RateLimitStatus::Allowed);
+
+        // The IP allowance is shared by every address behind it
+        let limit = limiter.config.limit(RateLimitClass::Posts);
+        for i in 0..limit * 2 {
+            let address = format!("0x{:040}", i);
+            assert_eq!(limiter.check_request(RateLimitClass::Posts, Some(&address), Some("10.0.0.1")).await, RateLimitStatus::Allowed);
+        }
+        assert_ne!(limiter.check_request(RateLimitClass::Posts, Some("0xnew"), Some("10.0.0.1")).await, RateLimitStatus::Allowed);
+    }
+
+    #[tokio::test]
+    async fn test_local_window_resets() {
+        let limiter = limiter(Duration::from_millis(50));
+        assert_eq!(limiter.check("k", 1).await, RateLimitStatus::Allowed);
+        assert_ne!(limiter.check("k", 1).await, RateLimitStatus::Allowed);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(limiter.check("k", 1).await, RateLimitStatus::Allowed);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::models::SearchRequest;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// Private-use characters marking matches in raw snippets. Both backends produce raw snippets
+/// with these markers; `render_snippet` escapes the text and turns them into `<mark>` tags, so
+/// user content can never inject markup.
+pub const HIGHLIGHT_START: char = '\u{E000}';
+pub const HIGHLIGHT_STOP: char = '\u{E001}';
+
+pub const DEFAULT_LIMIT: u32 = 20;
+pub const MAX_LIMIT: u32 = 50;
+const MAX_QUERY_CHARS: usize = 200;
+/// Approximate snippet length for the in-memory scan
+const SNIPPET_CHARS: usize = 160;
+
+/// `ts_headline` options producing raw snippets for `render_snippet`
+pub fn headline_options() -> String {
+    format!(
+        "StartSel={}, StopSel={}, MaxWords=35, MinWords=15, MaxFragments=2, FragmentDelimiter=\" … \"",
+        HIGHLIGHT_START, HIGHLIGHT_STOP
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// HTML-escape a raw snippet and turn the highlight markers into `<mark>` tags
+pub fn render_snippet(raw: &str) -> String {
+    escape_html(raw)
+        .replace(HIGHLIGHT_START, "<mark>")
+        .replace(HIGHLIGHT_STOP, "</mark>")
+}
+
+fn parse_bound(value: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let start = date.and_hms_opt(0, 0, 0)?.and_utc();
+    Some(if end_of_day { start + Duration::days(1) } else { start })
+}
+
+/// Parse `q`, `type` (comma-separated `posts`, `comments`, `users`; default all), `tag`, `author`,
+/// `from`/`to` (RFC 3339 or `YYYY-MM-DD`; a `to` date includes that whole day), `limit` and `offset`
+pub fn parse_search_request(query: &HashMap<String, String>) -> Result<SearchRequest, String> {
+    let q = query.get("q").map(|q| q.trim()).unwrap_or_default();
+    if q.is_empty() {
+        return Err("Query parameter q is required".to_string());
+    }
+    if q.chars().count() > MAX_QUERY_CHARS {
+        return Err(format!("Query cannot exceed {} characters", MAX_QUERY_CHARS));
+    }
+
+    let (mut include_posts, mut include_comments, mut include_users) = (true, true, true);
+    if let Some(types) = query.get("type").filter(|t| !t.is_empty() && t.as_str() != "all") {
+        (include_posts, include_comments, include_users) = (false, false, false);
+        for kind in types.split(',').map(str::trim) {
+            match kind {
+                "posts" => include_posts = true,
+                "comments" => include_comments = true,
+                "users" => include_users = true,
+                other => return Err(format!("Unknown search type: {}", other)),
+            }
+        }
+    }
+
+    let bound = |name: &str, end_of_day: bool| -> Result<Option<DateTime<Utc>>, String> {
+        query.get(name)
+            .filter(|v| !v.is_empty())
+            .map(|v| parse_bound(v, end_of_day).ok_or_else(|| format!("Invalid {} date: {}", name, v)))
+            .transpose()
+    };
+    let non_empty = |name: &str| query.get(name).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    Ok(SearchRequest {
+        q: q.to_string(),
+        include_posts,
+        include_comments,
+        include_users,
+        tag: non_empty("tag")
+            .map(|tag| crate::utils::normalize_tag(&tag).ok_or_else(|| format!("Invalid tag: {}", tag)))
+            .transpose()?,
+        author: non_empty("author"),
+        from: bound("from", false)?,
+        to: bound("to", true)?,
+        limit: query.get("limit")
+            .and_then(|s| s.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT),
+        offset: query.get("offset").and_then(|s| s.parse::<u32>().ok()).unwrap_or(0),
+    })
+}
+
+/// Case-insensitive substring matcher used by the in-memory search fallback.
+/// Every whitespace-separated term of the query must occur.
+pub struct TextMatcher {
+    terms: Vec<Vec<char>>,
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+impl TextMatcher {
+    pub fn new(query: &str) -> Self {
+        let mut terms: Vec<Vec<char>> = query
+            .split_whitespace()
+            .map(|term| term.trim_matches('"').chars().map(fold).collect::<Vec<char>>())
+            .filter(|term| !term.is_empty())
+            .collect();
+        terms.dedup();
+        Self { terms }
+    }
+
+    /// Char ranges of all term occurrences, in order and non-overlapping
+    fn matches(&self, text: &[char]) -> Vec<(usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        while i < text.len() {
+            let hit = self.terms.iter()
+                .filter(|term| text.len() - i >= term.len() && term.iter().zip(&text[i..]).all(|(a, b)| *a == fold(*b)))
+                .map(|term| term.len())
+                .max();
+            match hit {
+                Some(len) => {
+                    ranges.push((i, i + len));
+                    i += len;
+                }
+                None => i += 1,
+            }
+        }
+        ranges
+    }
+
+    /// Number of term occurrences across `texts`, or `None` unless every term occurs in one of them
+    pub fn score(&self, texts: &[&str]) -> Option<u32> {
+        if self.terms.is_empty() {
+            return None;
+        }
+        let folded: Vec<Vec<char>> = texts.iter().map(|t| t.chars().map(fold).collect()).collect();
+        let every_term = self.terms.iter().all(|term| {
+            folded.iter().any(|text| text.windows(term.len()).any(|window| window == term.as_slice()))
+        });
+        if !every_term {
+            return None;
+        }
+        Some(texts.iter().map(|t| self.matches(&t.chars().collect::<Vec<_>>()).len() as u32).sum())
+    }
+
+    /// Rendered snippet of `text` around the first match
+    pub fn snippet(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let ranges = self.matches(&chars);
+        let start = ranges.first()
+            .map(|(first, _)| first.saturating_sub(SNIPPET_CHARS / 4))
+            .unwrap_or(0);
+        let end = (start + SNIPPET_CHARS).min(chars.len());
+
+        let mut raw = String::new();
+        if start > 0 {
+            raw.push('…');
+        }
+        let mut pos = start;
+        for &(from, to) in ranges.iter().filter(|(from, to)| *from >= start && *to <= end) {
+            raw.extend(&chars[pos..from]);
+            raw.push(HIGHLIGHT_START);
+            raw.extend(&chars[from..to]);
+            raw.push(HIGHLIGHT_STOP);
+            pos = to;
+        }
+        raw.extend(&chars[pos..end]);
+        if end < chars.len() {
+            raw.push('…');
+        }
+        render_snippet(&raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_escapes_content_and_marks_matches() {
+        let matcher = TextMatcher::new("Rust");
+        let snippet = matcher.snippet("<script>alert(1)</script> I like rust & RUST");
+        assert_eq!(snippet, "&lt;script&gt;alert(1)&lt;/script&gt; I like <mark>rust</mark> &amp; <mark>RUST</mark>");
+        assert_eq!(render_snippet(&format!("a{}<b>{}", HIGHLIGHT_START, HIGHLIGHT_STOP)), "a<mark>&lt;b&gt;</mark>");
+    }
+
+    #[test]
+    fn test_every_term_must_match() {
+        let matcher = TextMatcher::new("irys  forum");
+        assert_eq!(matcher.score(&["Irys", "a forum about irys"]), Some(3));
+        assert_eq!(matcher.score(&["Irys only"]), None);
+        assert_eq!(TextMatcher::new("  ").score(&["anything"]), None);
+    }
+
+    #[test]
+    fn test_parse_search_request() {
+        let query = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+
+        let request = parse_search_request(&query(&[("q", " irys "), ("type", "posts,users"), ("to", "2024-05-01"), ("limit", "500")])).unwrap();
+        assert_eq!(request.q, "irys");
+        assert_eq!((request.include_posts, request.include_comments, request.include_users), (true, false, true));
+        assert_eq!(request.to.unwrap().to_rfc3339(), "2024-05-02T00:00:00+00:00");
+        assert_eq!(request.limit, MAX_LIMIT);
+
+        assert!(parse_search_request(&query(&[("q", "")])).is_err());
+        assert!(parse_search_request(&query(&[("q", "x"), ("type", "files")])).is_err());
+        assert!(parse_search_request(&query(&[("q", "x"), ("from", "yesterday")])).is_err());
+    }
+}
@@ -1,121 +1,182 @@
 use crate::models::*;
-use crate::blockchain::BlockchainService;
+use crate::async_queue::{creation_task_result, verification_task_error, QueueTask, QueuedTask, TaskError, TaskProcessor};
+use crate::blockchain::{BlockchainService, TransactionVerificationError};
 use crate::database::DatabaseService;
+use crate::irys::IrysService;
+use crate::memory_store::MemoryStore;
+use crate::markdown;
+use crate::media::{self, MediaConfig, MediaError};
+use crate::mentions;
+use crate::notifications;
+use crate::spam::{self, SpamCandidate, SpamScorer};
+use crate::store::ForumStore;
+use crate::utils::{normalize_tag, normalize_tags};
+use async_trait::async_trait;
 use chrono::Utc;
 use log::info;
-use reqwest::Client;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Mutex;
 use std::sync::Arc;
 use sha2::{Sha256, Digest};
-use uuid;
 
-pub struct IrysService {
-    client: Client,
-    testnet_url: String,
-    explorer_url: String,
+/// Failure of an action on existing content (edit, delete, ...); handlers map each variant to an HTTP status
+#[derive(Debug)]
+pub enum ContentActionError {
+    NotFound(String),
+    Forbidden(String),
+    Invalid(String),
+    Internal(String),
 }
 
-impl IrysService {
-    pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-            testnet_url: "https://testnet-rpc.irys.xyz/v1/execution-rpc".to_string(),
-            explorer_url: "https://explorer.irys.xyz".to_string(),
+impl std::fmt::Display for ContentActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentActionError::NotFound(message)
+            | ContentActionError::Forbidden(message)
+            | ContentActionError::Invalid(message)
+            | ContentActionError::Internal(message) => write!(f, "{}", message),
         }
     }
+}
 
-    pub async fn upload_data(&self, _data: &str, _tags: Vec<String>, _address: &str) -> Result<String, Box<dyn std::error::Error>> {
-      
-        let tx_id = format!("mock_tx_{}", chrono::Utc::now().timestamp_millis());
-        info!("Mock Irys upload with transaction ID: {}", tx_id);
-        Ok(tx_id)
-        
-        
-        /*
-        let payload = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": "irys_uploadData",
-            "params": {
-                "data": data,
-                "tags": tags,
-                "address": address
-            },
-            "id": 1
-        });
+impl std::error::Error for ContentActionError {}
 
-        let response = self.client
-            .post(&self.testnet_url)
-            .json(&payload)
-            .send()
-            .await?;
+impl From<sqlx::Error> for ContentActionError {
+    fn from(e: sqlx::Error) -> Self {
+        ContentActionError::Internal(format!("Database error: {}", e))
+    }
+}
 
-        let result: Value = response.json().await?;
-        
-        if let Some(error) = result.get("error") {
-            return Err(format!("Irys upload error: {}", error).into());
+impl From<MediaError> for ContentActionError {
+    fn from(e: MediaError) -> Self {
+        match e {
+            MediaError::Internal(message) => ContentActionError::Internal(message),
+            e => ContentActionError::Invalid(e.to_string()),
         }
+    }
+}
 
-        if let Some(result_data) = result.get("result") {
-            if let Some(tx_id) = result_data.get("transactionId") {
-                if let Some(tx_id_str) = tx_id.as_str() {
-                    info!("Successfully uploaded to Irys with transaction ID: {}", tx_id_str);
-                    return Ok(tx_id_str.to_string());
-                }
-            }
-        }
+/// Upper bounds for `GET /api/posts/{id}/comments/tree`
+pub const COMMENT_TREE_MAX_LIMIT: u32 = 100;
+pub const COMMENT_TREE_MAX_DEPTH: u32 = 10;
 
-        Err("Failed to get transaction ID from response".into())
-        */
+/// Reject addresses with an active suspension
+pub async fn check_not_suspended(store: &dyn ForumStore, address: &str) -> Result<(), ContentActionError> {
+    match store.get_active_suspension(address).await? {
+        Some(Suspension { expires_at: Some(expires_at), .. }) => {
+            Err(ContentActionError::Forbidden(format!("This address is suspended until {}", expires_at.to_rfc3339())))
+        }
+        Some(_) => Err(ContentActionError::Forbidden("This address is suspended".to_string())),
+        None => Ok(()),
     }
+}
 
-    pub async fn query_data(&self, address: Option<&str>, tags: Option<Vec<String>>, limit: Option<u32>) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
-        let mut params = HashMap::new();
-        
-        if let Some(addr) = address {
-            params.insert("address".to_string(), addr.to_string());
-        }
-        
-        if let Some(tags) = tags {
-            let tags_str = serde_json::to_string(&tags)?;
-            params.insert("tags".to_string(), tags_str);
-        }
-        
-        if let Some(limit) = limit {
-            let limit_str = limit.to_string();
-            params.insert("limit".to_string(), limit_str);
-        }
+/// Check that the post's thread is not locked and that a reply's `parent_id` is a live
+/// comment on the same post whose author has not blocked `author_address`
+pub async fn check_comment_target(store: &dyn ForumStore, post_id: &str, parent_id: Option<&str>, author_address: &str) -> Result<(), ContentActionError> {
+    if store.is_post_locked(post_id).await? {
+        return Err(ContentActionError::Forbidden("This thread is locked".to_string()));
+    }
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    let parent = store.get_comment_by_id(parent_id).await?
+        .ok_or_else(|| ContentActionError::NotFound("Parent comment not found".to_string()))?;
+    if parent.post_id != post_id {
+        return Err(ContentActionError::Invalid("Parent comment belongs to a different post".to_string()));
+    }
+    if store.is_blocked(&parent.author_address, author_address).await? {
+        return Err(ContentActionError::Forbidden("You cannot reply to this user".to_string()));
+    }
+    Ok(())
+}
 
-        let query_string = serde_urlencoded::to_string(&params)?;
-        let url = format!("{}/query?{}", self.explorer_url, query_string);
+/// Lowercase address to block or mute; rejects malformed addresses and the actor themselves
+fn restriction_target(actor: &str, target: &str, verb: &str) -> Result<String, ContentActionError> {
+    if !target.starts_with("0x") || target.len() != 42 {
+        return Err(ContentActionError::Invalid("Invalid address".to_string()));
+    }
+    if target.eq_ignore_ascii_case(actor) {
+        return Err(ContentActionError::Invalid(format!("You cannot {} yourself", verb)));
+    }
+    Ok(target.to_lowercase())
+}
 
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+/// The on-chain ID a comment transaction must reference. A post that was never created on chain
+/// has none, and no CommentCreated event can prove a comment on it.
+fn on_chain_post_id(post: &Post) -> Result<ethers::types::U256, TransactionVerificationError> {
+    post.blockchain_post_id
+        .map(ethers::types::U256::from)
+        .ok_or_else(|| TransactionVerificationError::Invalid("Post has no on-chain ID".to_string()))
+}
 
-        let result: Value = response.json().await?;
-        
-        if let Some(data) = result.get("data") {
-            if let Some(transactions) = data.as_array() {
-                return Ok(transactions.clone());
+/// Nest tree rows under their parents, sorted oldest first, and mark comments with replies left out
+fn build_comment_nodes(
+    children: &mut HashMap<Option<String>, Vec<CommentTreeRow>>,
+    parent_id: Option<String>,
+    depth: u32,
+) -> Vec<CommentNode> {
+    let mut rows = children.remove(&parent_id).unwrap_or_default();
+    rows.sort_by(|a, b| (a.comment.created_at, &a.comment.id).cmp(&(b.comment.created_at, &b.comment.id)));
+    rows.into_iter()
+        .map(|row| {
+            let replies = build_comment_nodes(children, Some(row.comment.id.clone()), depth + 1);
+            let has_more_replies = (replies.len() as u32) < row.reply_count;
+            let replies_cursor = replies.last()
+                .filter(|_| has_more_replies)
+                .map(|last| last.comment.page_cursor().encode());
+            CommentNode {
+                comment: row.comment,
+                depth,
+                reply_count: row.reply_count,
+                replies,
+                has_more_replies,
+                replies_cursor,
             }
-        }
+        })
+        .collect()
+}
 
-        Ok(Vec::new())
+/// Upper bound for `limit` on `GET /api/feed/following`
+pub const FEED_MAX_LIMIT: u32 = 50;
+/// A recommended post is mixed in after this many followed posts
+const FEED_RECOMMENDATION_INTERVAL: usize = 3;
+/// Upper bound for `limit` on `GET /api/tags/{tag}/posts`
+pub const TAG_POSTS_MAX_LIMIT: u32 = 50;
+/// Upper bound for `limit` on `GET /api/tags/trending`
+pub const TRENDING_TAGS_MAX_LIMIT: u32 = 50;
+/// Upper bound for `limit` on `GET /api/bookmarks`
+pub const BOOKMARKS_MAX_LIMIT: u32 = 50;
+/// Upper bound for `limit` on `GET /api/moderation/reports` and `GET /api/moderation/log`
+pub const MODERATION_MAX_LIMIT: u32 = 100;
+const REPORT_DETAILS_MAX_CHARS: usize = 1000;
+/// Upper bound for `limit` on `GET /api/notifications`
+pub const NOTIFICATIONS_MAX_LIMIT: u32 = 50;
+
+/// Interleave recommended posts into the followed ones; leftovers go at the end, so a user
+/// who follows nobody still gets a first page
+fn mix_recommendations(followed: Vec<Post>, recommended: Vec<Post>) -> Vec<FeedItem> {
+    let mut recommended = recommended.into_iter();
+    let mut items = Vec::new();
+    for (i, post) in followed.into_iter().enumerate() {
+        items.push(FeedItem { post, source: FeedSource::Following });
+        if (i + 1) % FEED_RECOMMENDATION_INTERVAL == 0 {
+            items.extend(recommended.next().map(|post| FeedItem { post, source: FeedSource::Recommended }));
+        }
     }
+    items.extend(recommended.map(|post| FeedItem { post, source: FeedSource::Recommended }));
+    items
 }
 
 pub struct ForumService {
-    posts: Arc<Mutex<HashMap<String, Post>>>, 
-    comments: Arc<Mutex<HashMap<String, Comment>>>, 
-    users: Arc<Mutex<HashMap<String, User>>>, 
+    store: Arc<dyn ForumStore>,
     irys_service: IrysService,
     blockchain_service: Option<BlockchainService>,
-    database_service: Option<DatabaseService>,
+
     cache_service: Option<Arc<crate::cache::CacheService>>, 
     async_queue_service: Option<Arc<crate::async_queue::AsyncQueueService>>, 
+    spam_scorer: Arc<dyn SpamScorer>,
+    media_config: MediaConfig,
 }
 
 impl ForumService {
@@ -131,29 +192,34 @@ impl ForumService {
             }
         };
 
-        let database_service = match std::env::var("DATABASE_URL") {
+        let store: Arc<dyn ForumStore> = match std::env::var("DATABASE_URL") {
             Ok(database_url) => {
                 match DatabaseService::new(&database_url).await {
                     Ok(service) => {
                         info!("✅ Database service initialization successful");
-                        Some(service)
+                        Arc::new(service)
                     },
+                    Err(sqlx::Error::Migrate(e)) => {
+                        // Never run against a schema we cannot migrate (or one newer than this build)
+                        log::error!("❌ Database migration failed: {}", e);
+                        std::process::exit(1);
+                    }
                     Err(e) => {
                         info!("⚠️ Database service initialization failed: {}, will use memory storage", e);
-                        None
+                        Arc::new(MemoryStore::new())
                     }
                 }
             },
             Err(_) => {
                 info!("⚠️ DATABASE-URL not set, memory storage will be used");
-                None
+                Arc::new(MemoryStore::new())
             }
         };
 
         
         let cache_service = match std::env::var("REDIS_URL") {
             Ok(redis_url) => {
-                match crate::cache::CacheService::new(&redis_url) {
+                match crate::cache::CacheService::new(&redis_url).await {
                     Ok(service) => {
                         info!("✅ Redis cache service initialization successful: {}", redis_url);
                         Some(Arc::new(service))
@@ -170,32 +236,77 @@ impl ForumService {
             }
         };
 
-        
+        let spam_config = spam::SpamConfig::from_env();
+        info!(
+            "🛡️ Spam filter {}: hold at score {}, reject at {}",
+            if spam_config.enabled { "enabled" } else { "disabled" }, spam_config.hold_score, spam_config.reject_score
+        );
+        let spam_scorer: Arc<dyn SpamScorer> = Arc::new(spam::HeuristicScorer::new(store.clone(), spam_config));
+
         let async_queue_service = {
-            let blockchain_service_arc = blockchain_service.as_ref().map(|s| Arc::new(s.clone()));
-            let database_service_arc = database_service.as_ref().map(|s| Arc::new(s.clone()));
-            
-            let queue_service = crate::async_queue::AsyncQueueService::new(
-                blockchain_service_arc,
-                database_service_arc,
-            );
+            let queue_service = crate::async_queue::AsyncQueueService::new(store.clone());
             
             info!("✅ Asynchronous queue service initialization completed");
             Some(Arc::new(queue_service))
         };
 
+        let indexer_enabled = std::env::var("CHAIN_INDEXER_ENABLED")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true);
+        match &blockchain_service {
+            Some(blockchain) if indexer_enabled => {
+                crate::indexer::ChainIndexer::new(
+                    Arc::new(blockchain.clone()),
+                    store.clone(),
+                    crate::indexer::ChainIndexerConfig::from_env(),
+                ).start();
+            }
+            _ => info!("⚠️ Chain indexer not started (disabled, or blockchain service unavailable)"),
+        }
+
+        let media_config = MediaConfig::from_env();
+        info!("🖼️ Media stored in {} (max {} bytes, {} pixels)", media_config.dir.display(), media_config.max_upload_bytes, media_config.max_pixels);
+
+        Self::from_parts(store, blockchain_service, cache_service, async_queue_service)
+            .with_spam_scorer(spam_scorer)
+            .with_media_config(media_config)
+    }
+
+    /// Assemble a service from already-initialized backends (tests use a `MemoryStore` and no integrations)
+    pub fn from_parts(
+        store: Arc<dyn ForumStore>,
+        blockchain_service: Option<BlockchainService>,
+        cache_service: Option<Arc<crate::cache::CacheService>>,
+        async_queue_service: Option<Arc<crate::async_queue::AsyncQueueService>>,
+    ) -> Self {
+        info!("🗄️ Storage backend: {}", store.backend_name());
         Self {
-            posts: Arc::new(Mutex::new(HashMap::new())),
-            comments: Arc::new(Mutex::new(HashMap::new())),
-            users: Arc::new(Mutex::new(HashMap::new())),
+            store,
             irys_service: IrysService::new(),
             blockchain_service,
-            database_service,
             cache_service,
             async_queue_service,
+            spam_scorer: Arc::new(spam::AllowAll),
+            media_config: MediaConfig::default(),
         }
     }
 
+    /// Replace the spam scorer; `from_parts` starts with `spam::AllowAll`
+    pub fn with_spam_scorer(mut self, spam_scorer: Arc<dyn SpamScorer>) -> Self {
+        self.spam_scorer = spam_scorer;
+        self
+    }
+
+    /// Replace the media settings; `from_parts` starts with `MediaConfig::default()`
+    pub fn with_media_config(mut self, media_config: MediaConfig) -> Self {
+        self.media_config = media_config;
+        self
+    }
+
+    pub fn media_config(&self) -> &MediaConfig {
+        &self.media_config
+    }
+
     pub fn generate_id() -> String {
         uuid::Uuid::new_v4().to_string()
     }
@@ -206,99 +317,170 @@ impl ForumService {
         format!("{:x}", hasher.finalize())
     }
 
-    pub async fn create_post(&self, request: CreatePostRequest) -> Result<Post, Box<dyn std::error::Error>> {
-        let post_data = serde_json::to_string(&request)?;
-        let tags = vec!["forum".to_string(), "post".to_string()];
-        let author_address = request.author_address.clone();
-        
-       
-        let author_name = if let Ok(Some(username)) = self.get_username_by_address(&author_address).await {
-            Some(username)
-        } else {
-            request.author_name
-        };
-        
-     
-        let tx_id = self.irys_service.upload_data(&post_data, tags, &author_address).await?;
+    /// Edit a post (author only). The new version is screened by the spam filter like a new post,
+    /// the replaced version is kept in the revision history and the new version is uploaded to
+    /// Irys, so `verify` reports it against its own payload.
+    pub async fn update_post(&self, post_id: &str, editor_address: &str, request: UpdatePostRequest) -> Result<Post, Box<dyn std::error::Error>> {
+        let (post, request) = self.check_post_edit(post_id, editor_address, request).await?;
+        spam::screen(self.store.as_ref(), self.spam_scorer.as_ref(), &SpamCandidate::post_edit(&post.author_address, &request), || {
+            HeldSubmission::PostEdit { post_id: post.id.clone(), author_address: post.author_address.clone(), request: request.clone() }
+        }).await?;
+        Ok(self.publish_post_edit(post, request).await?)
+    }
 
-        
-        if let Some(_blockchain_service) = &self.blockchain_service {
-            info!("🔗 Blockchain service available - contract address: {}", std::env::var("CONTRACT_ADDRESS").unwrap_or_default());
-            info!("📝 The post has been created, and the frontend can call the contract for on-chain recording");
-            info!("💡 Parameters: title={}, tags={:?}, irys_tx={}", request.title, request.tags, tx_id);
-        } else {
-            info!("⚠️ Offline mode: Skipping blockchain integration");
+    /// The post and the edit to apply to it, with its title trimmed and its image resolved
+    async fn check_post_edit(&self, post_id: &str, editor_address: &str, request: UpdatePostRequest) -> Result<(Post, UpdatePostRequest), ContentActionError> {
+        check_not_suspended(self.store.as_ref(), editor_address).await?;
+        let title = request.title.trim().to_string();
+        if title.is_empty() || request.content.trim().is_empty() {
+            return Err(ContentActionError::Invalid("Title and content cannot be empty".to_string()));
+        }
+        if title.chars().count() > 255 {
+            return Err(ContentActionError::Invalid("Title cannot exceed 255 characters".to_string()));
         }
 
-        let post = Post {
-            id: Self::generate_id(),
-            title: request.title,
-            content: request.content,
-            author_address: request.author_address,
-            author_id: None, 
-            author_name,
-            author_avatar: None,
-            created_at: Utc::now(),
+        let post = self.store.get_post_by_id(post_id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Post not found".to_string()))?;
+        if !post.author_address.eq_ignore_ascii_case(editor_address) {
+            return Err(ContentActionError::Forbidden("Only the author can edit this post".to_string()));
+        }
+        let tags = normalize_tags(&request.tags, &request.content);
+        // An image kept from before media uploads existed is left as is
+        let image = match request.image {
+            Some(image) if post.image.as_ref() == Some(&image) => Some(image),
+            image => self.resolve_image(image).await?,
+        };
+        if post.title == title && post.content == request.content && post.tags == tags && post.image == image {
+            return Err(ContentActionError::Invalid("No changes to save".to_string()));
+        }
+        Ok((post, UpdatePostRequest { title, image, ..request }))
+    }
+
+    /// Apply an edit that passed the checks in `update_post` (or a held one a moderator approved)
+    async fn publish_post_edit(&self, post: Post, request: UpdatePostRequest) -> Result<Post, ContentActionError> {
+        let UpdatePostRequest { title, content, tags, image } = request;
+        let tags = normalize_tags(&tags, &content);
+        let post_data = serde_json::json!({
+            "post_id": post.id,
+            "title": title,
+            "content": content,
+            "tags": tags,
+            "image": image,
+            "author_address": post.author_address,
+            "previous_irys_transaction_id": post.irys_transaction_id,
+        });
+        let irys_tags = vec!["forum".to_string(), "post".to_string(), "edit".to_string()];
+        let tx_id = self.irys_service.upload_data(&post_data.to_string(), irys_tags, &post.author_address).await
+            .map_err(|e| ContentActionError::Internal(format!("Irys upload failed: {}", e)))?;
+
+        let mut updated = Post {
+            title,
+            content_html: markdown::render(&content),
+            content,
+            tags,
+            image,
+            irys_transaction_id: tx_id,
             updated_at: Utc::now(),
-            likes: 0,
-            comments_count: 0,
-            tags: request.tags,
-            irys_transaction_id: Some(tx_id),
-            image: request.image,
-            blockchain_post_id: request.blockchain_post_id,
-            is_liked_by_user: false, 
-            views: 0,
-            heat_score: None, 
+            ..post
         };
+        self.store.update_post(&updated).await?;
+        mentions::record_post_mentions(self.store.as_ref(), self.blockchain_service.as_ref(), &mut updated).await;
+        info!("✏️ Post edited: {} (irys_tx={:?})", updated.id, updated.irys_transaction_id);
 
-        // Prioritize using database storage
-        if let Some(db) = &self.database_service {
-            match db.create_post(&post).await {
-                Ok(_) => {
-                    info!("📊 The post has been saved to the database: {}", post.id);
-                    
-                    
-                    if let Some(cache) = &self.cache_service {
-                        if let Err(e) = cache.invalidate_post_cache() {
-                            info!("⚠️ Clearing post cache failed: {}", e);
-                        } else {
-                            info!("🗑️ Cleared post list cache");
-                        }
-                    }
-                },
-                Err(e) => {
-                    info!("⚠️Database save failed, using memory storage: {}", e);
-                   
-                    self.posts.lock().unwrap().insert(post.id.clone(), post.clone());
-                    self.update_user_stats(&author_address, true, false).await;
-                }
+        if let Some(cache) = &self.cache_service {
+            if let Err(e) = cache.invalidate_post_cache().await {
+                info!("⚠️ Clearing post cache failed: {}", e);
             }
-        } else {
-       
-            self.posts.lock().unwrap().insert(post.id.clone(), post.clone());
-            self.update_user_stats(&author_address, true, false).await;
         }
 
-        Ok(post)
+        Ok(updated)
     }
 
-    pub async fn get_posts(&self) -> Vec<Post> {
-        self.get_posts_paginated(1000, 0).await 
+    /// Prior versions of a post, oldest first; `None` if the post does not exist
+    pub async fn get_post_revisions(&self, post_id: &str) -> Result<Option<Vec<PostRevision>>, Box<dyn std::error::Error>> {
+        if self.store.get_post_by_id(post_id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.store.get_post_revisions(post_id).await?))
     }
-    
-   
-    pub async fn get_posts_with_like_status(&self, user_address: Option<&str>) -> Vec<Post> {
-        self.get_posts_paginated_with_like_status(1000, 0, user_address).await
+
+    pub async fn search(&self, request: &SearchRequest) -> Result<SearchResults, Box<dyn std::error::Error>> {
+        let results = self.store.search(request).await?;
+        info!("🔍 Search {:?}: {} posts, {} comments, {} users ({} storage)",
+            request.q, results.posts.len(), results.comments.len(), results.users.len(), self.store.backend_name());
+        Ok(results)
     }
-    
-    pub async fn get_posts_paginated_with_like_status(&self, limit: u32, offset: u32, user_address: Option<&str>) -> Vec<Post> {
-        
-        let mut posts = self.get_posts_paginated(limit, offset).await;
+
+    /// Whether `actor` may delete content written by `author`: the author or a moderator
+    async fn can_delete(&self, actor: &str, author: &str) -> Result<bool, ContentActionError> {
+        Ok(actor.eq_ignore_ascii_case(author) || self.store.get_user_role(actor).await?.can_moderate())
+    }
+
+    /// Soft delete a post; allowed for its author and for moderators
+    pub async fn delete_post(&self, post_id: &str, actor_address: &str) -> Result<(), ContentActionError> {
+        let post = self.store.get_post_by_id(post_id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Post not found".to_string()))?;
+        if !self.can_delete(actor_address, &post.author_address).await? {
+            return Err(ContentActionError::Forbidden("Only the author or a moderator can delete this post".to_string()));
+        }
+        if !self.store.delete_post(post_id, actor_address).await? {
+            return Err(ContentActionError::NotFound("Post not found".to_string()));
+        }
+        info!("🗑️ Post deleted: {} by {}", post_id, actor_address);
+
+        if let Some(cache) = &self.cache_service {
+            if let Err(e) = cache.invalidate_post_cache().await {
+                info!("⚠️ Clearing post cache failed: {}", e);
+            }
+            if let Err(e) = cache.invalidate_comment_cache(post_id).await {
+                info!("⚠️ Clearing comment cache failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Soft delete a comment; allowed for its author and for moderators. A comment with
+    /// replies stays in the thread as a "[deleted]" tombstone.
+    pub async fn delete_comment(&self, comment_id: &str, actor_address: &str) -> Result<(), ContentActionError> {
+        let comment = self.store.get_comment_by_id(comment_id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Comment not found".to_string()))?;
+        if !self.can_delete(actor_address, &comment.author_address).await? {
+            return Err(ContentActionError::Forbidden("Only the author or a moderator can delete this comment".to_string()));
+        }
+        if !self.store.delete_comment(comment_id, actor_address).await? {
+            return Err(ContentActionError::NotFound("Comment not found".to_string()));
+        }
+        info!("🗑️ Comment deleted: {} by {}", comment_id, actor_address);
+
+        if let Some(cache) = &self.cache_service {
+            if let Err(e) = cache.invalidate_comment_cache(&comment.post_id).await {
+                info!("⚠️ Clearing comment cache failed: {}", e);
+            }
+            if let Err(e) = cache.invalidate_post_cache().await {
+                info!("⚠️ Clearing post cache failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// One page of posts with like flags for `user_address`. Mutes are private, so only the
+    /// signed-in `viewer`'s are applied, never those of an address passed in the query.
+    pub async fn get_posts_paginated_with_like_status(&self, page: &PageRequest, user_address: Option<&str>, viewer: Option<&str>) -> Page<Post> {
+        let muted = match viewer {
+            Some(viewer) => self.muted_addresses(viewer).await,
+            None => Vec::new(),
+        };
+        // Cached pages are shared by every viewer, so a viewer with mutes reads straight from the store
+        let mut posts = if muted.is_empty() {
+            self.get_posts_paginated(page).await
+        } else {
+            self.query_posts_page(page, &muted).await
+        };
         
       
-        if let (Some(user_addr), Some(db)) = (user_address, &self.database_service) {
-            for post in &mut posts {
-                if let Ok(is_liked) = db.has_user_liked_post(&post.id, user_addr).await {
+        if let Some(user_addr) = user_address {
+            for post in &mut posts.items {
+                if let Ok(is_liked) = self.store.has_user_liked_post(&post.id, user_addr).await {
                     post.is_liked_by_user = is_liked;
                 }
             }
@@ -307,12 +489,12 @@ impl ForumService {
         posts
     }
     
-    pub async fn get_posts_paginated(&self, limit: u32, offset: u32) -> Vec<Post> {
+    pub async fn get_posts_paginated(&self, page: &PageRequest) -> Page<Post> {
         
         if let Some(cache) = &self.cache_service {
-            match cache.get_cached_posts(limit, offset) {
+            match cache.get_cached_posts(page).await {
                 Ok(Some(posts)) => {
-                    info!("⚡ Retrieve {} posts from Redis cache (limit: {}, offset: {})", posts.len(), limit, offset);
+                    info!("⚡ Retrieve {} posts from Redis cache (limit: {}, offset: {}, after: {:?})", posts.items.len(), page.limit, page.offset, page.after);
                     return posts;
                 },
                 Ok(None) => {
@@ -325,412 +507,206 @@ impl ForumService {
         }
 
     
-        if let Some(db) = &self.database_service {
-            match db.get_posts_paginated(limit, offset).await {
-                Ok(posts) => {
-                    info!("📊 Retrieved {} posts from the database (limit: {}, offset: {})", posts.len(), limit, offset);
-                    
-                    
-                    if let Some(cache) = &self.cache_service {
-                        if let Err(e) = cache.cache_posts(&posts, limit, offset) {
-                            info!("⚠️ Cache post failed: {}", e);
-                        } else {
-                            info!("💾 The post has been cached Redis");
-                        }
-                    }
-                    
-                    return posts;
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, using in memory data: {}", e);
-                }
+    
+        let posts = self.query_posts_page(page, &[]).await;
+        if let Some(cache) = &self.cache_service {
+            if let Err(e) = cache.cache_posts(&posts, page).await {
+                info!("⚠️ Cache post failed: {}", e);
+            } else {
+                info!("💾 The post has been cached Redis");
             }
         }
-        
-    
-        let posts = self.posts.lock().unwrap();
-        let mut post_list: Vec<Post> = posts.values().cloned().collect();
-        post_list.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-        
-    
-        let start = offset as usize;
-        let end = (start + limit as usize).min(post_list.len());
-        let paginated_posts = if start < post_list.len() {
-            post_list[start..end].to_vec()
-        } else {
-            Vec::new()
-        };
-        
-        info!("📊 Retrieved {} posts from memory (limit: {}, offset: {})", paginated_posts.len(), limit, offset);
-        paginated_posts
+        posts
     }
 
-    pub async fn get_post(&self, id: &str) -> Option<Post> {
-     
-        if let Some(db) = &self.database_service {
-            match db.get_post_by_id(id).await {
-                Ok(Some(post)) => {
-                    info!("📊 Retrieved post from the database: {}", id);
-                    return Some(post);
-                },
-                Ok(None) => {
-                    info!("📊 Post not found in database: {}", id);
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, using in memory data: {}", e);
-                }
+    /// One page of posts from the store, skipping `excluded_authors`; empty if the query fails
+    async fn query_posts_page(&self, page: &PageRequest, excluded_authors: &[String]) -> Page<Post> {
+        match self.store.get_posts_paginated(page, excluded_authors).await {
+            Ok(mut posts) => {
+                mentions::attach_to_posts(self.store.as_ref(), &mut posts.items).await;
+                info!("📊 Retrieved {} posts from {} storage (limit: {}, offset: {}, after: {:?})", posts.items.len(), self.store.backend_name(), page.limit, page.offset, page.after);
+                posts
+            },
+            Err(e) => {
+                info!("⚠️ Post query failed: {}", e);
+                Page::empty()
             }
         }
-        
-       
-        let posts = self.posts.lock().unwrap();
-        posts.get(id).cloned()
     }
 
-    pub async fn get_post_with_like_status(&self, id: &str, user_address: Option<&str>) -> Option<Post> {
-        if let Some(db) = &self.database_service {
-            match db.get_post_by_id_with_like_status(id, user_address).await {
-                Ok(Some(post)) => {
-                    info!("📊 Retrieved post (including like status) from the database: {}", id);
-                    return Some(post);
-                },
-                Ok(None) => {
-                    info!("📊 No post found in the database: {}", id);
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, fallback to no like status: {}", e);
-                }
+    pub async fn get_post(&self, id: &str) -> Option<Post> {
+        match self.store.get_post_by_id(id).await {
+            Ok(Some(mut post)) => {
+                mentions::attach_to_posts(self.store.as_ref(), std::slice::from_mut(&mut post)).await;
+                info!("📊 Retrieved post: {}", id);
+                Some(post)
+            },
+            Ok(None) => {
+                info!("📊 Post not found: {}", id);
+                None
+            },
+            Err(e) => {
+                info!("⚠️ Post query failed: {}", e);
+                None
             }
         }
-        
-      
-        self.get_post(id).await
     }
 
-    pub async fn add_comment(&self, request: CreateCommentRequest) -> Result<Comment, Box<dyn std::error::Error>> {
-        let comment_data = serde_json::to_string(&request)?;
-        let tags = vec!["forum".to_string(), "comment".to_string()];
-        let author_address = request.author_address.clone();
-        let post_id = request.post_id.clone();
-        
-       
-        let author_name = if let Ok(Some(username)) = self.get_username_by_address(&author_address).await {
-            Some(username)
-        } else {
-            request.author_name
-        };
-        
-       
-        let tx_id = self.irys_service.upload_data(&comment_data, tags, &author_address).await?;
-
-        let comment = Comment {
-            id: Self::generate_id(),
-            post_id: request.post_id.clone(),
-            content: request.content.clone(),
-            author_address: request.author_address.clone(),
-            author_id: None, 
-            author_name: author_name.clone(),
-            author_avatar: None,
-            created_at: Utc::now(),
-            parent_id: request.parent_id.clone(),
-            likes: 0,
-            irys_transaction_id: Some(tx_id),
-            image: request.image.clone(),
-            content_hash: Self::generate_content_hash(&request.content),
-            is_liked_by_user: false,
-        };
-
-        
-        if let Some(db) = &self.database_service {
-            match db.create_comment(&comment).await {
-                Ok(_) => {
-                    info!("🔗 Blockchain service available - contract address: {:?}", 
-                          self.blockchain_service.as_ref().map(|s| s.get_contract_address()));
-                    info!("📝 The comment has been created, and the frontend can call the contract for on chain recording");
-                    info!("💡parameter: content={}, post_id={}", comment.content, comment.post_id);
-                    
-                
-                    if let Some(cache) = &self.cache_service {
-                        if let Err(e) = cache.invalidate_comment_cache(&comment.post_id) {
-                            info!("⚠️ Clearing comment cache failed: {}", e);
-                        } else {
-                            info!("🗑️ Cleared post comment cache");
-                        }
-                    }
-                    
-                    return Ok(comment);
-                },
-                Err(e) => {
-                    info!("⚠️ Database save failed, using memory storage: {}", e);
-                }
+    /// A post with like flags for `user_address`; the bookmark flag is only set for the signed-in `viewer`
+    pub async fn get_post_with_like_status(&self, id: &str, user_address: Option<&str>, viewer: Option<&str>) -> Option<Post> {
+        match self.store.get_post_by_id_with_like_status(id, user_address, viewer).await {
+            Ok(Some(mut post)) => {
+                mentions::attach_to_posts(self.store.as_ref(), std::slice::from_mut(&mut post)).await;
+                info!("📊 Retrieved post (including like status): {}", id);
+                Some(post)
+            },
+            Ok(None) => {
+                info!("📊 Post not found: {}", id);
+                None
+            },
+            Err(e) => {
+                info!("⚠️ Post query failed, fallback to no like status: {}", e);
+                self.get_post(id).await
             }
         }
+    }
 
-     
-        let comment_id = comment.id.clone();
-        self.comments.lock().unwrap().insert(comment_id.clone(), comment.clone());
-        
-    
-        if let Some(post) = self.posts.lock().unwrap().get_mut(&post_id) {
-            post.comments_count += 1;
+    pub async fn add_comment(&self, mut request: CreateCommentRequest) -> Result<Comment, Box<dyn std::error::Error>> {
+        check_not_suspended(self.store.as_ref(), &request.author_address).await?;
+        check_comment_target(self.store.as_ref(), &request.post_id, request.parent_id.as_deref(), &request.author_address).await?;
+        request.image = self.resolve_image(request.image.take()).await?;
+        spam::screen(self.store.as_ref(), self.spam_scorer.as_ref(), &SpamCandidate::comment(&request), || {
+            HeldSubmission::Comment { request: request.clone(), verification: None }
+        }).await?;
+        self.publish_comment(Self::generate_id(), request, None).await
+    }
+
+    pub async fn get_user_profile(&self, address: &str) -> Option<User> {
+        match self.store.get_user_by_address(address).await {
+            Ok(Some(user)) => {
+                info!("📊 Retrieved user profile: {} (posts: {}, comments: {}, reputation: {})", address, user.posts_count, user.comments_count, user.reputation);
+                Some(user)
+            },
+            Ok(None) => {
+                info!("📊 User not found: {}", address);
+                None
+            },
+            Err(e) => {
+                info!("⚠️ User query failed: {}", e);
+                None
+            }
         }
-        
-      
-        self.update_user_stats(&author_address, false, true).await;
+    }
 
-        Ok(comment)
+    pub async fn upload_to_irys(&self, request: IrysUploadRequest) -> Result<String, Box<dyn std::error::Error>> {
+        self.irys_service.upload_data(&request.data, request.tags, &request.address).await?
+            .ok_or_else(|| "Irys uploads are not configured".into())
     }
 
-    pub async fn get_comments(&self, post_id: &str) -> Result<Vec<Comment>, Box<dyn std::error::Error>> {
-     
-        if let Some(cache) = &self.cache_service {
-            match cache.get_cached_comments(post_id) {
-                Ok(Some(comments)) => {
-                    info!("⚡ Retrieve {} comments from Redis cache", comments.len());
-                    return Ok(comments);
-                },
-                Ok(None) => {
-                    info!("📭 Comment cache miss, query database");
-                },
-                Err(e) => {
-                    info!("⚠️ Redis cache query failed: {}", e);
+    // Get active users ranking
+    pub async fn get_active_users_ranking(&self, limit: u32) -> Vec<User> {
+        let mut users = match self.store.get_active_users_ranking(limit as i64).await {
+            Ok(users) => {
+                info!("📊 Retrieved {} active users", users.len());
+                users
+            },
+            Err(e) => {
+                info!("⚠️ Active users query failed: {}", e);
+                return Vec::new();
+            }
+        };
+
+        // Try to complete real username: if name is empty or default alias user_XXXX, query and sync username
+        for user in &mut users {
+            let needs_lookup = match &user.name {
+                None => true,
+                Some(n) => n.is_empty() || n.starts_with("user_"),
+            };
+            if needs_lookup {
+                if let Ok(Some(username)) = self.get_username_by_address(&user.address).await {
+                    user.name = Some(username);
                 }
             }
         }
 
-        if let Some(db) = &self.database_service {
-            match db.get_comments_by_post_id(post_id).await {
-                Ok(comments) => {
-                    info!("📊 Retrieved {} comments from database", comments.len());
-                    
-                   
-                    if let Some(cache) = &self.cache_service {
-                        if let Err(e) = cache.cache_comments(post_id, &comments) {
-                            info!("⚠️ Failed to cache comments: {}", e);
-                        } else {
-                            info!("💾 Comments cached to Redis");
-                        }
-                    }
-                    
-                    return Ok(comments);
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, using in-memory data: {}", e);
+        users
+    }
+
+    // Get global stats
+    pub async fn get_global_stats(&self) -> GlobalStats {
+        match self.store.get_global_stats().await {
+            Ok(stats) => {
+                info!("📊 Retrieved global stats: users={}, posts={}, comments={}, likes={}", stats.total_users, stats.total_posts, stats.total_comments, stats.total_likes);
+                stats
+            },
+            Err(e) => {
+                info!("⚠️ Global stats query failed: {}", e);
+                GlobalStats {
+                    total_users: 0,
+                    total_posts: 0,
+                    total_comments: 0,
+                    total_likes: 0,
                 }
             }
         }
-        
-        // Fallback to memory data
-        let comments = self.comments.lock().unwrap();
-        let post_comments: Vec<Comment> = comments
-            .values()
-            .filter(|comment| comment.post_id == post_id)
-            .cloned()
-            .collect();
-        info!("📊 Retrieve {} comments from memory", post_comments.len());
-        Ok(post_comments)
     }
 
-    pub async fn get_user_profile(&self, address: &str) -> Option<User> {
-        
-        if let Some(db) = &self.database_service {
-            match db.get_user_by_address(address).await {
-                Ok(Some(user)) => {
-                    info!("📊 Retrieved user profile from database: {} (posts: {}, comments: {}, reputation: {})", address, user.posts_count, user.comments_count, user.reputation);
-                    return Some(user);
-                },
-                Ok(None) => {
-                    info!("📊 User not found in database: {}", address);
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed: {}", e);
-                }
-            }
-        }
-        
-        
-        let users = self.users.lock().unwrap();
-        let posts = self.posts.lock().unwrap();
-        let comments = self.comments.lock().unwrap();
-        
-        
-        let actual_post_count = posts.values().filter(|post| post.author_address == address).count();
-        let actual_comment_count = comments.values().filter(|comment| comment.author_address == address).count();
-        
-        info!("📊 In-memory stats for user {} - posts: {}, comments: {}", address, actual_post_count, actual_comment_count);
-        
-        
-        if let Some(mut user) = users.get(address).cloned() {
-            
-            user.posts_count = actual_post_count as u32;
-            user.comments_count = actual_comment_count as u32;
-            user.reputation = (actual_post_count * 10 + actual_comment_count * 5) as u32;
-            Some(user)
-        } else if actual_post_count > 0 || actual_comment_count > 0 {
-            
-            Some(User {
-                id: "temp".to_string(),
-                address: address.to_string(),
-                name: None,
-                avatar: None,
-                bio: None,
-                created_at: Utc::now(),
-                posts_count: actual_post_count as u32,
-                comments_count: actual_comment_count as u32,
-                reputation: (actual_post_count * 10 + actual_comment_count * 5) as u32,
-            })
-        } else {
-            None
+    
+    pub async fn like_post(&self, post_id: &str, user_address: &str) -> Result<u32, Box<dyn std::error::Error>> {
+        match self.store.like_post(post_id, user_address).await {
+            Ok(new_likes) => {
+                info!("📊 Like succeeded: post {} new likes {}", post_id, new_likes);
+                self.notify_post_like(post_id, user_address).await;
+                Ok(new_likes)
+            },
+            Err(sqlx::Error::RowNotFound) => Err("Post not found".into()),
+            Err(e) => Err(e.into()),
         }
     }
 
-    async fn update_user_stats(&self, address: &str, is_post: bool, is_comment: bool) {
-        let mut users = self.users.lock().unwrap();
-        
-        let user = users.entry(address.to_string()).or_insert_with(|| User {
-            id: "temp".to_string(),
-            address: address.to_string(),
-            name: None,
-            avatar: None,
-            bio: None,
-            created_at: Utc::now(),
-            posts_count: 0,
-            comments_count: 0,
-            reputation: 0,
-        });
-
-        if is_post {
-            user.posts_count += 1;
-            user.reputation += 10;
-        }
-        
-        if is_comment {
-            user.comments_count += 1;
-            user.reputation += 5;
+    /// `like_post` toggles, so notify the post's author or retract the notification depending on the new state
+    async fn notify_post_like(&self, post_id: &str, user_address: &str) {
+        let store = self.store.as_ref();
+        let Ok(Some(post)) = store.get_post_by_id(post_id).await else {
+            return;
+        };
+        let notification = NewNotification::post_liked(&post.author_address, user_address, post_id);
+        match store.has_user_liked_post(post_id, user_address).await {
+            Ok(true) => notifications::notify(store, notification).await,
+            Ok(false) => notifications::retract(store, notification).await,
+            Err(e) => info!("⚠️ Failed to check like status for notification: {}", e),
         }
     }
 
-    pub async fn upload_to_irys(&self, request: IrysUploadRequest) -> Result<String, Box<dyn std::error::Error>> {
-        self.irys_service.upload_data(&request.data, request.tags, &request.address).await
-    }
-
-    // Get active users ranking
-    pub async fn get_active_users_ranking(&self, limit: u32) -> Vec<User> {
-        
-        if let Some(db) = &self.database_service {
-            match db.get_active_users_ranking(limit as i64).await {
-                Ok(mut users) => {
-                    info!("📊 Retrieved {} active users from database", users.len());
-
-                    // Try to complete real username: if name is empty or default alias user_XXXX, query and sync username
-                    for user in &mut users {
-                        let needs_lookup = match &user.name {
-                            None => true,
-                            Some(n) => n.is_empty() || n.starts_with("user_"),
-                        };
-                        if needs_lookup {
-                            if let Ok(Some(username)) = self.get_username_by_address(&user.address).await {
-                                user.name = Some(username);
-                            }
-                        }
-                    }
-
-                    return users;
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, using in-memory data: {}", e);
-                }
-            }
+    /// Bookmark a live post; bookmarking it again is a no-op
+    pub async fn bookmark_post(&self, post_id: &str, user_address: &str) -> Result<(), ContentActionError> {
+        if self.store.get_post_by_id(post_id).await?.is_none() {
+            return Err(ContentActionError::NotFound("Post not found".to_string()));
         }
-        
-        // Fallback to memory data
-        let users = self.users.lock().unwrap();
-        let mut user_list: Vec<User> = users.values().cloned().collect();
-        
-        // Sort by reputation
-        user_list.sort_by(|a, b| {
-            b.reputation.cmp(&a.reputation)
-                .then(b.posts_count.cmp(&a.posts_count))
-                .then(b.comments_count.cmp(&a.comments_count))
-        });
-        
-        // Only return users with activity
-        let mut user_list: Vec<User> = user_list
-            .into_iter()
-            .filter(|user| user.posts_count > 0 || user.comments_count > 0)
-            .take(limit as usize)
-            .collect();
-
-        // Sync and complete username
-        for user in &mut user_list {
-            let needs_lookup = match &user.name {
-                None => true,
-                Some(n) => n.is_empty() || n.starts_with("user_"),
-            };
-            if needs_lookup {
-                if let Ok(Some(username)) = self.get_username_by_address(&user.address).await {
-                    user.name = Some(username);
-                }
-            }
+        if self.store.add_bookmark(user_address, post_id).await? {
+            info!("🔖 Post {} bookmarked by {}", post_id, user_address);
         }
-
-        user_list
+        Ok(())
     }
 
-    // Get global stats
-    pub async fn get_global_stats(&self) -> GlobalStats {
-        
-        if let Some(db) = &self.database_service {
-            match db.get_global_stats().await {
-                Ok(stats) => {
-                    info!("📊 Retrieved global stats from database: users={}, posts={}, comments={}, likes={}", stats.total_users, stats.total_posts, stats.total_comments, stats.total_likes);
-                    return stats;
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, using in-memory data: {}", e);
-                }
-            }
-        }
-        
-        // Fallback to memory data
-        let users = self.users.lock().unwrap();
-        let posts = self.posts.lock().unwrap();
-        let comments = self.comments.lock().unwrap();
-        
-        let active_users = users.values().filter(|u| u.posts_count > 0 || u.comments_count > 0).count();
-        let total_likes = posts.values().map(|p| p.likes).sum();
-        
-        GlobalStats {
-            total_users: active_users as u32,
-            total_posts: posts.len() as u32,
-            total_comments: comments.len() as u32,
-            total_likes,
+    /// Remove a bookmark; removing one that does not exist is a no-op
+    pub async fn remove_bookmark(&self, post_id: &str, user_address: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.store.remove_bookmark(user_address, post_id).await? {
+            info!("🔖 Bookmark on post {} removed by {}", post_id, user_address);
         }
+        Ok(())
     }
 
-    
-    pub async fn like_post(&self, post_id: &str, user_address: &str) -> Result<u32, Box<dyn std::error::Error>> {
-        
-        if let Some(db) = &self.database_service {
-            match db.like_post(post_id, user_address).await {
-                Ok(new_likes) => {
-                    info!("📊 Database like succeeded: post {} new likes {}", post_id, new_likes);
-                    return Ok(new_likes);
-                },
-                Err(e) => {
-                    info!("⚠️ Database like failed, using in-memory storage: {}", e);
-                }
-            }
-        }
-        
-        
-        let mut posts = self.posts.lock().unwrap();
-        if let Some(post) = posts.get_mut(post_id) {
-            post.likes += 1;
-            Ok(post.likes)
-        } else {
-            Err("Post not found".into())
+    /// The user's bookmarked posts, most recently bookmarked first
+    pub async fn get_bookmarks(&self, user_address: &str, page: &PageRequest) -> Result<Page<BookmarkedPost>, Box<dyn std::error::Error>> {
+        let page = PageRequest { limit: page.limit.clamp(1, BOOKMARKS_MAX_LIMIT), ..page.clone() };
+        let mut bookmarks = self.store.get_bookmarks(user_address, &page).await?;
+        let mut posts: Vec<Post> = bookmarks.items.iter().map(|b| b.post.clone()).collect();
+        mentions::attach_to_posts(self.store.as_ref(), &mut posts).await;
+        for (bookmark, post) in bookmarks.items.iter_mut().zip(posts) {
+            bookmark.post = post;
         }
+        Ok(bookmarks)
     }
 
     pub async fn query_irys(&self, address: Option<String>, tags: Option<Vec<String>>, limit: Option<u32>) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
@@ -744,17 +720,13 @@ impl ForumService {
             match blockchain.user_has_username_on_chain(address).await {
                 Ok(true) => {
                     info!("⚠️ User already has a username on-chain: {}", address);
-                    // If username exists on-chain, try to sync to database
-                    if let Some(ref db) = self.database_service {
-                        if let Ok(Some(chain_username)) = blockchain.get_username_by_address_on_chain(address).await {
-                            info!("📊 Sync on-chain username to database: {} -> {}", address, chain_username);
-                            // Ensure user exists in database
-                            db.ensure_user_exists(address, &None).await?;
-                            // Update username in database
-                            let _ = db.register_username(address, &chain_username).await;
-                            // Return success because username already exists and is synced
-                            return Ok(true);
-                        }
+                    // If username exists on-chain, try to sync to storage
+                    if let Ok(Some(chain_username)) = blockchain.get_username_by_address_on_chain(address).await {
+                        info!("📊 Sync on-chain username to storage: {} -> {}", address, chain_username);
+                        self.store.ensure_user_exists(address, &None).await?;
+                        let _ = self.store.register_username(address, &chain_username).await;
+                        // Return success because username already exists and is synced
+                        return Ok(true);
                     }
                     return Ok(false);
                 }
@@ -767,77 +739,57 @@ impl ForumService {
             }
         }
 
-        // Then register in database
-        if let Some(ref db) = self.database_service {
-            match db.register_username(address, username).await {
-                Ok(success) => Ok(success),
-                Err(e) => {
-                    info!("⚠️ Database username registration failed: {}", e);
-                    Err(e.into())
-                }
+        // Then register in storage
+        match self.store.register_username(address, username).await {
+            Ok(success) => Ok(success),
+            Err(e) => {
+                info!("⚠️ Username registration failed: {}", e);
+                Err(e.into())
             }
-        } else {
-            Err("Database service unavailable".into())
         }
     }
     
     // Check if username is available
     pub async fn is_username_available(&self, username: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            match db.is_username_available(username).await {
-                Ok(available) => Ok(available),
-                Err(e) => {
-                    info!("⚠️ Database check username failed: {}", e);
-                    Err(e.into())
-                }
+        match self.store.is_username_available(username).await {
+            Ok(available) => Ok(available),
+            Err(e) => {
+                info!("⚠️ Check username failed: {}", e);
+                Err(e.into())
             }
-        } else {
-            Err("Database service unavailable".into())
         }
     }
     
     // Get username by address
     pub async fn get_username_by_address(&self, address: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        // First get from database
-        if let Some(ref db) = self.database_service {
-            match db.get_username_by_address(address).await {
-                Ok(Some(username)) => Ok(Some(username)),
-                Ok(None) => {
-                    // No username in database, try to get from chain
-                    if let Some(ref blockchain) = self.blockchain_service {
-                        match blockchain.get_username_by_address_on_chain(address).await {
-                            Ok(Some(chain_username)) => {
-                                info!("📊 Fetched username from chain and synced to database: {} -> {}", address, chain_username);
-                                // Sync to database
-                                self.sync_username_from_chain(address).await?;
-                                Ok(Some(chain_username))
-                            }
-                            Ok(None) => Ok(None),
-                            Err(e) => {
-                                info!("⚠️ Failed to fetch username from chain: {}", e);
-                                Ok(None)
-                            }
+        // First get from storage
+        match self.store.get_username_by_address(address).await {
+            Ok(Some(username)) => Ok(Some(username)),
+            Ok(None) => {
+                // No username stored, try to get from chain
+                if let Some(ref blockchain) = self.blockchain_service {
+                    // Settle the chain lookup first: its error must not be held across the sync below
+                    let chain_username = match blockchain.get_username_by_address_on_chain(address).await {
+                        Ok(chain_username) => chain_username,
+                        Err(e) => {
+                            info!("⚠️ Failed to fetch username from chain: {}", e);
+                            None
                         }
-                    } else {
-                        Ok(None)
+                    };
+                    match chain_username {
+                        Some(chain_username) => {
+                            info!("📊 Fetched username from chain and synced to storage: {} -> {}", address, chain_username);
+                            self.sync_username_from_chain(address).await?;
+                            Ok(Some(chain_username))
+                        }
+                        None => Ok(None),
                     }
-                },
-                Err(e) => {
-                    info!("⚠️ Failed to get username from database: {}", e);
+                } else {
                     Ok(None)
                 }
-            }
-        } else {
-            // Database unavailable, try to get from chain
-            if let Some(ref blockchain) = self.blockchain_service {
-                match blockchain.get_username_by_address_on_chain(address).await {
-                    Ok(username) => Ok(username),
-                    Err(e) => {
-                        info!("⚠️ Failed to fetch username from chain: {}", e);
-                        Ok(None)
-                    }
-                }
-            } else {
+            },
+            Err(e) => {
+                info!("⚠️ Failed to get username from storage: {}", e);
                 Ok(None)
             }
         }
@@ -845,26 +797,20 @@ impl ForumService {
     
     // Check if user has registered username
     pub async fn user_has_username(&self, address: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        // First check database
-        if let Some(ref db) = self.database_service {
-            match db.user_has_username(address).await {
-                Ok(has_username) => {
-                    if has_username {
-                        return Ok(true);
-                    }
-                },
-                Err(e) => {
-                    info!("⚠️ Database check username status failed: {}", e);
-                }
+        // First check storage
+        match self.store.user_has_username(address).await {
+            Ok(true) => return Ok(true),
+            Ok(false) => {}
+            Err(e) => {
+                info!("⚠️ Check username status failed: {}", e);
             }
         }
 
-        // If database has no username, check on-chain status
+        // If no username is stored, check on-chain status
         if let Some(ref blockchain) = self.blockchain_service {
             match blockchain.user_has_username_on_chain(address).await {
                 Ok(true) => {
-                    info!("📊 Found username on chain, sync to database: {}", address);
-                    // Sync username from chain to database
+                    info!("📊 Found username on chain, sync to storage: {}", address);
                     self.sync_username_from_chain(address).await?;
                     Ok(true)
                 }
@@ -879,17 +825,14 @@ impl ForumService {
         }
     }
 
-    // Sync username from chain to database
+    // Sync username from chain to storage
     async fn sync_username_from_chain(&self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(ref blockchain) = self.blockchain_service {
-            if let Some(ref db) = self.database_service {
-                if let Ok(Some(chain_username)) = blockchain.get_username_by_address_on_chain(address).await {
-                    info!("📊 Sync on-chain username to database: {} -> {}", address, chain_username);
-                    // Ensure user exists in database
-                    db.ensure_user_exists(address, &None).await?;
-                    // Update username in database
-                    let _ = db.register_username(address, &chain_username).await;
-                }
+            let chain_username = blockchain.get_username_by_address_on_chain(address).await.ok().flatten();
+            if let Some(chain_username) = chain_username {
+                info!("📊 Sync on-chain username to storage: {} -> {}", address, chain_username);
+                self.store.ensure_user_exists(address, &None).await?;
+                let _ = self.store.register_username(address, &chain_username).await;
             }
         }
         Ok(())
@@ -897,12 +840,8 @@ impl ForumService {
 
     // Check if transaction is used
     pub async fn is_transaction_used(&self, tx_hash: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        if let Some(database_service) = &self.database_service {
-            database_service.is_transaction_used(tx_hash).await
-                .map_err(|e| e.into())
-        } else {
-            Err("Database service unavailable".into())
-        }
+        self.store.is_transaction_used(tx_hash).await
+            .map_err(|e| e.into())
     }
     
     // Verify blockchain post transaction
@@ -912,20 +851,23 @@ impl ForumService {
         expected_sender: &str
     ) -> Result<crate::blockchain::PostTransactionVerification, Box<dyn std::error::Error>> {
         if let Some(blockchain_service) = &self.blockchain_service {
-            blockchain_service.verify_post_transaction(tx_hash, expected_sender).await
+            Ok(blockchain_service.verify_post_transaction(tx_hash, expected_sender).await?)
         } else {
             Err("Blockchain service unavailable".into())
         }
     }
     
-    // Verify blockchain comment transaction
+    // Verify blockchain comment transaction against the target post's on-chain ID
     pub async fn verify_blockchain_comment_transaction(
         &self, 
         tx_hash: &str, 
-        expected_sender: &str
+        expected_sender: &str,
+        post_id: &str
     ) -> Result<crate::blockchain::CommentTransactionVerification, Box<dyn std::error::Error>> {
         if let Some(blockchain_service) = &self.blockchain_service {
-            blockchain_service.verify_comment_transaction(tx_hash, expected_sender).await
+            let post = self.get_post(post_id).await.ok_or("Post not found")?;
+            let expected_post_id = on_chain_post_id(&post)?;
+            Ok(blockchain_service.verify_comment_transaction(tx_hash, expected_sender, expected_post_id).await?)
         } else {
             Err("Blockchain service unavailable".into())
         }
@@ -937,21 +879,53 @@ impl ForumService {
         request: CreatePostRequest,
         verification: crate::blockchain::PostTransactionVerification
     ) -> Result<Post, Box<dyn std::error::Error>> {
+        self.create_verified_post(Self::generate_id(), request, verification).await
+    }
+
+    /// Checks and creation shared by `create_post_with_verification` and queued posts. The queue
+    /// passes its task ID as `post_id`, so a retried task finishes its post instead of adding another.
+    async fn create_verified_post(
+        &self,
+        post_id: String,
+        mut request: CreatePostRequest,
+        verification: crate::blockchain::PostTransactionVerification
+    ) -> Result<Post, Box<dyn std::error::Error>> {
+        if let Some(post) = self.store.get_post_by_id(&post_id).await? {
+            self.record_post_transaction(&post.id, request.blockchain_transaction_hash.as_deref(), &verification).await?;
+            return Ok(post);
+        }
+
+        check_not_suspended(self.store.as_ref(), &request.author_address).await?;
+        self.check_transaction_unused(request.blockchain_transaction_hash.as_deref()).await?;
+        request.image = self.resolve_image(request.image.take()).await?;
     
-        if let Some(database_service) = &self.database_service {
-            match database_service.check_duplicate_post(&request.author_address, &request.content).await {
-                Ok(true) => {
-                    return Err("You have posted the same content within the last 5 minutes. Please avoid duplicate posts.".into());
-                }
-                Ok(false) => {
-                    info!("✅ Post content deduplication check passed");
-                }
-                Err(e) => {
-                    info!("⚠️ Post content deduplication check failed, continuing: {}", e);
-                }
+        match self.store.check_duplicate_post(&request.author_address, &request.content).await {
+            Ok(true) => {
+                return Err(ContentActionError::Invalid("You have posted the same content within the last 5 minutes. Please avoid duplicate posts.".to_string()).into());
+            }
+            Ok(false) => {
+                info!("✅ Post content deduplication check passed");
+            }
+            Err(e) => {
+                info!("⚠️ Post content deduplication check failed, continuing: {}", e);
             }
         }
 
+        spam::screen(self.store.as_ref(), self.spam_scorer.as_ref(), &SpamCandidate::post(&request), || {
+            HeldSubmission::Post { request: request.clone(), verification: Some(verification.clone()) }
+        }).await?;
+        self.publish_post(post_id, request, Some(&verification)).await
+    }
+
+    /// Create a post that passed the checks in `create_verified_post` (or a held one a
+    /// moderator approved) and record its transaction. Only posts held before every post
+    /// needed a transaction come without a verification.
+    async fn publish_post(
+        &self,
+        post_id: String,
+        request: CreatePostRequest,
+        verification: Option<&crate::blockchain::PostTransactionVerification>
+    ) -> Result<Post, Box<dyn std::error::Error>> {
         let post_data = serde_json::to_string(&request)?;
         let tags = vec!["forum".to_string(), "post".to_string()];
         let author_address = request.author_address.clone();
@@ -966,9 +940,11 @@ impl ForumService {
         // Upload to Irys
         let tx_id = self.irys_service.upload_data(&post_data, tags, &author_address).await?;
         
-        let post = Post {
-            id: Self::generate_id(),
+        let tags = normalize_tags(&request.tags, &request.content);
+        let mut post = Post {
+            id: post_id,
             title: request.title,
+            content_html: markdown::render(&request.content),
             content: request.content,
             author_address: request.author_address.clone(),
             author_id: None, 
@@ -978,53 +954,73 @@ impl ForumService {
             updated_at: Utc::now(),
             likes: 0,
             comments_count: 0,
-            tags: request.tags,
-            irys_transaction_id: Some(tx_id),
+            tags,
+            irys_transaction_id: tx_id,
             image: request.image,
-            blockchain_post_id: request.blockchain_post_id,
+            blockchain_post_id: verification.and_then(|v| u32::try_from(v.post_id).ok()).filter(|id| *id != 0),
             is_liked_by_user: false, 
+            is_bookmarked_by_user: false,
             views: 0, // New post views count is 0
             heat_score: None, // Heat score will be calculated later
+            mentions: Vec::new(),
         };
+
+        self.store.create_post(&post).await?;
+        self.spam_scorer.record(ModerationTarget::Post, &post.id, &post.author_address, &post.content).await;
+        mentions::record_post_mentions(self.store.as_ref(), self.blockchain_service.as_ref(), &mut post).await;
+
+        if let Some(verification) = verification {
+            self.record_post_transaction(&post.id, request.blockchain_transaction_hash.as_deref(), verification).await?;
+        }
+
+        if let Some(cache) = &self.cache_service {
+            if let Err(e) = cache.invalidate_post_cache().await {
+                info!("⚠️ Failed to clear post cache: {}", e);
+            } else {
+                info!("🗑️ Cleared post list cache");
+            }
+        }
         
-                      
-         if let Some(database_service) = &self.database_service {
-            
-             database_service.create_post(&post).await?;
-             
-            
-             if let Some(tx_hash) = &request.blockchain_transaction_hash {
-                 database_service.update_post_blockchain_hash(&post.id, tx_hash).await?;
-                 
-              
-                 let block_timestamp = chrono::DateTime::from_timestamp(
-                     verification.block_timestamp.as_u64() as i64, 0
-                 ).unwrap_or_else(|| Utc::now());
-                 
-                 database_service.record_post_transaction(
-                     tx_hash,
-                     &verification.sender,
-                     verification.block_number,
-                     block_timestamp,
-                     &post.id
-                 ).await?;
-             }
-             
-             
-             if let Some(cache) = &self.cache_service {
-                 if let Err(e) = cache.invalidate_post_cache() {
-                     info!("⚠️ Failed to clear post cache: {}", e);
-                 } else {
-                     info!("🗑️ Cleared post list cache (verified creation)");
-                 }
-             }
-         } else {
-             return Err("Database service unavailable".into());
-         }
-        
-        info!("✅ Post created successfully, blockchain transaction verified: {}", verification.transaction_hash);
+        info!("✅ Post created successfully: {} (transaction: {:?})", post.id, verification.map(|v| &v.transaction_hash));
         Ok(post)
     }
+
+    /// Link a created post to its transaction; skipped if an earlier attempt already did
+    async fn record_post_transaction(
+        &self,
+        post_id: &str,
+        tx_hash: Option<&str>,
+        verification: &crate::blockchain::PostTransactionVerification
+    ) -> Result<(), ContentActionError> {
+        let Some(tx_hash) = tx_hash else {
+            return Ok(());
+        };
+        self.store.update_post_blockchain_hash(post_id, tx_hash).await?;
+        if self.store.is_transaction_used(tx_hash).await? {
+            return Ok(());
+        }
+
+        let block_timestamp = chrono::DateTime::from_timestamp(
+            verification.block_timestamp.as_u64() as i64, 0
+        ).unwrap_or_else(Utc::now);
+        self.store.record_post_transaction(
+            tx_hash,
+            &verification.sender,
+            verification.block_number,
+            block_timestamp,
+            post_id
+        ).await?;
+        Ok(())
+    }
+
+    async fn check_transaction_unused(&self, tx_hash: Option<&str>) -> Result<(), ContentActionError> {
+        match tx_hash {
+            Some(tx_hash) if self.store.is_transaction_used(tx_hash).await? => {
+                Err(ContentActionError::Invalid("The transaction has already been used".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
     
     
     pub async fn add_comment_with_verification(
@@ -1032,21 +1028,52 @@ impl ForumService {
         request: CreateCommentRequest,
         verification: crate::blockchain::CommentTransactionVerification
     ) -> Result<Comment, Box<dyn std::error::Error>> {
-      
-        if let Some(database_service) = &self.database_service {
-            match database_service.check_duplicate_comment(&request.author_address, &request.content, &request.post_id).await {
-                Ok(true) => {
-                    return Err("You have posted the same comment within the last 5 minutes. Please avoid duplicate comments.".into());
-                }
-                Ok(false) => {
-                    info!("✅ Comment content deduplication check passed");
-                }
-                Err(e) => {
-                    info!("⚠️ Comment content deduplication check failed, continuing: {}", e);
-                }
+        self.create_verified_comment(Self::generate_id(), request, verification).await
+    }
+
+    /// Comment counterpart of `create_verified_post`
+    async fn create_verified_comment(
+        &self,
+        comment_id: String,
+        mut request: CreateCommentRequest,
+        verification: crate::blockchain::CommentTransactionVerification
+    ) -> Result<Comment, Box<dyn std::error::Error>> {
+        if let Some(comment) = self.store.get_comment_by_id(&comment_id).await? {
+            self.record_comment_transaction(&comment.id, request.blockchain_transaction_hash.as_deref(), &verification).await?;
+            return Ok(comment);
+        }
+
+        check_not_suspended(self.store.as_ref(), &request.author_address).await?;
+        check_comment_target(self.store.as_ref(), &request.post_id, request.parent_id.as_deref(), &request.author_address).await?;
+        self.check_transaction_unused(request.blockchain_transaction_hash.as_deref()).await?;
+        request.image = self.resolve_image(request.image.take()).await?;
+
+        match self.store.check_duplicate_comment(&request.author_address, &request.content, &request.post_id).await {
+            Ok(true) => {
+                return Err(ContentActionError::Invalid("You have posted the same comment within the last 5 minutes. Please avoid duplicate comments.".to_string()).into());
+            }
+            Ok(false) => {
+                info!("✅ Comment content deduplication check passed");
+            }
+            Err(e) => {
+                info!("⚠️ Comment content deduplication check failed, continuing: {}", e);
             }
         }
 
+        spam::screen(self.store.as_ref(), self.spam_scorer.as_ref(), &SpamCandidate::comment(&request), || {
+            HeldSubmission::Comment { request: request.clone(), verification: Some(verification.clone()) }
+        }).await?;
+        self.publish_comment(comment_id, request, Some(&verification)).await
+    }
+
+    /// Create a comment that passed the checks in `add_comment` or `create_verified_comment`
+    /// (or a held one a moderator approved) and record its transaction, if it came with one
+    async fn publish_comment(
+        &self,
+        comment_id: String,
+        request: CreateCommentRequest,
+        verification: Option<&crate::blockchain::CommentTransactionVerification>
+    ) -> Result<Comment, Box<dyn std::error::Error>> {
         let comment_data = serde_json::to_string(&request)?;
         let tags = vec!["forum".to_string(), "comment".to_string()];
         let author_address = request.author_address.clone();
@@ -1061,10 +1088,11 @@ impl ForumService {
  
         let tx_id = self.irys_service.upload_data(&comment_data, tags, &author_address).await?;
         
-        let comment = Comment {
-            id: Self::generate_id(),
+        let mut comment = Comment {
+            id: comment_id,
             post_id: request.post_id.clone(),
             content: request.content.clone(),
+            content_html: markdown::render(&request.content),
             author_address: request.author_address.clone(),
             author_id: None, 
             author_name: author_name.clone(),
@@ -1072,144 +1100,184 @@ impl ForumService {
             created_at: Utc::now(),
             parent_id: request.parent_id.clone(),
             likes: 0,
-            irys_transaction_id: Some(tx_id),
+            irys_transaction_id: tx_id,
             image: request.image.clone(),
             content_hash: Self::generate_content_hash(&request.content),
             is_liked_by_user: false,
+            is_deleted: false,
+            mentions: Vec::new(),
         };
+
+        self.store.add_comment(&comment).await?;
+        self.spam_scorer.record(ModerationTarget::Comment, &comment.id, &comment.author_address, &comment.content).await;
+        notifications::notify_comment(self.store.as_ref(), &comment).await;
+        mentions::record_comment_mentions(self.store.as_ref(), self.blockchain_service.as_ref(), &mut comment).await;
+
+        if let Some(verification) = verification {
+            self.record_comment_transaction(&comment.id, request.blockchain_transaction_hash.as_deref(), verification).await?;
+        }
+
+        if let Some(cache) = &self.cache_service {
+            if let Err(e) = cache.invalidate_comment_cache(&comment.post_id).await {
+                info!("⚠️ Failed to clear comment cache: {}", e);
+            } else {
+                info!("🗑️ Cleared post comment cache");
+            }
+        }
         
-               
-         if let Some(database_service) = &self.database_service {
-        
-             database_service.add_comment(&comment).await?;
-             
-          
-             if let Some(tx_hash) = &request.blockchain_transaction_hash {
-                 database_service.update_comment_blockchain_hash(&comment.id, tx_hash).await?;
-                 
-             
-                 let block_timestamp = chrono::DateTime::from_timestamp(
-                     verification.block_timestamp.as_u64() as i64, 0
-                 ).unwrap_or_else(|| Utc::now());
-                 
-                 database_service.record_comment_transaction(
-                     tx_hash,
-                     &verification.sender,
-                     verification.block_number,
-                     block_timestamp,
-                     &comment.id
-                 ).await?;
-             }
-             
-         
-             if let Some(cache) = &self.cache_service {
-                 if let Err(e) = cache.invalidate_comment_cache(&comment.post_id) {
-                     info!("⚠️ Failed to clear comment cache: {}", e);
-                 } else {
-                     info!("🗑️ Cleared post comment cache (verified creation)");
-                 }
-             }
-         } else {
-             return Err("Database service unavailable".into());
-         }
-        
-        info!("✅ Comment created successfully, blockchain transaction verified: {}", verification.transaction_hash);
+        info!("✅ Comment created successfully: {} (transaction: {:?})", comment.id, verification.map(|v| &v.transaction_hash));
         Ok(comment)
     }
-    
-    pub fn get_database_performance(&self) -> Option<serde_json::Value> {
-        if let Some(database_service) = &self.database_service {
-            Some(database_service.get_database_stats())
-        } else {
-            None
+
+    /// Comment counterpart of `record_post_transaction`
+    async fn record_comment_transaction(
+        &self,
+        comment_id: &str,
+        tx_hash: Option<&str>,
+        verification: &crate::blockchain::CommentTransactionVerification
+    ) -> Result<(), ContentActionError> {
+        let Some(tx_hash) = tx_hash else {
+            return Ok(());
+        };
+        self.store.update_comment_blockchain_hash(comment_id, tx_hash).await?;
+        if self.store.is_transaction_used(tx_hash).await? {
+            return Ok(());
         }
+
+        let block_timestamp = chrono::DateTime::from_timestamp(
+            verification.block_timestamp.as_u64() as i64, 0
+        ).unwrap_or_else(Utc::now);
+        self.store.record_comment_transaction(
+            tx_hash,
+            &verification.sender,
+            verification.block_number,
+            block_timestamp,
+            comment_id
+        ).await?;
+        Ok(())
+    }
+    
+    /// Storage backend name plus its stats (query timings for Postgres, row counts in memory)
+    pub fn get_storage_stats(&self) -> serde_json::Value {
+        serde_json::json!({
+            "backend": self.store.backend_name(),
+            "stats": self.store.get_database_stats()
+        })
     }
     
   
     pub fn has_cache_service(&self) -> bool {
         self.cache_service.is_some()
     }
-    
- 
-    pub fn get_memory_stats(&self) -> serde_json::Value {
-        serde_json::json!({
-            "posts_in_memory": self.posts.lock().unwrap().len(),
-            "comments_in_memory": self.comments.lock().unwrap().len(),
-            "users_in_memory": self.users.lock().unwrap().len()
-        })
+
+    pub fn cache_service(&self) -> Option<Arc<crate::cache::CacheService>> {
+        self.cache_service.clone()
     }
     
     
-    pub async fn create_post_async(&self, request: CreatePostRequest) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn create_post_async(&self, mut request: CreatePostRequest) -> Result<String, Box<dyn std::error::Error>> {
+        request.image = self.resolve_image(request.image.take()).await?;
+        let tx_hash = request.blockchain_transaction_hash.clone()
+            .ok_or("Missing blockchain transaction hash")?;
         if let Some(async_queue) = &self.async_queue_service {
-            if let Some(tx_hash) = request.blockchain_transaction_hash.clone() {
-                // Submit to async queue
-                let task_id = async_queue.submit_post_creation(request, tx_hash).await?;
-                info!("🚀 Post creation task submitted to async queue: {}", task_id);
-                Ok(task_id)
-            } else {
-                Err("Missing blockchain transaction hash".into())
-            }
+            // Submit to async queue
+            let task_id = async_queue.submit_post_creation(request, tx_hash).await?;
+            info!("🚀 Post creation task submitted to async queue: {}", task_id);
+            Ok(task_id)
         } else {
             // Fallback to sync processing
-            self.create_post_with_verification(
-                request,
-                crate::blockchain::PostTransactionVerification {
-                    transaction_hash: "sync".to_string(),
-                    sender: "unknown".to_string(),
-                    block_number: 0,
-                    block_timestamp: ethers::types::U256::zero(),
-                    post_id: ethers::types::U256::zero(),
-                    points_earned: ethers::types::U256::zero(),
-                    value_paid: ethers::types::U256::zero(),
-                    gas_used: ethers::types::U256::zero(),
-                    verified: true,
-                }
-            ).await.map(|post| post.id)
+            let verification = self.verify_blockchain_post_transaction(&tx_hash, &request.author_address).await?;
+            self.create_post_with_verification(request, verification).await.map(|post| post.id)
         }
     }
     
     // Asynchronous create comment - immediately return task ID
-    pub async fn create_comment_async(&self, request: CreateCommentRequest) -> Result<String, Box<dyn std::error::Error>> {
+    pub async fn create_comment_async(&self, mut request: CreateCommentRequest) -> Result<String, Box<dyn std::error::Error>> {
+        check_comment_target(self.store.as_ref(), &request.post_id, request.parent_id.as_deref(), &request.author_address).await?;
+        request.image = self.resolve_image(request.image.take()).await?;
+        let tx_hash = request.blockchain_transaction_hash.clone()
+            .ok_or("Missing blockchain transaction hash")?;
         if let Some(async_queue) = &self.async_queue_service {
-            if let Some(tx_hash) = request.blockchain_transaction_hash.clone() {
-                // Submit to async queue
-                let task_id = async_queue.submit_comment_creation(request, tx_hash).await?;
-                info!("🚀 Comment creation task submitted to async queue: {}", task_id);
-                Ok(task_id)
-            } else {
-                Err("Missing blockchain transaction hash".into())
-            }
+            // Submit to async queue
+            let task_id = async_queue.submit_comment_creation(request, tx_hash).await?;
+            info!("🚀 Comment creation task submitted to async queue: {}", task_id);
+            Ok(task_id)
         } else {
             // Fallback to sync processing
-            self.add_comment_with_verification(
-                request,
-                crate::blockchain::CommentTransactionVerification {
-                    transaction_hash: "sync".to_string(),
-                    sender: "unknown".to_string(),
-                    block_number: 0,
-                    block_timestamp: ethers::types::U256::zero(),
-                    comment_id: ethers::types::U256::zero(),
-                    post_id: ethers::types::U256::zero(),
-                    points_earned: ethers::types::U256::zero(),
-                    value_paid: ethers::types::U256::zero(),
-                    gas_used: ethers::types::U256::zero(),
-                    verified: true,
-                }
-            ).await.map(|comment| comment.id)
+            let verification = self.verify_blockchain_comment_transaction(&tx_hash, &request.author_address, &request.post_id).await?;
+            self.add_comment_with_verification(request, verification).await.map(|comment| comment.id)
+        }
+    }
+
+    /// Start the queue workers; they create queued posts and comments through this service
+    pub fn start_queue_workers(self: &Arc<Self>) {
+        if let Some(async_queue) = &self.async_queue_service {
+            async_queue.start_workers(self.clone());
+        }
+    }
+
+    /// Verify a queued post's transaction and create it with the task ID as post ID
+    async fn process_post_task(&self, task_id: &str, request: &CreatePostRequest, tx_hash: &str) -> Result<Value, TaskError> {
+        let blockchain = self.blockchain_service.as_ref()
+            .ok_or_else(|| TaskError::Retryable("Blockchain service unavailable".to_string()))?;
+        let verification = blockchain.verify_post_transaction(tx_hash, &request.author_address).await
+            .map_err(verification_task_error)?;
+        info!("✅ Blockchain verification succeeded: {}", verification.transaction_hash);
+
+        let mut request = request.clone();
+        request.blockchain_transaction_hash = Some(tx_hash.to_string());
+        match self.create_verified_post(task_id.to_string(), request, verification).await {
+            Ok(post) => Ok(serde_json::json!({
+                "success": true,
+                "message": "Post created successfully",
+                "post_id": post.id,
+                "transaction_hash": tx_hash
+            })),
+            Err(e) => creation_task_result(e),
+        }
+    }
+
+    /// Comment counterpart of `process_post_task`
+    async fn process_comment_task(&self, task_id: &str, request: &CreateCommentRequest, tx_hash: &str) -> Result<Value, TaskError> {
+        let blockchain = self.blockchain_service.as_ref()
+            .ok_or_else(|| TaskError::Retryable("Blockchain service unavailable".to_string()))?;
+        let post = self.store.get_post_by_id(&request.post_id).await
+            .map_err(|e| TaskError::Retryable(format!("Failed to load post: {}", e)))?
+            .ok_or_else(|| TaskError::Permanent("Post not found".to_string()))?;
+        let expected_post_id = on_chain_post_id(&post).map_err(verification_task_error)?;
+        let verification = blockchain.verify_comment_transaction(tx_hash, &request.author_address, expected_post_id).await
+            .map_err(verification_task_error)?;
+        info!("✅ Blockchain verification succeeded: {}", verification.transaction_hash);
+
+        let mut request = request.clone();
+        request.blockchain_transaction_hash = Some(tx_hash.to_string());
+        match self.create_verified_comment(task_id.to_string(), request, verification).await {
+            Ok(comment) => Ok(serde_json::json!({
+                "success": true,
+                "message": "Comment created successfully",
+                "comment_id": comment.id,
+                "post_id": comment.post_id,
+                "transaction_hash": tx_hash
+            })),
+            Err(e) => creation_task_result(e),
         }
     }
     
     // Query async task status
     pub async fn get_task_status(&self, task_id: &str) -> Option<serde_json::Value> {
         if let Some(async_queue) = &self.async_queue_service {
-            if let Some(result) = async_queue.get_task_status(task_id).await {
+            if let Some(task) = async_queue.get_task_status(task_id).await {
+                let next_attempt_at = (task.status == crate::async_queue::TaskStatus::Pending).then_some(task.run_at);
                 Some(serde_json::json!({
                     "task_id": task_id,
-                    "status": format!("{:?}", result.status),
-                    "result": result.result_data,
-                    "created_at": result.created_at,
-                    "completed_at": result.completed_at
+                    "kind": task.task.kind(),
+                    "status": format!("{:?}", task.status),
+                    "result": task.result,
+                    "attempts": task.attempts,
+                    "last_error": task.last_error,
+                    "next_attempt_at": next_attempt_at,
+                    "created_at": task.created_at,
+                    "completed_at": task.completed_at
                 }))
             } else {
                 None
@@ -1221,335 +1289,1515 @@ impl ForumService {
     
     // Like comment
     pub async fn like_comment(&self, comment_id: &str, user_address: &str) -> Result<(u32, bool), Box<dyn std::error::Error + Send + Sync>> {
-        // Call database service to update like count
-        if let Some(db) = &self.database_service {
-            db.like_comment(comment_id, user_address).await
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
-        } else {
-            Err("Database service unavailable".into())
+        let (likes, liked) = self.store.like_comment(comment_id, user_address).await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        if let Ok(Some(comment)) = self.store.get_comment_by_id(comment_id).await {
+            let notification = NewNotification::comment_liked(&comment.author_address, user_address, &comment);
+            if liked {
+                notifications::notify(self.store.as_ref(), notification).await;
+            } else {
+                notifications::retract(self.store.as_ref(), notification).await;
+            }
         }
+        Ok((likes, liked))
     }
     
-    // Get comments with like status
-    pub async fn get_comments_with_like_status(&self, post_id: &str, user_address: Option<&str>) -> Result<Vec<Comment>, Box<dyn std::error::Error>> {
-        if let Some(db) = &self.database_service {
-            match db.get_comments_by_post_id(post_id).await {
-                Ok(mut comments) => {
-                    // If user address is provided, check like status for each comment
-                    if let Some(user_addr) = user_address {
-                        for comment in &mut comments {
-                            if let Ok(is_liked) = db.check_comment_liked(&comment.id, user_addr).await {
-                                comment.is_liked_by_user = is_liked;
-                            }
-                        }
-                    }
-                    Ok(comments)
-                }
-                Err(e) => Err(Box::new(e))
+    // Get comments with like status (paginated version)
+    pub async fn get_comments_with_like_status_paginated(&self, post_id: &str, user_address: Option<&str>, page: &PageRequest) -> Result<Page<Comment>, Box<dyn std::error::Error>> {
+        let mut comments = self.store.get_comments_by_post_id_paginated(post_id, page).await?;
+        self.apply_comment_like_status(&mut comments.items, user_address).await;
+        mentions::attach_to_comments(self.store.as_ref(), &mut comments.items).await;
+        Ok(comments)
+    }
+
+    /// Nested comment thread under `parent_id` (top level if `None`), `limit` comments per level and
+    /// `max_depth` levels deep, continuing after `cursor` at the first level
+    pub async fn get_comment_tree(
+        &self,
+        post_id: &str,
+        parent_id: Option<&str>,
+        cursor: Option<&str>,
+        limit: u32,
+        max_depth: u32,
+        user_address: Option<&str>,
+    ) -> Result<CommentTree, ContentActionError> {
+        let after = cursor
+            .map(|c| PageCursor::decode(c).ok_or_else(|| ContentActionError::Invalid("Invalid cursor".to_string())))
+            .transpose()?;
+        let limit = limit.clamp(1, COMMENT_TREE_MAX_LIMIT);
+        let max_depth = max_depth.clamp(1, COMMENT_TREE_MAX_DEPTH);
+        if self.store.get_post_by_id(post_id).await?.is_none() {
+            return Err(ContentActionError::NotFound("Post not found".to_string()));
+        }
+
+        let mut rows = self.store.get_comment_tree(post_id, parent_id, after.as_ref(), limit, max_depth).await?;
+        mentions::attach_to_comments(self.store.as_ref(), rows.iter_mut().map(|row| &mut row.comment)).await;
+        let mut children: HashMap<Option<String>, Vec<CommentTreeRow>> = HashMap::new();
+        for mut row in rows {
+            if let (Some(user_addr), false) = (user_address, row.comment.is_deleted) {
+                row.comment.is_liked_by_user = self.store.check_comment_liked(&row.comment.id, user_addr).await.unwrap_or(false);
             }
-        } else {
-            
-            let comments_map = self.comments.lock().unwrap();
-            let comments: Vec<Comment> = comments_map.values()
-                .filter(|comment| comment.post_id == post_id)
-                .cloned()
-                .collect();
-            Ok(comments)
+            children.entry(row.comment.parent_id.clone()).or_default().push(row);
         }
+
+        let mut comments = build_comment_nodes(&mut children, parent_id.map(str::to_string), 1);
+        let next_cursor = if comments.len() > limit as usize {
+            comments.truncate(limit as usize);
+            comments.last().map(|last| last.comment.page_cursor().encode())
+        } else {
+            None
+        };
+        Ok(CommentTree { comments, next_cursor })
     }
 
-    // Get comments with like status (paginated version)
-    pub async fn get_comments_with_like_status_paginated(&self, post_id: &str, user_address: Option<&str>, limit: u32, offset: u32) -> Result<Vec<Comment>, Box<dyn std::error::Error>> {
-        if let Some(db) = &self.database_service {
-            match db.get_comments_by_post_id_paginated(post_id, limit, offset).await {
-                Ok(mut comments) => {
-                    // If user address is provided, check like status for each comment
-                    if let Some(user_addr) = user_address {
-                        for comment in &mut comments {
-                            if let Ok(is_liked) = db.check_comment_liked(&comment.id, user_addr).await {
-                                comment.is_liked_by_user = is_liked;
-                            }
-                        }
-                    }
-                    Ok(comments)
+    // If user address is provided, check like status for each comment
+    async fn apply_comment_like_status(&self, comments: &mut [Comment], user_address: Option<&str>) {
+        if let Some(user_addr) = user_address {
+            for comment in comments.iter_mut() {
+                if let Ok(is_liked) = self.store.check_comment_liked(&comment.id, user_addr).await {
+                    comment.is_liked_by_user = is_liked;
                 }
-                Err(e) => Err(Box::new(e))
             }
-        } else {
-            // Simple pagination
-            let comments_map = self.comments.lock().unwrap();
-            let mut comments: Vec<Comment> = comments_map.values()
-                .filter(|comment| comment.post_id == post_id)
-                .cloned()
-                .collect();
-            
-            // Sort by time
-            comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-            
-            // Apply pagination
-            let start = offset as usize;
-            let end = (start + limit as usize).min(comments.len());
-            let paginated_comments = if start < comments.len() {
-                comments[start..end].to_vec()
-            } else {
-                Vec::new()
-            };
-            
-            Ok(paginated_comments)
         }
     }
     
     // Get user's own posts
-    pub async fn get_user_posts(&self, user_address: &str, limit: u32, offset: u32) -> Result<Vec<Post>, Box<dyn std::error::Error>> {
-        // 1. First get from database
-        if let Some(db) = &self.database_service {
-            match db.get_posts_by_user(user_address, limit, offset).await {
-                Ok(posts) => {
-                    info!("📊 Get user posts from database: {} (count: {})", user_address, posts.len());
-                    return Ok(posts);
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, using in-memory data: {}", e);
-                }
-            }
-        }
-        
-        // 2. Fallback to memory data
-        let posts = self.posts.lock().unwrap();
-        let mut user_posts: Vec<Post> = posts
-            .values()
-            .filter(|post| post.author_address.eq_ignore_ascii_case(user_address))
-            .cloned()
-            .collect();
-        
-            // Sort by creation time (latest first)
-        user_posts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-        
-        // Apply pagination
-        let start = offset as usize;
-        let end = (start + limit as usize).min(user_posts.len());
-        let paginated_posts = if start < user_posts.len() {
-            user_posts[start..end].to_vec()
-        } else {
-            Vec::new()
-        };
-        
-        info!("📊 Get user posts from memory: {} (count: {})", user_address, paginated_posts.len());
-        Ok(paginated_posts)
+    pub async fn get_user_posts(&self, user_address: &str, page: &PageRequest) -> Result<Page<Post>, Box<dyn std::error::Error>> {
+        let mut posts = self.store.get_posts_by_user(user_address, page).await?;
+        mentions::attach_to_posts(self.store.as_ref(), &mut posts.items).await;
+        info!("📊 Get user posts: {} (count: {})", user_address, posts.items.len());
+        Ok(posts)
     }
 
     // Get user posts (with like status)
-    pub async fn get_user_posts_with_like_status(&self, user_address: &str, limit: u32, offset: u32, request_user_address: Option<&str>) -> Result<Vec<Post>, Box<dyn std::error::Error>> {
-        // 1. First get from database
-        if let Some(db) = &self.database_service {
-            match db.get_posts_by_user_with_like_status(user_address, limit, offset, request_user_address).await {
-                Ok(posts) => {
-                    info!("📊 Get user posts from database (with like status): {} (count: {})", user_address, posts.len());
-                    return Ok(posts);
-                },
-                Err(e) => {
-                    info!("⚠️ Database query failed, fallback to no like status: {}", e);
-                    // Fallback to query without like status
-                    return self.get_user_posts(user_address, limit, offset).await;
-                }
+    pub async fn get_user_posts_with_like_status(&self, user_address: &str, page: &PageRequest, request_user_address: Option<&str>) -> Result<Page<Post>, Box<dyn std::error::Error>> {
+        match self.store.get_posts_by_user_with_like_status(user_address, page, request_user_address).await {
+            Ok(mut posts) => {
+                mentions::attach_to_posts(self.store.as_ref(), &mut posts.items).await;
+                info!("📊 Get user posts (with like status): {} (count: {})", user_address, posts.items.len());
+                Ok(posts)
+            },
+            Err(e) => {
+                info!("⚠️ User posts query failed, fallback to no like status: {}", e);
+                self.get_user_posts(user_address, page).await
             }
         }
-        
-        // 2. Fallback to memory data (without like status)
-        self.get_user_posts(user_address, limit, offset).await
     }
 
     // Get user address by ID
     pub async fn get_user_address_by_id(&self, user_id: &str) -> Result<String, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            match db.get_user_address_by_id(user_id).await {
-                Ok(address) => Ok(address),
-                Err(sqlx::Error::RowNotFound) => Err(format!("User ID {} does not exist", user_id).into()),
-                Err(e) => Err(e.into())
+        match self.store.get_user_address_by_id(user_id).await {
+            Ok(address) => Ok(address),
+            Err(sqlx::Error::RowNotFound) => Err(format!("User ID {} does not exist", user_id).into()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    /// Decode, re-encode and store an uploaded image (see `media::ingest`). Uploading a picture
+    /// that already exists returns the existing media.
+    pub async fn upload_media(&self, owner_address: &str, kind: MediaKind, data: Vec<u8>) -> Result<Media, ContentActionError> {
+        check_not_suspended(self.store.as_ref(), owner_address).await?;
+        let config = self.media_config.clone();
+        let owner = owner_address.to_string();
+        let media = tokio::task::spawn_blocking(move || media::ingest(&config, kind, &owner, &data))
+            .await
+            .map_err(|e| ContentActionError::Internal(format!("Image processing failed: {}", e)))??;
+
+        self.store.create_media(&media).await?;
+        info!("🖼️ Media stored: {} ({} {}x{}, {} files) by {}", media.id, media.source_format, media.width, media.height, media.variants.len(), owner_address);
+        Ok(self.store.get_media(&media.id).await?.unwrap_or(media))
+    }
+
+    pub async fn get_media(&self, media_id: &str) -> Result<Option<Media>, Box<dyn std::error::Error>> {
+        Ok(self.store.get_media(media_id).await?)
+    }
+
+    /// The URL stored with a post or comment for its `image`, which must name an uploaded
+    /// post image by ID (or by one of its URLs)
+    async fn resolve_image(&self, image: Option<String>) -> Result<Option<String>, ContentActionError> {
+        let Some(image) = image.filter(|image| !image.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let media = match media::parse_reference(&image) {
+            Some(media_id) => self.store.get_media(media_id).await?,
+            None => None,
+        };
+        match media {
+            Some(media) if media.kind == MediaKind::Post => Ok(Some(media.display_url())),
+            _ => Err(ContentActionError::Invalid("image must be the ID of an image uploaded to /api/media".to_string())),
+        }
+    }
+
+    /// Process an avatar upload and make it the user's avatar, removing the files of the one it
+    /// replaces. Returns the new avatar.
+    pub async fn update_avatar(&self, user_address: &str, data: Vec<u8>) -> Result<Media, ContentActionError> {
+        let media = self.upload_media(user_address, MediaKind::Avatar, data).await?;
+        let previous = self.store.get_user_by_address(user_address).await?.and_then(|user| user.avatar);
+        let avatar_url = media.display_url();
+        self.store.update_user_avatar(user_address, &avatar_url).await?;
+
+        if let Some(previous) = previous.filter(|previous| *previous != avatar_url) {
+            if let Err(e) = self.remove_avatar_files(&previous).await {
+                info!("⚠️ Failed to remove replaced avatar {}: {}", previous, e);
             }
-        } else {
-            Err("Database service unavailable".into())
         }
+        Ok(media)
     }
 
-    // Update user avatar
-    pub async fn update_user_avatar(&self, user_address: &str, avatar_url: &str) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(db) = &self.database_service {
-            db.update_user_avatar(user_address, avatar_url).await?;
+    /// Delete a replaced avatar's files unless another user still shows the same picture.
+    /// Handles both processed avatars and the raw files earlier versions wrote to `static/avatars`.
+    async fn remove_avatar_files(&self, avatar_url: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.store.count_users_with_avatar(avatar_url).await? > 0 {
+            return Ok(());
+        }
+        if let Some(media_id) = media::parse_reference(avatar_url) {
+            if self.store.get_media(media_id).await?.is_some_and(|m| m.kind == MediaKind::Avatar) {
+                self.store.delete_media(media_id).await?;
+                media::remove_files(&self.media_config, media_id)?;
+                info!("🗑️ Removed replaced avatar {}", media_id);
+            }
+        } else if let Some(file_name) = avatar_url.strip_prefix("/avatars/") {
+            let safe = !file_name.starts_with('.')
+                && file_name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+            if safe {
+                match std::fs::remove_file(std::path::Path::new("static/avatars").join(file_name)) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => info!("🗑️ Removed replaced avatar {}", file_name),
+                }
+            }
         }
         Ok(())
     }
 
     // Update user bio
     pub async fn update_user_bio(&self, user_address: &str, bio: &str) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(db) = &self.database_service {
-            db.update_user_bio(user_address, bio).await?;
-        }
+        self.store.update_user_bio(user_address, bio).await?;
         Ok(())
     }
 
     // Get daily recommendations
-    pub async fn get_daily_recommendations(&self, user_address: Option<&str>) -> Result<RecommendationResult, Box<dyn std::error::Error>> {
-        if let Some(db) = &self.database_service {
-            // Check if need to refresh recommendations
-            let should_refresh = db.should_refresh_daily_recommendations().await?;
+    /// Today's hot posts with like flags for `user_address`, leaving out authors the signed-in `viewer` muted
+    pub async fn get_daily_recommendations(&self, user_address: Option<&str>, viewer: Option<&str>) -> Result<RecommendationResult, Box<dyn std::error::Error>> {
+        // Check if need to refresh recommendations
+        if self.store.should_refresh_daily_recommendations().await? {
+            info!("🔄 Start calculating today's hot posts...");
             
-            if should_refresh {
-                info!("🔄 Start calculating today's hot posts...");
-                
-                // Calculate hot posts
-                let hot_posts = db.calculate_hot_posts().await?;
-                
-                    // Update cache
-                db.update_daily_recommendations(&hot_posts).await?;
-                
-                info!("✅ Today's recommendations updated, {} hot posts", hot_posts.len());
-            }
+            // Calculate hot posts
+            let hot_posts = self.store.calculate_hot_posts().await?;
             
-            // Get recommendation result
-            let result = db.get_daily_recommendations(user_address).await?;
-            Ok(result)
-        } else {
-            // Fallback to memory mode, return empty result
-            Ok(RecommendationResult {
-                posts: vec![],
-                last_refresh_time: None,
-            })
+            // Update cache
+            self.store.update_daily_recommendations(&hot_posts).await?;
+            
+            info!("✅ Today's recommendations updated, {} hot posts", hot_posts.len());
         }
+        
+        // Get recommendation result
+        let muted = match viewer {
+            Some(viewer) => self.muted_addresses(viewer).await,
+            None => Vec::new(),
+        };
+        Ok(self.store.get_daily_recommendations(user_address, &muted).await?)
     }
 
-    // Follow system related methods
-    pub async fn follow_user(&self, request: FollowRequest) -> Result<FollowResponse, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let (follower_addr, following_addr) = if let (Some(follower_addr), Some(following_addr)) = 
-                (request.follower_address.as_deref(), request.following_address.as_deref()) {
-                // Based on address (backward compatibility)
-                (follower_addr.to_string(), following_addr.to_string())
-            } else if let (Some(follower_id), Some(following_id)) = 
-                (request.follower_id.as_deref(), request.following_id.as_deref()) {
-                // Based on ID, need to query address
-                let follower_addr = self.get_user_address_by_id(follower_id).await?;
-                let following_addr = self.get_user_address_by_id(following_id).await?;
-                (follower_addr, following_addr)
-            } else {
-                return Err("Need to provide follower_address and following_address, or follower_id and following_id".into());
-            };
-                
-            let success = db.follow_user(&follower_addr, &following_addr).await?;
-            
-            if success {
-                // Get updated follow data
-                let (following_count, followers_count, _) = db.get_follow_counts(&following_addr).await.unwrap_or((0, 0, 0));
-                
-                info!("👥 User follow success: {} followed {}", follower_addr, following_addr);
-                
-                Ok(FollowResponse {
-                    success: true,
-                    is_following: true,
-                    following_count,
-                    followers_count,
-                })
-            } else {
-                info!("⚠️ User already followed: {} -> {}", follower_addr, following_addr);
-                
-                // Get current follow data
-                let (following_count, followers_count, _) = db.get_follow_counts(&following_addr).await.unwrap_or((0, 0, 0));
-                
-                Ok(FollowResponse {
-                    success: false,
-                    is_following: true,
-                    following_count,
-                    followers_count,
-                })
+    /// Posts from the users `user_address` follows, newest first. With `include_recommended`, the
+    /// first page also gets up to a quarter of `limit` daily recommendations mixed in.
+    pub async fn get_following_feed(&self, user_address: &str, page: &PageRequest, include_recommended: bool) -> Result<Page<FeedItem>, Box<dyn std::error::Error>> {
+        let page = PageRequest { limit: page.limit.clamp(1, FEED_MAX_LIMIT), ..page.clone() };
+        let muted = self.muted_addresses(user_address).await;
+        let mut followed = self.store.get_following_feed(user_address, &page, &muted).await?;
+        mentions::attach_to_posts(self.store.as_ref(), &mut followed.items).await;
+
+        let mut recommended = Vec::new();
+        if include_recommended && page.after.is_none() && page.offset == 0 {
+            match self.get_daily_recommendations(Some(user_address), Some(user_address)).await {
+                Ok(result) => recommended = result.posts,
+                Err(e) => info!("⚠️ Feed recommendations unavailable: {}", e),
             }
-        } else {
-            Err("Database service unavailable".into())
-        }
-    }
-
-    pub async fn unfollow_user(&self, request: FollowRequest) -> Result<FollowResponse, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let (follower_addr, following_addr) = if let (Some(follower_addr), Some(following_addr)) = 
-                (request.follower_address.as_deref(), request.following_address.as_deref()) {
-                // Based on address (backward compatibility)
-                (follower_addr.to_string(), following_addr.to_string())
-            } else if let (Some(follower_id), Some(following_id)) = 
-                (request.follower_id.as_deref(), request.following_id.as_deref()) {
-                // Based on ID, need to query address
-                let follower_addr = self.get_user_address_by_id(follower_id).await?;
-                let following_addr = self.get_user_address_by_id(following_id).await?;
-                (follower_addr, following_addr)
-            } else {
-                return Err("Need to provide follower_address and following_address, or follower_id and following_id".into());
-            };
-                
-            let success = db.unfollow_user(&follower_addr, &following_addr).await?;
-            
-                    // Get updated follow data
-            let (following_count, followers_count, _) = db.get_follow_counts(&following_addr).await.unwrap_or((0, 0, 0));
-            
-            if success {
-                info!("👥 User unfollow success: {} unfollowed {}", follower_addr, following_addr);
-            } else {
-                info!("⚠️ User not followed: {} -> {}", follower_addr, following_addr);
+            recommended.retain(|post| {
+                !post.author_address.eq_ignore_ascii_case(user_address) && !followed.items.iter().any(|p| p.id == post.id)
+            });
+            recommended.truncate((page.limit / 4).max(1) as usize);
+        }
+
+        info!("📰 Following feed for {}: {} followed, {} recommended", user_address, followed.items.len(), recommended.len());
+        Ok(followed.map(|posts| mix_recommendations(posts, recommended)))
+    }
+
+    /// A tag's post count and one page of its posts, newest first; `None` if no live post has the tag
+    pub async fn get_tag_posts(&self, tag: &str, page: &PageRequest, viewer: Option<&str>) -> Result<Option<TagPosts>, ContentActionError> {
+        let tag = normalize_tag(tag).ok_or_else(|| ContentActionError::Invalid(format!("Invalid tag: {}", tag)))?;
+        let Some(summary) = self.store.get_tag(&tag).await? else {
+            return Ok(None);
+        };
+        let page = PageRequest { limit: page.limit.clamp(1, TAG_POSTS_MAX_LIMIT), ..page.clone() };
+        let mut posts = self.store.get_posts_by_tag(&tag, &page, viewer).await?;
+        mentions::attach_to_posts(self.store.as_ref(), &mut posts.items).await;
+        Ok(Some(TagPosts { tag: summary, posts }))
+    }
+
+    pub async fn get_trending_tags(&self, window: TrendingWindow, limit: u32) -> Result<Vec<TrendingTag>, Box<dyn std::error::Error>> {
+        let since = Utc::now() - window.duration();
+        let tags = self.store.get_trending_tags(since, limit.clamp(1, TRENDING_TAGS_MAX_LIMIT)).await?;
+        info!("🏷️ Trending tags ({}): {}", window.as_str(), tags.len());
+        Ok(tags)
+    }
+
+    /// Report a post, comment or user. A reporter can have one open report per target.
+    pub async fn report(&self, reporter_address: &str, target_type: ModerationTarget, target_id: &str, request: CreateReportRequest) -> Result<Report, ContentActionError> {
+        let details = request.details.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+        if details.as_ref().is_some_and(|d| d.chars().count() > REPORT_DETAILS_MAX_CHARS) {
+            return Err(ContentActionError::Invalid(format!("Details cannot exceed {} characters", REPORT_DETAILS_MAX_CHARS)));
+        }
+        let target_id = match target_type {
+            ModerationTarget::Post => self.store.get_post_by_id(target_id).await?
+                .ok_or_else(|| ContentActionError::NotFound("Post not found".to_string()))?
+                .id,
+            ModerationTarget::Comment => self.store.get_comment_by_id(target_id).await?
+                .ok_or_else(|| ContentActionError::NotFound("Comment not found".to_string()))?
+                .id,
+            ModerationTarget::User => {
+                if !target_id.starts_with("0x") || target_id.len() != 42 {
+                    return Err(ContentActionError::Invalid("Invalid address".to_string()));
+                }
+                if target_id.eq_ignore_ascii_case(reporter_address) {
+                    return Err(ContentActionError::Invalid("You cannot report yourself".to_string()));
+                }
+                target_id.to_lowercase()
             }
-            
-            Ok(FollowResponse {
-                success,
-                is_following: false,
-                following_count,
-                followers_count,
-            })
-        } else {
-            Err("Database service unavailable".into())
+        };
+
+        let report = Report {
+            id: Self::generate_id(),
+            reporter_address: reporter_address.to_lowercase(),
+            target_type,
+            target_id,
+            reason: request.reason,
+            details,
+            status: ReportStatus::Open,
+            resolution: None,
+            resolved_by: None,
+            resolved_at: None,
+            created_at: Utc::now(),
+        };
+        if !self.store.create_report(&report).await? {
+            return Err(ContentActionError::Invalid("You have already reported this".to_string()));
         }
+        info!("🚩 {} reported {} {} ({})", report.reporter_address, target_type.as_str(), report.target_id, report.reason.as_str());
+        Ok(report)
     }
 
-    pub async fn get_following_list(&self, user_address: &str, limit: u32, offset: u32) -> Result<Vec<UserProfile>, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let profiles = db.get_following_list(user_address, limit as i64, offset as i64).await?;
-            info!("📋 Get following list: {} (count: {})", user_address, profiles.len());
-            Ok(profiles)
-        } else {
-            Err("Database service unavailable".into())
+    async fn require_moderator(&self, address: &str) -> Result<(), ContentActionError> {
+        if !self.store.get_user_role(address).await?.can_moderate() {
+            return Err(ContentActionError::Forbidden("Moderator role required".to_string()));
         }
+        Ok(())
     }
 
-    pub async fn get_followers_list(&self, user_address: &str, limit: u32, offset: u32) -> Result<Vec<UserProfile>, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let profiles = db.get_followers_list(user_address, limit as i64, offset as i64).await?;
-            info!("📋 Get followers list: {} (count: {})", user_address, profiles.len());
-            Ok(profiles)
+    async fn log_moderation(
+        &self,
+        moderator: &str,
+        action: ModerationAction,
+        target_type: ModerationTarget,
+        target_id: &str,
+        report_id: Option<&str>,
+        reason: Option<String>,
+    ) -> Result<(), ContentActionError> {
+        self.store.append_moderation_log(&ModerationLogEntry {
+            id: Self::generate_id(),
+            moderator_address: moderator.to_lowercase(),
+            action,
+            target_type,
+            target_id: target_id.to_string(),
+            report_id: report_id.map(str::to_string),
+            reason,
+            created_at: Utc::now(),
+        }).await?;
+        info!("🛡️ Moderation: {} {} {} {}", moderator, action.as_str(), target_type.as_str(), target_id);
+        Ok(())
+    }
+
+    /// Open reports, oldest first (moderators only)
+    pub async fn get_report_queue(&self, moderator: &str, page: &PageRequest) -> Result<Page<Report>, ContentActionError> {
+        self.require_moderator(moderator).await?;
+        let page = PageRequest { limit: page.limit.clamp(1, MODERATION_MAX_LIMIT), ..page.clone() };
+        Ok(self.store.get_open_reports(&page).await?)
+    }
+
+    /// Act on a report: dismiss it, hide the post or comment, lock the thread or suspend the
+    /// author (or the reported user). Every open report on the same target is closed with it,
+    /// and the action is written to the audit log.
+    pub async fn resolve_report(&self, moderator: &str, report_id: &str, request: ResolveReportRequest) -> Result<Report, ContentActionError> {
+        self.require_moderator(moderator).await?;
+        let report = self.store.get_report(report_id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Report not found".to_string()))?;
+        if report.status != ReportStatus::Open {
+            return Err(ContentActionError::Invalid("Report is already closed".to_string()));
+        }
+        let reason = request.reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+
+        let (target_type, target_id) = match request.action {
+            ModerationAction::Dismiss => (report.target_type, report.target_id.clone()),
+            ModerationAction::Hide => self.hide_reported(moderator, &report).await?,
+            ModerationAction::Lock => {
+                let post_id = self.reported_post_id(&report).await?;
+                self.store.set_post_locked(&post_id, Some(moderator)).await?;
+                (ModerationTarget::Post, post_id)
+            }
+            ModerationAction::Suspend => {
+                let address = self.reported_address(&report).await?;
+                if address.eq_ignore_ascii_case(moderator) || self.store.get_user_role(&address).await?.can_moderate() {
+                    return Err(ContentActionError::Forbidden("Moderators cannot be suspended".to_string()));
+                }
+                let expires_at = match request.duration_hours {
+                    Some(0) => return Err(ContentActionError::Invalid("duration_hours must be positive".to_string())),
+                    Some(hours) => Some(Utc::now() + chrono::Duration::hours(i64::from(hours))),
+                    None => None,
+                };
+                self.store.suspend_address(&Suspension {
+                    address: address.clone(),
+                    reason: reason.clone(),
+                    suspended_by: moderator.to_lowercase(),
+                    created_at: Utc::now(),
+                    expires_at,
+                }).await?;
+                (ModerationTarget::User, address)
+            }
+            other => {
+                return Err(ContentActionError::Invalid(format!("{} cannot resolve a report", other.as_str())));
+            }
+        };
+
+        let status = if request.action == ModerationAction::Dismiss { ReportStatus::Dismissed } else { ReportStatus::Resolved };
+        let closed = self.store.close_reports(report.target_type, &report.target_id, status, request.action, moderator).await?;
+        self.log_moderation(moderator, request.action, target_type, &target_id, Some(&report.id), reason).await?;
+        info!("🛡️ Closed {} report(s) on {} {}", closed, report.target_type.as_str(), report.target_id);
+
+        self.store.get_report(&report.id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Report not found".to_string()))
+    }
+
+    /// Soft delete a reported post or comment; content that is already gone counts as hidden
+    async fn hide_reported(&self, moderator: &str, report: &Report) -> Result<(ModerationTarget, String), ContentActionError> {
+        let result = match report.target_type {
+            ModerationTarget::Post => self.delete_post(&report.target_id, moderator).await,
+            ModerationTarget::Comment => self.delete_comment(&report.target_id, moderator).await,
+            ModerationTarget::User => {
+                return Err(ContentActionError::Invalid("Only posts and comments can be hidden".to_string()));
+            }
+        };
+        match result {
+            Ok(()) | Err(ContentActionError::NotFound(_)) => Ok((report.target_type, report.target_id.clone())),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The thread a reported post or comment belongs to
+    async fn reported_post_id(&self, report: &Report) -> Result<String, ContentActionError> {
+        let post_id = match report.target_type {
+            ModerationTarget::Post => report.target_id.clone(),
+            ModerationTarget::Comment => self.store.get_comment_by_id(&report.target_id).await?
+                .ok_or_else(|| ContentActionError::NotFound("Comment not found".to_string()))?
+                .post_id,
+            ModerationTarget::User => {
+                return Err(ContentActionError::Invalid("Only threads can be locked".to_string()));
+            }
+        };
+        match self.store.get_post_by_id(&post_id).await? {
+            Some(post) => Ok(post.id),
+            None => Err(ContentActionError::NotFound("Post not found".to_string())),
+        }
+    }
+
+    /// The reported user, or the author of the reported post or comment
+    async fn reported_address(&self, report: &Report) -> Result<String, ContentActionError> {
+        let author = match report.target_type {
+            ModerationTarget::User => Some(report.target_id.clone()),
+            ModerationTarget::Post => self.store.get_post_by_id(&report.target_id).await?.map(|p| p.author_address),
+            ModerationTarget::Comment => self.store.get_comment_by_id(&report.target_id).await?.map(|c| c.author_address),
+        };
+        author
+            .filter(|address| !address.is_empty())
+            .map(|address| address.to_lowercase())
+            .ok_or_else(|| ContentActionError::NotFound("Reported content not found".to_string()))
+    }
+
+    /// Unlock a locked thread (moderators only)
+    pub async fn unlock_post(&self, moderator: &str, post_id: &str) -> Result<(), ContentActionError> {
+        self.require_moderator(moderator).await?;
+        if !self.store.set_post_locked(post_id, None).await? {
+            return Err(ContentActionError::NotFound("Post not found or not locked".to_string()));
+        }
+        self.log_moderation(moderator, ModerationAction::Unlock, ModerationTarget::Post, post_id, None, None).await
+    }
+
+    /// Lift an address's suspension (moderators only)
+    pub async fn lift_suspension(&self, moderator: &str, address: &str) -> Result<(), ContentActionError> {
+        self.require_moderator(moderator).await?;
+        if !self.store.lift_suspension(address).await? {
+            return Err(ContentActionError::NotFound("Address is not suspended".to_string()));
+        }
+        self.log_moderation(moderator, ModerationAction::Unsuspend, ModerationTarget::User, &address.to_lowercase(), None, None).await
+    }
+
+    /// Change a user's role (admins only)
+    pub async fn set_user_role(&self, admin: &str, address: &str, role: UserRole) -> Result<(), ContentActionError> {
+        if self.store.get_user_role(admin).await? != UserRole::Admin {
+            return Err(ContentActionError::Forbidden("Admin role required".to_string()));
+        }
+        if address.eq_ignore_ascii_case(admin) {
+            return Err(ContentActionError::Invalid("You cannot change your own role".to_string()));
+        }
+        if !self.store.update_user_role(address, role).await? {
+            return Err(ContentActionError::NotFound("User not found".to_string()));
+        }
+        let address = address.to_lowercase();
+        self.log_moderation(admin, ModerationAction::SetRole, ModerationTarget::User, &address, None, Some(role.as_str().to_string())).await
+    }
+
+    /// Audit log of moderator actions, newest first (moderators only)
+    pub async fn get_moderation_log(&self, moderator: &str, page: &PageRequest) -> Result<Page<ModerationLogEntry>, ContentActionError> {
+        self.require_moderator(moderator).await?;
+        let page = PageRequest { limit: page.limit.clamp(1, MODERATION_MAX_LIMIT), ..page.clone() };
+        Ok(self.store.get_moderation_log(&page).await?)
+    }
+
+    /// Submissions held by the spam filter, oldest first (moderators only)
+    pub async fn get_held_queue(&self, moderator: &str, page: &PageRequest) -> Result<Page<HeldContent>, ContentActionError> {
+        self.require_moderator(moderator).await?;
+        let page = PageRequest { limit: page.limit.clamp(1, MODERATION_MAX_LIMIT), ..page.clone() };
+        Ok(self.store.get_held_queue(&page).await?)
+    }
+
+    /// Approve a held submission, creating the post or comment as submitted (suspensions, locked
+    /// threads and blocks still apply), or reject it. The decision goes to the audit log.
+    pub async fn resolve_held(&self, moderator: &str, held_id: &str, request: ResolveHeldRequest) -> Result<HeldContent, ContentActionError> {
+        self.require_moderator(moderator).await?;
+        let held = self.store.get_held_content(held_id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Held submission not found".to_string()))?;
+        if held.status != HeldStatus::Held {
+            return Err(ContentActionError::Invalid("Submission was already reviewed".to_string()));
+        }
+        let reason = request.reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+
+        let (status, content_id) = match request.action {
+            ModerationAction::Approve => (HeldStatus::Approved, Some(self.publish_held(held.submission.clone()).await?)),
+            ModerationAction::Reject => (HeldStatus::Rejected, None),
+            other => {
+                return Err(ContentActionError::Invalid(format!("{} cannot resolve a held submission", other.as_str())));
+            }
+        };
+        if !self.store.resolve_held_content(&held.id, status, content_id.as_deref(), moderator).await? {
+            info!("⚠️ Held submission {} was reviewed twice at the same time", held.id);
+        }
+        let target_id = content_id.as_deref().unwrap_or(&held.id);
+        self.log_moderation(moderator, request.action, held.submission.content_type(), target_id, None, reason).await?;
+
+        self.store.get_held_content(&held.id).await?
+            .ok_or_else(|| ContentActionError::NotFound("Held submission not found".to_string()))
+    }
+
+    /// Create (or for an edit, apply) an approved submission without screening it again; returns
+    /// the post or comment id
+    async fn publish_held(&self, submission: HeldSubmission) -> Result<String, ContentActionError> {
+        check_not_suspended(self.store.as_ref(), submission.author_address()).await?;
+        let tx_hash = match &submission {
+            HeldSubmission::Post { request, .. } => request.blockchain_transaction_hash.clone(),
+            HeldSubmission::Comment { request, .. } => {
+                check_comment_target(self.store.as_ref(), &request.post_id, request.parent_id.as_deref(), &request.author_address).await?;
+                request.blockchain_transaction_hash.clone()
+            }
+            HeldSubmission::PostEdit { .. } => None,
+        };
+        if let Some(tx_hash) = tx_hash {
+            if self.store.is_transaction_used(&tx_hash).await? {
+                return Err(ContentActionError::Invalid("The transaction has already been used".to_string()));
+            }
+        }
+
+        let published = match submission {
+            HeldSubmission::Post { request, verification } => {
+                self.publish_post(Self::generate_id(), request, verification.as_ref()).await.map(|post| post.id)
+            }
+            HeldSubmission::Comment { request, verification } => {
+                self.publish_comment(Self::generate_id(), request, verification.as_ref()).await.map(|comment| comment.id)
+            }
+            HeldSubmission::PostEdit { post_id, request, .. } => {
+                let post = self.store.get_post_by_id(&post_id).await?
+                    .ok_or_else(|| ContentActionError::NotFound("Post not found".to_string()))?;
+                return self.publish_post_edit(post, request).await.map(|post| post.id);
+            }
+        };
+        published.map_err(|e| match e.downcast::<ContentActionError>() {
+            Ok(e) => *e,
+            Err(e) => ContentActionError::Internal(e.to_string()),
+        })
+    }
+
+    /// Lowercase addresses `viewer` has muted; empty if the lookup fails
+    async fn muted_addresses(&self, viewer: &str) -> Vec<String> {
+        match self.store.get_muted_users(viewer).await {
+            Ok(users) => users.into_iter().map(|u| u.address).collect(),
+            Err(e) => {
+                info!("⚠️ Failed to load muted users of {}: {}", viewer, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Block `target`, ending any follow between the two; blocking again is a no-op
+    pub async fn block_user(&self, blocker: &str, target: &str) -> Result<(), ContentActionError> {
+        let target = restriction_target(blocker, target, "block")?;
+        if self.store.block_user(blocker, &target).await? {
+            info!("🚫 {} blocked {}", blocker, target);
+        }
+        Ok(())
+    }
+
+    pub async fn unblock_user(&self, blocker: &str, target: &str) -> Result<(), ContentActionError> {
+        if self.store.unblock_user(blocker, target).await? {
+            info!("🚫 {} unblocked {}", blocker, target);
+        }
+        Ok(())
+    }
+
+    pub async fn get_blocked_users(&self, blocker: &str) -> Result<Vec<RestrictedUser>, ContentActionError> {
+        Ok(self.store.get_blocked_users(blocker).await?)
+    }
+
+    /// Mute `target`; muting again is a no-op
+    pub async fn mute_user(&self, muter: &str, target: &str) -> Result<(), ContentActionError> {
+        let target = restriction_target(muter, target, "mute")?;
+        if self.store.mute_user(muter, &target).await? {
+            info!("🔇 {} muted {}", muter, target);
+        }
+        Ok(())
+    }
+
+    pub async fn unmute_user(&self, muter: &str, target: &str) -> Result<(), ContentActionError> {
+        if self.store.unmute_user(muter, target).await? {
+            info!("🔇 {} unmuted {}", muter, target);
+        }
+        Ok(())
+    }
+
+    pub async fn get_muted_users(&self, muter: &str) -> Result<Vec<RestrictedUser>, ContentActionError> {
+        Ok(self.store.get_muted_users(muter).await?)
+    }
+
+    /// Grouped notifications for `user_address`, newest first
+    pub async fn get_notifications(&self, user_address: &str, page: &PageRequest, unread_only: bool) -> Result<Page<NotificationGroup>, Box<dyn std::error::Error>> {
+        let page = PageRequest { limit: page.limit.clamp(1, NOTIFICATIONS_MAX_LIMIT), ..page.clone() };
+        let groups = self.store.get_notification_groups(user_address, &page, unread_only).await?;
+        Ok(groups.map(|groups| {
+            groups.into_iter()
+                .map(|group| NotificationGroup { message: group.describe(), ..group })
+                .collect()
+        }))
+    }
+
+    /// Mark the given notification groups, or all of them with `None`, as read
+    pub async fn mark_notifications_read(&self, user_address: &str, group_keys: Option<&[String]>) -> Result<u64, Box<dyn std::error::Error>> {
+        let marked = self.store.mark_notifications_read(user_address, group_keys).await?;
+        info!("🔔 Marked {} notifications read for {}", marked, user_address);
+        Ok(marked)
+    }
+
+    pub async fn count_unread_notifications(&self, user_address: &str) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(self.store.count_unread_notifications(user_address).await?)
+    }
+
+    // Resolve the followed user's address from the request (address or user ID)
+    async fn resolve_follow_target(&self, request: &FollowRequest) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(following_addr) = request.following_address.as_deref() {
+            Ok(following_addr.to_string())
+        } else if let Some(following_id) = request.following_id.as_deref() {
+            self.get_user_address_by_id(following_id).await
         } else {
-            Err("Database service unavailable".into())
+            Err("Need to provide following_address or following_id".into())
         }
     }
 
-    pub async fn get_mutual_follows_list(&self, user_address: &str, limit: u32, offset: u32) -> Result<Vec<UserProfile>, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let profiles = db.get_mutual_follows_list(user_address, limit as i64, offset as i64).await?;
-            info!("📋 Get mutual follows list: {} (count: {})", user_address, profiles.len());
-            Ok(profiles)
+    // Follow system related methods
+    pub async fn follow_user(&self, follower_addr: &str, request: FollowRequest) -> Result<FollowResponse, Box<dyn std::error::Error>> {
+        let following_addr = self.resolve_follow_target(&request).await?;
+        if self.store.is_blocked(&following_addr, follower_addr).await? {
+            return Err(Box::new(ContentActionError::Forbidden("You cannot follow this user".to_string())));
+        }
+            
+        let success = self.store.follow_user(follower_addr, &following_addr).await?;
+        
+        // Get updated follow data
+        let (following_count, followers_count, _) = self.store.get_follow_counts(&following_addr).await.unwrap_or((0, 0, 0));
+        
+        if success {
+            info!("👥 User follow success: {} followed {}", follower_addr, following_addr);
+            notifications::notify(self.store.as_ref(), NewNotification::followed(&following_addr, follower_addr)).await;
         } else {
-            Err("Database service unavailable".into())
+            info!("⚠️ User already followed: {} -> {}", follower_addr, following_addr);
         }
+        
+        Ok(FollowResponse {
+            success,
+            is_following: true,
+            following_count,
+            followers_count,
+        })
     }
 
-    pub async fn get_follow_counts(&self, user_address: &str) -> Result<(u32, u32, u32), Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let counts = db.get_follow_counts(user_address).await?;
-            Ok(counts)
+    pub async fn unfollow_user(&self, follower_addr: &str, request: FollowRequest) -> Result<FollowResponse, Box<dyn std::error::Error>> {
+        let following_addr = self.resolve_follow_target(&request).await?;
+            
+        let success = self.store.unfollow_user(follower_addr, &following_addr).await?;
+        
+        // Get updated follow data
+        let (following_count, followers_count, _) = self.store.get_follow_counts(&following_addr).await.unwrap_or((0, 0, 0));
+        
+        if success {
+            info!("👥 User unfollow success: {} unfollowed {}", follower_addr, following_addr);
+            notifications::retract(self.store.as_ref(), NewNotification::followed(&following_addr, follower_addr)).await;
         } else {
-            Ok((0, 0, 0))
+            info!("⚠️ User not followed: {} -> {}", follower_addr, following_addr);
         }
+        
+        Ok(FollowResponse {
+            success,
+            is_following: false,
+            following_count,
+            followers_count,
+        })
+    }
+
+    pub async fn get_following_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, Box<dyn std::error::Error>> {
+        let profiles = self.store.get_following_list(user_address, page).await?;
+        info!("📋 Get following list: {} (count: {})", user_address, profiles.items.len());
+        Ok(profiles)
+    }
+
+    pub async fn get_followers_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, Box<dyn std::error::Error>> {
+        let profiles = self.store.get_followers_list(user_address, page).await?;
+        info!("📋 Get followers list: {} (count: {})", user_address, profiles.items.len());
+        Ok(profiles)
+    }
+
+    pub async fn get_mutual_follows_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, Box<dyn std::error::Error>> {
+        let profiles = self.store.get_mutual_follows_list(user_address, page).await?;
+        info!("📋 Get mutual follows list: {} (count: {})", user_address, profiles.items.len());
+        Ok(profiles)
+    }
+
+    pub async fn get_follow_counts(&self, user_address: &str) -> Result<(u32, u32, u32), Box<dyn std::error::Error>> {
+        Ok(self.store.get_follow_counts(user_address).await?)
     }
 
     pub async fn is_following(&self, follower_address: &str, following_address: &str) -> Result<bool, Box<dyn std::error::Error>> {
-        if let Some(ref db) = self.database_service {
-            let is_following = db.is_following(follower_address, following_address).await?;
-            Ok(is_following)
+        Ok(self.store.is_following(follower_address, following_address).await?)
+    }
+
+    pub async fn get_comment(&self, comment_id: &str) -> Option<Comment> {
+        match self.store.get_comment_by_id(comment_id).await {
+            Ok(comment) => comment,
+            Err(e) => {
+                info!("⚠️ Comment query failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Verify a post against its stored hash, its Irys payload and the on-chain getPost record
+    pub async fn verify_post_integrity(&self, post_id: &str) -> Option<ContentVerificationReport> {
+        let post = self.get_post(post_id).await?;
+
+        let stored_hash = self.store.get_post_content_hash(&post.id).await.map_err(|e| e.to_string());
+        let chain_check = match (&self.blockchain_service, post.blockchain_post_id) {
+            (None, _) => Self::integrity_check("chain", IntegrityStatus::Unavailable, None, "Blockchain service unavailable"),
+            (_, None) => Self::integrity_check("chain", IntegrityStatus::Unavailable, None, "Post has no on-chain ID"),
+            (Some(blockchain), Some(chain_id)) => match blockchain.get_blockchain_post(ethers::types::U256::from(chain_id)).await {
+                Ok(chain_post) => Self::compare_fields("chain", &chain_post.content, &[
+                    ("title", &post.title, &chain_post.title),
+                    ("content", &post.content, &chain_post.content),
+                    ("author", &post.author_address.to_lowercase(), &chain_post.author.to_lowercase()),
+                ]),
+                Err(e) => Self::integrity_check("chain", IntegrityStatus::Error, None, &format!("getPost failed: {}", e)),
+            },
+        };
+
+        let checks = vec![
+            Self::check_stored_hash(&post.content, stored_hash),
+            self.check_irys_payload(post.irys_transaction_id.as_deref(), Some(&post.title), &post.content).await,
+            chain_check,
+        ];
+        Some(Self::integrity_report("post", post.id, &post.content, checks))
+    }
+
+    /// Verify a comment against its stored hash, its Irys payload and the on-chain getComment record
+    pub async fn verify_comment_integrity(&self, comment_id: &str) -> Option<ContentVerificationReport> {
+        let comment = self.get_comment(comment_id).await?;
+
+        let stored_hash = if comment.content_hash.is_empty() { None } else { Some(comment.content_hash.clone()) };
+        let chain_id = self.store.get_comment_chain_id(&comment.id).await.map_err(|e| e.to_string());
+        let chain_check = match (&self.blockchain_service, chain_id) {
+            (None, _) => Self::integrity_check("chain", IntegrityStatus::Unavailable, None, "Blockchain service unavailable"),
+            (_, Err(e)) => Self::integrity_check("chain", IntegrityStatus::Error, None, &format!("Failed to resolve on-chain ID: {}", e)),
+            (_, Ok(None)) => Self::integrity_check("chain", IntegrityStatus::Unavailable, None, "Comment has no indexed on-chain ID"),
+            (Some(blockchain), Ok(Some(chain_id))) => match blockchain.get_blockchain_comment(ethers::types::U256::from(chain_id)).await {
+                Ok(chain_comment) => Self::compare_fields("chain", &chain_comment.content, &[
+                    ("content", &comment.content, &chain_comment.content),
+                    ("author", &comment.author_address.to_lowercase(), &chain_comment.author.to_lowercase()),
+                ]),
+                Err(e) => Self::integrity_check("chain", IntegrityStatus::Error, None, &format!("getComment failed: {}", e)),
+            },
+        };
+
+        let checks = vec![
+            Self::check_stored_hash(&comment.content, Ok(stored_hash)),
+            self.check_irys_payload(comment.irys_transaction_id.as_deref(), None, &comment.content).await,
+            chain_check,
+        ];
+        Some(Self::integrity_report("comment", comment.id, &comment.content, checks))
+    }
+
+    fn integrity_check(source: &str, status: IntegrityStatus, content_hash: Option<String>, detail: &str) -> IntegrityCheck {
+        IntegrityCheck {
+            source: source.to_string(),
+            status,
+            content_hash,
+            detail: if detail.is_empty() { None } else { Some(detail.to_string()) },
+        }
+    }
+
+    /// Compare (field, served value, source value) triples; any difference is a mismatch
+    fn compare_fields(source: &str, source_content: &str, fields: &[(&str, &str, &str)]) -> IntegrityCheck {
+        let mismatched: Vec<&str> = fields.iter()
+            .filter(|(_, served, original)| served != original)
+            .map(|(name, _, _)| *name)
+            .collect();
+        let content_hash = Some(Self::generate_content_hash(source_content));
+
+        if mismatched.is_empty() {
+            Self::integrity_check(source, IntegrityStatus::Match, content_hash, "")
         } else {
-            Ok(false)
+            Self::integrity_check(source, IntegrityStatus::Mismatch, content_hash, &format!("Mismatched fields: {}", mismatched.join(", ")))
+        }
+    }
+
+    /// Stored hashes are SHA-256 or (for older rows) MD5 hex digests of the content
+    fn check_stored_hash(content: &str, stored_hash: Result<Option<String>, String>) -> IntegrityCheck {
+        match stored_hash {
+            Ok(Some(hash)) if !hash.is_empty() => {
+                let matches = hash == Self::generate_content_hash(content)
+                    || hash == format!("{:x}", md5::compute(content));
+                let status = if matches { IntegrityStatus::Match } else { IntegrityStatus::Mismatch };
+                Self::integrity_check("stored_hash", status, Some(hash), "")
+            }
+            Ok(_) => Self::integrity_check("stored_hash", IntegrityStatus::Unavailable, None, "No content hash stored"),
+            Err(e) => Self::integrity_check("stored_hash", IntegrityStatus::Error, None, &e),
+        }
+    }
+
+    /// Fetch the JSON payload uploaded at creation time and compare its title/content
+    async fn check_irys_payload(&self, tx_id: Option<&str>, title: Option<&str>, content: &str) -> IntegrityCheck {
+        let Some(tx_id) = tx_id.filter(|id| !id.is_empty()) else {
+            return Self::integrity_check("irys", IntegrityStatus::Unavailable, None, "No Irys transaction ID");
+        };
+
+        let payload = match self.irys_service.fetch_data(tx_id).await {
+            Ok(bytes) => bytes,
+            Err(e) => return Self::integrity_check("irys", IntegrityStatus::Error, None, &format!("Failed to fetch {}: {}", tx_id, e)),
+        };
+        let payload: Value = match serde_json::from_slice(&payload) {
+            Ok(value) => value,
+            Err(e) => return Self::integrity_check("irys", IntegrityStatus::Error, None, &format!("Invalid payload in {}: {}", tx_id, e)),
+        };
+
+        let irys_content = payload.get("content").and_then(|v| v.as_str()).unwrap_or_default();
+        let mut fields = vec![("content", content, irys_content)];
+        if let Some(title) = title {
+            fields.push(("title", title, payload.get("title").and_then(|v| v.as_str()).unwrap_or_default()));
+        }
+        Self::compare_fields("irys", irys_content, &fields)
+    }
+
+    fn integrity_report(content_type: &str, id: String, content: &str, checks: Vec<IntegrityCheck>) -> ContentVerificationReport {
+        let verified = checks.iter().any(|c| c.status == IntegrityStatus::Match)
+            && !checks.iter().any(|c| c.status == IntegrityStatus::Mismatch);
+
+        ContentVerificationReport {
+            content_type: content_type.to_string(),
+            id,
+            content_hash: Self::generate_content_hash(content),
+            verified,
+            checks,
         }
     }
 } 
+
+#[async_trait]
+impl TaskProcessor for ForumService {
+    async fn process(&self, task: &QueuedTask) -> Result<Value, TaskError> {
+        match &task.task {
+            QueueTask::PostCreation { request, tx_hash } => self.process_post_task(&task.id, request, tx_hash).await,
+            QueueTask::CommentCreation { request, tx_hash } => self.process_comment_task(&task.id, request, tx_hash).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A post whose content repeats its title; tests override other fields with struct update syntax
+    fn post_request(author: &str, title: &str) -> CreatePostRequest {
+        CreatePostRequest {
+            title: title.to_string(),
+            content: title.to_string(),
+            author_address: author.to_string(),
+            author_name: None,
+            tags: vec![],
+            image: None,
+            blockchain_transaction_hash: None,
+            blockchain_transaction_proof: None,
+        }
+    }
+
+    /// Create a post the way the handler does, with a stub standing in for the verified
+    /// on-chain transaction. Each call gets its own transaction hash and on-chain post ID.
+    async fn create_post(service: &ForumService, mut request: CreatePostRequest) -> Result<Post, Box<dyn std::error::Error>> {
+        static NEXT_CHAIN_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+        let chain_id = NEXT_CHAIN_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let tx_hash = format!("0x{:064x}", chain_id);
+        request.blockchain_transaction_hash = Some(tx_hash.clone());
+        let verification = crate::blockchain::PostTransactionVerification {
+            transaction_hash: tx_hash,
+            sender: request.author_address.clone(),
+            block_number: chain_id,
+            block_timestamp: Utc::now().timestamp().into(),
+            post_id: chain_id.into(),
+            points_earned: 0.into(),
+            value_paid: 0.into(),
+            gas_used: 0.into(),
+            verified: true,
+        };
+        service.create_post_with_verification(request, verification).await
+    }
+
+    #[tokio::test]
+    async fn test_offline_service_uses_memory_store() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+
+        let post = create_post(&service, CreatePostRequest {
+            content: "First post".to_string(),
+            tags: vec!["intro".to_string()],
+            ..post_request(author, "Hello")
+        }).await.unwrap();
+        service.add_comment(CreateCommentRequest {
+            post_id: post.id.clone(),
+            content: "Welcome".to_string(),
+            author_address: author.to_string(),
+            author_name: None,
+            parent_id: None,
+            image: None,
+            blockchain_transaction_hash: None,
+        }).await.unwrap();
+
+        assert_eq!(service.like_post(&post.id, author).await.unwrap(), 1);
+        let stored = service.get_post_with_like_status(&post.id, Some(author), Some(author)).await.unwrap();
+        assert_eq!(stored.comments_count, 1);
+        assert!(stored.is_liked_by_user);
+
+        let profile = service.get_user_profile(author).await.unwrap();
+        assert_eq!((profile.posts_count, profile.comments_count), (1, 1));
+        assert_eq!(service.get_storage_stats()["backend"], "memory");
+    }
+
+    #[tokio::test]
+    async fn test_comment_proof_requires_on_chain_post_id() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let post = create_post(&service, post_request("0x1111111111111111111111111111111111111111", "On chain")).await.unwrap();
+        let chain_id = post.blockchain_post_id.unwrap();
+        assert_eq!(on_chain_post_id(&post).unwrap(), ethers::types::U256::from(chain_id));
+
+        // Without an on-chain ID there is nothing to match CommentCreated.postId against
+        let post = Post { blockchain_post_id: None, ..post };
+        assert!(matches!(on_chain_post_id(&post), Err(TransactionVerificationError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_post_edit_keeps_revisions_and_checks_author() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+        let post = create_post(&service, CreatePostRequest {
+            content: "First draft".to_string(),
+            ..post_request(author, "Original")
+        }).await.unwrap();
+        let edit = |content: &str| UpdatePostRequest {
+            title: "Edited".to_string(),
+            content: content.to_string(),
+            tags: vec!["update".to_string()],
+            image: None,
+        };
+
+        let stranger = "0x2222222222222222222222222222222222222222";
+        let result = service.update_post(&post.id, stranger, edit("Hijacked")).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Forbidden(_))));
+        let result = service.update_post("missing", author, edit("x")).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::NotFound(_))));
+
+        let edited = service.update_post(&post.id, &author.to_uppercase(), edit("Second draft")).await.unwrap();
+        assert_eq!((edited.title.as_str(), edited.content.as_str()), ("Edited", "Second draft"));
+        let result = service.update_post(&post.id, author, edit("Second draft")).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Invalid(_))));
+        service.update_post(&post.id, author, edit("Third draft")).await.unwrap();
+
+        let revisions = service.get_post_revisions(&post.id).await.unwrap().unwrap();
+        let contents: Vec<_> = revisions.iter().map(|r| (r.revision, r.content.as_str())).collect();
+        assert_eq!(contents, vec![(1, "First draft"), (2, "Second draft")]);
+        assert_eq!(service.get_post(&post.id).await.unwrap().content, "Third draft");
+        assert!(service.get_post_revisions("missing").await.unwrap().is_none());
+
+        // The Markdown source is kept as written next to its sanitized rendering
+        let source = "**Fourth** <img src=x onerror=alert(1)>";
+        assert_eq!(service.update_post(&post.id, author, edit(source)).await.unwrap().content, source);
+        assert_eq!(service.get_post(&post.id).await.unwrap().content_html, "<p><strong>Fourth</strong> <img src=\"x\"></p>\n");
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_permissions_and_tombstones() {
+        let store = Arc::new(MemoryStore::new());
+        let service = ForumService::from_parts(store.clone(), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+        let stranger = "0x2222222222222222222222222222222222222222";
+        let moderator = "0x3333333333333333333333333333333333333333";
+        store.set_user_role(moderator, UserRole::Moderator);
+
+        let post = create_post(&service, CreatePostRequest {
+            content: "Discuss".to_string(),
+            ..post_request(author, "Thread")
+        }).await.unwrap();
+        let comment = |content: &str, parent_id: Option<String>| CreateCommentRequest {
+            post_id: post.id.clone(),
+            content: content.to_string(),
+            author_address: author.to_string(),
+            author_name: None,
+            parent_id,
+            image: None,
+            blockchain_transaction_hash: None,
+        };
+        let parent = service.add_comment(comment("Parent", None)).await.unwrap();
+        service.add_comment(comment("Reply", Some(parent.id.clone()))).await.unwrap();
+        let leaf = service.add_comment(comment("Leaf", None)).await.unwrap();
+
+        assert!(matches!(service.delete_comment(&parent.id, stranger).await, Err(ContentActionError::Forbidden(_))));
+        service.delete_comment(&parent.id, author).await.unwrap();
+        service.delete_comment(&leaf.id, moderator).await.unwrap();
+        assert!(matches!(service.delete_comment(&leaf.id, author).await, Err(ContentActionError::NotFound(_))));
+
+        // The parent keeps its place in the thread as a tombstone; the childless comment disappears
+        let comments = service.get_comments_with_like_status_paginated(&post.id, None, &PageRequest::offset(10, 0)).await.unwrap().items;
+        let shape: Vec<_> = comments.iter().map(|c| (c.content.as_str(), c.is_deleted, c.author_address.is_empty())).collect();
+        assert_eq!(shape, vec![("[deleted]", true, true), ("Reply", false, false)]);
+        assert_eq!(service.get_post(&post.id).await.unwrap().comments_count, 1);
+        assert_eq!(service.get_user_profile(author).await.unwrap().comments_count, 1);
+
+        assert!(matches!(service.delete_post(&post.id, stranger).await, Err(ContentActionError::Forbidden(_))));
+        service.delete_post(&post.id, moderator).await.unwrap();
+        assert!(service.get_post(&post.id).await.is_none());
+        assert!(service.get_posts_paginated(&PageRequest::offset(10, 0)).await.items.is_empty());
+        assert_eq!(service.get_user_profile(author).await.unwrap().posts_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_search_filters_and_highlights() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+        for (title, content, tag) in [("Irys guide", "Storing <b>data</b> on Irys", "guide"), ("Chatter", "irys is neat", "misc"), ("Unrelated", "nothing here", "guide")] {
+            create_post(&service, CreatePostRequest {
+                content: content.to_string(),
+                tags: vec![tag.to_string()],
+                ..post_request(author, title)
+            }).await.unwrap();
+        }
+        let search = |pairs: &[(&str, &str)]| {
+            let query = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            crate::search::parse_search_request(&query).unwrap()
+        };
+
+        let results = service.search(&search(&[("q", "IRYS"), ("type", "posts")])).await.unwrap();
+        let titles: Vec<_> = results.posts.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["Irys guide", "Chatter"]);
+        assert_eq!(results.posts[0].snippet, "Storing &lt;b&gt;data&lt;/b&gt; on <mark>Irys</mark>");
+
+        let results = service.search(&search(&[("q", "irys"), ("tag", "misc"), ("author", &author.to_uppercase())])).await.unwrap();
+        assert_eq!(results.posts.len(), 1);
+        assert_eq!(results.posts[0].title, "Chatter");
+        assert!(service.search(&search(&[("q", "irys"), ("to", "2000-01-01")])).await.unwrap().posts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_following_feed_pages_and_mixes_recommendations() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let (reader, followed, other) = (
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "0x3333333333333333333333333333333333333333",
+        );
+        for i in 0..4 {
+            create_post(&service, post_request(followed, &format!("Followed {}", i))).await.unwrap();
+        }
+        let popular = create_post(&service, post_request(other, "Popular")).await.unwrap();
+        service.like_post(&popular.id, followed).await.unwrap();
+        service.store.ensure_user_exists(reader, &None).await.unwrap();
+        service.store.follow_user(reader, followed).await.unwrap();
+
+        let first = service.get_following_feed(reader, &PageRequest::offset(3, 0), false).await.unwrap();
+        let titles: Vec<_> = first.items.iter().map(|i| i.post.title.as_str()).collect();
+        assert_eq!(titles, vec!["Followed 3", "Followed 2", "Followed 1"]);
+        assert!(first.has_more);
+        let after = PageCursor::decode(first.next_cursor.as_deref().unwrap());
+        let second = service.get_following_feed(reader, &PageRequest { limit: 3, offset: 0, after }, true).await.unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert!(!second.has_more && second.items.iter().all(|i| i.source == FeedSource::Following));
+
+        let mixed = service.get_following_feed(reader, &PageRequest::offset(3, 0), true).await.unwrap();
+        let shape: Vec<_> = mixed.items.iter().map(|i| (i.post.title.as_str(), i.source)).collect();
+        assert_eq!(shape.last(), Some(&("Popular", FeedSource::Recommended)));
+        assert_eq!(shape.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_group_and_track_read_state() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+        let likers: Vec<String> = (2..7).map(|i| format!("0x{}", i.to_string().repeat(40))).collect();
+        service.store.ensure_user_exists(&likers[4], &None).await.unwrap();
+        service.store.register_username(&likers[4], "alice").await.unwrap();
+        let post = create_post(&service, post_request(author, "Hello")).await.unwrap();
+
+        for liker in &likers {
+            service.like_post(&post.id, liker).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+        service.like_post(&post.id, author).await.unwrap();
+        let follow = FollowRequest { following_id: None, following_address: Some(author.to_string()) };
+        service.follow_user(&likers[0], follow).await.unwrap();
+
+        let groups = service.get_notifications(author, &PageRequest::offset(10, 0), false).await.unwrap().items;
+        let messages: Vec<_> = groups.iter().map(|g| g.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            format!("{} followed you", likers[0]),
+            "alice and 4 others liked your post".to_string(),
+        ]);
+        assert_eq!(groups[1].actors.len(), NotificationGroup::MAX_ACTORS);
+        assert_eq!(groups[1].post_title.as_deref(), Some("Hello"));
+        assert_eq!(service.count_unread_notifications(author).await.unwrap(), 2);
+
+        // Unliking retracts the like; reading a group leaves the others unread
+        service.like_post(&post.id, &likers[4]).await.unwrap();
+        service.mark_notifications_read(author, Some(&["follow".to_string()])).await.unwrap();
+        let unread = service.get_notifications(author, &PageRequest::offset(10, 0), true).await.unwrap().items;
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].message, format!("{} and 3 others liked your post", likers[3]));
+        assert_eq!(service.mark_notifications_read(author, None).await.unwrap(), 4);
+        assert_eq!(service.count_unread_notifications(author).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mentions_are_resolved_stored_and_notified() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let (author, alice, bob) = (
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "0x3333333333333333333333333333333333333333",
+        );
+        for (address, name) in [(alice, "alice"), (bob, "bob")] {
+            service.store.ensure_user_exists(address, &None).await.unwrap();
+            service.store.register_username(address, name).await.unwrap();
+        }
+        let post = create_post(&service, CreatePostRequest {
+            content: "hey @alice and @nobody, mail alice@example.com".to_string(),
+            ..post_request(author, "Hi")
+        }).await.unwrap();
+        assert_eq!(post.mentions, vec![Mention { username: "alice".to_string(), address: alice.to_string(), start: 4, end: 10 }]);
+
+        // An edit keeps existing mentions quiet and notifies newly mentioned users only
+        let edit = UpdatePostRequest { title: "Hi".to_string(), content: "@bob @alice".to_string(), tags: vec![], image: None };
+        service.update_post(&post.id, author, edit).await.unwrap();
+        let read = service.get_post(&post.id).await.unwrap();
+        assert_eq!(read.mentions.iter().map(|m| (m.username.as_str(), m.start)).collect::<Vec<_>>(), vec![("bob", 0), ("alice", 5)]);
+        service.mark_notifications_read(alice, None).await.unwrap();
+        let comment = service.add_comment(CreateCommentRequest {
+            post_id: post.id.clone(),
+            content: "thanks @bob".to_string(),
+            author_address: alice.to_string(),
+            author_name: None,
+            parent_id: None,
+            image: None,
+            blockchain_transaction_hash: None,
+        }).await.unwrap();
+        assert_eq!(comment.mentions.len(), 1);
+
+        let alice_unread = service.get_notifications(alice, &PageRequest::offset(10, 0), true).await.unwrap();
+        assert!(alice_unread.items.is_empty());
+        let bob_groups = service.get_notifications(bob, &PageRequest::offset(10, 0), false).await.unwrap().items;
+        assert_eq!(bob_groups.len(), 1);
+        assert_eq!((bob_groups[0].actor_count, bob_groups[0].actors[0].name.as_deref()), (2, Some("alice")));
+        let comments = service.get_comments_with_like_status_paginated(&post.id, None, &PageRequest::offset(10, 0)).await.unwrap().items;
+        assert_eq!(comments[0].mentions[0].address, bob);
+    }
+
+    #[tokio::test]
+    async fn test_comment_tree_depth_limits_and_cursors() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+        let post = create_post(&service, post_request(author, "Thread")).await.unwrap();
+        let other = create_post(&service, post_request(author, "Other")).await.unwrap();
+        let comment = |post_id: &str, content: &str, parent: Option<&Comment>| CreateCommentRequest {
+            post_id: post_id.to_string(),
+            content: content.to_string(),
+            author_address: author.to_string(),
+            author_name: None,
+            parent_id: parent.map(|p| p.id.clone()),
+            image: None,
+            blockchain_transaction_hash: None,
+        };
+        let mut added = Vec::new();
+        for (content, parent) in [("a", None), ("a1", Some(0)), ("a1x", Some(1)), ("a1xy", Some(2)), ("a2", Some(0)), ("a3", Some(0)), ("b", None), ("c", None)] {
+            let parent = parent.map(|i: usize| &added[i]);
+            let created = service.add_comment(comment(&post.id, content, parent)).await.unwrap();
+            added.push(created);
+            // Keep creation times distinct at the microsecond precision used by cursors
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+
+        let tree = service.get_comment_tree(&post.id, None, None, 2, 3, None).await.unwrap();
+        let roots: Vec<_> = tree.comments.iter().map(|n| n.comment.content.as_str()).collect();
+        assert_eq!(roots, vec!["a", "b"]);
+        let a = &tree.comments[0];
+        assert_eq!((a.reply_count, a.replies.len(), a.has_more_replies), (3, 2, true));
+        let a1x = &a.replies[0].replies[0];
+        assert_eq!((a1x.comment.content.as_str(), a1x.depth, a1x.replies.len(), a1x.has_more_replies), ("a1x", 3, 0, true));
+        assert!(a1x.replies_cursor.is_none());
+
+        // "Load more replies" continues after the last reply shown
+        let more = service.get_comment_tree(&post.id, Some(&a.comment.id), a.replies_cursor.as_deref(), 2, 3, None).await.unwrap();
+        assert_eq!(more.comments.iter().map(|n| n.comment.content.as_str()).collect::<Vec<_>>(), vec!["a3"]);
+        assert!(more.next_cursor.is_none());
+        let next = service.get_comment_tree(&post.id, None, tree.next_cursor.as_deref(), 2, 3, None).await.unwrap();
+        assert_eq!(next.comments.iter().map(|n| n.comment.content.as_str()).collect::<Vec<_>>(), vec!["c"]);
+        assert!(matches!(service.get_comment_tree(&post.id, None, Some("bogus"), 2, 3, None).await, Err(ContentActionError::Invalid(_))));
+
+        // Replies must stay on the parent's post
+        let result = service.add_comment(comment(&other.id, "misplaced", Some(&added[0]))).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_normalized_and_listed() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let author = "0x1111111111111111111111111111111111111111";
+        let post = |title: &str, tags: &[&str]| CreatePostRequest {
+            content: format!("{} #Irys", title),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..post_request(author, title)
+        };
+
+        let first = create_post(&service, post("First", &[" #Rust", "rust", "bad tag!"])).await.unwrap();
+        assert_eq!(first.tags, vec!["rust", "irys"]);
+        let second = create_post(&service, post("Second", &["IRYS"])).await.unwrap();
+        assert_eq!(second.tags, vec!["irys"]);
+        service.like_post(&first.id, author).await.unwrap();
+
+        let irys = service.get_tag_posts("#Irys", &PageRequest::offset(1, 0), None).await.unwrap().unwrap();
+        assert_eq!((irys.tag.name.as_str(), irys.tag.post_count), ("irys", 2));
+        assert_eq!(irys.posts.items[0].id, second.id);
+        assert!(irys.posts.has_more);
+        assert!(service.get_tag_posts("unused", &PageRequest::offset(10, 0), None).await.unwrap().is_none());
+        assert!(matches!(service.get_tag_posts("no spaces", &PageRequest::offset(10, 0), None).await, Err(ContentActionError::Invalid(_))));
+
+        let trending = service.get_trending_tags(TrendingWindow::Day, 10).await.unwrap();
+        let ranked: Vec<_> = trending.iter().map(|t| (t.name.as_str(), t.recent_posts, t.recent_likes)).collect();
+        assert_eq!(ranked, vec![("irys", 2, 1), ("rust", 1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_bookmarks_page_by_bookmark_time_and_hide_deleted_posts() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let (author, reader) = ("0x1111111111111111111111111111111111111111", "0x2222222222222222222222222222222222222222");
+        let mut posts = Vec::new();
+        for i in 0..3 {
+            posts.push(create_post(&service, post_request(author, &format!("Post {}", i))).await.unwrap());
+        }
+        // Bookmark order, not post order, decides the page order
+        for post in [&posts[2], &posts[0], &posts[1]] {
+            service.bookmark_post(&post.id, reader).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+        service.bookmark_post(&posts[0].id, &reader.to_uppercase()).await.unwrap();
+        service.like_post(&posts[1].id, reader).await.unwrap();
+        assert!(matches!(service.bookmark_post("missing", reader).await, Err(ContentActionError::NotFound(_))));
+
+        let first = service.get_bookmarks(reader, &PageRequest::offset(2, 0)).await.unwrap();
+        let titles: Vec<_> = first.items.iter().map(|b| b.post.title.as_str()).collect();
+        assert_eq!(titles, vec!["Post 1", "Post 0"]);
+        assert!(first.items[0].post.is_liked_by_user && first.items.iter().all(|b| b.post.is_bookmarked_by_user));
+        let after = PageCursor::decode(first.next_cursor.as_deref().unwrap());
+        let second = service.get_bookmarks(reader, &PageRequest { limit: 2, offset: 0, after }).await.unwrap();
+        assert_eq!(second.items.iter().map(|b| b.post.title.as_str()).collect::<Vec<_>>(), vec!["Post 2"]);
+
+        assert!(service.get_post_with_like_status(&posts[0].id, Some(reader), Some(reader)).await.unwrap().is_bookmarked_by_user);
+        assert!(!service.get_post_with_like_status(&posts[0].id, Some(author), Some(author)).await.unwrap().is_bookmarked_by_user);
+        // Naming the reader without their session doesn't reveal the bookmark
+        assert!(!service.get_post_with_like_status(&posts[0].id, Some(reader), None).await.unwrap().is_bookmarked_by_user);
+        service.remove_bookmark(&posts[0].id, reader).await.unwrap();
+        service.delete_post(&posts[1].id, author).await.unwrap();
+        let remaining = service.get_bookmarks(reader, &PageRequest::offset(10, 0)).await.unwrap();
+        assert_eq!(remaining.items.iter().map(|b| b.post.title.as_str()).collect::<Vec<_>>(), vec!["Post 2"]);
+    }
+
+    #[tokio::test]
+    async fn test_reports_resolve_with_moderator_actions_and_audit_log() {
+        let store = Arc::new(MemoryStore::new());
+        let service = ForumService::from_parts(store.clone(), None, None, None);
+        let (author, reporter, other, moderator) = (
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "0x3333333333333333333333333333333333333333",
+            "0x4444444444444444444444444444444444444444",
+        );
+        store.set_user_role(moderator, UserRole::Moderator);
+        let comment = |post_id: &str| CreateCommentRequest {
+            post_id: post_id.to_string(),
+            content: "Reply".to_string(),
+            author_address: other.to_string(),
+            author_name: None,
+            parent_id: None,
+            image: None,
+            blockchain_transaction_hash: None,
+        };
+        let spam = || CreateReportRequest { reason: ReportReason::Spam, details: Some(" buy now ".to_string()) };
+        let resolve = |action: ModerationAction, duration_hours: Option<u32>| ResolveReportRequest { action, reason: Some("rule 1".to_string()), duration_hours };
+        let post = create_post(&service, post_request(author, "Spam")).await.unwrap();
+
+        let first = service.report(reporter, ModerationTarget::Post, &post.id, spam()).await.unwrap();
+        assert_eq!(first.details.as_deref(), Some("buy now"));
+        assert!(matches!(service.report(&reporter.to_uppercase(), ModerationTarget::Post, &post.id, spam()).await, Err(ContentActionError::Invalid(_))));
+        assert!(matches!(service.report(reporter, ModerationTarget::User, reporter, spam()).await, Err(ContentActionError::Invalid(_))));
+        assert!(matches!(service.report(reporter, ModerationTarget::Post, "missing", spam()).await, Err(ContentActionError::NotFound(_))));
+        service.report(other, ModerationTarget::Post, &post.id, spam()).await.unwrap();
+
+        // Only moderators see the queue; locking closes every open report on the post
+        assert!(matches!(service.get_report_queue(reporter, &PageRequest::offset(10, 0)).await, Err(ContentActionError::Forbidden(_))));
+        let queue = service.get_report_queue(moderator, &PageRequest::offset(10, 0)).await.unwrap();
+        assert_eq!(queue.items.iter().map(|r| r.reporter_address.as_str()).collect::<Vec<_>>(), vec![reporter, other]);
+        let locked = service.resolve_report(moderator, &first.id, resolve(ModerationAction::Lock, None)).await.unwrap();
+        assert_eq!((locked.status, locked.resolution), (ReportStatus::Resolved, Some(ModerationAction::Lock)));
+        assert!(service.get_report_queue(moderator, &PageRequest::offset(10, 0)).await.unwrap().items.is_empty());
+        let result = service.add_comment(comment(&post.id)).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Forbidden(_))));
+        service.unlock_post(moderator, &post.id).await.unwrap();
+        let reply = service.add_comment(comment(&post.id)).await.unwrap();
+
+        // Hiding soft deletes; suspending blocks new posts from the author until lifted
+        let report = service.report(reporter, ModerationTarget::Comment, &reply.id, spam()).await.unwrap();
+        service.resolve_report(moderator, &report.id, resolve(ModerationAction::Hide, None)).await.unwrap();
+        assert!(service.store.get_comment_by_id(&reply.id).await.unwrap().is_none());
+        let report = service.report(reporter, ModerationTarget::User, author, spam()).await.unwrap();
+        assert!(matches!(service.resolve_report(moderator, &report.id, resolve(ModerationAction::Unlock, None)).await, Err(ContentActionError::Invalid(_))));
+        service.resolve_report(moderator, &report.id, resolve(ModerationAction::Suspend, Some(24))).await.unwrap();
+        let result = create_post(&service, post_request(author, "Again")).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Forbidden(_))));
+        let edit = UpdatePostRequest { title: "Spam".to_string(), content: "Rewritten".to_string(), tags: vec![], image: None };
+        let result = service.update_post(&post.id, author, edit).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Forbidden(_))));
+        assert!(matches!(service.resolve_report(moderator, &report.id, resolve(ModerationAction::Dismiss, None)).await, Err(ContentActionError::Invalid(_))));
+        service.lift_suspension(moderator, author).await.unwrap();
+        create_post(&service, post_request(author, "Again")).await.unwrap();
+
+        assert!(matches!(service.set_user_role(moderator, other, UserRole::Moderator).await, Err(ContentActionError::Forbidden(_))));
+        let log = service.get_moderation_log(moderator, &PageRequest::offset(10, 0)).await.unwrap();
+        let actions: Vec<_> = log.items.iter().map(|e| (e.action, e.target_type)).collect();
+        assert_eq!(actions, vec![
+            (ModerationAction::Unsuspend, ModerationTarget::User),
+            (ModerationAction::Suspend, ModerationTarget::User),
+            (ModerationAction::Hide, ModerationTarget::Comment),
+            (ModerationAction::Unlock, ModerationTarget::Post),
+            (ModerationAction::Lock, ModerationTarget::Post),
+        ]);
+        assert_eq!(log.items[1].report_id.as_deref(), Some(report.id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_blocks_stop_follows_replies_and_mentions_and_mutes_filter_lists() {
+        let service = ForumService::from_parts(Arc::new(MemoryStore::new()), None, None, None);
+        let (victim, harasser, author) = (
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "0x3333333333333333333333333333333333333333",
+        );
+        let comment = |post_id: &str, author: &str, parent_id: Option<&str>| CreateCommentRequest {
+            post_id: post_id.to_string(),
+            content: "Reply".to_string(),
+            author_address: author.to_string(),
+            author_name: None,
+            parent_id: parent_id.map(str::to_string),
+            image: None,
+            blockchain_transaction_hash: None,
+        };
+        let follow = |address: &str| FollowRequest { following_id: None, following_address: Some(address.to_string()) };
+        service.store.ensure_user_exists(victim, &None).await.unwrap();
+        service.store.register_username(victim, "victim").await.unwrap();
+        let post = create_post(&service, post_request(author, "Thread")).await.unwrap();
+        let victim_comment = service.add_comment(comment(&post.id, victim, None)).await.unwrap();
+        service.follow_user(harasser, follow(victim)).await.unwrap();
+
+        // Blocking ends the follow and stops new follows, replies and mentions
+        assert!(matches!(service.block_user(victim, victim).await, Err(ContentActionError::Invalid(_))));
+        service.block_user(victim, harasser).await.unwrap();
+        assert!(!service.is_following(harasser, victim).await.unwrap());
+        let result = service.follow_user(harasser, follow(victim)).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Forbidden(_))));
+        let result = service.add_comment(comment(&post.id, harasser, Some(&victim_comment.id))).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<ContentActionError>(), Some(ContentActionError::Forbidden(_))));
+        service.add_comment(comment(&post.id, harasser, None)).await.unwrap();
+        assert!(create_post(&service, post_request(harasser, "hi @victim")).await.unwrap().mentions.is_empty());
+        assert_eq!(create_post(&service, post_request(author, "hi @victim")).await.unwrap().mentions.len(), 1);
+        let blocked = service.get_blocked_users(victim).await.unwrap();
+        assert_eq!(blocked.iter().map(|u| u.address.as_str()).collect::<Vec<_>>(), vec![harasser]);
+        service.unblock_user(victim, harasser).await.unwrap();
+        service.follow_user(harasser, follow(victim)).await.unwrap();
+
+        // Muting hides the author's posts from the muter only
+        service.follow_user(victim, follow(author)).await.unwrap();
+        service.mute_user(victim, author).await.unwrap();
+        let all = PageRequest::offset(10, 0);
+        let visible = service.get_posts_paginated_with_like_status(&all, Some(victim), Some(victim)).await;
+        assert!(visible.items.iter().all(|p| p.author_address != author));
+        // Muted authors are skipped before paging, so the newest muted post doesn't leave a short page
+        let first = service.get_posts_paginated_with_like_status(&PageRequest::offset(1, 0), Some(victim), Some(victim)).await;
+        assert_eq!(first.items.iter().map(|p| p.author_address.as_str()).collect::<Vec<_>>(), vec![harasser]);
+        assert!(!first.has_more);
+        assert!(service.get_following_feed(victim, &all, false).await.unwrap().items.is_empty());
+        assert!(service.get_posts_paginated_with_like_status(&all, Some(harasser), Some(harasser)).await.items.iter().any(|p| p.author_address == author));
+        // Mutes are private: naming the victim without their session doesn't apply them
+        assert!(service.get_posts_paginated_with_like_status(&all, Some(victim), None).await.items.iter().any(|p| p.author_address == author));
+        service.unmute_user(victim, author).await.unwrap();
+        assert!(service.get_muted_users(victim).await.unwrap().is_empty());
+        assert_eq!(service.get_following_feed(victim, &all, false).await.unwrap().items.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_spam_filter_holds_and_rejects_and_moderators_review_held_content() {
+        let store = Arc::new(MemoryStore::new());
+        let service = ForumService::from_parts(store.clone(), None, None, None)
+            .with_spam_scorer(Arc::new(spam::HeuristicScorer::new(store.clone(), spam::SpamConfig::default())));
+        let moderator = "0x9999999999999999999999999999999999999999";
+        store.set_user_role(moderator, UserRole::Moderator);
+        let accounts: Vec<String> = (1..=5).map(|i| format!("0x{}", i.to_string().repeat(40))).collect();
+        let pitch = "Earn free IRYS tokens today! Visit our site to claim your airdrop bonus before it runs out, limited slots for early users only";
+        let new_post = |author: &str, content: &str, tags: Vec<String>| CreatePostRequest {
+            content: content.to_string(),
+            tags,
+            ..post_request(author, "Airdrop")
+        };
+        let held_id = |e: Box<dyn std::error::Error>| match e.downcast::<spam::SpamError>() {
+            Ok(e) => match *e {
+                spam::SpamError::Held { held_id } => held_id,
+                other => panic!("expected a hold, got {:?}", other),
+            },
+            Err(e) => panic!("expected a hold, got {}", e),
+        };
+        let resolve = |action| ResolveHeldRequest { action, reason: Some("reviewed".to_string()) };
+
+        // A near-duplicate from another account waits for review; approving publishes it
+        let original = create_post(&service, new_post(&accounts[0], pitch, vec![])).await.unwrap();
+        let copy = pitch.replace("today!", "today!!").replace("to claim", "and claim");
+        let held = held_id(create_post(&service, new_post(&accounts[1], &copy, vec![])).await.unwrap_err());
+        assert!(matches!(service.get_held_queue(&accounts[0], &PageRequest::offset(10, 0)).await, Err(ContentActionError::Forbidden(_))));
+        let queue = service.get_held_queue(moderator, &PageRequest::offset(10, 0)).await.unwrap();
+        assert_eq!(queue.items.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), [held.as_str()]);
+        assert_eq!(queue.items[0].signals[0].name, "near_duplicate");
+        let approved = service.resolve_held(moderator, &held, resolve(ModerationAction::Approve)).await.unwrap();
+        assert_eq!(approved.status, HeldStatus::Approved);
+        let published = store.get_post_by_id(approved.content_id.as_deref().unwrap()).await.unwrap().unwrap();
+        assert_ne!(published.id, original.id);
+        assert!(matches!(service.resolve_held(moderator, &held, resolve(ModerationAction::Reject)).await, Err(ContentActionError::Invalid(_))));
+
+        // Copies spread by more accounts, plus stuffed tags, are rejected outright
+        let stuffed = create_post(&service, new_post(&accounts[2], pitch, vec!["airdrop".to_string(); 6])).await;
+        assert!(matches!(stuffed.unwrap_err().downcast_ref::<spam::SpamError>(), Some(spam::SpamError::Rejected)));
+
+        // A rejected hold never goes live
+        let comment = CreateCommentRequest {
+            post_id: original.id.clone(),
+            content: pitch.to_string(),
+            author_address: accounts[3].clone(),
+            author_name: None,
+            parent_id: None,
+            image: None,
+            blockchain_transaction_hash: None,
+        };
+        let held = held_id(service.add_comment(comment).await.unwrap_err());
+        service.resolve_held(moderator, &held, resolve(ModerationAction::Reject)).await.unwrap();
+        assert!(store.get_comments_by_post_id_paginated(&original.id, &PageRequest::offset(10, 0)).await.unwrap().items.is_empty());
+        let log = service.get_moderation_log(moderator, &PageRequest::offset(10, 0)).await.unwrap();
+        assert_eq!(log.items.iter().map(|e| e.action).collect::<Vec<_>>(), [ModerationAction::Reject, ModerationAction::Approve]);
+
+        // New accounts may only post so fast
+        let mut hellos = Vec::new();
+        for i in 0..5 {
+            hellos.push(create_post(&service, new_post(&accounts[4], &format!("Hello {}", i), vec![])).await.unwrap());
+        }
+        held_id(create_post(&service, new_post(&accounts[4], "Hello again", vec![])).await.unwrap_err());
+
+        // Edits are screened like new posts; approving a held edit applies it
+        let edit = UpdatePostRequest { title: "Airdrop".to_string(), content: "Hello, edited".to_string(), tags: vec![], image: None };
+        let held = held_id(service.update_post(&hellos[0].id, &accounts[4], edit).await.unwrap_err());
+        assert_eq!(service.get_post(&hellos[0].id).await.unwrap().content, "Hello 0");
+        let approved = service.resolve_held(moderator, &held, resolve(ModerationAction::Approve)).await.unwrap();
+        assert_eq!(approved.content_id.as_deref(), Some(hellos[0].id.as_str()));
+        assert_eq!(service.get_post(&hellos[0].id).await.unwrap().content, "Hello, edited");
+    }
+
+    #[tokio::test]
+    async fn test_posts_reference_uploaded_media_and_replaced_avatars_are_removed() {
+        let store = Arc::new(MemoryStore::new());
+        let config = MediaConfig {
+            dir: std::env::temp_dir().join(format!("irys-media-{}", ForumService::generate_id())),
+            ..MediaConfig::default()
+        };
+        let service = ForumService::from_parts(store.clone(), None, None, None).with_media_config(config.clone());
+        let author = "0x1111111111111111111111111111111111111111";
+        let png = |shade: u8| {
+            let mut out = Vec::new();
+            image::RgbImage::from_pixel(40, 30, image::Rgb([shade, 0, 0]))
+                .write_with_encoder(image::codecs::png::PngEncoder::new(&mut out))
+                .unwrap();
+            out
+        };
+        let new_post = |image: &str| CreatePostRequest {
+            content: "Look at this".to_string(),
+            image: Some(image.to_string()),
+            ..post_request(author, "Photo")
+        };
+        let is_invalid = |e: Box<dyn std::error::Error>| matches!(e.downcast::<ContentActionError>().map(|e| *e), Ok(ContentActionError::Invalid(_)));
+
+        // Posts store the URL of the uploaded image; inline data and unknown IDs are refused
+        let media = service.upload_media(author, MediaKind::Post, png(10)).await.unwrap();
+        let post = create_post(&service, new_post(&media.id)).await.unwrap();
+        assert_eq!(post.image, Some(media.display_url()));
+        assert!(is_invalid(create_post(&service, new_post("data:image/png;base64,iVBORw0KGgo=")).await.unwrap_err()));
+        assert!(is_invalid(create_post(&service, new_post(&"0".repeat(64))).await.unwrap_err()));
+
+        // Avatars are not post images, and replacing one deletes its files
+        let first = service.update_avatar(author, png(20)).await.unwrap();
+        assert!(is_invalid(create_post(&service, new_post(&first.id)).await.unwrap_err()));
+        let second = service.update_avatar(author, png(30)).await.unwrap();
+        assert_eq!(store.get_user_by_address(author).await.unwrap().unwrap().avatar, Some(second.display_url()));
+        assert!(store.get_media(&first.id).await.unwrap().is_none());
+        assert!(!config.dir.join(&first.id).exists());
+        assert!(config.dir.join(&second.id).exists());
+
+        std::fs::remove_dir_all(&config.dir).unwrap();
+    }
+}
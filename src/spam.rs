@@ -0,0 +1,508 @@
+use crate::models::{
+    ContentFingerprint, CreateCommentRequest, CreatePostRequest, HeldContent, HeldStatus, HeldSubmission, ModerationTarget,
+    SpamSignal, UpdatePostRequest,
+};
+use crate::store::ForumStore;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// Texts with fewer words are not compared for near-duplicates; short replies repeat legitimately
+const MIN_SIMHASH_WORDS: usize = 8;
+/// Most recent fingerprints a submission is compared with
+const MAX_COMPARED_FINGERPRINTS: u32 = 5000;
+
+const NEAR_DUPLICATE_POINTS: u32 = 60;
+/// Added for every further author who posted a near-duplicate
+const NEAR_DUPLICATE_AUTHOR_POINTS: u32 = 20;
+const NEAR_DUPLICATE_MAX_POINTS: u32 = 120;
+/// Per near-duplicate of the author's own recent content
+const REPEATED_CONTENT_POINTS: u32 = 25;
+const REPEATED_CONTENT_MAX_POINTS: u32 = 50;
+/// Per link beyond `max_links`
+const EXTRA_LINK_POINTS: u32 = 15;
+const EXTRA_LINK_MAX_POINTS: u32 = 60;
+const LINK_DENSITY_POINTS: u32 = 30;
+const VELOCITY_POINTS: u32 = 50;
+/// Per tag repeat beyond `max_repeated_tags`
+const REPEATED_TAG_POINTS: u32 = 10;
+const REPEATED_TAG_MAX_POINTS: u32 = 40;
+
+/// Spam filter thresholds, from `SPAM_*` environment variables. Submissions scoring at least
+/// `hold_score` wait for a moderator; at least `reject_score` are refused.
+#[derive(Debug, Clone)]
+pub struct SpamConfig {
+    pub enabled: bool,
+    pub hold_score: u32,
+    pub reject_score: u32,
+    /// Simhash bits two texts may differ in and still count as near-duplicates
+    pub near_duplicate_distance: u32,
+    pub near_duplicate_window_hours: u32,
+    /// Links allowed before each further link adds points
+    pub max_links: u32,
+    /// Highest share of words, in percent, that may be links once there are two or more
+    pub max_link_density_percent: u32,
+    /// Authors without a profile, or with one younger than this, count as new accounts
+    pub new_account_hours: u32,
+    /// Posts plus comments per hour allowed before the next one adds points
+    pub new_account_max_per_hour: u32,
+    pub max_per_hour: u32,
+    /// Repeats of one tag (explicit tags plus hashtags) allowed before each further repeat adds points
+    pub max_repeated_tags: u32,
+}
+
+impl Default for SpamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hold_score: 50,
+            reject_score: 100,
+            near_duplicate_distance: 10,
+            near_duplicate_window_hours: 24,
+            max_links: 3,
+            max_link_density_percent: 30,
+            new_account_hours: 24,
+            new_account_max_per_hour: 5,
+            max_per_hour: 30,
+            max_repeated_tags: 2,
+        }
+    }
+}
+
+impl SpamConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env_u32 = |name: &str, default: u32| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            enabled: std::env::var("SPAM_FILTER_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(defaults.enabled),
+            hold_score: env_u32("SPAM_HOLD_SCORE", defaults.hold_score),
+            reject_score: env_u32("SPAM_REJECT_SCORE", defaults.reject_score),
+            near_duplicate_distance: env_u32("SPAM_NEAR_DUPLICATE_DISTANCE", defaults.near_duplicate_distance).min(64),
+            near_duplicate_window_hours: env_u32("SPAM_NEAR_DUPLICATE_WINDOW_HOURS", defaults.near_duplicate_window_hours),
+            max_links: env_u32("SPAM_MAX_LINKS", defaults.max_links),
+            max_link_density_percent: env_u32("SPAM_MAX_LINK_DENSITY_PERCENT", defaults.max_link_density_percent),
+            new_account_hours: env_u32("SPAM_NEW_ACCOUNT_HOURS", defaults.new_account_hours),
+            new_account_max_per_hour: env_u32("SPAM_NEW_ACCOUNT_MAX_PER_HOUR", defaults.new_account_max_per_hour),
+            max_per_hour: env_u32("SPAM_MAX_PER_HOUR", defaults.max_per_hour),
+            max_repeated_tags: env_u32("SPAM_MAX_REPEATED_TAGS", defaults.max_repeated_tags),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamVerdict {
+    Allow,
+    /// Keep the submission for a moderator to approve or reject
+    Hold,
+    Reject,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpamAssessment {
+    pub score: u32,
+    pub verdict: SpamVerdict,
+    pub signals: Vec<SpamSignal>,
+}
+
+impl SpamAssessment {
+    pub fn allow() -> Self {
+        Self { score: 0, verdict: SpamVerdict::Allow, signals: Vec::new() }
+    }
+
+    /// Sum the signals and compare the score with the configured thresholds
+    pub fn from_signals(signals: Vec<SpamSignal>, config: &SpamConfig) -> Self {
+        let score: u32 = signals.iter().map(|s| s.points).sum();
+        let verdict = if score >= config.reject_score {
+            SpamVerdict::Reject
+        } else if score >= config.hold_score {
+            SpamVerdict::Hold
+        } else {
+            SpamVerdict::Allow
+        };
+        Self { score, verdict, signals }
+    }
+}
+
+/// A submission as seen by a scorer
+pub struct SpamCandidate<'a> {
+    pub content_type: ModerationTarget,
+    pub author_address: &'a str,
+    pub title: Option<&'a str>,
+    pub content: &'a str,
+    /// Tags as submitted, before normalization drops repeats
+    pub tags: &'a [String],
+}
+
+impl<'a> SpamCandidate<'a> {
+    pub fn post(request: &'a CreatePostRequest) -> Self {
+        Self {
+            content_type: ModerationTarget::Post,
+            author_address: &request.author_address,
+            title: Some(&request.title),
+            content: &request.content,
+            tags: &request.tags,
+        }
+    }
+
+    pub fn post_edit(author_address: &'a str, request: &'a UpdatePostRequest) -> Self {
+        Self {
+            content_type: ModerationTarget::Post,
+            author_address,
+            title: Some(&request.title),
+            content: &request.content,
+            tags: &request.tags,
+        }
+    }
+
+    pub fn comment(request: &'a CreateCommentRequest) -> Self {
+        Self {
+            content_type: ModerationTarget::Comment,
+            author_address: &request.author_address,
+            title: None,
+            content: &request.content,
+            tags: &[],
+        }
+    }
+}
+
+/// Decides whether a submission goes live. `HeuristicScorer` is the default; another scorer can
+/// be plugged in with `ForumService::with_spam_scorer`. Scorers fail open: a storage error drops
+/// the affected signal instead of blocking posting.
+#[async_trait]
+pub trait SpamScorer: Send + Sync {
+    async fn assess(&self, candidate: &SpamCandidate<'_>) -> SpamAssessment;
+
+    /// Called once a post or comment is live (created directly or approved by a moderator),
+    /// so later submissions can be compared with it
+    async fn record(&self, _content_type: ModerationTarget, _content_id: &str, _author_address: &str, _content: &str) {}
+}
+
+/// Lets everything through; `ForumService::from_parts` starts with it
+pub struct AllowAll;
+
+#[async_trait]
+impl SpamScorer for AllowAll {
+    async fn assess(&self, _candidate: &SpamCandidate<'_>) -> SpamAssessment {
+        SpamAssessment::allow()
+    }
+}
+
+/// Default scorer: near-duplicates of recent content (simhash), link count and density,
+/// posting velocity (stricter for new accounts) and repeated tags
+pub struct HeuristicScorer {
+    store: Arc<dyn ForumStore>,
+    config: SpamConfig,
+}
+
+impl HeuristicScorer {
+    pub fn new(store: Arc<dyn ForumStore>, config: SpamConfig) -> Self {
+        Self { store, config }
+    }
+
+    async fn duplicate_signals(&self, candidate: &SpamCandidate<'_>, signals: &mut Vec<SpamSignal>) {
+        let Some(hash) = simhash(candidate.content) else {
+            return;
+        };
+        let since = Utc::now() - Duration::hours(i64::from(self.config.near_duplicate_window_hours));
+        let fingerprints = match self.store.get_recent_fingerprints(since, MAX_COMPARED_FINGERPRINTS).await {
+            Ok(fingerprints) => fingerprints,
+            Err(e) => {
+                warn!("⚠️ Spam filter could not load fingerprints, skipping near-duplicate check: {}", e);
+                return;
+            }
+        };
+
+        let author = candidate.author_address.to_lowercase();
+        let mut other_authors = HashSet::new();
+        let mut own = 0;
+        for fingerprint in &fingerprints {
+            let near = fingerprint.simhash.is_some_and(|other| distance(hash, other) <= self.config.near_duplicate_distance);
+            if !near {
+                continue;
+            }
+            if fingerprint.author_address == author {
+                own += 1;
+            } else {
+                other_authors.insert(fingerprint.author_address.as_str());
+            }
+        }
+
+        if !other_authors.is_empty() {
+            let extra = other_authors.len() as u32 - 1;
+            signals.push(SpamSignal {
+                name: "near_duplicate".to_string(),
+                points: (NEAR_DUPLICATE_POINTS + extra * NEAR_DUPLICATE_AUTHOR_POINTS).min(NEAR_DUPLICATE_MAX_POINTS),
+                detail: format!("Near-duplicate of recent content by {} other author(s)", other_authors.len()),
+            });
+        }
+        if own > 0 {
+            signals.push(SpamSignal {
+                name: "repeated_content".to_string(),
+                points: (own * REPEATED_CONTENT_POINTS).min(REPEATED_CONTENT_MAX_POINTS),
+                detail: format!("Near-duplicate of {} recent submission(s) by the same author", own),
+            });
+        }
+    }
+
+    async fn velocity_signal(&self, candidate: &SpamCandidate<'_>) -> Option<SpamSignal> {
+        let new_account = match self.store.get_user_by_address(candidate.author_address).await {
+            Ok(user) => user.is_none_or(|u| Utc::now() - u.created_at < Duration::hours(i64::from(self.config.new_account_hours))),
+            Err(e) => {
+                warn!("⚠️ Spam filter could not load the author, skipping velocity check: {}", e);
+                return None;
+            }
+        };
+        let limit = if new_account { self.config.new_account_max_per_hour } else { self.config.max_per_hour };
+        let recent = match self.store.count_recent_fingerprints(candidate.author_address, Utc::now() - Duration::hours(1)).await {
+            Ok(recent) => recent,
+            Err(e) => {
+                warn!("⚠️ Spam filter could not count recent submissions, skipping velocity check: {}", e);
+                return None;
+            }
+        };
+
+        (recent >= limit).then(|| SpamSignal {
+            name: "velocity".to_string(),
+            points: VELOCITY_POINTS,
+            detail: format!(
+                "{} posts and comments in the last hour ({} allowed{})",
+                recent, limit, if new_account { " for new accounts" } else { "" }
+            ),
+        })
+    }
+}
+
+#[async_trait]
+impl SpamScorer for HeuristicScorer {
+    async fn assess(&self, candidate: &SpamCandidate<'_>) -> SpamAssessment {
+        if !self.config.enabled {
+            return SpamAssessment::allow();
+        }
+
+        let mut signals = Vec::new();
+        self.duplicate_signals(candidate, &mut signals).await;
+        signals.extend(link_signals(candidate, &self.config));
+        signals.extend(self.velocity_signal(candidate).await);
+        signals.extend(repeated_tag_signal(candidate, &self.config));
+        SpamAssessment::from_signals(signals, &self.config)
+    }
+
+    async fn record(&self, content_type: ModerationTarget, content_id: &str, author_address: &str, content: &str) {
+        let fingerprint = ContentFingerprint {
+            content_id: content_id.to_string(),
+            content_type,
+            author_address: author_address.to_lowercase(),
+            simhash: simhash(content),
+            created_at: Utc::now(),
+        };
+        if let Err(e) = self.store.record_fingerprint(&fingerprint).await {
+            warn!("⚠️ Failed to record fingerprint of {} {}: {}", content_type.as_str(), content_id, e);
+        }
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 64-bit FNV-1a with a final mix. Stored fingerprints must hash the same in every build,
+/// which `DefaultHasher` does not promise.
+fn stable_hash(word: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in word.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// Simhash over the words of `text`: texts sharing most of their words get hashes that differ
+/// in few bits. `None` below `MIN_SIMHASH_WORDS` words.
+pub fn simhash(text: &str) -> Option<u64> {
+    let words = words(text);
+    if words.len() < MIN_SIMHASH_WORDS {
+        return None;
+    }
+    let mut weights = [0i32; 64];
+    for word in &words {
+        let hash = stable_hash(word);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    Some(weights.iter().enumerate().filter(|(_, w)| **w > 0).fold(0, |hash, (bit, _)| hash | 1 << bit))
+}
+
+/// Number of differing bits
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+fn is_link(token: &str) -> bool {
+    let token = token.to_lowercase();
+    token.contains("http://") || token.contains("https://") || token.starts_with("www.")
+}
+
+fn link_signals(candidate: &SpamCandidate<'_>, config: &SpamConfig) -> Vec<SpamSignal> {
+    let tokens: Vec<&str> = candidate.title.into_iter()
+        .chain([candidate.content])
+        .flat_map(str::split_whitespace)
+        .collect();
+    let links = tokens.iter().filter(|t| is_link(t)).count() as u32;
+
+    let mut signals = Vec::new();
+    if links > config.max_links {
+        signals.push(SpamSignal {
+            name: "links".to_string(),
+            points: ((links - config.max_links) * EXTRA_LINK_POINTS).min(EXTRA_LINK_MAX_POINTS),
+            detail: format!("{} links ({} allowed)", links, config.max_links),
+        });
+    }
+    let density = links * 100 / (tokens.len() as u32).max(1);
+    if links >= 2 && density > config.max_link_density_percent {
+        signals.push(SpamSignal {
+            name: "link_density".to_string(),
+            points: LINK_DENSITY_POINTS,
+            detail: format!("{}% of words are links", density),
+        });
+    }
+    signals
+}
+
+fn repeated_tag_signal(candidate: &SpamCandidate<'_>, config: &SpamConfig) -> Option<SpamSignal> {
+    let tags: Vec<String> = candidate.tags.iter()
+        .filter_map(|t| crate::utils::normalize_tag(t))
+        .chain(crate::utils::hashtag_occurrences(candidate.content))
+        .collect();
+    let unique: HashSet<&String> = tags.iter().collect();
+    let repeats = (tags.len() - unique.len()) as u32;
+
+    (repeats > config.max_repeated_tags).then(|| SpamSignal {
+        name: "repeated_tags".to_string(),
+        points: ((repeats - config.max_repeated_tags) * REPEATED_TAG_POINTS).min(REPEATED_TAG_MAX_POINTS),
+        detail: format!("Tags repeated {} times", repeats),
+    })
+}
+
+/// Why a submission did not go live
+#[derive(Debug)]
+pub enum SpamError {
+    Rejected,
+    /// Stored for moderator review under this id
+    Held { held_id: String },
+    Internal(String),
+}
+
+impl fmt::Display for SpamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejected => write!(f, "Your submission was rejected by the spam filter"),
+            Self::Held { .. } => write!(f, "Your submission is awaiting moderator review"),
+            Self::Internal(message) => write!(f, "Spam filter error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SpamError {}
+
+/// Score a submission and act on the verdict: `Ok` lets it through, a hold stores
+/// `submission()` in the moderation queue, a reject drops it
+pub async fn screen(
+    store: &dyn ForumStore,
+    scorer: &dyn SpamScorer,
+    candidate: &SpamCandidate<'_>,
+    submission: impl FnOnce() -> HeldSubmission,
+) -> Result<(), SpamError> {
+    let assessment = scorer.assess(candidate).await;
+    match assessment.verdict {
+        SpamVerdict::Allow => Ok(()),
+        SpamVerdict::Reject => {
+            info!("🚫 Spam filter rejected a {} by {} (score {})", candidate.content_type.as_str(), candidate.author_address, assessment.score);
+            Err(SpamError::Rejected)
+        }
+        SpamVerdict::Hold => {
+            let held = HeldContent {
+                id: uuid::Uuid::new_v4().to_string(),
+                author_address: candidate.author_address.to_lowercase(),
+                submission: submission(),
+                score: assessment.score,
+                signals: assessment.signals,
+                status: HeldStatus::Held,
+                content_id: None,
+                resolved_by: None,
+                resolved_at: None,
+                created_at: Utc::now(),
+            };
+            store.hold_content(&held).await
+                .map_err(|e| SpamError::Internal(format!("Failed to hold submission: {}", e)))?;
+            info!("⏸️ Spam filter held a {} by {} for review: {} (score {})", candidate.content_type.as_str(), candidate.author_address, held.id, held.score);
+            Err(SpamError::Held { held_id: held.id })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(content: &str, tags: &[&str]) -> CreatePostRequest {
+        CreatePostRequest {
+            title: "Title".to_string(),
+            content: content.to_string(),
+            author_address: "0x1111111111111111111111111111111111111111".to_string(),
+            author_name: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            image: None,
+            blockchain_transaction_hash: None,
+            blockchain_transaction_proof: None,
+        }
+    }
+
+    #[test]
+    fn test_simhash_finds_near_duplicates() {
+        let spam = "Earn free IRYS tokens today! Visit our site to claim your airdrop bonus before it runs out, limited slots for early users only";
+        let edited = "Earn FREE IRYS tokens today!! Visit our site and claim your airdrop bonus before it runs out, limited slots for early users only";
+        let unrelated = "I have been testing the new storage layer and the upload speed is impressive compared to last month";
+        let limit = SpamConfig::default().near_duplicate_distance;
+
+        assert!(distance(simhash(spam).unwrap(), simhash(edited).unwrap()) <= limit);
+        assert!(distance(simhash(spam).unwrap(), simhash(unrelated).unwrap()) > limit);
+        assert_eq!(simhash("thanks, great post!"), None);
+    }
+
+    #[test]
+    fn test_link_and_tag_signals() {
+        let config = SpamConfig::default();
+        let names = |signals: Vec<SpamSignal>| signals.into_iter().map(|s| s.name).collect::<Vec<_>>();
+
+        let request = post("Read https://irys.xyz and the docs at https://docs.irys.xyz before asking", &[]);
+        assert!(link_signals(&SpamCandidate::post(&request), &config).is_empty());
+        let request = post("https://a.example https://b.example https://c.example www.d.example https://e.example", &[]);
+        assert_eq!(names(link_signals(&SpamCandidate::post(&request), &config)), ["links", "link_density"]);
+
+        let request = post("#irys gm", &["irys"]);
+        assert!(repeated_tag_signal(&SpamCandidate::post(&request), &config).is_none());
+        let request = post("#irys #irys gm", &["irys", "#Irys"]);
+        assert_eq!(repeated_tag_signal(&SpamCandidate::post(&request), &config).unwrap().points, 10);
+        let request = post("#airdrop #airdrop #airdrop #airdrop", &["airdrop", "airdrop"]);
+        assert_eq!(repeated_tag_signal(&SpamCandidate::post(&request), &config).unwrap().points, 30);
+    }
+}
@@ -0,0 +1,655 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use crate::async_queue::QueuedTask;
+use crate::database::DatabaseService;
+use crate::indexer::IndexedEvent;
+use crate::models::*;
+
+/// Storage backend used by `ForumService`, the async queue and the chain indexer.
+///
+/// `DatabaseService` (Postgres) and `MemoryStore` implement the same semantics,
+/// so every feature works offline and services can be tested without a database.
+/// Errors use `sqlx::Error` for both backends; missing rows are `RowNotFound`.
+#[async_trait]
+pub trait ForumStore: Send + Sync {
+    /// Backend name reported in logs and performance stats
+    fn backend_name(&self) -> &'static str;
+    fn get_database_stats(&self) -> serde_json::Value;
+
+    // Posts
+    async fn create_post(&self, post: &Post) -> Result<(), sqlx::Error>;
+    /// Newest posts, skipping any by `excluded_authors` (lowercase addresses)
+    async fn get_posts_paginated(&self, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error>;
+    async fn get_posts_by_user(&self, user_address: &str, page: &PageRequest) -> Result<Page<Post>, sqlx::Error>;
+    async fn get_posts_by_user_with_like_status(&self, user_address: &str, page: &PageRequest, request_user_address: Option<&str>) -> Result<Page<Post>, sqlx::Error>;
+    /// Posts by the users `user_address` follows, newest first, with its like status
+    async fn get_following_feed(&self, user_address: &str, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error>;
+    async fn get_post_by_id(&self, id: &str) -> Result<Option<Post>, sqlx::Error>;
+    /// The post with its like status for `user_address`. Bookmarks are private, so the bookmark
+    /// flag is only filled for the signed-in `viewer`.
+    async fn get_post_by_id_with_like_status(&self, id: &str, user_address: Option<&str>, viewer: Option<&str>) -> Result<Option<Post>, sqlx::Error>;
+    async fn get_post_content_hash(&self, post_id: &str) -> Result<Option<String>, sqlx::Error>;
+    async fn check_duplicate_post(&self, author_address: &str, content: &str) -> Result<bool, sqlx::Error>;
+    async fn update_post_blockchain_hash(&self, post_id: &str, tx_hash: &str) -> Result<(), sqlx::Error>;
+    /// Archive the current version as the next revision, then store the title, content (with its HTML), tags, image
+    /// and Irys ID of `post`; `RowNotFound` if the post does not exist
+    async fn update_post(&self, post: &Post) -> Result<(), sqlx::Error>;
+    /// Prior versions of a post, oldest first
+    async fn get_post_revisions(&self, post_id: &str) -> Result<Vec<PostRevision>, sqlx::Error>;
+    /// Soft delete a post and decrement the author's `posts_count`; false if missing or already deleted
+    async fn delete_post(&self, post_id: &str, deleted_by: &str) -> Result<bool, sqlx::Error>;
+    async fn like_post(&self, post_id: &str, user_address: &str) -> Result<u32, sqlx::Error>;
+    async fn has_user_liked_post(&self, post_id: &str, user_address: &str) -> Result<bool, sqlx::Error>;
+    /// Bookmark a post; false if it was already bookmarked
+    async fn add_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error>;
+    /// False if the post was not bookmarked
+    async fn remove_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error>;
+    /// The user's bookmarked live posts, most recently bookmarked first, with their like status
+    async fn get_bookmarks(&self, user_address: &str, page: &PageRequest) -> Result<Page<BookmarkedPost>, sqlx::Error>;
+
+    // Comments
+    async fn add_comment(&self, comment: &Comment) -> Result<(), sqlx::Error>;
+    async fn get_comments_by_post_id_paginated(&self, post_id: &str, page: &PageRequest) -> Result<Page<Comment>, sqlx::Error>;
+    async fn get_comment_by_id(&self, comment_id: &str) -> Result<Option<Comment>, sqlx::Error>;
+    /// A window of a comment thread, in no particular order: up to `limit + 1` comments directly under
+    /// `parent_id` (top level if `None`) after `after` in (created_at, id) order, then up to `limit`
+    /// replies per comment, down to `max_depth` levels. Deleted comments appear as tombstones.
+    async fn get_comment_tree(&self, post_id: &str, parent_id: Option<&str>, after: Option<&PageCursor>, limit: u32, max_depth: u32) -> Result<Vec<CommentTreeRow>, sqlx::Error>;
+    async fn get_comment_chain_id(&self, comment_id: &str) -> Result<Option<u64>, sqlx::Error>;
+    async fn check_duplicate_comment(&self, author_address: &str, content: &str, post_id: &str) -> Result<bool, sqlx::Error>;
+    async fn update_comment_blockchain_hash(&self, comment_id: &str, tx_hash: &str) -> Result<(), sqlx::Error>;
+    async fn like_comment(&self, comment_id: &str, user_address: &str) -> Result<(u32, bool), sqlx::Error>;
+    async fn check_comment_liked(&self, comment_id: &str, user_address: &str) -> Result<bool, sqlx::Error>;
+    /// Soft delete a comment and decrement the post and author comment counts; false if missing or
+    /// already deleted. Deleted comments with replies stay in comment lists as tombstones.
+    async fn delete_comment(&self, comment_id: &str, deleted_by: &str) -> Result<bool, sqlx::Error>;
+
+    // Users
+    async fn get_user_by_address(&self, address: &str) -> Result<Option<User>, sqlx::Error>;
+    async fn get_user_address_by_id(&self, user_id: &str) -> Result<String, sqlx::Error>;
+    async fn get_user_role(&self, address: &str) -> Result<UserRole, sqlx::Error>;
+    /// False if the user does not exist
+    async fn update_user_role(&self, address: &str, role: UserRole) -> Result<bool, sqlx::Error>;
+    async fn ensure_user_exists(&self, address: &str, name: &Option<String>) -> Result<(), sqlx::Error>;
+    async fn get_active_users_ranking(&self, limit: i64) -> Result<Vec<User>, sqlx::Error>;
+    async fn get_global_stats(&self) -> Result<GlobalStats, sqlx::Error>;
+    async fn update_user_avatar(&self, user_address: &str, avatar_url: &str) -> Result<(), sqlx::Error>;
+    async fn update_user_bio(&self, user_address: &str, bio: &str) -> Result<(), sqlx::Error>;
+
+    // Search
+    /// Ranked full-text search; the memory backend does a case-insensitive substring scan instead
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, sqlx::Error>;
+
+    // Usernames
+    async fn register_username(&self, address: &str, username: &str) -> Result<bool, sqlx::Error>;
+    async fn is_username_available(&self, username: &str) -> Result<bool, sqlx::Error>;
+    async fn get_username_by_address(&self, address: &str) -> Result<Option<String>, sqlx::Error>;
+    async fn user_has_username(&self, address: &str) -> Result<bool, sqlx::Error>;
+    async fn get_address_by_username(&self, username: &str) -> Result<Option<String>, sqlx::Error>;
+
+    // Used transactions
+    async fn is_transaction_used(&self, tx_hash: &str) -> Result<bool, sqlx::Error>;
+    async fn record_post_transaction(&self, tx_hash: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>, post_id: &str) -> Result<(), sqlx::Error>;
+    async fn record_comment_transaction(&self, tx_hash: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>, comment_id: &str) -> Result<(), sqlx::Error>;
+
+    // Follows
+    async fn follow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error>;
+    async fn unfollow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error>;
+    async fn is_following(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error>;
+    async fn get_follow_counts(&self, user_address: &str) -> Result<(u32, u32, u32), sqlx::Error>;
+    async fn get_following_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error>;
+    async fn get_followers_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error>;
+    async fn get_mutual_follows_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error>;
+
+    // Daily recommendations
+    async fn should_refresh_daily_recommendations(&self) -> Result<bool, sqlx::Error>;
+    async fn calculate_hot_posts(&self) -> Result<Vec<String>, sqlx::Error>;
+    async fn update_daily_recommendations(&self, post_ids: &[String]) -> Result<(), sqlx::Error>;
+    async fn get_daily_recommendations(&self, user_address: Option<&str>, excluded_authors: &[String]) -> Result<RecommendationResult, sqlx::Error>;
+
+    // Notifications
+    /// Record a notification, or mark the actor's existing one in the same group unread and newest again
+    async fn create_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error>;
+    /// Remove the actor's notification from the group (e.g. after an unlike)
+    async fn delete_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error>;
+    /// Notification groups newest first; `message` is left empty for `NotificationGroup::describe`
+    async fn get_notification_groups(&self, recipient_address: &str, page: &PageRequest, unread_only: bool) -> Result<Page<NotificationGroup>, sqlx::Error>;
+    /// Mark the given groups (all when `None`) read; returns the number of notifications changed
+    async fn mark_notifications_read(&self, recipient_address: &str, group_keys: Option<&[String]>) -> Result<u64, sqlx::Error>;
+    /// Number of groups with unread notifications
+    async fn count_unread_notifications(&self, recipient_address: &str) -> Result<u32, sqlx::Error>;
+
+    // Tags
+    /// Live posts carrying `tag` (already normalized), newest first, with the viewer's like status
+    async fn get_posts_by_tag(&self, tag: &str, page: &PageRequest, viewer: Option<&str>) -> Result<Page<Post>, sqlx::Error>;
+    /// `None` if no live post carries the tag
+    async fn get_tag(&self, tag: &str) -> Result<Option<TagSummary>, sqlx::Error>;
+    /// Tags used by live posts created since `since`, most used first
+    async fn get_trending_tags(&self, since: DateTime<Utc>, limit: u32) -> Result<Vec<TrendingTag>, sqlx::Error>;
+
+    // Mentions
+    /// Replace the mentions stored for a post's own content (`comment_id` None) or for one of its comments
+    async fn replace_mentions(&self, post_id: &str, comment_id: Option<&str>, mentions: &[Mention]) -> Result<(), sqlx::Error>;
+    /// Mentions in each post's own content, keyed by post id, in order of appearance
+    async fn get_post_mentions(&self, post_ids: &[String]) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error>;
+    /// Mentions keyed by comment id, in order of appearance
+    async fn get_comment_mentions(&self, comment_ids: &[String]) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error>;
+
+    // Moderation
+    /// Record an open report; false if the reporter already has an open report on the target
+    async fn create_report(&self, report: &Report) -> Result<bool, sqlx::Error>;
+    async fn get_report(&self, report_id: &str) -> Result<Option<Report>, sqlx::Error>;
+    /// Open reports, oldest first
+    async fn get_open_reports(&self, page: &PageRequest) -> Result<Page<Report>, sqlx::Error>;
+    /// Close every open report on the target; returns how many were closed
+    async fn close_reports(&self, target_type: ModerationTarget, target_id: &str, status: ReportStatus, resolution: ModerationAction, resolved_by: &str) -> Result<u32, sqlx::Error>;
+    /// Lock a live post (`locked_by` set) or unlock it; false if missing or already in that state
+    async fn set_post_locked(&self, post_id: &str, locked_by: Option<&str>) -> Result<bool, sqlx::Error>;
+    async fn is_post_locked(&self, post_id: &str) -> Result<bool, sqlx::Error>;
+    /// Create or replace the suspension of `suspension.address`
+    async fn suspend_address(&self, suspension: &Suspension) -> Result<(), sqlx::Error>;
+    /// False if the address had no suspension
+    async fn lift_suspension(&self, address: &str) -> Result<bool, sqlx::Error>;
+    /// The address's suspension unless it has expired
+    async fn get_active_suspension(&self, address: &str) -> Result<Option<Suspension>, sqlx::Error>;
+    /// Append to the audit log; entries are never changed or removed
+    async fn append_moderation_log(&self, entry: &ModerationLogEntry) -> Result<(), sqlx::Error>;
+    /// Audit log, newest first
+    async fn get_moderation_log(&self, page: &PageRequest) -> Result<Page<ModerationLogEntry>, sqlx::Error>;
+
+    // Blocks and mutes
+    /// Block a user and remove any follow between the two; false if already blocked
+    async fn block_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error>;
+    /// False if the user was not blocked
+    async fn unblock_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error>;
+    async fn is_blocked(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error>;
+    /// Users `blocker_address` has blocked, most recent first
+    async fn get_blocked_users(&self, blocker_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error>;
+    /// False if already muted
+    async fn mute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error>;
+    /// False if the user was not muted
+    async fn unmute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error>;
+    /// Users `muter_address` has muted, most recent first
+    async fn get_muted_users(&self, muter_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error>;
+
+    // Spam filter
+    async fn record_fingerprint(&self, fingerprint: &ContentFingerprint) -> Result<(), sqlx::Error>;
+    /// Fingerprints with a simhash created since `since`, newest first
+    async fn get_recent_fingerprints(&self, since: DateTime<Utc>, limit: u32) -> Result<Vec<ContentFingerprint>, sqlx::Error>;
+    /// Posts and comments by the author created since `since`
+    async fn count_recent_fingerprints(&self, author_address: &str, since: DateTime<Utc>) -> Result<u32, sqlx::Error>;
+    async fn hold_content(&self, held: &HeldContent) -> Result<(), sqlx::Error>;
+    async fn get_held_content(&self, held_id: &str) -> Result<Option<HeldContent>, sqlx::Error>;
+    /// Submissions awaiting review, oldest first
+    async fn get_held_queue(&self, page: &PageRequest) -> Result<Page<HeldContent>, sqlx::Error>;
+    /// Approve (with the created post or comment) or reject a held submission; false unless it was still held
+    async fn resolve_held_content(&self, held_id: &str, status: HeldStatus, content_id: Option<&str>, resolved_by: &str) -> Result<bool, sqlx::Error>;
+
+    // Media
+    /// Record an uploaded image; an existing media with the same ID is kept as is
+    async fn create_media(&self, media: &Media) -> Result<(), sqlx::Error>;
+    async fn get_media(&self, media_id: &str) -> Result<Option<Media>, sqlx::Error>;
+    async fn delete_media(&self, media_id: &str) -> Result<bool, sqlx::Error>;
+    /// Users whose avatar is `avatar_url`
+    async fn count_users_with_avatar(&self, avatar_url: &str) -> Result<u32, sqlx::Error>;
+
+    // Chain indexer
+    async fn get_chain_checkpoints(&self) -> Result<Vec<(u64, String)>, sqlx::Error>;
+    async fn store_chain_events(&self, events: &[IndexedEvent], checkpoint_block: u64, checkpoint_hash: &str) -> Result<(), sqlx::Error>;
+    async fn rollback_chain_events(&self, after_block: Option<u64>) -> Result<(), sqlx::Error>;
+
+    // Async task queue
+    /// Persist a new task unless `max_queued` pending/processing tasks already exist; returns false when full
+    async fn enqueue_task(&self, task: &QueuedTask, max_queued: u64) -> Result<bool, sqlx::Error>;
+    /// Claim the oldest due task (pending, or processing with an expired lease) and count the attempt
+    async fn claim_task(&self, lease: std::time::Duration) -> Result<Option<QueuedTask>, sqlx::Error>;
+    async fn complete_task(&self, task_id: &str, result: &serde_json::Value) -> Result<(), sqlx::Error>;
+    /// Record a failed attempt and make the task due again at `run_at`
+    async fn reschedule_task(&self, task_id: &str, error: &str, run_at: DateTime<Utc>) -> Result<(), sqlx::Error>;
+    /// Move a task to the dead-letter list
+    async fn dead_letter_task(&self, task_id: &str, error: &str) -> Result<(), sqlx::Error>;
+    async fn get_task(&self, task_id: &str) -> Result<Option<QueuedTask>, sqlx::Error>;
+    /// Delete completed tasks finished before `before`; returns the number removed
+    async fn evict_completed_tasks(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error>;
+}
+
+/// Postgres backend: delegates to the inherent `DatabaseService` queries
+#[async_trait]
+impl ForumStore for DatabaseService {
+    fn backend_name(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn get_database_stats(&self) -> serde_json::Value {
+        DatabaseService::get_database_stats(self)
+    }
+
+    async fn create_post(&self, post: &Post) -> Result<(), sqlx::Error> {
+        DatabaseService::create_post(self, post).await
+    }
+
+    async fn get_posts_paginated(&self, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error> {
+        DatabaseService::get_posts_paginated(self, page, excluded_authors).await
+    }
+
+    async fn get_posts_by_user(&self, user_address: &str, page: &PageRequest) -> Result<Page<Post>, sqlx::Error> {
+        DatabaseService::get_posts_by_user(self, user_address, page).await
+    }
+
+    async fn get_posts_by_user_with_like_status(&self, user_address: &str, page: &PageRequest, request_user_address: Option<&str>) -> Result<Page<Post>, sqlx::Error> {
+        DatabaseService::get_posts_by_user_with_like_status(self, user_address, page, request_user_address).await
+    }
+
+    async fn get_following_feed(&self, user_address: &str, page: &PageRequest, excluded_authors: &[String]) -> Result<Page<Post>, sqlx::Error> {
+        DatabaseService::get_following_feed(self, user_address, page, excluded_authors).await
+    }
+
+    async fn get_post_by_id(&self, id: &str) -> Result<Option<Post>, sqlx::Error> {
+        DatabaseService::get_post_by_id(self, id).await
+    }
+
+    async fn get_post_by_id_with_like_status(&self, id: &str, user_address: Option<&str>, viewer: Option<&str>) -> Result<Option<Post>, sqlx::Error> {
+        DatabaseService::get_post_by_id_with_like_status(self, id, user_address, viewer).await
+    }
+
+    async fn get_post_content_hash(&self, post_id: &str) -> Result<Option<String>, sqlx::Error> {
+        DatabaseService::get_post_content_hash(self, post_id).await
+    }
+
+    async fn check_duplicate_post(&self, author_address: &str, content: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::check_duplicate_post(self, author_address, content).await
+    }
+
+    async fn update_post_blockchain_hash(&self, post_id: &str, tx_hash: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::update_post_blockchain_hash(self, post_id, tx_hash).await
+    }
+
+    async fn update_post(&self, post: &Post) -> Result<(), sqlx::Error> {
+        DatabaseService::update_post(self, post).await
+    }
+
+    async fn get_post_revisions(&self, post_id: &str) -> Result<Vec<PostRevision>, sqlx::Error> {
+        DatabaseService::get_post_revisions(self, post_id).await
+    }
+
+    async fn delete_post(&self, post_id: &str, deleted_by: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::delete_post(self, post_id, deleted_by).await
+    }
+
+    async fn like_post(&self, post_id: &str, user_address: &str) -> Result<u32, sqlx::Error> {
+        DatabaseService::like_post(self, post_id, user_address).await
+    }
+
+    async fn has_user_liked_post(&self, post_id: &str, user_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::has_user_liked_post(self, post_id, user_address).await
+    }
+
+    async fn add_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::add_bookmark(self, user_address, post_id).await
+    }
+
+    async fn remove_bookmark(&self, user_address: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::remove_bookmark(self, user_address, post_id).await
+    }
+
+    async fn get_bookmarks(&self, user_address: &str, page: &PageRequest) -> Result<Page<BookmarkedPost>, sqlx::Error> {
+        DatabaseService::get_bookmarks(self, user_address, page).await
+    }
+
+    async fn add_comment(&self, comment: &Comment) -> Result<(), sqlx::Error> {
+        DatabaseService::add_comment(self, comment).await
+    }
+
+    async fn get_comments_by_post_id_paginated(&self, post_id: &str, page: &PageRequest) -> Result<Page<Comment>, sqlx::Error> {
+        DatabaseService::get_comments_by_post_id_paginated(self, post_id, page).await
+    }
+
+    async fn get_comment_by_id(&self, comment_id: &str) -> Result<Option<Comment>, sqlx::Error> {
+        DatabaseService::get_comment_by_id(self, comment_id).await
+    }
+
+    async fn get_comment_tree(&self, post_id: &str, parent_id: Option<&str>, after: Option<&PageCursor>, limit: u32, max_depth: u32) -> Result<Vec<CommentTreeRow>, sqlx::Error> {
+        DatabaseService::get_comment_tree(self, post_id, parent_id, after, limit, max_depth).await
+    }
+
+    async fn get_comment_chain_id(&self, comment_id: &str) -> Result<Option<u64>, sqlx::Error> {
+        DatabaseService::get_comment_chain_id(self, comment_id).await
+    }
+
+    async fn check_duplicate_comment(&self, author_address: &str, content: &str, post_id: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::check_duplicate_comment(self, author_address, content, post_id).await
+    }
+
+    async fn update_comment_blockchain_hash(&self, comment_id: &str, tx_hash: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::update_comment_blockchain_hash(self, comment_id, tx_hash).await
+    }
+
+    async fn like_comment(&self, comment_id: &str, user_address: &str) -> Result<(u32, bool), sqlx::Error> {
+        DatabaseService::like_comment(self, comment_id, user_address).await
+    }
+
+    async fn check_comment_liked(&self, comment_id: &str, user_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::check_comment_liked(self, comment_id, user_address).await
+    }
+
+    async fn delete_comment(&self, comment_id: &str, deleted_by: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::delete_comment(self, comment_id, deleted_by).await
+    }
+
+    async fn get_user_by_address(&self, address: &str) -> Result<Option<User>, sqlx::Error> {
+        DatabaseService::get_user_by_address(self, address).await
+    }
+
+    async fn get_user_address_by_id(&self, user_id: &str) -> Result<String, sqlx::Error> {
+        DatabaseService::get_user_address_by_id(self, user_id).await
+    }
+
+    async fn get_user_role(&self, address: &str) -> Result<UserRole, sqlx::Error> {
+        DatabaseService::get_user_role(self, address).await
+    }
+
+    async fn update_user_role(&self, address: &str, role: UserRole) -> Result<bool, sqlx::Error> {
+        DatabaseService::update_user_role(self, address, role).await
+    }
+
+    async fn ensure_user_exists(&self, address: &str, name: &Option<String>) -> Result<(), sqlx::Error> {
+        DatabaseService::ensure_user_exists(self, address, name).await
+    }
+
+    async fn get_active_users_ranking(&self, limit: i64) -> Result<Vec<User>, sqlx::Error> {
+        DatabaseService::get_active_users_ranking(self, limit).await
+    }
+
+    async fn get_global_stats(&self) -> Result<GlobalStats, sqlx::Error> {
+        DatabaseService::get_global_stats(self).await
+    }
+
+    async fn update_user_avatar(&self, user_address: &str, avatar_url: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::update_user_avatar(self, user_address, avatar_url).await
+    }
+
+    async fn update_user_bio(&self, user_address: &str, bio: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::update_user_bio(self, user_address, bio).await
+    }
+
+    async fn search(&self, request: &SearchRequest) -> Result<SearchResults, sqlx::Error> {
+        DatabaseService::search(self, request).await
+    }
+
+    async fn register_username(&self, address: &str, username: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::register_username(self, address, username).await
+    }
+
+    async fn is_username_available(&self, username: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::is_username_available(self, username).await
+    }
+
+    async fn get_username_by_address(&self, address: &str) -> Result<Option<String>, sqlx::Error> {
+        DatabaseService::get_username_by_address(self, address).await
+    }
+
+    async fn user_has_username(&self, address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::user_has_username(self, address).await
+    }
+
+    async fn get_address_by_username(&self, username: &str) -> Result<Option<String>, sqlx::Error> {
+        DatabaseService::get_address_by_username(self, username).await
+    }
+
+    async fn is_transaction_used(&self, tx_hash: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::is_transaction_used(self, tx_hash).await
+    }
+
+    async fn record_post_transaction(&self, tx_hash: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>, post_id: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::record_post_transaction(self, tx_hash, user_address, block_number, block_timestamp, post_id).await
+    }
+
+    async fn record_comment_transaction(&self, tx_hash: &str, user_address: &str, block_number: u64, block_timestamp: DateTime<Utc>, comment_id: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::record_comment_transaction(self, tx_hash, user_address, block_number, block_timestamp, comment_id).await
+    }
+
+    async fn follow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::follow_user(self, follower_address, following_address).await
+    }
+
+    async fn unfollow_user(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::unfollow_user(self, follower_address, following_address).await
+    }
+
+    async fn is_following(&self, follower_address: &str, following_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::is_following(self, follower_address, following_address).await
+    }
+
+    async fn get_follow_counts(&self, user_address: &str) -> Result<(u32, u32, u32), sqlx::Error> {
+        DatabaseService::get_follow_counts(self, user_address).await
+    }
+
+    async fn get_following_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error> {
+        DatabaseService::get_following_list(self, user_address, page).await
+    }
+
+    async fn get_followers_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error> {
+        DatabaseService::get_followers_list(self, user_address, page).await
+    }
+
+    async fn get_mutual_follows_list(&self, user_address: &str, page: &PageRequest) -> Result<Page<UserProfile>, sqlx::Error> {
+        DatabaseService::get_mutual_follows_list(self, user_address, page).await
+    }
+
+    async fn should_refresh_daily_recommendations(&self) -> Result<bool, sqlx::Error> {
+        DatabaseService::should_refresh_daily_recommendations(self).await
+    }
+
+    async fn calculate_hot_posts(&self) -> Result<Vec<String>, sqlx::Error> {
+        DatabaseService::calculate_hot_posts(self).await
+    }
+
+    async fn update_daily_recommendations(&self, post_ids: &[String]) -> Result<(), sqlx::Error> {
+        DatabaseService::update_daily_recommendations(self, post_ids).await
+    }
+
+    async fn get_daily_recommendations(&self, user_address: Option<&str>, excluded_authors: &[String]) -> Result<RecommendationResult, sqlx::Error> {
+        DatabaseService::get_daily_recommendations(self, user_address, excluded_authors).await
+    }
+
+    async fn create_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error> {
+        DatabaseService::create_notification(self, notification).await
+    }
+
+    async fn delete_notification(&self, notification: &NewNotification) -> Result<(), sqlx::Error> {
+        DatabaseService::delete_notification(self, notification).await
+    }
+
+    async fn get_notification_groups(&self, recipient_address: &str, page: &PageRequest, unread_only: bool) -> Result<Page<NotificationGroup>, sqlx::Error> {
+        DatabaseService::get_notification_groups(self, recipient_address, page, unread_only).await
+    }
+
+    async fn mark_notifications_read(&self, recipient_address: &str, group_keys: Option<&[String]>) -> Result<u64, sqlx::Error> {
+        DatabaseService::mark_notifications_read(self, recipient_address, group_keys).await
+    }
+
+    async fn count_unread_notifications(&self, recipient_address: &str) -> Result<u32, sqlx::Error> {
+        DatabaseService::count_unread_notifications(self, recipient_address).await
+    }
+
+    async fn get_posts_by_tag(&self, tag: &str, page: &PageRequest, viewer: Option<&str>) -> Result<Page<Post>, sqlx::Error> {
+        DatabaseService::get_posts_by_tag(self, tag, page, viewer).await
+    }
+
+    async fn get_tag(&self, tag: &str) -> Result<Option<TagSummary>, sqlx::Error> {
+        DatabaseService::get_tag(self, tag).await
+    }
+
+    async fn get_trending_tags(&self, since: DateTime<Utc>, limit: u32) -> Result<Vec<TrendingTag>, sqlx::Error> {
+        DatabaseService::get_trending_tags(self, since, limit).await
+    }
+
+    async fn replace_mentions(&self, post_id: &str, comment_id: Option<&str>, mentions: &[Mention]) -> Result<(), sqlx::Error> {
+        DatabaseService::replace_mentions(self, post_id, comment_id, mentions).await
+    }
+
+    async fn get_post_mentions(&self, post_ids: &[String]) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error> {
+        DatabaseService::get_mentions(self, post_ids, false).await
+    }
+
+    async fn get_comment_mentions(&self, comment_ids: &[String]) -> Result<HashMap<String, Vec<Mention>>, sqlx::Error> {
+        DatabaseService::get_mentions(self, comment_ids, true).await
+    }
+
+    async fn create_report(&self, report: &Report) -> Result<bool, sqlx::Error> {
+        DatabaseService::create_report(self, report).await
+    }
+
+    async fn get_report(&self, report_id: &str) -> Result<Option<Report>, sqlx::Error> {
+        DatabaseService::get_report(self, report_id).await
+    }
+
+    async fn get_open_reports(&self, page: &PageRequest) -> Result<Page<Report>, sqlx::Error> {
+        DatabaseService::get_open_reports(self, page).await
+    }
+
+    async fn close_reports(&self, target_type: ModerationTarget, target_id: &str, status: ReportStatus, resolution: ModerationAction, resolved_by: &str) -> Result<u32, sqlx::Error> {
+        DatabaseService::close_reports(self, target_type, target_id, status, resolution, resolved_by).await
+    }
+
+    async fn set_post_locked(&self, post_id: &str, locked_by: Option<&str>) -> Result<bool, sqlx::Error> {
+        DatabaseService::set_post_locked(self, post_id, locked_by).await
+    }
+
+    async fn is_post_locked(&self, post_id: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::is_post_locked(self, post_id).await
+    }
+
+    async fn suspend_address(&self, suspension: &Suspension) -> Result<(), sqlx::Error> {
+        DatabaseService::suspend_address(self, suspension).await
+    }
+
+    async fn lift_suspension(&self, address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::lift_suspension(self, address).await
+    }
+
+    async fn get_active_suspension(&self, address: &str) -> Result<Option<Suspension>, sqlx::Error> {
+        DatabaseService::get_active_suspension(self, address).await
+    }
+
+    async fn append_moderation_log(&self, entry: &ModerationLogEntry) -> Result<(), sqlx::Error> {
+        DatabaseService::append_moderation_log(self, entry).await
+    }
+
+    async fn get_moderation_log(&self, page: &PageRequest) -> Result<Page<ModerationLogEntry>, sqlx::Error> {
+        DatabaseService::get_moderation_log(self, page).await
+    }
+
+    async fn block_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::block_user(self, blocker_address, blocked_address).await
+    }
+
+    async fn unblock_user(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::unblock_user(self, blocker_address, blocked_address).await
+    }
+
+    async fn is_blocked(&self, blocker_address: &str, blocked_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::is_blocked(self, blocker_address, blocked_address).await
+    }
+
+    async fn get_blocked_users(&self, blocker_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error> {
+        DatabaseService::get_blocked_users(self, blocker_address).await
+    }
+
+    async fn mute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::mute_user(self, muter_address, muted_address).await
+    }
+
+    async fn unmute_user(&self, muter_address: &str, muted_address: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::unmute_user(self, muter_address, muted_address).await
+    }
+
+    async fn get_muted_users(&self, muter_address: &str) -> Result<Vec<RestrictedUser>, sqlx::Error> {
+        DatabaseService::get_muted_users(self, muter_address).await
+    }
+
+    async fn record_fingerprint(&self, fingerprint: &ContentFingerprint) -> Result<(), sqlx::Error> {
+        DatabaseService::record_fingerprint(self, fingerprint).await
+    }
+
+    async fn get_recent_fingerprints(&self, since: DateTime<Utc>, limit: u32) -> Result<Vec<ContentFingerprint>, sqlx::Error> {
+        DatabaseService::get_recent_fingerprints(self, since, limit).await
+    }
+
+    async fn count_recent_fingerprints(&self, author_address: &str, since: DateTime<Utc>) -> Result<u32, sqlx::Error> {
+        DatabaseService::count_recent_fingerprints(self, author_address, since).await
+    }
+
+    async fn hold_content(&self, held: &HeldContent) -> Result<(), sqlx::Error> {
+        DatabaseService::hold_content(self, held).await
+    }
+
+    async fn get_held_content(&self, held_id: &str) -> Result<Option<HeldContent>, sqlx::Error> {
+        DatabaseService::get_held_content(self, held_id).await
+    }
+
+    async fn get_held_queue(&self, page: &PageRequest) -> Result<Page<HeldContent>, sqlx::Error> {
+        DatabaseService::get_held_queue(self, page).await
+    }
+
+    async fn resolve_held_content(&self, held_id: &str, status: HeldStatus, content_id: Option<&str>, resolved_by: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::resolve_held_content(self, held_id, status, content_id, resolved_by).await
+    }
+
+    async fn create_media(&self, media: &Media) -> Result<(), sqlx::Error> {
+        DatabaseService::create_media(self, media).await
+    }
+
+    async fn get_media(&self, media_id: &str) -> Result<Option<Media>, sqlx::Error> {
+        DatabaseService::get_media(self, media_id).await
+    }
+
+    async fn delete_media(&self, media_id: &str) -> Result<bool, sqlx::Error> {
+        DatabaseService::delete_media(self, media_id).await
+    }
+
+    async fn count_users_with_avatar(&self, avatar_url: &str) -> Result<u32, sqlx::Error> {
+        DatabaseService::count_users_with_avatar(self, avatar_url).await
+    }
+
+    async fn get_chain_checkpoints(&self) -> Result<Vec<(u64, String)>, sqlx::Error> {
+        DatabaseService::get_chain_checkpoints(self).await
+    }
+
+    async fn store_chain_events(&self, events: &[IndexedEvent], checkpoint_block: u64, checkpoint_hash: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::store_chain_events(self, events, checkpoint_block, checkpoint_hash).await
+    }
+
+    async fn rollback_chain_events(&self, after_block: Option<u64>) -> Result<(), sqlx::Error> {
+        DatabaseService::rollback_chain_events(self, after_block).await
+    }
+
+    async fn enqueue_task(&self, task: &QueuedTask, max_queued: u64) -> Result<bool, sqlx::Error> {
+        DatabaseService::enqueue_task(self, task, max_queued).await
+    }
+
+    async fn claim_task(&self, lease: std::time::Duration) -> Result<Option<QueuedTask>, sqlx::Error> {
+        DatabaseService::claim_task(self, lease).await
+    }
+
+    async fn complete_task(&self, task_id: &str, result: &serde_json::Value) -> Result<(), sqlx::Error> {
+        DatabaseService::complete_task(self, task_id, result).await
+    }
+
+    async fn reschedule_task(&self, task_id: &str, error: &str, run_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        DatabaseService::reschedule_task(self, task_id, error, run_at).await
+    }
+
+    async fn dead_letter_task(&self, task_id: &str, error: &str) -> Result<(), sqlx::Error> {
+        DatabaseService::dead_letter_task(self, task_id, error).await
+    }
+
+    async fn get_task(&self, task_id: &str) -> Result<Option<QueuedTask>, sqlx::Error> {
+        DatabaseService::get_task(self, task_id).await
+    }
+
+    async fn evict_completed_tasks(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        DatabaseService::evict_completed_tasks(self, before).await
+    }
+}
@@ -1,11 +1,11 @@
 use chrono::{DateTime, Utc};
-use serde_json::Value;
-use std::collections::HashMap;
 
+#[allow(dead_code)]
 pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
     timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
+#[allow(dead_code)]
 pub fn format_relative_time(timestamp: DateTime<Utc>) -> String {
     let now = Utc::now();
     let duration = now.signed_duration_since(timestamp);
@@ -23,67 +23,109 @@ pub fn format_relative_time(timestamp: DateTime<Utc>) -> String {
     }
 }
 
+#[allow(dead_code)]
 pub fn truncate_text(text: &str, max_length: usize) -> String {
-    if text.len() <= max_length {
-        text.to_string()
-    } else {
-        format!("{}...", &text[..max_length])
+    match text.char_indices().nth(max_length) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
     }
 }
 
+/// Longest tag accepted by `normalize_tag`
+pub const MAX_TAG_CHARS: usize = 32;
+/// Tags kept per post after merging explicit tags with hashtags from the content
+pub const MAX_TAGS_PER_POST: usize = 10;
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Canonical form of a tag: leading `#` removed and lowercased, 1-32 letters, digits, `_` or `-`
+/// with at least one letter or digit. `None` if the tag breaks these rules.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let tag = raw.trim().trim_start_matches('#').to_lowercase();
+    let valid = (1..=MAX_TAG_CHARS).contains(&tag.chars().count())
+        && tag.chars().all(is_tag_char)
+        && tag.chars().any(char::is_alphanumeric);
+    valid.then_some(tag)
+}
+
+/// Every normalized `#hashtag` in order of appearance, repeats included; punctuation after a
+/// hashtag ends it
+pub fn hashtag_occurrences(content: &str) -> Vec<String> {
+    content.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .filter_map(|hashtag| {
+            let end = hashtag.find(|c: char| !is_tag_char(c)).unwrap_or(hashtag.len());
+            normalize_tag(&hashtag[..end])
+        })
+        .collect()
+}
+
+/// Normalized `#hashtags` in order of appearance, without repeats
 pub fn extract_tags_from_content(content: &str) -> Vec<String> {
     let mut tags = Vec::new();
-    let words: Vec<&str> = content.split_whitespace().collect();
-    
-    for word in words {
-        if word.starts_with('#') && word.len() > 1 {
-            let tag = word[1..].to_lowercase();
-            if !tags.contains(&tag) {
-                tags.push(tag);
-            }
+    for tag in hashtag_occurrences(content) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
         }
     }
-    
     tags
 }
 
-pub fn validate_address(address: &str) -> bool {
-   
-    address.len() >= 26 && address.len() <= 35 && address.starts_with("1")
+/// Tags stored for a post: the explicit tags, then hashtags from the content, normalized and
+/// deduplicated, at most `MAX_TAGS_PER_POST`. Tags breaking the rules are dropped.
+pub fn normalize_tags(explicit: &[String], content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in explicit.iter().filter_map(|t| normalize_tag(t)).chain(extract_tags_from_content(content)) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.truncate(MAX_TAGS_PER_POST);
+    tags
 }
 
-pub fn sanitize_html(input: &str) -> String {
-  
-    input
-        .replace("<script>", "")
-        .replace("</script>", "")
-        .replace("<iframe>", "")
-        .replace("</iframe>", "")
-        .replace("javascript:", "")
-        .replace("onclick", "")
-        .replace("onload", "")
-        .replace("onerror", "")
+/// An `@username` found in content; `start`/`end` are char offsets including the `@`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionToken {
+    pub username: String,
+    pub start: usize,
+    pub end: usize,
 }
 
-pub fn parse_irys_transaction(transaction: &Value) -> Option<HashMap<String, String>> {
-    if let Some(data) = transaction.get("data") {
-        if let Some(data_str) = data.as_str() {
-            if let Ok(parsed) = serde_json::from_str::<HashMap<String, String>>(data_str) {
-                return Some(parsed);
-            }
-        }
-    }
-    None
+/// Same character set as `DatabaseService::is_valid_username`
+fn is_username_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '·'
 }
 
-pub fn generate_avatar_url(address: &str) -> String {
- 
-    format!("https://www.gravatar.com/avatar/{:x}?d=identicon&s=64", 
-        md5::compute(address.to_lowercase()))
-}
+/// Find `@username` mentions in order of appearance. An `@` only starts a mention at the
+/// beginning of the content or after a character that cannot be part of a username, so
+/// email addresses are skipped. Names outside the 2-20 char username range are ignored.
+pub fn extract_mentions_from_content(content: &str) -> Vec<MentionToken> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut mentions = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '@' || (i > 0 && (is_username_char(chars[i - 1]) || chars[i - 1] == '@')) {
+            i += 1;
+            continue;
+        }
 
-pub fn calculate_reputation(posts_count: u32, comments_count: u32, likes_received: u32) -> u32 {
-    posts_count * 10 + comments_count * 5 + likes_received * 2
+        let mut end = i + 1;
+        while end < chars.len() && is_username_char(chars[end]) {
+            end += 1;
+        }
+        if (2..=20).contains(&(end - i - 1)) {
+            mentions.push(MentionToken {
+                username: chars[i + 1..end].iter().collect(),
+                start: i,
+                end,
+            });
+        }
+        i = end;
+    }
+    mentions
 }
 
 #[cfg(test)]
@@ -105,15 +147,44 @@ mod tests {
 
     #[test]
     fn test_extract_tags() {
+        // A lone `#` is not a hashtag, and repeats are only kept once
         let content = "This is a post about # rust # blockchain #irys";
-        let tags = extract_tags_from_content(content);
-        assert_eq!(tags, vec!["rust", "blockchain", "irys"]);
+        assert_eq!(extract_tags_from_content(content), vec!["irys"]);
+        let content = "#Rust on #blockchain: shipping #irys, then more #rust";
+        assert_eq!(extract_tags_from_content(content), vec!["rust", "blockchain", "irys"]);
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(normalize_tag(" #Web3 "), Some("web3".to_string()));
+        assert_eq!(normalize_tag("rust-lang_2"), Some("rust-lang_2".to_string()));
+        assert_eq!(normalize_tag("two words"), None);
+        assert_eq!(normalize_tag("--"), None);
+        assert_eq!(normalize_tag(&"x".repeat(MAX_TAG_CHARS + 1)), None);
+
+        let explicit = vec!["Rust".to_string(), "bad tag".to_string()];
+        assert_eq!(normalize_tags(&explicit, "Learning #rust and #Irys, see #irys!"), vec!["rust", "irys"]);
+        let many: Vec<String> = (0..20).map(|i| format!("t{}", i)).collect();
+        assert_eq!(normalize_tags(&many, "").len(), MAX_TAGS_PER_POST);
+    }
+
+    #[test]
+    fn test_extract_mentions() {
+        let mentions = extract_mentions_from_content("@alice thanks, cc @bob_1. Mail me@example.com or @@xy, @a @");
+        let found: Vec<_> = mentions.iter().map(|m| (m.username.as_str(), m.start, m.end)).collect();
+        assert_eq!(found, vec![("alice", 0, 6), ("bob_1", 18, 24)]);
+
+        let unicode = extract_mentions_from_content("héllo @zoë!");
+        assert_eq!((unicode[0].username.as_str(), unicode[0].start, unicode[0].end), ("zoë", 6, 10));
+        assert!(extract_mentions_from_content(&format!("@{}", "a".repeat(21))).is_empty());
     }
 
     #[test]
     fn test_truncate_text() {
         let text = "This is a very long text content";
-        let truncated = truncate_text(text, 10);
-        assert_eq!(truncated, "This is a very long one...");
+        assert_eq!(truncate_text(text, 10), "This is a ...");
+        assert_eq!(truncate_text(text, 100), text);
+        // Cuts on characters, not bytes
+        assert_eq!(truncate_text("héllo wörld", 7), "héllo w...");
     }
 } 
\ No newline at end of file
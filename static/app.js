@@ -155,6 +155,91 @@ async function retryRequest(requestFn, maxRetries = 3, delay = 1000) {
 
 const API_BASE = '/api';
 
+// Sign-In with Ethereum (EIP-4361) session handling
+const SESSION_STORAGE_KEY = 'irysSession';
+
+function getStoredSession() {
+    try {
+        const session = JSON.parse(localStorage.getItem(SESSION_STORAGE_KEY) || 'null');
+        if (session && walletAccount &&
+            session.address.toLowerCase() === walletAccount.toLowerCase() &&
+            new Date(session.expires_at) > new Date()) {
+            return session;
+        }
+    } catch (error) {
+        console.error('Failed to read stored session:', error);
+    }
+    return null;
+}
+
+function clearSession() {
+    localStorage.removeItem(SESSION_STORAGE_KEY);
+}
+
+async function signIn() {
+    if (!walletAccount) {
+        throw new Error('Please connect your wallet first');
+    }
+
+    const nonceResponse = await fetch(`${API_BASE}/auth/nonce`);
+    const nonceResult = await nonceResponse.json();
+    if (!nonceResult.success) {
+        throw new Error(nonceResult.error || 'Failed to get sign-in nonce');
+    }
+
+    const chainId = parseInt(await window.ethereum.request({ method: 'eth_chainId' }), 16);
+    const message = [
+        `${window.location.host} wants you to sign in with your Ethereum account:`,
+        ethers.utils.getAddress(walletAccount),
+        '',
+        'Sign in to Irys Forum',
+        '',
+        `URI: ${window.location.origin}`,
+        'Version: 1',
+        `Chain ID: ${chainId}`,
+        `Nonce: ${nonceResult.data.nonce}`,
+        `Issued At: ${new Date().toISOString()}`
+    ].join('\n');
+
+    const signature = await window.ethereum.request({
+        method: 'personal_sign',
+        params: [message, walletAccount]
+    });
+
+    const verifyResponse = await fetch(`${API_BASE}/auth/verify`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ message, signature })
+    });
+    const verifyResult = await verifyResponse.json();
+    if (!verifyResult.success) {
+        throw new Error(verifyResult.error || 'Sign-in failed');
+    }
+
+    localStorage.setItem(SESSION_STORAGE_KEY, JSON.stringify(verifyResult.data));
+    return verifyResult.data;
+}
+
+// Session header for public reads that the server personalizes (e.g. mutes); never prompts a sign-in
+function sessionHeaders() {
+    const session = getStoredSession();
+    return session ? { 'Authorization': `Bearer ${session.token}` } : {};
+}
+
+// fetch wrapper for endpoints that act on behalf of the signed-in wallet
+async function authFetch(url, options = {}) {
+    const session = getStoredSession() || await signIn();
+    const headers = Object.assign({}, options.headers, {
+        'Authorization': `Bearer ${session.token}`
+    });
+
+    const response = await fetch(url, Object.assign({}, options, { headers }));
+    if (response.status === 401) {
+        clearSession();
+    }
+    return response;
+}
+
 
 const CONTRACT_ADDRESS = '0xBebfAC28e35c7a70eAe9Df606199E45c85a73a9a';
 const CONTRACT_ABI = [
@@ -955,13 +1040,12 @@ async function followUserById(targetUserId) {
             button.innerHTML = '<i class="fas fa-spinner fa-spin"></i> Attention in progress...';
         }
         
-        const response = await fetch(`${API_BASE}/follow`, {
+        const response = await authFetch(`${API_BASE}/follow`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json',
             },
             body: JSON.stringify({
-                follower_id: currentUser.id,
                 following_id: targetUserId
             })
         });
@@ -1003,12 +1087,11 @@ async function unfollowUserById(targetUserId) {
         }
         
         const requestData = {
-            follower_id: currentUser.id,
             following_id: targetUserId
         };
         console.log('Sending unfollow request (unfollowUserById):', requestData, 'currentUser:', currentUser);
         
-        const response = await fetch(`${API_BASE}/unfollow`, {
+        const response = await authFetch(`${API_BASE}/unfollow`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json',
@@ -1109,13 +1192,12 @@ async function followUserFromPostById(targetUserId) {
             button.innerHTML = '<i class="fas fa-spinner fa-spin"></i> Follow in progress...';
         }
         
-        const response = await fetch(`${API_BASE}/follow`, {
+        const response = await authFetch(`${API_BASE}/follow`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json',
             },
             body: JSON.stringify({
-                follower_id: currentUser.id,
                 following_id: targetUserId
             })
         });
@@ -1168,12 +1250,11 @@ async function unfollowUserFromPostById(targetUserId) {
         }
         
         const requestData = {
-            follower_id: currentUser.id,
             following_id: targetUserId
         };
         console.log('Sending unfollow request (unfollowUserFromPostById):', requestData, 'currentUser:', currentUser);
         
-        const response = await fetch(`${API_BASE}/unfollow`, {
+        const response = await authFetch(`${API_BASE}/unfollow`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json',
@@ -1422,14 +1503,13 @@ async function registerUsername() {
       
         statusDiv.innerHTML = '<span class="processing">Save username...</span>';
         
-        const response = await fetch(`${API_BASE}/username/register`, {
+        const response = await authFetch(`${API_BASE}/username/register`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json'
             },
             body: JSON.stringify({
-                username: username,
-                user_address: walletAccount
+                username: username
             })
         });
         
@@ -1566,6 +1646,7 @@ async function initializeApp() {
                 disconnectWallet();
                 updateNetworkStatus(); 
             } else if (accounts[0] !== walletAccount) {
+                clearSession();
                 walletAccount = accounts[0];
                 currentUser.address = walletAccount;
                 updateWalletUI();
@@ -1787,6 +1868,7 @@ async function updateBalance() {
 function disconnectWallet() {
     walletAccount = null;
     currentUser.address = '';
+    clearSession();
     
    
     const connectBtn = document.getElementById('connectWalletBtn');
@@ -1908,7 +1990,7 @@ async function loadPosts() {
            
         }
         
-        const response = await fetch(url);
+        const response = await fetch(url, { headers: sessionHeaders() });
         const result = await response.json();
         
         if (result.success && result.data) {
@@ -1957,7 +2039,7 @@ async function loadMorePosts() {
            
         }
         
-        const response = await fetch(url);
+        const response = await fetch(url, { headers: sessionHeaders() });
         const result = await response.json();
         
         if (result.success && result.data) {
@@ -2199,7 +2281,7 @@ async function createPost() {
     
     console.log('📤 Payload:', {
         ...postData,
-        image: currentPostImage ? `image data (${currentPostImage.length} chars)` : 'no image'
+        image: currentPostImage || 'no image'
     }); 
     
     const startTime = performanceMonitor.startRequest();
@@ -2249,14 +2331,13 @@ async function createPost() {
             console.log('✅ Now save to backend database...');
             
             
-            const response = await fetch(`${API_BASE}/posts`, {
+            const response = await authFetch(`${API_BASE}/posts`, {
                 method: 'POST',
                 headers: {
                     'Content-Type': 'application/json',
                 },
                 body: JSON.stringify({
                     ...postData,
-                    blockchain_post_id: blockchainPostId, 
                     blockchain_transaction_hash: transactionHash 
                 })
             });
@@ -2325,7 +2406,7 @@ async function openPost(postId) {
             `${API_BASE}/posts/${postId}?user_address=${encodeURIComponent(walletAccount)}` : 
             `${API_BASE}/posts/${postId}`;
         
-        const response = await fetch(url);
+        const response = await fetch(url, { headers: sessionHeaders() });
         const result = await response.json();
         
         if (result.success && result.data) {
@@ -2360,7 +2441,7 @@ async function openPost(postId) {
                     </div>
                     
                     <div class="post-detail-content">
-                        <div class="post-content">${escapeHtml(post.content)}</div>
+                        <div class="post-content">${post.content_html ?? escapeHtml(post.content)}</div>
                         ${post.image ? `<img src="${post.image}" alt="Post image" class="post-image">` : ''}
                         ${post.tags && post.tags.length > 0 ? `
                             <div class="post-tags">
@@ -2386,7 +2467,7 @@ async function openPost(postId) {
                             <textarea id="commentContent" placeholder="Write your comment..." rows="3"></textarea>
                             <div class="comment-actions">
                                 <div class="media-upload">
-                                    <input type="file" id="commentImage" accept="image/jpeg,image/jpg,image/png" style="display: none;" onchange="handleImageUpload(this, 'commentImagePreview')">
+                                    <input type="file" id="commentImage" accept="image/jpeg,image/jpg,image/png,image/gif,image/webp" style="display: none;" onchange="handleImageUpload(this, 'commentImagePreview')">
                                     <button class="media-btn" onclick="document.getElementById('commentImage').click()">
                                         <i class="fas fa-image"></i> Image
                                     </button>
@@ -2553,7 +2634,7 @@ function renderComment(comment, level = 0) {
                     </button>
                 </div>
             </div>
-            <div class="comment-content">${escapeHtml(comment.content)}</div>
+            <div class="comment-content">${comment.content_html ?? escapeHtml(comment.content)}</div>
             ${comment.image ? `<img src="${comment.image}" alt="Comment image" class="comment-image" onclick="showImageModal('${comment.image}')">` : ''}
             
             <!-- Reply form container -->
@@ -2649,7 +2730,7 @@ async function submitReply(parentCommentId) {
         }
         
         // Call backend API
-        const response = await fetch(`${API_BASE}/posts/${currentPostId}/comments`, {
+        const response = await authFetch(`${API_BASE}/posts/${currentPostId}/comments`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json',
@@ -2708,14 +2789,8 @@ async function submitReply(parentCommentId) {
 // Like comment
 async function likeComment(commentId) {
     try {
-        const response = await fetch(`${API_BASE}/comments/${commentId}/like`, {
-            method: 'POST',
-            headers: {
-                'Content-Type': 'application/json',
-            },
-            body: JSON.stringify({
-                user_address: currentUser.address
-            })
+        const response = await authFetch(`${API_BASE}/comments/${commentId}/like`, {
+            method: 'POST'
         });
         
         const result = await response.json();
@@ -2833,7 +2908,7 @@ async function addComment() {
             console.log('✅ Now save to backend database...');
             
             // After smart contract is successful, save to backend database
-            const response = await fetch(`${API_BASE}/posts/${currentPostId}/comments`, {
+            const response = await authFetch(`${API_BASE}/posts/${currentPostId}/comments`, {
                 method: 'POST',
                 headers: {
                     'Content-Type': 'application/json',
@@ -3269,21 +3344,21 @@ function handleImageUpload(input, previewId) {
         sizeKB: Math.round(file.size / 1024)
     });
     
-    // Whitelist validation - only allow jpg and png
-    const allowedTypes = ['image/jpeg', 'image/jpg', 'image/png'];
+    // Whitelist validation - the server checks the file contents again
+    const allowedTypes = ['image/jpeg', 'image/jpg', 'image/png', 'image/gif', 'image/webp'];
     if (!allowedTypes.includes(file.type)) {
         console.log('❌ File type not supported:', file.type);
-        showSuccessMessage('Image format error', 'Only JPG and PNG images are supported');
+        showSuccessMessage('Image format error', 'Only JPG, PNG, GIF and WebP images are supported');
         input.value = '';
         clearImagePreview(previewId);
         return;
     }
     
-    // Size limit - 200KB
-    const maxSize = 200 * 1024; // 200KB
+    // Size limit - 10MB
+    const maxSize = 10 * 1024 * 1024;
     if (file.size > maxSize) {
         console.log('❌ File too large:', file.size, 'bytes');
-        showSuccessMessage('File too large', 'File size cannot exceed 200KB');
+        showSuccessMessage('File too large', 'File size cannot exceed 10MB');
         input.value = '';
         clearImagePreview(previewId);
         return;
@@ -3293,19 +3368,37 @@ function handleImageUpload(input, previewId) {
     
     // Read and preview image
     const reader = new FileReader();
-    reader.onload = function(e) {
+    reader.onload = async function(e) {
         const imageData = e.target.result;
         console.log('📷 Image read completed, data length:', imageData.length);
         
         showImagePreview(preview, imageData, previewId);
         
-        // 保存图片数据到全局变量
-        if (previewId === 'postImagePreview') {
-            currentPostImage = imageData;
-            console.log('💾 Post image saved to global variable');
-        } else if (previewId === 'commentImagePreview') {
-            currentCommentImage = imageData;
-            console.log('💾 Comment image saved to global variable');
+        // Upload right away; posts and comments send the returned media ID as `image`
+        try {
+            const formData = new FormData();
+            formData.append('image', file);
+            const response = await authFetch(`${API_BASE}/media`, {
+                method: 'POST',
+                body: formData
+            });
+            const result = await response.json();
+            if (!result.success) {
+                throw new Error(result.error || 'Upload failed');
+            }
+            
+            if (previewId === 'postImagePreview') {
+                currentPostImage = result.data.id;
+                console.log('💾 Post image uploaded:', result.data.id);
+            } else if (previewId === 'commentImagePreview') {
+                currentCommentImage = result.data.id;
+                console.log('💾 Comment image uploaded:', result.data.id);
+            }
+        } catch (error) {
+            console.error('❌ Image upload failed:', error);
+            showSuccessMessage('Image upload failed', error.message);
+            input.value = '';
+            clearImagePreview(previewId);
         }
     };
     
@@ -3765,14 +3858,8 @@ async function likePost(postId, element) {
         element.style.pointerEvents = 'none';
         element.style.opacity = '0.6';
         
-        const response = await fetch(`${API_BASE}/posts/${postId}/like`, {
-            method: 'POST',
-            headers: {
-                'Content-Type': 'application/json'
-            },
-            body: JSON.stringify({
-                user_address: walletAccount
-            })
+        const response = await authFetch(`${API_BASE}/posts/${postId}/like`, {
+            method: 'POST'
         });
         
         const result = await response.json();
@@ -4167,7 +4254,7 @@ async function loadDailyRecommendations() {
             </div>
         `;
         
-        const response = await fetch(`${API_BASE}/recommendations/daily?user_address=${encodeURIComponent(walletAccount || '')}`);
+        const response = await fetch(`${API_BASE}/recommendations/daily?user_address=${encodeURIComponent(walletAccount || '')}`, { headers: sessionHeaders() });
         const result = await response.json();
         
         if (result.success && result.data) {
@@ -4441,27 +4528,26 @@ async function handleAvatarUpload(event) {
     if (!file) return;
     
     // Verify file type
-    const allowedTypes = ['image/jpeg', 'image/jpg', 'image/png'];
+    const allowedTypes = ['image/jpeg', 'image/jpg', 'image/png', 'image/gif', 'image/webp'];
     if (!allowedTypes.includes(file.type)) {
-        showWarningToast('Only JPG and PNG images are supported');
+        showWarningToast('Only JPG, PNG, GIF and WebP images are supported');
         return;
     }
     
   
-    const maxSize = 5 * 1024 * 1024;
+    const maxSize = 10 * 1024 * 1024;
     if (file.size > maxSize) {
-        showWarningToast('Image size cannot exceed 5MB');
+        showWarningToast('Image size cannot exceed 10MB');
         return;
     }
     
     try {
         const formData = new FormData();
         formData.append('avatar', file);
-        formData.append('user_address', walletAccount);
         
         showSuccessMessage('Uploading...', 'Uploading avatar...');
         
-        const response = await fetch(`${API_BASE}/users/avatar/upload`, {
+        const response = await authFetch(`${API_BASE}/users/avatar/upload`, {
             method: 'POST',
             body: formData
         });
@@ -4558,13 +4644,12 @@ async function saveBioProfile() {
         saveBioBtn.innerHTML = '<i class="fas fa-spinner fa-spin"></i> 保存中...';
         saveBioBtn.disabled = true;
         
-        const response = await fetch(`${API_BASE}/users/bio/update`, {
+        const response = await authFetch(`${API_BASE}/users/bio/update`, {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json'
             },
             body: JSON.stringify({
-                user_address: walletAccount,
                 bio: bio
             })
         });